@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+// Opções que recebem valor (`--saida ARQUIVO` ou `--saida=ARQUIVO`); as outras,
+// como `--reparar` e `--simular`, são interruptores e não levam o posicional seguinte
+const COM_VALOR: &[&str] = &[
+    "ano",
+    "assunto",
+    "ate",
+    "autor",
+    "autosalvar",
+    "cabecalho",
+    "colunas",
+    "config",
+    "data",
+    "data-atual",
+    "de",
+    "delimitador",
+    "dias",
+    "email",
+    "entidade",
+    "feito-por",
+    "formato",
+    "limite",
+    "login",
+    "modelo",
+    "modelos",
+    "motivo",
+    "nome",
+    "oai-dominio",
+    "oai-email",
+    "oai-nome",
+    "papel",
+    "pascoa",
+    "porta",
+    "remetente",
+    "saida",
+    "smtp",
+    "smtp-usuario",
+    "titulo",
+    "unidade",
+    "vencimento",
+];
+
+// Linha de comando já separada em posicionais (`serve`, `tui`...) e opções `--nome valor`
+pub struct Argumentos {
+    pub posicionais: Vec<String>,
+    opcoes: HashMap<String, String>,
+}
+
+impl Argumentos {
+    pub fn do_ambiente() -> Self {
+        Self::interpretar(std::env::args().skip(1))
+    }
+
+    pub fn interpretar<I: Iterator<Item = String>>(args: I) -> Self {
+        let mut posicionais = Vec::new();
+        let mut opcoes = HashMap::new();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(nome) => {
+                    let (nome, valor) = match nome.split_once('=') {
+                        Some((nome, valor)) => (nome, valor.to_string()),
+                        None if COM_VALOR.contains(&nome) => {
+                            let valor = args.next_if(|v| !v.starts_with("--"));
+                            (nome, valor.unwrap_or_default())
+                        }
+                        None => (nome, String::new()),
+                    };
+                    opcoes.insert(nome.to_string(), valor);
+                }
+                None => posicionais.push(arg),
+            }
+        }
+
+        Self {
+            posicionais,
+            opcoes,
+        }
+    }
+
+    pub fn comando(&self) -> Option<&str> {
+        self.posicionais.first().map(String::as_str)
+    }
+
+    // Para opções sem valor, como `--reparar`
+    pub fn tem_opcao(&self, nome: &str) -> bool {
+        self.opcoes.contains_key(nome)
+    }
+
+    pub fn opcao(&self, nome: &str) -> Option<&str> {
+        self.opcoes.get(nome).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn interpretar(linha: &str) -> Argumentos {
+        Argumentos::interpretar(linha.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn interruptor_nao_leva_o_posicional_seguinte() {
+        let argumentos = interpretar("verificar --reparar outro.json");
+        assert_eq!(argumentos.posicionais, ["verificar", "outro.json"]);
+        assert!(argumentos.tem_opcao("reparar"));
+        assert_eq!(argumentos.opcao("reparar"), Some(""));
+    }
+
+    #[test]
+    fn opcao_com_valor_leva_a_palavra_seguinte() {
+        let argumentos = interpretar("exportar --saida livros.csv livros --bom");
+        assert_eq!(argumentos.posicionais, ["exportar", "livros"]);
+        assert_eq!(argumentos.opcao("saida"), Some("livros.csv"));
+        assert!(argumentos.tem_opcao("bom"));
+
+        // Sem valor antes da próxima opção, fica vazia
+        let argumentos = interpretar("auditoria --formato --saida trilha.json");
+        assert_eq!(argumentos.opcao("formato"), Some(""));
+        assert_eq!(argumentos.opcao("saida"), Some("trilha.json"));
+    }
+
+    #[test]
+    fn valor_depois_do_igual() {
+        let argumentos = interpretar("relatorio --de=2024-01-01 --simular=sim todos");
+        assert_eq!(argumentos.posicionais, ["relatorio", "todos"]);
+        assert_eq!(argumentos.opcao("de"), Some("2024-01-01"));
+        assert_eq!(argumentos.opcao("simular"), Some("sim"));
+    }
+}
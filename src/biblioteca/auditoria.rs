@@ -0,0 +1,159 @@
+// Trilha de auditoria: quem fez o quê, quando e sobre quais entidades.
+// É derivada do diário de eventos, reaplicando o histórico para descobrir
+// como cada entidade estava antes e depois de cada operação.
+
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::errors::ErroBiblioteca;
+
+use super::{
+    DadosPersistencia,
+    diario::{self, Evento},
+};
+
+#[derive(Debug, Default)]
+pub struct FiltroAuditoria {
+    pub de: Option<DateTime<Local>>,
+    pub ate: Option<DateTime<Local>>,
+    pub operador: Option<String>,
+    pub entidade: Option<Uuid>,
+}
+
+impl FiltroAuditoria {
+    fn aceita(&self, registro: &RegistroAuditoria) -> bool {
+        self.de.is_none_or(|de| registro.momento >= de)
+            && self.ate.is_none_or(|ate| registro.momento <= ate)
+            && self
+                .operador
+                .as_ref()
+                .is_none_or(|operador| registro.operador.eq_ignore_ascii_case(operador))
+            && self
+                .entidade
+                .is_none_or(|entidade| registro.entidades.contains(&entidade))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegistroAuditoria {
+    pub sequencia: u64,
+    pub momento: DateTime<Local>,
+    pub operador: String,
+    pub acao: &'static str,
+    pub entidades: Vec<Uuid>,
+    pub antes: Option<Value>,
+    pub depois: Option<Value>,
+}
+
+impl fmt::Display for RegistroAuditoria {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let valor = |v: &Option<Value>| match v {
+            Some(v) => v.to_string(),
+            None => "-".to_string(),
+        };
+
+        write!(
+            f,
+            "#{} {} [{}] {}\nEntidades: {}\nAntes: {}\nDepois: {}",
+            self.sequencia,
+            self.momento.format("%Y-%m-%d %H:%M:%S"),
+            self.operador,
+            self.acao,
+            self.entidades
+                .iter()
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            valor(&self.antes),
+            valor(&self.depois),
+        )
+    }
+}
+
+// Estado atual da entidade principal do evento (o livro, o usuário, o empréstimo,
+// a unidade, a parceira ou o pedido de intercâmbio)
+fn valor_da_entidade(dados: &DadosPersistencia, evento: &Evento) -> Option<Value> {
+    let valor = match evento {
+        Evento::EstadoImportado { .. } | Evento::DadosReparados { .. } => {
+            return Some(json!({
+                "livros": dados.livros.len(),
+                "usuarios": dados.usuarios.len(),
+                "emprestimos": dados.emprestimos.len(),
+            }));
+        }
+        Evento::LivroAdicionado(_)
+        | Evento::LivroAtualizado(_)
+        | Evento::LivroRemovido(_)
+        | Evento::LivroArquivado(_) => {
+            serde_json::to_value(dados.livros.get(&evento.entidades()[0])?)
+        }
+        Evento::UsuarioAdicionado(_)
+        | Evento::UsuarioAtualizado(_)
+        | Evento::UsuarioRemovido(_)
+        | Evento::UsuarioArquivado(_) => {
+            serde_json::to_value(dados.usuarios.get(&evento.entidades()[0])?)
+        }
+        Evento::EmprestimoRealizado(_)
+        | Evento::EmprestimoDevolvido(_)
+        | Evento::EmprestimoRenovado(_)
+        | Evento::EmprestimoAtualizado(_)
+        | Evento::EmprestimoCancelado(_)
+        | Evento::DevolucaoDesfeita(_) => {
+            serde_json::to_value(dados.emprestimos.get(&evento.entidades()[0])?)
+        }
+        Evento::UnidadeAdicionada(_)
+        | Evento::UnidadeAtualizada(_)
+        | Evento::UnidadeRemovida(_) => {
+            serde_json::to_value(dados.unidades.get(&evento.entidades()[0])?)
+        }
+        Evento::ParceiraAdicionada(_) => {
+            serde_json::to_value(dados.parceiras.get(&evento.entidades()[0])?)
+        }
+        Evento::SolicitacaoRegistrada(_) | Evento::SolicitacaoAtualizada(_) => {
+            serde_json::to_value(dados.solicitacoes.get(&evento.entidades()[0])?)
+        }
+        // Lotes são desdobrados antes de chegar aqui
+        Evento::Lote(_) => return None,
+    };
+    valor.ok()
+}
+
+pub(super) fn consultar(
+    caminho_dados: &std::path::Path,
+    filtro: &FiltroAuditoria,
+) -> Result<Vec<RegistroAuditoria>, ErroBiblioteca> {
+    let mut dados = DadosPersistencia::default();
+    let mut registros = Vec::new();
+
+    // Cada operação de um lote aparece separada, todas com a sequência do lote
+    for registro in diario::ler_historico(caminho_dados)? {
+        for evento in registro.evento.desdobrar() {
+            let antes = match evento {
+                Evento::EstadoImportado { .. } => None,
+                _ => valor_da_entidade(&dados, &evento),
+            };
+            diario::aplicar(&mut dados, &evento);
+            let depois = valor_da_entidade(&dados, &evento);
+
+            let auditoria = RegistroAuditoria {
+                sequencia: registro.sequencia,
+                momento: registro.momento,
+                operador: registro.operador.clone(),
+                acao: evento.nome(),
+                entidades: evento.entidades(),
+                antes,
+                depois,
+            };
+
+            if filtro.aceita(&auditoria) {
+                registros.push(auditoria);
+            }
+        }
+    }
+
+    Ok(registros)
+}
@@ -0,0 +1,137 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use crate::errors::ErroBiblioteca;
+
+use super::{
+    DadosPersistencia,
+    diario::{self, RegistroEvento},
+    escrever_dados,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModoAutosalvamento {
+    // Só salva quando o usuário pedir
+    Desligado,
+    // Grava no diário cada operação que altera a biblioteca, logo depois dela
+    ACadaAlteracao,
+    // Grava no diário as alterações pendentes de tempos em tempos, numa thread separada
+    Intervalo(Duration),
+}
+
+impl ModoAutosalvamento {
+    // Aceita "sempre", "nunca" ou um número de segundos
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "sempre" => Ok(ModoAutosalvamento::ACadaAlteracao),
+            "nunca" => Ok(ModoAutosalvamento::Desligado),
+            segundos => match segundos.parse::<u64>() {
+                Ok(s) if s > 0 => Ok(ModoAutosalvamento::Intervalo(Duration::from_secs(s))),
+                _ => Err(ErroBiblioteca::DadosInvalidos(format!(
+                    "Autosalvamento deve ser \"sempre\", \"nunca\" ou um número de segundos, não \"{}\".",
+                    texto
+                ))),
+            },
+        }
+    }
+}
+
+// Eventos que ainda não foram para o diário, na ordem em que aconteceram. O
+// diário é o que garante as alterações: o retrato só é refeito quando a
+// biblioteca é salva, e ao carregar os eventos que faltam nele são reaplicados.
+#[derive(Default, Clone)]
+pub(super) struct Pendencias {
+    eventos: Vec<RegistroEvento>,
+}
+
+impl Pendencias {
+    fn gravar_diario(&mut self, caminho: &Path) -> Result<(), ErroBiblioteca> {
+        diario::anexar(caminho, &self.eventos)?;
+        self.eventos.clear();
+        Ok(())
+    }
+}
+
+// Guarda o que ainda não foi salvo da biblioteca.
+// Pode ser clonada e usada de outras threads (timer, sinais, pânico) para
+// gravar os eventos pendentes sem precisar acessar a `Biblioteca` em si.
+#[derive(Clone)]
+pub struct Salvaguarda {
+    pendente: Arc<Mutex<Pendencias>>,
+    caminho: PathBuf,
+}
+
+impl Salvaguarda {
+    pub(super) fn nova(caminho: PathBuf) -> Self {
+        Self {
+            pendente: Arc::new(Mutex::new(Pendencias::default())),
+            caminho,
+        }
+    }
+
+    pub(super) fn caminho(&self) -> &Path {
+        &self.caminho
+    }
+
+    // Um pânico no meio de uma escrita não deve impedir as próximas
+    fn travar(&self) -> MutexGuard<'_, Pendencias> {
+        self.pendente.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(super) fn marcar(&self, evento: RegistroEvento) {
+        self.travar().eventos.push(evento);
+    }
+
+    // Eventos primeiro; se o retrato falhar depois, eles são reaplicados ao
+    // carregar e nada se perde. Segura a trava para não competir com o timer.
+    pub(super) fn gravar(&self, dados: &DadosPersistencia) -> Result<(), ErroBiblioteca> {
+        self.travar().gravar_diario(&self.caminho)?;
+        escrever_dados(&self.caminho, dados)
+    }
+
+    pub(super) fn pendencias(&self) -> Pendencias {
+        self.travar().clone()
+    }
+
+    pub(super) fn restaurar(&self, pendencias: Pendencias) {
+        *self.travar() = pendencias;
+    }
+
+    // Todos os eventos já estão no diário (mesmo que o retrato não tenha sido gravado)
+    pub(super) fn diario_em_dia(&self) -> bool {
+        self.travar().eventos.is_empty()
+    }
+
+    pub(super) fn descartar(&self) {
+        *self.travar() = Pendencias::default();
+    }
+
+    pub fn tem_pendencias(&self) -> bool {
+        !self.travar().eventos.is_empty()
+    }
+
+    // Devolve `true` se havia algo pendente e foi gravado
+    pub fn salvar_pendencias(&self) -> Result<bool, ErroBiblioteca> {
+        let mut pendente = self.travar();
+        if pendente.eventos.is_empty() {
+            return Ok(false);
+        }
+        pendente.gravar_diario(&self.caminho).map(|()| true)
+    }
+
+    pub(super) fn iniciar_timer(&self, intervalo: Duration) {
+        let salvaguarda = self.clone();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(intervalo);
+                if let Err(e) = salvaguarda.salvar_pendencias() {
+                    eprintln!("Erro no autosalvamento: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,490 @@
+// Dias em que a biblioteca abre: os dias da semana de funcionamento, menos os
+// feriados (os nacionais, calculados todo ano, e os que a biblioteca cadastrar)
+// e os fechamentos avulsos (recesso, reforma, greve...). Os prazos de devolução
+// caem sempre num dia aberto e os dias de atraso só contam dias abertos.
+// Fica só em dados_biblioteca.calendario.json. O arquivo nasce do [calendario]
+// da configuração (ou do padrão: segunda a sábado, com os feriados nacionais)
+// e a partir daí é ele que vale.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ErroBiblioteca;
+
+// Até onde procurar um dia aberto antes de desistir (calendário todo fechado)
+const LIMITE_BUSCA_DIAS: u64 = 366;
+
+// Feriados nacionais de data fixa (Lei 662/1949 e atualizações)
+const NACIONAIS_FIXOS: [(u32, u32, &str); 9] = [
+    (1, 1, "Confraternização Universal"),
+    (4, 21, "Tiradentes"),
+    (5, 1, "Dia do Trabalho"),
+    (9, 7, "Independência do Brasil"),
+    (10, 12, "Nossa Senhora Aparecida"),
+    (11, 2, "Finados"),
+    (11, 15, "Proclamação da República"),
+    (11, 20, "Dia Nacional de Zumbi e da Consciência Negra"),
+    (12, 25, "Natal"),
+];
+
+// Feriados e pontos facultativos que dependem da Páscoa (dias em relação a ela)
+const NACIONAIS_MOVEIS: [(i64, &str); 4] = [
+    (-48, "Carnaval"),
+    (-47, "Carnaval"),
+    (-2, "Sexta-feira Santa"),
+    (60, "Corpus Christi"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiaSemana {
+    Seg,
+    Ter,
+    Qua,
+    Qui,
+    Sex,
+    Sab,
+    Dom,
+}
+
+impl DiaSemana {
+    pub const TODOS: [DiaSemana; 7] = [
+        DiaSemana::Seg,
+        DiaSemana::Ter,
+        DiaSemana::Qua,
+        DiaSemana::Qui,
+        DiaSemana::Sex,
+        DiaSemana::Sab,
+        DiaSemana::Dom,
+    ];
+
+    fn de(dia: Weekday) -> Self {
+        match dia {
+            Weekday::Mon => DiaSemana::Seg,
+            Weekday::Tue => DiaSemana::Ter,
+            Weekday::Wed => DiaSemana::Qua,
+            Weekday::Thu => DiaSemana::Qui,
+            Weekday::Fri => DiaSemana::Sex,
+            Weekday::Sat => DiaSemana::Sab,
+            Weekday::Sun => DiaSemana::Dom,
+        }
+    }
+
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto.trim().to_lowercase().as_str() {
+            "seg" => Ok(DiaSemana::Seg),
+            "ter" => Ok(DiaSemana::Ter),
+            "qua" => Ok(DiaSemana::Qua),
+            "qui" => Ok(DiaSemana::Qui),
+            "sex" => Ok(DiaSemana::Sex),
+            "sab" | "sáb" => Ok(DiaSemana::Sab),
+            "dom" => Ok(DiaSemana::Dom),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Dia da semana desconhecido: {} (use seg, ter, qua, qui, sex, sab ou dom).",
+                outro
+            ))),
+        }
+    }
+
+    fn nome(&self) -> &'static str {
+        match self {
+            DiaSemana::Seg => "segunda-feira",
+            DiaSemana::Ter => "terça-feira",
+            DiaSemana::Qua => "quarta-feira",
+            DiaSemana::Qui => "quinta-feira",
+            DiaSemana::Sex => "sexta-feira",
+            DiaSemana::Sab => "sábado",
+            DiaSemana::Dom => "domingo",
+        }
+    }
+}
+
+impl fmt::Display for DiaSemana {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let abreviado = match self {
+            DiaSemana::Seg => "seg",
+            DiaSemana::Ter => "ter",
+            DiaSemana::Qua => "qua",
+            DiaSemana::Qui => "qui",
+            DiaSemana::Sex => "sex",
+            DiaSemana::Sab => "sab",
+            DiaSemana::Dom => "dom",
+        };
+        write!(f, "{}", abreviado)
+    }
+}
+
+// Feriado da biblioteca: ou todo ano no mesmo dia (`data` no formato MM-DD),
+// ou a tantos dias da Páscoa (`pascoa`, negativo para antes dela)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feriado {
+    pub nome: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pascoa: Option<i64>,
+}
+
+impl Feriado {
+    // Mês e dia de um feriado fixo
+    fn dia_do_ano(texto: &str) -> Result<(u32, u32), ErroBiblioteca> {
+        let invalido = || {
+            ErroBiblioteca::DadosInvalidos(format!(
+                "Data de feriado inválida: {} (use MM-DD, ex.: 01-20).",
+                texto
+            ))
+        };
+        let (mes, dia) = texto.split_once('-').ok_or_else(invalido)?;
+        let mes = mes.parse::<u32>().map_err(|_| invalido())?;
+        let dia = dia.parse::<u32>().map_err(|_| invalido())?;
+        // 2024 é bissexto: aceita 02-29, que só cai nos anos bissextos
+        NaiveDate::from_ymd_opt(2024, mes, dia).ok_or_else(invalido)?;
+        Ok((mes, dia))
+    }
+
+    fn validar(&self) -> Result<(), ErroBiblioteca> {
+        if self.nome.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Todo feriado precisa de um nome.".to_string(),
+            ));
+        }
+        match (&self.data, self.pascoa) {
+            (Some(data), None) => Self::dia_do_ano(data).map(|_| ()),
+            (None, Some(dias)) if dias.abs() <= 200 => Ok(()),
+            (None, Some(dias)) => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "O feriado {} está a {} dias da Páscoa; use no máximo 200.",
+                self.nome, dias
+            ))),
+            _ => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "O feriado {} precisa de `data` (MM-DD) ou de `pascoa` (dias em relação à Páscoa), não dos dois.",
+                self.nome
+            ))),
+        }
+    }
+
+    fn no_ano(&self, ano: i32) -> Option<NaiveDate> {
+        match (&self.data, self.pascoa) {
+            (Some(data), _) => {
+                let (mes, dia) = Self::dia_do_ano(data).ok()?;
+                NaiveDate::from_ymd_opt(ano, mes, dia)
+            }
+            (None, Some(dias)) => deslocar(pascoa(ano)?, dias),
+            (None, None) => None,
+        }
+    }
+}
+
+// Período em que a biblioteca fica fechada, incluindo as duas pontas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fechamento {
+    pub de: NaiveDate,
+    pub ate: NaiveDate,
+    pub motivo: String,
+}
+
+fn deslocar(data: NaiveDate, dias: i64) -> Option<NaiveDate> {
+    if dias >= 0 {
+        data.checked_add_days(Days::new(dias.unsigned_abs()))
+    } else {
+        data.checked_sub_days(Days::new(dias.unsigned_abs()))
+    }
+}
+
+// Domingo de Páscoa no calendário gregoriano (algoritmo de Meeus/Jones/Butcher)
+pub fn pascoa(ano: i32) -> Option<NaiveDate> {
+    let a = ano.rem_euclid(19);
+    let b = ano.div_euclid(100);
+    let c = ano.rem_euclid(100);
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let mes = (h + l - 7 * m + 114) / 31;
+    let dia = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(ano, mes as u32, dia as u32)
+}
+
+fn funcionamento_padrao() -> Vec<DiaSemana> {
+    DiaSemana::TODOS[..6].to_vec()
+}
+
+fn sim() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calendario {
+    #[serde(default = "funcionamento_padrao")]
+    pub funcionamento: Vec<DiaSemana>,
+    #[serde(default = "sim")]
+    pub feriados_nacionais: bool,
+    #[serde(default)]
+    pub feriados: Vec<Feriado>,
+    #[serde(default)]
+    pub fechamentos: Vec<Fechamento>,
+}
+
+impl Default for Calendario {
+    fn default() -> Self {
+        Calendario {
+            funcionamento: funcionamento_padrao(),
+            feriados_nacionais: true,
+            feriados: Vec::new(),
+            fechamentos: Vec::new(),
+        }
+    }
+}
+
+impl Calendario {
+    // dados_biblioteca.json -> dados_biblioteca.calendario.json
+    pub fn caminho_calendario(caminho_dados: &Path) -> PathBuf {
+        caminho_dados.with_extension("calendario.json")
+    }
+
+    // Sem o arquivo, ele é criado a partir de `inicial`; com o arquivo, `inicial`
+    // é ignorado (veja `diverge_de`)
+    pub fn carregar(
+        caminho_dados: &Path,
+        inicial: Option<&Calendario>,
+    ) -> Result<Self, ErroBiblioteca> {
+        let caminho = Self::caminho_calendario(caminho_dados);
+        if !caminho.exists() {
+            let calendario = inicial.cloned().unwrap_or_default();
+            calendario.salvar(caminho_dados)?;
+            return Ok(calendario);
+        }
+        let texto = fs::read_to_string(&caminho).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao ler calendário: {}", e))
+        })?;
+        let calendario: Calendario = serde_json::from_str(&texto).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!(
+                "Calendário inválido em {}: {}",
+                caminho.display(),
+                e
+            ))
+        })?;
+        calendario.validar()?;
+        Ok(calendario)
+    }
+
+    // A configuração ainda traz um [calendario] diferente do que está em vigor:
+    // quem editou o TOML espera que ele valha, e não vale
+    pub fn diverge_de(&self, configurado: Option<&Calendario>) -> bool {
+        configurado.is_some_and(|configurado| configurado != self)
+    }
+
+    pub fn salvar(&self, caminho_dados: &Path) -> Result<(), ErroBiblioteca> {
+        self.validar()?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao serializar calendário: {}", e))
+        })?;
+        fs::write(Self::caminho_calendario(caminho_dados), json).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar calendário: {}", e))
+        })
+    }
+
+    pub fn validar(&self) -> Result<(), ErroBiblioteca> {
+        if self.funcionamento.is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "A biblioteca precisa abrir em pelo menos um dia da semana.".to_string(),
+            ));
+        }
+        for feriado in &self.feriados {
+            feriado.validar()?;
+        }
+        for fechamento in &self.fechamentos {
+            if fechamento.ate < fechamento.de {
+                return Err(ErroBiblioteca::DadosInvalidos(format!(
+                    "O fechamento \"{}\" termina ({}) antes de começar ({}).",
+                    fechamento.motivo, fechamento.ate, fechamento.de
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Feriados nacionais (se ligados) e os da biblioteca, em ordem de data
+    pub fn feriados_do_ano(&self, ano: i32) -> Vec<(NaiveDate, String)> {
+        let mut feriados = Vec::new();
+        if self.feriados_nacionais {
+            for (mes, dia, nome) in NACIONAIS_FIXOS {
+                if let Some(data) = NaiveDate::from_ymd_opt(ano, mes, dia) {
+                    feriados.push((data, nome.to_string()));
+                }
+            }
+            if let Some(pascoa) = pascoa(ano) {
+                for (dias, nome) in NACIONAIS_MOVEIS {
+                    if let Some(data) = deslocar(pascoa, dias) {
+                        feriados.push((data, nome.to_string()));
+                    }
+                }
+            }
+        }
+        for feriado in &self.feriados {
+            if let Some(data) = feriado.no_ano(ano) {
+                feriados.push((data, feriado.nome.clone()));
+            }
+        }
+        feriados.sort();
+        feriados
+    }
+
+    // Por que a biblioteca está fechada nesse dia (`None` se está aberta)
+    pub fn fechamento(&self, data: NaiveDate) -> Option<String> {
+        if let Some(fechamento) = self
+            .fechamentos
+            .iter()
+            .find(|f| f.de <= data && data <= f.ate)
+        {
+            return Some(fechamento.motivo.clone());
+        }
+        let dia = DiaSemana::de(data.weekday());
+        if !self.funcionamento.contains(&dia) {
+            return Some(format!("{} sem expediente", dia.nome()));
+        }
+        self.feriados_do_ano(data.year())
+            .into_iter()
+            .find(|(feriado, _)| *feriado == data)
+            .map(|(_, nome)| nome)
+    }
+
+    pub fn esta_aberta(&self, data: NaiveDate) -> bool {
+        self.fechamento(data).is_none()
+    }
+
+    // O próprio dia, se aberto, ou o primeiro dia aberto depois dele
+    pub fn proximo_dia_aberto(&self, data: NaiveDate) -> NaiveDate {
+        (0..LIMITE_BUSCA_DIAS)
+            .filter_map(|dias| data.checked_add_days(Days::new(dias)))
+            .find(|dia| self.esta_aberta(*dia))
+            .unwrap_or(data)
+    }
+
+    // Dias abertos depois do prazo até `data` (inclusive); 0 se não atrasou
+    pub fn dias_de_atraso(&self, prazo: NaiveDate, data: NaiveDate) -> i64 {
+        if data <= prazo {
+            return 0;
+        }
+        prazo
+            .iter_days()
+            .skip(1)
+            .take_while(|dia| *dia <= data)
+            .filter(|dia| self.esta_aberta(*dia))
+            .count() as i64
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn dia(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).unwrap()
+    }
+
+    fn feriado(calendario: &Calendario, data: NaiveDate) -> Option<String> {
+        calendario
+            .feriados_do_ano(data.year())
+            .into_iter()
+            .find(|(feriado, _)| *feriado == data)
+            .map(|(_, nome)| nome)
+    }
+
+    #[test]
+    fn pascoa_em_anos_conhecidos() {
+        assert_eq!(pascoa(2000), Some(dia(2000, 4, 23)));
+        assert_eq!(pascoa(2019), Some(dia(2019, 4, 21)));
+        assert_eq!(pascoa(2024), Some(dia(2024, 3, 31)));
+        assert_eq!(pascoa(2025), Some(dia(2025, 4, 20)));
+        // As datas extremas possíveis
+        assert_eq!(pascoa(1818), Some(dia(1818, 3, 22)));
+        assert_eq!(pascoa(2038), Some(dia(2038, 4, 25)));
+    }
+
+    #[test]
+    fn carnaval_sexta_santa_e_corpus_christi() {
+        let calendario = Calendario::default();
+        for (data, nome) in [
+            (dia(2024, 2, 12), "Carnaval"),
+            (dia(2024, 2, 13), "Carnaval"),
+            (dia(2024, 3, 29), "Sexta-feira Santa"),
+            (dia(2024, 5, 30), "Corpus Christi"),
+            (dia(2025, 3, 3), "Carnaval"),
+            (dia(2025, 3, 4), "Carnaval"),
+            (dia(2025, 6, 19), "Corpus Christi"),
+        ] {
+            assert_eq!(
+                feriado(&calendario, data).as_deref(),
+                Some(nome),
+                "{}",
+                data
+            );
+        }
+        assert_eq!(feriado(&calendario, dia(2025, 2, 12)), None);
+
+        let sem_nacionais = Calendario {
+            feriados_nacionais: false,
+            ..Calendario::default()
+        };
+        assert_eq!(feriado(&sem_nacionais, dia(2024, 5, 30)), None);
+    }
+
+    #[test]
+    fn atraso_conta_so_dias_abertos() {
+        let mut calendario = Calendario::default();
+        // Prazo numa quinta; sexta é Sexta-feira Santa e domingo não abre
+        let prazo = dia(2024, 3, 28);
+        assert_eq!(calendario.dias_de_atraso(prazo, prazo), 0);
+        assert_eq!(calendario.dias_de_atraso(prazo, dia(2024, 3, 27)), 0);
+        assert_eq!(calendario.dias_de_atraso(prazo, dia(2024, 3, 29)), 0);
+        assert_eq!(calendario.dias_de_atraso(prazo, dia(2024, 4, 1)), 2);
+
+        calendario.fechamentos.push(Fechamento {
+            de: dia(2024, 4, 1),
+            ate: dia(2024, 4, 3),
+            motivo: "Reforma".to_string(),
+        });
+        assert_eq!(calendario.dias_de_atraso(prazo, dia(2024, 4, 4)), 2);
+        assert_eq!(
+            calendario.proximo_dia_aberto(dia(2024, 3, 29)),
+            dia(2024, 3, 30)
+        );
+        assert_eq!(
+            calendario.proximo_dia_aberto(dia(2024, 3, 31)),
+            dia(2024, 4, 4)
+        );
+    }
+
+    #[test]
+    fn arquivo_nasce_da_configuracao_e_depois_prevalece() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let dados = pasta.path().join("dados.json");
+        let configurado = Calendario {
+            funcionamento: vec![DiaSemana::Seg, DiaSemana::Qua],
+            ..Calendario::default()
+        };
+
+        let calendario = Calendario::carregar(&dados, Some(&configurado)).expect("carregar");
+        assert_eq!(calendario, configurado);
+        assert!(Calendario::caminho_calendario(&dados).exists());
+        assert!(!calendario.diverge_de(Some(&configurado)));
+
+        // Mudar a configuração depois não altera o calendário em vigor
+        let editado = Calendario::default();
+        let calendario = Calendario::carregar(&dados, Some(&editado)).expect("carregar");
+        assert_eq!(calendario, configurado);
+        assert!(calendario.diverge_de(Some(&editado)));
+        assert!(!calendario.diverge_de(None));
+    }
+}
@@ -0,0 +1,66 @@
+// Datas de criação e da última alteração de cada entidade, para a colheita
+// incremental do catálogo. Registros gravados antes deste controle não têm as
+// datas; elas ficam fora do JSON enquanto vazias.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Carimbos {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub criado_em: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modificado_em: Option<DateTime<Local>>,
+}
+
+impl Carimbos {
+    pub fn em(momento: DateTime<Local>) -> Self {
+        Carimbos {
+            criado_em: Some(momento),
+            modificado_em: Some(momento),
+        }
+    }
+
+    pub fn tocar(&mut self, momento: DateTime<Local>) {
+        self.modificado_em = Some(momento);
+    }
+
+    pub fn ultima_alteracao(&self) -> Option<DateTime<Local>> {
+        self.modificado_em.or(self.criado_em)
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn alteracao_vale_mais_que_criacao() {
+        let criado = Local
+            .with_ymd_and_hms(2024, 3, 4, 12, 0, 0)
+            .single()
+            .expect("momento válido");
+        let mut carimbos = Carimbos::em(criado);
+        assert_eq!(carimbos.ultima_alteracao(), Some(criado));
+
+        carimbos.tocar(criado + Duration::days(1));
+        assert_eq!(carimbos.criado_em, Some(criado));
+        assert_eq!(
+            carimbos.ultima_alteracao(),
+            Some(criado + Duration::days(1))
+        );
+
+        // Registros antigos: sem datas, fora do JSON; só a criação também serve
+        assert_eq!(Carimbos::default().ultima_alteracao(), None);
+        assert_eq!(serde_json::to_string(&Carimbos::default()).unwrap(), "{}");
+        let antigo: Carimbos = serde_json::from_str("{}").unwrap();
+        assert_eq!(antigo, Carimbos::default());
+        let so_criado = Carimbos {
+            criado_em: Some(criado),
+            modificado_em: None,
+        };
+        assert_eq!(so_criado.ultima_alteracao(), Some(criado));
+    }
+}
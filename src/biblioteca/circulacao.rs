@@ -0,0 +1,374 @@
+// Circulação: saída e retorno de livros.
+// O status do livro é só um reflexo dos empréstimos ativos. Ele continua no JSON
+// e na ficha do livro, mas nunca é alterado à parte: toda mudança de empréstimo
+// passa por aqui, que valida antes e depois atualiza o empréstimo e o livro juntos.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, NaiveDate};
+use uuid::Uuid;
+
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+
+use super::{
+    emprestimos::{Emprestimo, StatusEmprestimo},
+    livros::{Livro, StatusLivro},
+};
+
+pub struct Circulacao<'a> {
+    livros: &'a mut HashMap<Uuid, Livro>,
+    emprestimos: &'a mut HashMap<Uuid, Emprestimo>,
+}
+
+impl<'a> Circulacao<'a> {
+    pub fn new(
+        livros: &'a mut HashMap<Uuid, Livro>,
+        emprestimos: &'a mut HashMap<Uuid, Emprestimo>,
+    ) -> Self {
+        Circulacao {
+            livros,
+            emprestimos,
+        }
+    }
+
+    // ---- Operações validadas (usadas pela Biblioteca) ----
+
+    pub fn saida(&mut self, emprestimo: Emprestimo) -> Result<(), ErroBiblioteca> {
+        let id_livro = emprestimo.get_id_livro();
+        let livro = self
+            .livros
+            .get(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+        if livro.arquivado {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro arquivado não pode ser emprestado!".to_string(),
+            ));
+        }
+        if !livro.esta_disponivel() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro já está emprestado!".to_string(),
+            ));
+        }
+        if livro.extraviado().is_some() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro marcado como extraviado; se foi achado, confira-o no inventário."
+                    .to_string(),
+            ));
+        }
+        if !emprestimo.esta_ativo() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O empréstimo não está ativo.".to_string(),
+            ));
+        }
+        if livro.em_transito().is_some() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O exemplar está em trânsito para a unidade dona; receba-o antes de emprestar."
+                    .to_string(),
+            ));
+        }
+        if let (Some(aqui), Some(onde)) = (emprestimo.get_unidade(), livro.get_local())
+            && aqui != onde
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O exemplar não está nesta unidade.".to_string(),
+            ));
+        }
+
+        self.abrir(emprestimo);
+        Ok(())
+    }
+
+    // Vale em qualquer unidade; fora da dona, o exemplar entra em trânsito
+    pub fn retorno(
+        &mut self,
+        id_emprestimo: Uuid,
+        unidade: Option<Uuid>,
+        momento: DateTime<Local>,
+    ) -> Result<Emprestimo, ErroBiblioteca> {
+        let mut emprestimo = self.ativo(id_emprestimo, "Livro já foi devolvido!")?;
+        emprestimo.definir_retorno(Some(momento.date_naive()), unidade);
+        emprestimo.carimbos.tocar(momento);
+        Ok(self.fechar(emprestimo))
+    }
+
+    // Apaga o empréstimo; se estava ativo, o livro volta a ficar disponível
+    pub fn cancelamento(&mut self, id_emprestimo: Uuid) -> Result<Emprestimo, ErroBiblioteca> {
+        self.descartar(id_emprestimo)
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))
+    }
+
+    // Desfaz uma devolução: o empréstimo volta a valer, se o livro ainda estiver livre
+    pub fn reabertura(
+        &mut self,
+        id_emprestimo: Uuid,
+        momento: DateTime<Local>,
+    ) -> Result<Emprestimo, ErroBiblioteca> {
+        let mut emprestimo = self
+            .emprestimos
+            .get(&id_emprestimo)
+            .cloned()
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))?;
+        if emprestimo.esta_ativo() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O empréstimo não está devolvido.".to_string(),
+            ));
+        }
+        if self
+            .livros
+            .get(&emprestimo.get_id_livro())
+            .is_some_and(|livro| !livro.esta_disponivel())
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O livro já foi emprestado de novo.".to_string(),
+            ));
+        }
+
+        emprestimo.status = StatusEmprestimo::Ativo;
+        emprestimo.definir_retorno(None, None);
+        emprestimo.carimbos.tocar(momento);
+        self.abrir(emprestimo.clone());
+        Ok(emprestimo)
+    }
+
+    pub fn ativo(&self, id_emprestimo: Uuid, mensagem: &str) -> Result<Emprestimo, ErroBiblioteca> {
+        let emprestimo = self
+            .emprestimos
+            .get(&id_emprestimo)
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))?;
+        if !emprestimo.esta_ativo() {
+            return Err(ErroBiblioteca::EstadoInvalido(mensagem.to_string()));
+        }
+        Ok(emprestimo.clone())
+    }
+
+    // ---- Aplicação direta (replay do diário, onde os eventos já são fatos) ----
+
+    pub fn abrir(&mut self, emprestimo: Emprestimo) {
+        self.refletir(emprestimo.get_id_livro(), StatusLivro::Emprestado);
+        self.mover(
+            emprestimo.get_id_livro(),
+            emprestimo.get_unidade(),
+            emprestimo.get_data_emprestimo(),
+        );
+        self.emprestimos.insert(emprestimo.id(), emprestimo);
+    }
+
+    pub fn fechar(&mut self, mut emprestimo: Emprestimo) -> Emprestimo {
+        emprestimo.status = StatusEmprestimo::Devolvido;
+        self.refletir(emprestimo.get_id_livro(), StatusLivro::Disponivel);
+        self.mover(
+            emprestimo.get_id_livro(),
+            emprestimo.get_unidade_devolucao(),
+            emprestimo
+                .get_data_retorno()
+                .unwrap_or(emprestimo.get_data_devolucao()),
+        );
+        self.emprestimos.insert(emprestimo.id(), emprestimo.clone());
+        emprestimo
+    }
+
+    pub fn descartar(&mut self, id_emprestimo: Uuid) -> Option<Emprestimo> {
+        let emprestimo = self.emprestimos.remove(&id_emprestimo)?;
+        if emprestimo.esta_ativo() {
+            self.refletir(emprestimo.get_id_livro(), StatusLivro::Disponivel);
+        }
+        Some(emprestimo)
+    }
+
+    // Recalcula o status do livro do zero, olhando todos os empréstimos
+    pub fn sincronizar(&mut self, id_livro: Uuid) {
+        let emprestado = self
+            .emprestimos
+            .values()
+            .any(|e| e.esta_ativo() && e.get_id_livro() == id_livro);
+        self.refletir(
+            id_livro,
+            if emprestado {
+                StatusLivro::Emprestado
+            } else {
+                StatusLivro::Disponivel
+            },
+        );
+    }
+
+    fn refletir(&mut self, id_livro: Uuid, status: StatusLivro) {
+        if let Some(livro) = self.livros.get_mut(&id_livro) {
+            livro.refletir(status);
+        }
+    }
+
+    // Como o status, a localização do exemplar acompanha a circulação
+    fn mover(&mut self, id_livro: Uuid, unidade: Option<Uuid>, desde: NaiveDate) {
+        if let Some(livro) = self.livros.get_mut(&id_livro) {
+            livro.localizar(unidade, desde);
+        }
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn momento() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap()
+    }
+
+    fn acervo() -> (HashMap<Uuid, Livro>, HashMap<Uuid, Emprestimo>, Uuid) {
+        let livro = Livro::new(
+            "Dom Casmurro".to_string(),
+            "Machado de Assis".to_string(),
+            1899,
+            momento(),
+        );
+        let id = livro.id();
+        (HashMap::from([(id, livro)]), HashMap::new(), id)
+    }
+
+    fn emprestimo(id_livro: Uuid, unidade: Option<Uuid>) -> Emprestimo {
+        let prazo = momento().date_naive() + chrono::Days::new(14);
+        Emprestimo::new(Uuid::new_v4(), id_livro, prazo, unidade, momento())
+    }
+
+    fn recusa(resultado: Result<(), ErroBiblioteca>, trecho: &str) {
+        match resultado {
+            Err(ErroBiblioteca::EstadoInvalido(mensagem)) => {
+                assert!(
+                    mensagem.contains(trecho),
+                    "mensagem inesperada: {}",
+                    mensagem
+                )
+            }
+            outro => panic!("esperava recusa com \"{}\", veio {:?}", trecho, outro),
+        }
+    }
+
+    #[test]
+    fn saida_empresta_e_recusa_livro_ja_emprestado() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        circulacao.saida(emprestimo(id, None)).unwrap();
+        recusa(circulacao.saida(emprestimo(id, None)), "já está emprestado");
+        assert_eq!(livros[&id].status(), StatusLivro::Emprestado);
+        assert_eq!(emprestimos.len(), 1);
+    }
+
+    #[test]
+    fn saida_recusa_arquivado_extraviado_e_em_transito() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        livros.get_mut(&id).unwrap().arquivado = true;
+        recusa(
+            Circulacao::new(&mut livros, &mut emprestimos).saida(emprestimo(id, None)),
+            "arquivado",
+        );
+
+        livros.get_mut(&id).unwrap().arquivado = false;
+        livros
+            .get_mut(&id)
+            .unwrap()
+            .marcar_extravio(true, momento());
+        recusa(
+            Circulacao::new(&mut livros, &mut emprestimos).saida(emprestimo(id, None)),
+            "extraviado",
+        );
+
+        let (dona, outra) = (Uuid::new_v4(), Uuid::new_v4());
+        let livro = livros.get_mut(&id).unwrap();
+        livro.marcar_extravio(false, momento());
+        livro.definir_unidade(Some(dona), momento());
+        livro.localizar(Some(outra), momento().date_naive());
+        recusa(
+            Circulacao::new(&mut livros, &mut emprestimos).saida(emprestimo(id, Some(outra))),
+            "em trânsito",
+        );
+        assert!(emprestimos.is_empty());
+    }
+
+    #[test]
+    fn saida_recusa_exemplar_de_outra_unidade() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        let dona = Uuid::new_v4();
+        livros
+            .get_mut(&id)
+            .unwrap()
+            .definir_unidade(Some(dona), momento());
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        recusa(
+            circulacao.saida(emprestimo(id, Some(Uuid::new_v4()))),
+            "não está nesta unidade",
+        );
+        circulacao.saida(emprestimo(id, Some(dona))).unwrap();
+    }
+
+    #[test]
+    fn retorno_libera_o_livro_e_nao_se_repete() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        let saida = emprestimo(id, None);
+        let id_emprestimo = saida.id();
+        circulacao.saida(saida).unwrap();
+
+        let devolvido = circulacao.retorno(id_emprestimo, None, momento()).unwrap();
+        assert!(!devolvido.esta_ativo());
+        assert_eq!(devolvido.get_data_retorno(), Some(momento().date_naive()));
+        recusa(
+            circulacao
+                .retorno(id_emprestimo, None, momento())
+                .map(|_| ()),
+            "já foi devolvido",
+        );
+        assert!(livros[&id].esta_disponivel());
+    }
+
+    #[test]
+    fn retorno_em_outra_unidade_poe_o_exemplar_em_transito() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        let (dona, outra) = (Uuid::new_v4(), Uuid::new_v4());
+        livros
+            .get_mut(&id)
+            .unwrap()
+            .definir_unidade(Some(dona), momento());
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        let saida = emprestimo(id, Some(dona));
+        let id_emprestimo = saida.id();
+        circulacao.saida(saida).unwrap();
+        circulacao
+            .retorno(id_emprestimo, Some(outra), momento())
+            .unwrap();
+
+        assert_eq!(livros[&id].get_local(), Some(outra));
+        assert_eq!(livros[&id].em_transito(), Some(momento().date_naive()));
+    }
+
+    #[test]
+    fn reabertura_so_com_o_livro_livre() {
+        let (mut livros, mut emprestimos, id) = acervo();
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        let primeiro = emprestimo(id, None);
+        let id_primeiro = primeiro.id();
+        circulacao.saida(primeiro).unwrap();
+        recusa(
+            circulacao.reabertura(id_primeiro, momento()).map(|_| ()),
+            "não está devolvido",
+        );
+
+        circulacao.retorno(id_primeiro, None, momento()).unwrap();
+        circulacao.saida(emprestimo(id, None)).unwrap();
+        recusa(
+            circulacao.reabertura(id_primeiro, momento()).map(|_| ()),
+            "emprestado de novo",
+        );
+
+        let (mut livros, mut emprestimos, id) = acervo();
+        let mut circulacao = Circulacao::new(&mut livros, &mut emprestimos);
+        let saida = emprestimo(id, None);
+        let id_emprestimo = saida.id();
+        circulacao.saida(saida).unwrap();
+        circulacao.retorno(id_emprestimo, None, momento()).unwrap();
+        let reaberto = circulacao.reabertura(id_emprestimo, momento()).unwrap();
+        assert!(reaberto.esta_ativo());
+        assert_eq!(reaberto.get_data_retorno(), None);
+        assert_eq!(livros[&id].status(), StatusLivro::Emprestado);
+    }
+}
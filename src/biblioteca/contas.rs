@@ -0,0 +1,591 @@
+// Contas dos funcionários que operam o sistema (não confundir com `Usuario`,
+// que são os leitores). Ficam num arquivo separado dos dados da biblioteca,
+// com as senhas guardadas só como hash Argon2.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::SystemTime,
+};
+
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ErroBiblioteca;
+
+use super::escrever_dados;
+
+pub const TAMANHO_MINIMO_SENHA: usize = 8;
+
+// Hash conferido quando o login não existe, para a resposta demorar o mesmo
+// que uma senha errada e não denunciar quais logins são válidos
+static HASH_FICTICIO: LazyLock<String> = LazyLock::new(|| {
+    let sal = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(b"login-inexistente", &sal)
+        .map(|hash| hash.to_string())
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Papel {
+    #[serde(rename = "admin")]
+    Administrador,
+    #[serde(rename = "bibliotecario")]
+    Bibliotecario,
+    #[serde(rename = "assistente")]
+    Assistente,
+}
+
+impl Papel {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto.trim().to_lowercase().as_str() {
+            "admin" | "administrador" => Ok(Papel::Administrador),
+            "bibliotecario" | "bibliotecário" => Ok(Papel::Bibliotecario),
+            "assistente" => Ok(Papel::Assistente),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Papel deve ser admin, bibliotecario ou assistente, não \"{}\".",
+                outro
+            ))),
+        }
+    }
+
+    pub fn permite(&self, permissao: Permissao) -> bool {
+        match self {
+            Papel::Administrador => true,
+            Papel::Bibliotecario => !matches!(
+                permissao,
+                Permissao::RemoverLivro
+                    | Permissao::RemoverUsuario
+                    | Permissao::RemoverEmprestimo
+                    | Permissao::Compactar
+                    | Permissao::RepararDados
+                    | Permissao::ConsultarAuditoria
+                    | Permissao::GerenciarContas
+            ),
+            Papel::Assistente => matches!(
+                permissao,
+                Permissao::CadastrarUsuario
+                    | Permissao::Emprestar
+                    | Permissao::Devolver
+                    | Permissao::Renovar
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Papel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Papel::Administrador => write!(f, "admin"),
+            Papel::Bibliotecario => write!(f, "bibliotecario"),
+            Papel::Assistente => write!(f, "assistente"),
+        }
+    }
+}
+
+// Tudo o que depende do papel de quem está operando
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permissao {
+    CadastrarLivro,
+    EditarLivro,
+    RemoverLivro,
+    CadastrarUsuario,
+    EditarUsuario,
+    RemoverUsuario,
+    Emprestar,
+    Devolver,
+    Renovar,
+    EditarEmprestimo,
+    RemoverEmprestimo,
+    Compactar,
+    RepararDados,
+    ConsultarAuditoria,
+    GerenciarContas,
+    ConfigurarCalendario,
+    GerenciarUnidades,
+    Intercambio,
+}
+
+impl fmt::Display for Permissao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let descricao = match self {
+            Permissao::CadastrarLivro => "cadastrar livros",
+            Permissao::EditarLivro => "editar livros",
+            Permissao::RemoverLivro => "remover livros",
+            Permissao::CadastrarUsuario => "cadastrar usuários",
+            Permissao::EditarUsuario => "editar usuários",
+            Permissao::RemoverUsuario => "remover usuários",
+            Permissao::Emprestar => "registrar empréstimos",
+            Permissao::Devolver => "registrar devoluções",
+            Permissao::Renovar => "renovar empréstimos",
+            Permissao::EditarEmprestimo => "editar empréstimos",
+            Permissao::RemoverEmprestimo => "remover empréstimos",
+            Permissao::Compactar => "compactar o diário",
+            Permissao::RepararDados => "reparar os dados",
+            Permissao::ConsultarAuditoria => "consultar a auditoria",
+            Permissao::GerenciarContas => "gerenciar contas de funcionários",
+            Permissao::ConfigurarCalendario => "configurar o calendário da biblioteca",
+            Permissao::GerenciarUnidades => "gerenciar unidades e transferir exemplares",
+            Permissao::Intercambio => "tratar do intercâmbio com bibliotecas parceiras",
+        };
+        write!(f, "{}", descricao)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conta {
+    pub login: String,
+    pub papel: Papel,
+    // Hash no formato PHC ($argon2id$...), já com o sal
+    senha: String,
+}
+
+// Quem entrou no sistema e com qual papel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sessao {
+    pub login: String,
+    pub papel: Papel,
+}
+
+impl Sessao {
+    pub fn exigir(&self, permissao: Permissao) -> Result<(), ErroBiblioteca> {
+        if self.papel.permite(permissao) {
+            Ok(())
+        } else {
+            Err(ErroBiblioteca::PermissaoNegada(format!(
+                "{} ({}) não pode {}",
+                self.login, self.papel, permissao
+            )))
+        }
+    }
+}
+
+fn gerar_hash(senha: &str) -> Result<String, ErroBiblioteca> {
+    if senha.chars().count() < TAMANHO_MINIMO_SENHA {
+        return Err(ErroBiblioteca::DadosInvalidos(format!(
+            "A senha deve ter pelo menos {} caracteres.",
+            TAMANHO_MINIMO_SENHA
+        )));
+    }
+
+    let sal = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(senha.as_bytes(), &sal)
+        .map(|hash| hash.to_string())
+        .map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao gerar hash da senha: {}", e))
+        })
+}
+
+fn validar_login(login: &str) -> Result<(), ErroBiblioteca> {
+    if login.is_empty() || login.chars().any(|c| c.is_whitespace() || c == ':') {
+        return Err(ErroBiblioteca::DadosInvalidos(
+            "O login não pode ser vazio nem conter espaços ou ':'.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub struct Contas {
+    caminho: PathBuf,
+    contas: BTreeMap<String, Conta>,
+}
+
+impl Contas {
+    // dados_biblioteca.json -> dados_biblioteca.contas.json
+    pub fn caminho_contas(caminho_dados: &Path) -> PathBuf {
+        caminho_dados.with_extension("contas.json")
+    }
+
+    pub fn carregar(caminho_dados: &Path) -> Result<Self, ErroBiblioteca> {
+        let mut contas = Self {
+            caminho: Self::caminho_contas(caminho_dados),
+            contas: BTreeMap::new(),
+        };
+        contas.recarregar()?;
+        Ok(contas)
+    }
+
+    // Lê de novo o arquivo, para quem fica muito tempo com as contas em memória
+    // (o servidor) ver o que outro processo mudou. Se falhar, nada muda.
+    pub fn recarregar(&mut self) -> Result<(), ErroBiblioteca> {
+        if !self.caminho.exists() {
+            self.contas.clear();
+            return Ok(());
+        }
+
+        let arquivo = File::open(&self.caminho).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir contas: {}", e))
+        })?;
+        let lista: Vec<Conta> = serde_json::from_reader(BufReader::new(arquivo))
+            .map_err(|e| ErroBiblioteca::ErroPersistencia(format!("Erro ao ler contas: {}", e)))?;
+
+        self.contas = lista.into_iter().map(|c| (c.login.clone(), c)).collect();
+        Ok(())
+    }
+
+    // Quando o arquivo de contas foi alterado pela última vez (None se ainda não existe)
+    pub fn versao(&self) -> Option<SystemTime> {
+        fs::metadata(&self.caminho).and_then(|m| m.modified()).ok()
+    }
+
+    // As senhas só existem neste arquivo: nunca gravar por cima pela metade
+    fn salvar(&self) -> Result<(), ErroBiblioteca> {
+        let lista: Vec<&Conta> = self.contas.values().collect();
+        escrever_dados(&self.caminho, &lista)
+    }
+
+    // Troca (ou, com None, tira) a conta e grava. Se a gravação falhar, a conta
+    // volta a ser como era: o servidor fica com as contas em memória e não pode
+    // continuar aceitando o que não está no arquivo.
+    fn gravar_conta(
+        &mut self,
+        login: &str,
+        conta: Option<Conta>,
+    ) -> Result<Option<Conta>, ErroBiblioteca> {
+        let anterior = match conta {
+            Some(conta) => self.contas.insert(login.to_string(), conta),
+            None => self.contas.remove(login),
+        };
+        if let Err(e) = self.salvar() {
+            match &anterior {
+                Some(conta) => self.contas.insert(login.to_string(), conta.clone()),
+                None => self.contas.remove(login),
+            };
+            return Err(e);
+        }
+        Ok(anterior)
+    }
+
+    pub fn esta_vazia(&self) -> bool {
+        self.contas.is_empty()
+    }
+
+    pub fn listar(&self) -> impl Iterator<Item = &Conta> {
+        self.contas.values()
+    }
+
+    pub fn autenticar(&self, login: &str, senha: &str) -> Result<Sessao, ErroBiblioteca> {
+        let Some(conta) = self.contas.get(login) else {
+            if let Ok(hash) = PasswordHash::new(&HASH_FICTICIO) {
+                let _ = Argon2::default().verify_password(senha.as_bytes(), &hash);
+            }
+            return Err(ErroBiblioteca::CredenciaisInvalidas);
+        };
+
+        let hash = PasswordHash::new(&conta.senha).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!(
+                "Hash de senha inválido para {}: {}",
+                login, e
+            ))
+        })?;
+        Argon2::default()
+            .verify_password(senha.as_bytes(), &hash)
+            .map_err(|_| ErroBiblioteca::CredenciaisInvalidas)?;
+
+        Ok(Sessao {
+            login: conta.login.clone(),
+            papel: conta.papel,
+        })
+    }
+
+    fn inserir(&mut self, login: &str, senha: &str, papel: Papel) -> Result<(), ErroBiblioteca> {
+        validar_login(login)?;
+        if self.contas.contains_key(login) {
+            return Err(ErroBiblioteca::EstadoInvalido(format!(
+                "Já existe uma conta com o login {}.",
+                login
+            )));
+        }
+
+        let conta = Conta {
+            login: login.to_string(),
+            papel,
+            senha: gerar_hash(senha)?,
+        };
+        self.gravar_conta(login, Some(conta)).map(|_| ())
+    }
+
+    // Só vale enquanto não há nenhuma conta: é assim que o sistema é configurado da primeira vez
+    pub fn criar_primeiro_admin(&mut self, login: &str, senha: &str) -> Result<(), ErroBiblioteca> {
+        if !self.esta_vazia() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Já existem contas cadastradas.".to_string(),
+            ));
+        }
+        self.inserir(login, senha, Papel::Administrador)
+    }
+
+    pub fn criar(
+        &mut self,
+        sessao: &Sessao,
+        login: &str,
+        senha: &str,
+        papel: Papel,
+    ) -> Result<(), ErroBiblioteca> {
+        sessao.exigir(Permissao::GerenciarContas)?;
+        self.inserir(login, senha, papel)
+    }
+
+    pub fn remover(&mut self, sessao: &Sessao, login: &str) -> Result<Conta, ErroBiblioteca> {
+        sessao.exigir(Permissao::GerenciarContas)?;
+
+        let conta = self
+            .contas
+            .get(login)
+            .ok_or_else(|| ErroBiblioteca::ContaNaoEncontrada(login.to_string()))?;
+        let admins = self
+            .contas
+            .values()
+            .filter(|c| c.papel == Papel::Administrador)
+            .count();
+        if conta.papel == Papel::Administrador && admins == 1 {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Não é possível remover o último administrador.".to_string(),
+            ));
+        }
+
+        let removida = self.gravar_conta(login, None)?;
+        Ok(removida.expect("conta verificada acima"))
+    }
+
+    // Cada um pode trocar a própria senha; a dos outros só quem gerencia contas
+    pub fn alterar_senha(
+        &mut self,
+        sessao: &Sessao,
+        login: &str,
+        senha: &str,
+    ) -> Result<(), ErroBiblioteca> {
+        if sessao.login != login {
+            sessao.exigir(Permissao::GerenciarContas)?;
+        }
+
+        let hash = gerar_hash(senha)?;
+        let conta = self
+            .contas
+            .get(login)
+            .ok_or_else(|| ErroBiblioteca::ContaNaoEncontrada(login.to_string()))?;
+        let conta = Conta {
+            senha: hash,
+            ..conta.clone()
+        };
+        self.gravar_conta(login, Some(conta)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const SENHA: &str = "segredo123";
+
+    // Contas num diretório temporário, já com o administrador criado
+    fn com_admin() -> (Contas, Sessao, TempDir) {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut contas = Contas::carregar(&pasta.path().join("dados.json")).expect("contas");
+        contas.criar_primeiro_admin("admin", SENHA).expect("admin");
+        let admin = contas.autenticar("admin", SENHA).expect("login do admin");
+        (contas, admin, pasta)
+    }
+
+    #[test]
+    fn cada_papel_tem_suas_permissoes() {
+        let todas = [
+            Permissao::CadastrarLivro,
+            Permissao::EditarLivro,
+            Permissao::RemoverLivro,
+            Permissao::CadastrarUsuario,
+            Permissao::EditarUsuario,
+            Permissao::RemoverUsuario,
+            Permissao::Emprestar,
+            Permissao::Devolver,
+            Permissao::Renovar,
+            Permissao::EditarEmprestimo,
+            Permissao::RemoverEmprestimo,
+            Permissao::Compactar,
+            Permissao::RepararDados,
+            Permissao::ConsultarAuditoria,
+            Permissao::GerenciarContas,
+            Permissao::ConfigurarCalendario,
+            Permissao::GerenciarUnidades,
+            Permissao::Intercambio,
+        ];
+        let permitidas = |papel: Papel| {
+            todas
+                .into_iter()
+                .filter(|p| papel.permite(*p))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(permitidas(Papel::Administrador), todas);
+        assert_eq!(
+            permitidas(Papel::Assistente),
+            [
+                Permissao::CadastrarUsuario,
+                Permissao::Emprestar,
+                Permissao::Devolver,
+                Permissao::Renovar,
+            ]
+        );
+        assert_eq!(
+            permitidas(Papel::Bibliotecario),
+            [
+                Permissao::CadastrarLivro,
+                Permissao::EditarLivro,
+                Permissao::CadastrarUsuario,
+                Permissao::EditarUsuario,
+                Permissao::Emprestar,
+                Permissao::Devolver,
+                Permissao::Renovar,
+                Permissao::EditarEmprestimo,
+                Permissao::ConfigurarCalendario,
+                Permissao::GerenciarUnidades,
+                Permissao::Intercambio,
+            ]
+        );
+    }
+
+    #[test]
+    fn ultimo_administrador_nao_pode_ser_removido() {
+        let (mut contas, admin, _pasta) = com_admin();
+        assert!(matches!(
+            contas.remover(&admin, "admin"),
+            Err(ErroBiblioteca::EstadoInvalido(_))
+        ));
+
+        // Com outro administrador, pode
+        contas
+            .criar(&admin, "chefe", SENHA, Papel::Administrador)
+            .expect("segundo admin");
+        assert_eq!(
+            contas.remover(&admin, "admin").expect("remoção").login,
+            "admin"
+        );
+        assert!(matches!(
+            contas.remover(&admin, "admin"),
+            Err(ErroBiblioteca::ContaNaoEncontrada(_))
+        ));
+    }
+
+    #[test]
+    fn senha_alheia_so_com_gerencia_de_contas() {
+        let (mut contas, admin, _pasta) = com_admin();
+        contas
+            .criar(&admin, "ana", SENHA, Papel::Bibliotecario)
+            .expect("bibliotecária");
+        contas
+            .criar(&admin, "balcao", SENHA, Papel::Assistente)
+            .expect("assistente");
+        let ana = contas.autenticar("ana", SENHA).expect("login da ana");
+
+        assert!(matches!(
+            contas.alterar_senha(&ana, "balcao", "outra-senha"),
+            Err(ErroBiblioteca::PermissaoNegada(_))
+        ));
+        assert!(contas.autenticar("balcao", SENHA).is_ok());
+        assert!(matches!(
+            contas.criar(&ana, "novo", SENHA, Papel::Assistente),
+            Err(ErroBiblioteca::PermissaoNegada(_))
+        ));
+
+        // A própria senha cada um troca
+        contas
+            .alterar_senha(&ana, "ana", "outra-senha")
+            .expect("própria senha");
+        assert!(contas.autenticar("ana", "outra-senha").is_ok());
+
+        contas
+            .alterar_senha(&admin, "balcao", "outra-senha")
+            .expect("admin troca a senha alheia");
+        assert!(contas.autenticar("balcao", "outra-senha").is_ok());
+        assert!(matches!(
+            contas.alterar_senha(&admin, "ninguem", "outra-senha"),
+            Err(ErroBiblioteca::ContaNaoEncontrada(_))
+        ));
+    }
+
+    #[test]
+    fn senha_errada_ou_login_inexistente_nao_entram() {
+        let (contas, _admin, _pasta) = com_admin();
+        assert!(matches!(
+            contas.autenticar("admin", "senha-errada"),
+            Err(ErroBiblioteca::CredenciaisInvalidas)
+        ));
+        assert!(matches!(
+            contas.autenticar("ninguem", SENHA),
+            Err(ErroBiblioteca::CredenciaisInvalidas)
+        ));
+        let sessao = contas.autenticar("admin", SENHA).expect("login");
+        assert_eq!(sessao.papel, Papel::Administrador);
+    }
+
+    #[test]
+    fn senha_curta_e_recusada() {
+        let (mut contas, admin, _pasta) = com_admin();
+        let curta = "a".repeat(TAMANHO_MINIMO_SENHA - 1);
+        assert!(matches!(
+            contas.criar(&admin, "ana", &curta, Papel::Bibliotecario),
+            Err(ErroBiblioteca::DadosInvalidos(_))
+        ));
+        assert!(contas.autenticar("ana", &curta).is_err());
+
+        let minima = "a".repeat(TAMANHO_MINIMO_SENHA);
+        contas
+            .criar(&admin, "ana", &minima, Papel::Bibliotecario)
+            .expect("senha do tamanho mínimo");
+    }
+
+    #[test]
+    fn primeiro_admin_so_sem_contas() {
+        let (mut contas, _admin, pasta) = com_admin();
+        assert!(matches!(
+            contas.criar_primeiro_admin("outro", SENHA),
+            Err(ErroBiblioteca::EstadoInvalido(_))
+        ));
+        assert!(contas.autenticar("outro", SENHA).is_err());
+
+        // O que foi gravado vale para o próximo processo
+        let relidas = Contas::carregar(&pasta.path().join("dados.json")).expect("contas");
+        assert_eq!(relidas.listar().count(), 1);
+        assert!(relidas.autenticar("admin", SENHA).is_ok());
+    }
+
+    #[test]
+    fn falha_ao_gravar_nao_muda_as_contas_em_memoria() {
+        let (mut contas, admin, pasta) = com_admin();
+        contas
+            .criar(&admin, "balcao", SENHA, Papel::Assistente)
+            .expect("assistente");
+
+        // O arquivo temporário não pode ser criado: toda gravação falha
+        fs::create_dir(pasta.path().join("dados.contas.json.tmp")).expect("bloquear");
+
+        assert!(
+            contas
+                .criar(&admin, "novo", SENHA, Papel::Bibliotecario)
+                .is_err()
+        );
+        assert!(contas.autenticar("novo", SENHA).is_err());
+
+        assert!(contas.remover(&admin, "balcao").is_err());
+        assert!(contas.autenticar("balcao", SENHA).is_ok());
+
+        assert!(
+            contas
+                .alterar_senha(&admin, "balcao", "outra-senha")
+                .is_err()
+        );
+        assert!(contas.autenticar("balcao", SENHA).is_ok());
+        assert!(contas.autenticar("balcao", "outra-senha").is_err());
+    }
+}
@@ -0,0 +1,91 @@
+// Pilhas de desfazer/refazer das operações da sessão atual.
+// Valem até o próximo salvamento explícito: depois disso, o que foi salvo fica.
+
+use std::fmt;
+
+use super::{diario::Evento, emprestimos::Emprestimo, livros::Livro, usuarios::Usuario};
+
+// Cada comando guarda a entidade como ficou depois da operação,
+// o suficiente para desfazer e para refazer exatamente igual
+#[derive(Debug, Clone)]
+pub enum Comando {
+    AdicionarLivro(Livro),
+    AdicionarUsuario(Usuario),
+    RegistrarEmprestimo(Emprestimo),
+    RegistrarDevolucao(Emprestimo),
+}
+
+impl Comando {
+    // Só estas operações entram na pilha; as demais não podem ser desfeitas
+    pub fn do_evento(evento: &Evento) -> Option<Self> {
+        match evento {
+            Evento::LivroAdicionado(livro) => Some(Comando::AdicionarLivro(livro.clone())),
+            Evento::UsuarioAdicionado(usuario) => Some(Comando::AdicionarUsuario(usuario.clone())),
+            Evento::EmprestimoRealizado(emprestimo) => {
+                Some(Comando::RegistrarEmprestimo(emprestimo.clone()))
+            }
+            Evento::EmprestimoDevolvido(emprestimo) => {
+                Some(Comando::RegistrarDevolucao(emprestimo.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Comando {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Comando::AdicionarLivro(livro) => {
+                write!(f, "adicionar o livro \"{}\"", livro.get_titulo())
+            }
+            Comando::AdicionarUsuario(usuario) => {
+                write!(f, "adicionar o usuário \"{}\"", usuario.get_nome())
+            }
+            Comando::RegistrarEmprestimo(_) => write!(f, "registrar o empréstimo"),
+            Comando::RegistrarDevolucao(_) => write!(f, "registrar a devolução"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PilhaDesfazer {
+    desfazer: Vec<Comando>,
+    refazer: Vec<Comando>,
+}
+
+impl PilhaDesfazer {
+    // Uma operação nova invalida o que havia para refazer
+    pub fn registrar(&mut self, comando: Comando) {
+        self.desfazer.push(comando);
+        self.refazer.clear();
+    }
+
+    pub fn limpar(&mut self) {
+        self.desfazer.clear();
+        self.refazer.clear();
+    }
+
+    pub fn proximo_desfazer(&self) -> Option<&Comando> {
+        self.desfazer.last()
+    }
+
+    pub fn proximo_refazer(&self) -> Option<&Comando> {
+        self.refazer.last()
+    }
+
+    pub(super) fn retirar_desfazer(&mut self) -> Option<Comando> {
+        self.desfazer.pop()
+    }
+
+    pub(super) fn retirar_refazer(&mut self) -> Option<Comando> {
+        self.refazer.pop()
+    }
+
+    pub(super) fn devolver_desfazer(&mut self, comando: Comando) {
+        self.desfazer.push(comando);
+    }
+
+    pub(super) fn devolver_refazer(&mut self, comando: Comando) {
+        self.refazer.push(comando);
+    }
+}
@@ -0,0 +1,376 @@
+// Diário de eventos: toda operação que altera a biblioteca vira uma linha JSON
+// num arquivo que só cresce. O arquivo de dados é apenas um retrato (snapshot)
+// do estado até certo evento; o resto é reaplicado a partir daqui ao carregar.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+
+use super::{
+    DadosPersistencia,
+    circulacao::Circulacao,
+    emprestimos::Emprestimo,
+    intercambio::{Parceira, Solicitacao},
+    livros::Livro,
+    unidades::Unidade,
+    usuarios::Usuario,
+};
+
+// Os eventos guardam a entidade inteira já alterada, então reaplicar é só sobrescrever
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "tipo", content = "dados")]
+pub enum Evento {
+    // Estado que já existia quando o diário foi criado
+    EstadoImportado {
+        livros: HashMap<Uuid, Livro>,
+        usuarios: HashMap<Uuid, Usuario>,
+        emprestimos: HashMap<Uuid, Emprestimo>,
+    },
+    // Estado inteiro substituído pelo reparo de integridade
+    DadosReparados {
+        problemas: Vec<String>,
+        livros: HashMap<Uuid, Livro>,
+        usuarios: HashMap<Uuid, Usuario>,
+        emprestimos: HashMap<Uuid, Emprestimo>,
+    },
+    LivroAdicionado(Livro),
+    LivroAtualizado(Livro),
+    LivroRemovido(Uuid),
+    LivroArquivado(Livro),
+    UsuarioAdicionado(Usuario),
+    UsuarioAtualizado(Usuario),
+    UsuarioRemovido(Uuid),
+    UsuarioArquivado(Usuario),
+    EmprestimoRealizado(Emprestimo),
+    EmprestimoDevolvido(Emprestimo),
+    EmprestimoRenovado(Emprestimo),
+    EmprestimoAtualizado(Emprestimo),
+    // Desfazer (ou remover) um empréstimo o apaga; desfazer uma devolução reabre o empréstimo
+    EmprestimoCancelado(Emprestimo),
+    DevolucaoDesfeita(Emprestimo),
+    UnidadeAdicionada(Unidade),
+    UnidadeAtualizada(Unidade),
+    UnidadeRemovida(Uuid),
+    ParceiraAdicionada(Parceira),
+    SolicitacaoRegistrada(Solicitacao),
+    // Cada passo do intercâmbio (envio, chegada, empréstimo, devolução à parceira)
+    SolicitacaoAtualizada(Solicitacao),
+    // Operações de uma transação: ficam numa linha só do diário, valem todas ou nenhuma
+    Lote(Vec<Evento>),
+}
+
+impl Evento {
+    pub fn nome(&self) -> &'static str {
+        match self {
+            Evento::EstadoImportado { .. } => "EstadoImportado",
+            Evento::DadosReparados { .. } => "DadosReparados",
+            Evento::LivroAdicionado(_) => "LivroAdicionado",
+            Evento::LivroAtualizado(_) => "LivroAtualizado",
+            Evento::LivroRemovido(_) => "LivroRemovido",
+            Evento::LivroArquivado(_) => "LivroArquivado",
+            Evento::UsuarioAdicionado(_) => "UsuarioAdicionado",
+            Evento::UsuarioAtualizado(_) => "UsuarioAtualizado",
+            Evento::UsuarioRemovido(_) => "UsuarioRemovido",
+            Evento::UsuarioArquivado(_) => "UsuarioArquivado",
+            Evento::EmprestimoRealizado(_) => "EmprestimoRealizado",
+            Evento::EmprestimoDevolvido(_) => "EmprestimoDevolvido",
+            Evento::EmprestimoRenovado(_) => "EmprestimoRenovado",
+            Evento::EmprestimoAtualizado(_) => "EmprestimoAtualizado",
+            Evento::EmprestimoCancelado(_) => "EmprestimoCancelado",
+            Evento::DevolucaoDesfeita(_) => "DevolucaoDesfeita",
+            Evento::UnidadeAdicionada(_) => "UnidadeAdicionada",
+            Evento::UnidadeAtualizada(_) => "UnidadeAtualizada",
+            Evento::UnidadeRemovida(_) => "UnidadeRemovida",
+            Evento::ParceiraAdicionada(_) => "ParceiraAdicionada",
+            Evento::SolicitacaoRegistrada(_) => "SolicitacaoRegistrada",
+            Evento::SolicitacaoAtualizada(_) => "SolicitacaoAtualizada",
+            Evento::Lote(_) => "Lote",
+        }
+    }
+
+    // Todas as entidades envolvidas; num empréstimo, também o livro e o usuário
+    pub fn entidades(&self) -> Vec<Uuid> {
+        match self {
+            Evento::EstadoImportado { .. } | Evento::DadosReparados { .. } => Vec::new(),
+            Evento::LivroAdicionado(livro)
+            | Evento::LivroAtualizado(livro)
+            | Evento::LivroArquivado(livro) => vec![livro.id()],
+            Evento::UsuarioAdicionado(usuario)
+            | Evento::UsuarioAtualizado(usuario)
+            | Evento::UsuarioArquivado(usuario) => vec![usuario.id],
+            Evento::UnidadeAdicionada(unidade) | Evento::UnidadeAtualizada(unidade) => {
+                vec![unidade.id]
+            }
+            Evento::ParceiraAdicionada(parceira) => vec![parceira.id],
+            Evento::SolicitacaoRegistrada(solicitacao)
+            | Evento::SolicitacaoAtualizada(solicitacao) => {
+                vec![solicitacao.id, solicitacao.id_usuario]
+            }
+            Evento::LivroRemovido(id)
+            | Evento::UsuarioRemovido(id)
+            | Evento::UnidadeRemovida(id) => vec![*id],
+            Evento::EmprestimoRealizado(emprestimo)
+            | Evento::EmprestimoDevolvido(emprestimo)
+            | Evento::EmprestimoRenovado(emprestimo)
+            | Evento::EmprestimoAtualizado(emprestimo)
+            | Evento::EmprestimoCancelado(emprestimo)
+            | Evento::DevolucaoDesfeita(emprestimo) => vec![
+                emprestimo.id(),
+                emprestimo.get_id_livro(),
+                emprestimo.get_id_usuario(),
+            ],
+            Evento::Lote(eventos) => {
+                let mut entidades = Vec::new();
+                for id in eventos.iter().flat_map(Evento::entidades) {
+                    if !entidades.contains(&id) {
+                        entidades.push(id);
+                    }
+                }
+                entidades
+            }
+        }
+    }
+
+    // Os eventos individuais, na ordem em que aconteceram (um lote vira vários)
+    pub fn desdobrar(self) -> Vec<Evento> {
+        match self {
+            Evento::Lote(eventos) => eventos.into_iter().flat_map(Evento::desdobrar).collect(),
+            evento => vec![evento],
+        }
+    }
+}
+
+// Quem fez a operação; registros anteriores ao controle de operadores não têm essa informação
+pub const OPERADOR_DESCONHECIDO: &str = "desconhecido";
+
+fn operador_desconhecido() -> String {
+    OPERADOR_DESCONHECIDO.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistroEvento {
+    pub sequencia: u64,
+    pub momento: DateTime<Local>,
+    #[serde(default = "operador_desconhecido")]
+    pub operador: String,
+    pub evento: Evento,
+}
+
+pub(super) fn aplicar(dados: &mut DadosPersistencia, evento: &Evento) {
+    match evento.clone() {
+        Evento::EstadoImportado {
+            livros,
+            usuarios,
+            emprestimos,
+        }
+        | Evento::DadosReparados {
+            livros,
+            usuarios,
+            emprestimos,
+            ..
+        } => {
+            dados.livros = livros;
+            dados.usuarios = usuarios;
+            dados.emprestimos = emprestimos;
+        }
+        Evento::LivroAdicionado(livro)
+        | Evento::LivroAtualizado(livro)
+        | Evento::LivroArquivado(livro) => {
+            dados.livros.insert(livro.id(), livro);
+        }
+        Evento::LivroRemovido(id) => {
+            dados.livros.remove(&id);
+        }
+        Evento::UsuarioAdicionado(usuario)
+        | Evento::UsuarioAtualizado(usuario)
+        | Evento::UsuarioArquivado(usuario) => {
+            dados.usuarios.insert(usuario.id, usuario);
+        }
+        Evento::UsuarioRemovido(id) => {
+            dados.usuarios.remove(&id);
+        }
+        Evento::EmprestimoRealizado(emprestimo) | Evento::DevolucaoDesfeita(emprestimo) => {
+            Circulacao::new(&mut dados.livros, &mut dados.emprestimos).abrir(emprestimo);
+        }
+        Evento::EmprestimoDevolvido(emprestimo) => {
+            Circulacao::new(&mut dados.livros, &mut dados.emprestimos).fechar(emprestimo);
+        }
+        Evento::EmprestimoRenovado(emprestimo) | Evento::EmprestimoAtualizado(emprestimo) => {
+            dados.emprestimos.insert(emprestimo.id(), emprestimo);
+        }
+        Evento::EmprestimoCancelado(emprestimo) => {
+            Circulacao::new(&mut dados.livros, &mut dados.emprestimos).descartar(emprestimo.id());
+        }
+        Evento::UnidadeAdicionada(unidade) | Evento::UnidadeAtualizada(unidade) => {
+            dados.unidades.insert(unidade.id, unidade);
+        }
+        Evento::UnidadeRemovida(id) => {
+            dados.unidades.remove(&id);
+        }
+        Evento::ParceiraAdicionada(parceira) => {
+            dados.parceiras.insert(parceira.id, parceira);
+        }
+        Evento::SolicitacaoRegistrada(solicitacao) | Evento::SolicitacaoAtualizada(solicitacao) => {
+            dados.solicitacoes.insert(solicitacao.id, solicitacao);
+        }
+        Evento::Lote(eventos) => {
+            for evento in &eventos {
+                aplicar(dados, evento);
+            }
+        }
+    }
+}
+
+// dados_biblioteca.json -> dados_biblioteca.diario.jsonl
+pub fn caminho_diario(caminho_dados: &Path) -> PathBuf {
+    caminho_dados.with_extension("diario.jsonl")
+}
+
+// Trecho do diário guardado numa compactação, nomeado pelo último evento que contém
+fn caminho_arquivado(caminho_dados: &Path, ultima_sequencia: u64) -> PathBuf {
+    caminho_dados.with_extension(format!("diario.{:08}.jsonl", ultima_sequencia))
+}
+
+pub(super) fn anexar(
+    caminho_dados: &Path,
+    registros: &[RegistroEvento],
+) -> Result<(), ErroBiblioteca> {
+    if registros.is_empty() {
+        return Ok(());
+    }
+
+    let erro = |e: std::io::Error| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar diário de eventos: {}", e))
+    };
+
+    let mut arquivo = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(caminho_diario(caminho_dados))
+        .map_err(erro)?;
+
+    let mut linhas = String::new();
+    for registro in registros {
+        let linha = serde_json::to_string(registro).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao serializar evento: {}", e))
+        })?;
+        linhas.push_str(&linha);
+        linhas.push('\n');
+    }
+
+    arquivo.write_all(linhas.as_bytes()).map_err(erro)?;
+    arquivo.sync_data().map_err(erro)
+}
+
+fn ler_arquivo(caminho: &Path) -> Result<Vec<RegistroEvento>, ErroBiblioteca> {
+    if !caminho.exists() {
+        return Ok(Vec::new());
+    }
+
+    let arquivo = File::open(caminho).map_err(|e| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir diário de eventos: {}", e))
+    })?;
+
+    let linhas: Vec<String> = BufReader::new(arquivo)
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao ler diário de eventos: {}", e))
+        })?;
+
+    let mut registros = Vec::new();
+    for (numero, linha) in linhas.iter().enumerate() {
+        if linha.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(linha) {
+            Ok(registro) => registros.push(registro),
+            // Última linha pela metade: o programa caiu no meio da escrita
+            Err(_) if numero + 1 == linhas.len() => break,
+            Err(e) => {
+                return Err(ErroBiblioteca::ErroPersistencia(format!(
+                    "Diário de eventos {} corrompido na linha {}: {}",
+                    caminho.display(),
+                    numero + 1,
+                    e
+                )));
+            }
+        }
+    }
+
+    Ok(registros)
+}
+
+// Eventos ainda não compactados (os que o snapshot atual pode não conter)
+pub(super) fn ler_atual(caminho_dados: &Path) -> Result<Vec<RegistroEvento>, ErroBiblioteca> {
+    ler_arquivo(&caminho_diario(caminho_dados))
+}
+
+// Todo o histórico, dos trechos arquivados mais antigos até o diário atual
+pub(super) fn ler_historico(caminho_dados: &Path) -> Result<Vec<RegistroEvento>, ErroBiblioteca> {
+    let mut registros = Vec::new();
+    for caminho in trechos_arquivados(caminho_dados)? {
+        registros.extend(ler_arquivo(&caminho)?);
+    }
+    registros.extend(ler_atual(caminho_dados)?);
+    Ok(registros)
+}
+
+pub(super) fn existe_historico(caminho_dados: &Path) -> Result<bool, ErroBiblioteca> {
+    Ok(caminho_diario(caminho_dados).exists() || !trechos_arquivados(caminho_dados)?.is_empty())
+}
+
+fn trechos_arquivados(caminho_dados: &Path) -> Result<Vec<PathBuf>, ErroBiblioteca> {
+    let pasta = match caminho_dados.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let prefixo = match caminho_dados.file_stem() {
+        Some(nome) => format!("{}.diario.", nome.to_string_lossy()),
+        None => return Ok(Vec::new()),
+    };
+    if !pasta.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entradas = fs::read_dir(&pasta).map_err(|e| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao listar {}: {}", pasta.display(), e))
+    })?;
+
+    let mut trechos: Vec<(u64, PathBuf)> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .filter_map(|entrada| {
+            let nome = entrada.file_name().to_string_lossy().into_owned();
+            let sequencia = nome
+                .strip_prefix(&prefixo)?
+                .strip_suffix(".jsonl")?
+                .parse::<u64>()
+                .ok()?;
+            Some((sequencia, entrada.path()))
+        })
+        .collect();
+
+    trechos.sort();
+    Ok(trechos.into_iter().map(|(_, caminho)| caminho).collect())
+}
+
+// Move o diário atual para um trecho arquivado; o próximo evento começa um diário novo
+pub(super) fn arquivar(caminho_dados: &Path, ultima_sequencia: u64) -> Result<(), ErroBiblioteca> {
+    let atual = caminho_diario(caminho_dados);
+    if !atual.exists() {
+        return Ok(());
+    }
+
+    fs::rename(&atual, caminho_arquivado(caminho_dados, ultima_sequencia)).map_err(|e| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao arquivar diário de eventos: {}", e))
+    })
+}
@@ -1,10 +1,15 @@
 use std::fmt;
 
-use crate::traits::Identificavel;
-use chrono::{Duration, Local, NaiveDate};
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::carimbos::Carimbos;
+
+// Quantos dias o usuário fica com o livro (também vale para cada renovação)
+pub const PRAZO_EMPRESTIMO_DIAS: i64 = 14;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 
 pub enum StatusEmprestimo {
@@ -20,26 +25,102 @@ pub struct Emprestimo {
     id_usuario: Uuid,
     data_emprestimo: NaiveDate,
     data_devolucao: NaiveDate,
+    // Dia em que o livro voltou; devoluções anteriores a este controle não têm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data_retorno: Option<NaiveDate>,
+    // Unidades onde o livro saiu e onde voltou (sem unidades cadastradas, nenhuma)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unidade: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unidade_devolucao: Option<Uuid>,
     pub status: StatusEmprestimo,
+    #[serde(flatten)]
+    pub carimbos: Carimbos,
 }
 
 impl Emprestimo {
-    pub fn new(id_usuario: Uuid, id_livro: Uuid) -> Self {
-        let emprestimo = Emprestimo {
+    // O prazo vem da biblioteca, que o ajusta ao calendário
+    pub fn new(
+        id_usuario: Uuid,
+        id_livro: Uuid,
+        data_devolucao: NaiveDate,
+        unidade: Option<Uuid>,
+        momento: DateTime<Local>,
+    ) -> Self {
+        Emprestimo {
             id_emprestimo: Uuid::new_v4(),
-            id_livro: id_livro,
-            id_usuario: id_usuario,
-            data_emprestimo: Local::now().date_naive(),
-            data_devolucao: Local::now().date_naive() + Duration::days(14),
+            id_livro,
+            id_usuario,
+            data_emprestimo: momento.date_naive(),
+            data_devolucao,
+            data_retorno: None,
+            unidade,
+            unidade_devolucao: None,
             status: StatusEmprestimo::Ativo,
-        };
-        emprestimo
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    // Nunca encurta o prazo atual
+    pub fn renovar(&mut self, novo_prazo: NaiveDate, momento: DateTime<Local>) -> NaiveDate {
+        self.data_devolucao = self.data_devolucao.max(novo_prazo);
+        self.carimbos.tocar(momento);
+        self.data_devolucao
+    }
+
+    // Correção manual: troca o usuário (empréstimo lançado para a pessoa errada) ou o prazo
+    pub fn alterar(
+        &mut self,
+        id_usuario: Uuid,
+        data_devolucao: NaiveDate,
+        momento: DateTime<Local>,
+    ) -> Result<(), ErroBiblioteca> {
+        if data_devolucao < self.data_emprestimo {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "A data de devolução não pode ser anterior ao empréstimo ({}).",
+                self.data_emprestimo
+            )));
+        }
+        self.id_usuario = id_usuario;
+        self.data_devolucao = data_devolucao;
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+
+    pub fn corrigir_id(&mut self, id: Uuid) {
+        self.id_emprestimo = id;
+    }
+
+    pub fn esta_ativo(&self) -> bool {
+        matches!(self.status, StatusEmprestimo::Ativo)
+    }
+
+    pub fn get_data_emprestimo(&self) -> NaiveDate {
+        self.data_emprestimo
     }
 
     pub fn get_data_devolucao(&self) -> NaiveDate {
         self.data_devolucao
     }
 
+    pub fn get_data_retorno(&self) -> Option<NaiveDate> {
+        self.data_retorno
+    }
+
+    pub fn get_unidade(&self) -> Option<Uuid> {
+        self.unidade
+    }
+
+    pub fn get_unidade_devolucao(&self) -> Option<Uuid> {
+        self.unidade_devolucao
+    }
+
+    // Só a circulação marca (na devolução) ou limpa (ao desfazê-la)
+    pub(super) fn definir_retorno(&mut self, data: Option<NaiveDate>, unidade: Option<Uuid>) {
+        self.data_retorno = data;
+        self.unidade_devolucao = unidade;
+    }
+
     pub fn get_id_livro(&self) -> Uuid {
         self.id_livro
     }
@@ -0,0 +1,505 @@
+// Empréstimo entre bibliotecas (intercâmbio): o leitor pede um livro que não
+// temos, a biblioteca o pede emprestado a uma parceira e ele circula aqui como
+// um registro temporário do catálogo até voltar para a dona.
+
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+
+use super::carimbos::Carimbos;
+
+// O leitor devolve alguns dias antes do vencimento da parceira, para dar tempo
+// de o livro voltar até ela
+pub const FOLGA_DEVOLUCAO_DIAS: i64 = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Parceira {
+    pub id: Uuid,
+    pub nome: String,
+    // Para onde vão os pedidos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub carimbos: Carimbos,
+}
+
+impl Parceira {
+    pub fn new(nome: String, email: Option<String>, momento: DateTime<Local>) -> Self {
+        Parceira {
+            id: Uuid::new_v4(),
+            nome,
+            email,
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    pub fn validar(nome: &str) -> Result<(), ErroBiblioteca> {
+        if nome.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Nome da biblioteca parceira não pode ficar vazio.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Identificavel for Parceira {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl fmt::Display for Parceira {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.email {
+            Some(email) => write!(f, "{} <{}>", self.nome, email),
+            None => write!(f, "{}", self.nome),
+        }
+    }
+}
+
+// Solicitada -> Enviada -> Recebida -> Emprestada -> Devolvida; antes de o livro
+// chegar, o pedido ainda pode ser cancelado
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Etapa {
+    Solicitada,
+    Enviada,
+    Recebida,
+    Emprestada,
+    Devolvida,
+    Cancelada,
+}
+
+impl Etapa {
+    // Pedidos encerrados saem da lista do dia a dia
+    pub fn em_aberto(&self) -> bool {
+        !matches!(self, Etapa::Devolvida | Etapa::Cancelada)
+    }
+}
+
+impl fmt::Display for Etapa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let descricao = match self {
+            Etapa::Solicitada => "solicitada",
+            Etapa::Enviada => "enviada à parceira",
+            Etapa::Recebida => "recebida",
+            Etapa::Emprestada => "emprestada ao leitor",
+            Etapa::Devolvida => "devolvida à parceira",
+            Etapa::Cancelada => "cancelada",
+        };
+        write!(f, "{}", descricao)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Solicitacao {
+    pub id: Uuid,
+    // Leitor que pediu o livro
+    pub id_usuario: Uuid,
+    pub titulo: String,
+    pub autor: String,
+    pub ano: u16,
+    etapa: Etapa,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id_parceira: Option<Uuid>,
+    solicitada_em: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    enviada_em: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recebida_em: Option<NaiveDate>,
+    // Devolvida à parceira ou cancelada
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encerrada_em: Option<NaiveDate>,
+    // Até quando a parceira quer o livro de volta
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vencimento: Option<NaiveDate>,
+    // Registro temporário no catálogo, enquanto o livro está aqui
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id_livro: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id_emprestimo: Option<Uuid>,
+    #[serde(flatten)]
+    pub carimbos: Carimbos,
+}
+
+impl Solicitacao {
+    pub fn new(
+        id_usuario: Uuid,
+        titulo: String,
+        autor: String,
+        ano: u16,
+        momento: DateTime<Local>,
+    ) -> Self {
+        Solicitacao {
+            id: Uuid::new_v4(),
+            id_usuario,
+            titulo,
+            autor,
+            ano,
+            etapa: Etapa::Solicitada,
+            id_parceira: None,
+            solicitada_em: momento.date_naive(),
+            enviada_em: None,
+            recebida_em: None,
+            encerrada_em: None,
+            vencimento: None,
+            id_livro: None,
+            id_emprestimo: None,
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    pub fn etapa(&self) -> Etapa {
+        self.etapa
+    }
+
+    pub fn get_parceira(&self) -> Option<Uuid> {
+        self.id_parceira
+    }
+
+    pub fn solicitada_em(&self) -> NaiveDate {
+        self.solicitada_em
+    }
+
+    pub fn vencimento(&self) -> Option<NaiveDate> {
+        self.vencimento
+    }
+
+    pub fn get_livro(&self) -> Option<Uuid> {
+        self.id_livro
+    }
+
+    pub fn get_emprestimo(&self) -> Option<Uuid> {
+        self.id_emprestimo
+    }
+
+    // Data em que o pedido chegou à etapa atual
+    pub fn desde(&self) -> NaiveDate {
+        match self.etapa {
+            Etapa::Solicitada => Some(self.solicitada_em),
+            Etapa::Enviada => self.enviada_em,
+            Etapa::Recebida | Etapa::Emprestada => self.recebida_em,
+            Etapa::Devolvida | Etapa::Cancelada => self.encerrada_em,
+        }
+        .unwrap_or(self.solicitada_em)
+    }
+
+    fn exigir_etapa(&self, aceitas: &[Etapa], acao: &str) -> Result<(), ErroBiblioteca> {
+        if aceitas.contains(&self.etapa) {
+            Ok(())
+        } else {
+            Err(ErroBiblioteca::EstadoInvalido(format!(
+                "Não é possível {} um pedido de intercâmbio na etapa \"{}\".",
+                acao, self.etapa
+            )))
+        }
+    }
+
+    // Reenviar a outra parceira (a primeira recusou) também passa por aqui
+    pub(super) fn enviar(
+        &mut self,
+        id_parceira: Uuid,
+        momento: DateTime<Local>,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir_etapa(&[Etapa::Solicitada, Etapa::Enviada], "enviar")?;
+        self.etapa = Etapa::Enviada;
+        self.id_parceira = Some(id_parceira);
+        self.enviada_em = Some(momento.date_naive());
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+
+    pub(super) fn receber(
+        &mut self,
+        vencimento: NaiveDate,
+        id_livro: Uuid,
+        momento: DateTime<Local>,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir_etapa(&[Etapa::Enviada], "receber")?;
+        self.etapa = Etapa::Recebida;
+        self.vencimento = Some(vencimento);
+        self.id_livro = Some(id_livro);
+        self.recebida_em = Some(momento.date_naive());
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+
+    pub(super) fn emprestar(
+        &mut self,
+        id_emprestimo: Uuid,
+        momento: DateTime<Local>,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir_etapa(&[Etapa::Recebida], "emprestar")?;
+        self.etapa = Etapa::Emprestada;
+        self.id_emprestimo = Some(id_emprestimo);
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+
+    // O leitor pode nem ter vindo buscar: o livro volta direto da etapa Recebida
+    pub(super) fn devolver(&mut self, momento: DateTime<Local>) -> Result<(), ErroBiblioteca> {
+        self.exigir_etapa(&[Etapa::Recebida, Etapa::Emprestada], "devolver")?;
+        self.etapa = Etapa::Devolvida;
+        self.encerrada_em = Some(momento.date_naive());
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+
+    pub(super) fn cancelar(&mut self, momento: DateTime<Local>) -> Result<(), ErroBiblioteca> {
+        self.exigir_etapa(&[Etapa::Solicitada, Etapa::Enviada], "cancelar")?;
+        self.etapa = Etapa::Cancelada;
+        self.encerrada_em = Some(momento.date_naive());
+        self.carimbos.tocar(momento);
+        Ok(())
+    }
+}
+
+impl Identificavel for Solicitacao {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        Biblioteca,
+        contas::{Papel, Sessao},
+        relogio::RelogioFixo,
+    };
+
+    fn momento(dia: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, dia, 10, 0, 0).unwrap()
+    }
+
+    fn data(mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, mes, dia).expect("data válida")
+    }
+
+    fn pedido() -> Solicitacao {
+        Solicitacao::new(
+            Uuid::new_v4(),
+            "Memorial de Aires".to_string(),
+            "Machado de Assis".to_string(),
+            1908,
+            momento(4),
+        )
+    }
+
+    fn recusa(resultado: Result<(), ErroBiblioteca>, trecho: &str) {
+        match resultado {
+            Err(ErroBiblioteca::EstadoInvalido(mensagem)) => {
+                assert!(
+                    mensagem.contains(trecho),
+                    "mensagem inesperada: {}",
+                    mensagem
+                )
+            }
+            outro => panic!("esperava recusa com \"{}\", veio {:?}", trecho, outro),
+        }
+    }
+
+    #[test]
+    fn pedido_percorre_as_etapas_em_ordem() {
+        let mut solicitacao = pedido();
+        assert_eq!(solicitacao.etapa(), Etapa::Solicitada);
+
+        // Reenviar a outra parceira troca a parceira e a data de envio
+        let (primeira, segunda) = (Uuid::new_v4(), Uuid::new_v4());
+        solicitacao.enviar(primeira, momento(5)).unwrap();
+        solicitacao.enviar(segunda, momento(6)).unwrap();
+        assert_eq!(solicitacao.etapa(), Etapa::Enviada);
+        assert_eq!(solicitacao.get_parceira(), Some(segunda));
+        assert_eq!(solicitacao.desde(), data(3, 6));
+
+        let (livro, emprestimo) = (Uuid::new_v4(), Uuid::new_v4());
+        solicitacao
+            .receber(data(4, 15), livro, momento(11))
+            .unwrap();
+        assert_eq!(solicitacao.etapa(), Etapa::Recebida);
+        assert_eq!(solicitacao.get_livro(), Some(livro));
+        assert_eq!(solicitacao.vencimento(), Some(data(4, 15)));
+
+        solicitacao.emprestar(emprestimo, momento(12)).unwrap();
+        assert_eq!(solicitacao.etapa(), Etapa::Emprestada);
+        assert_eq!(solicitacao.get_emprestimo(), Some(emprestimo));
+        assert_eq!(solicitacao.desde(), data(3, 11));
+        assert!(solicitacao.etapa().em_aberto());
+
+        solicitacao.devolver(momento(25)).unwrap();
+        assert_eq!(solicitacao.etapa(), Etapa::Devolvida);
+        assert_eq!(solicitacao.desde(), data(3, 25));
+        assert!(!solicitacao.etapa().em_aberto());
+    }
+
+    #[test]
+    fn transicoes_fora_de_ordem_sao_recusadas() {
+        let mut solicitacao = pedido();
+        recusa(
+            solicitacao.receber(data(4, 15), Uuid::new_v4(), momento(5)),
+            "receber um pedido de intercâmbio na etapa \"solicitada\"",
+        );
+        recusa(
+            solicitacao.emprestar(Uuid::new_v4(), momento(5)),
+            "emprestar",
+        );
+        recusa(solicitacao.devolver(momento(5)), "devolver");
+
+        solicitacao.enviar(Uuid::new_v4(), momento(5)).unwrap();
+        recusa(
+            solicitacao.emprestar(Uuid::new_v4(), momento(5)),
+            "emprestar",
+        );
+
+        // Depois que o livro chega, não se cancela nem se reenvia
+        solicitacao
+            .receber(data(4, 15), Uuid::new_v4(), momento(6))
+            .unwrap();
+        recusa(solicitacao.cancelar(momento(7)), "cancelar");
+        recusa(solicitacao.enviar(Uuid::new_v4(), momento(7)), "enviar");
+        recusa(
+            solicitacao.receber(data(4, 15), Uuid::new_v4(), momento(7)),
+            "receber",
+        );
+
+        // O leitor não veio buscar: volta direto da etapa Recebida
+        solicitacao.devolver(momento(8)).unwrap();
+        for resultado in [
+            solicitacao.devolver(momento(9)),
+            solicitacao.cancelar(momento(9)),
+            solicitacao.enviar(Uuid::new_v4(), momento(9)),
+            solicitacao.emprestar(Uuid::new_v4(), momento(9)),
+        ] {
+            recusa(resultado, "na etapa \"devolvida à parceira\"");
+        }
+    }
+
+    #[test]
+    fn cancelado_so_antes_de_o_livro_chegar() {
+        let mut solicitado = pedido();
+        solicitado.cancelar(momento(5)).unwrap();
+        assert_eq!(solicitado.etapa(), Etapa::Cancelada);
+        assert_eq!(solicitado.desde(), data(3, 5));
+        recusa(solicitado.enviar(Uuid::new_v4(), momento(6)), "cancelada");
+
+        let mut enviado = pedido();
+        enviado.enviar(Uuid::new_v4(), momento(5)).unwrap();
+        enviado.cancelar(momento(6)).unwrap();
+        recusa(
+            enviado.receber(data(4, 15), Uuid::new_v4(), momento(7)),
+            "cancelada",
+        );
+    }
+
+    #[test]
+    fn livro_de_intercambio_nao_renova_e_so_volta_depois_do_leitor() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca.definir_relogio(Arc::new(RelogioFixo::new(momento(4))));
+        let leitor = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        let outro = biblioteca.cadastrar_usuario("Bento".to_string()).unwrap();
+        let parceira = biblioteca
+            .cadastrar_parceira("Biblioteca Municipal".to_string(), None)
+            .unwrap();
+        let id = biblioteca
+            .solicitar_intercambio(
+                leitor,
+                "Memorial de Aires".to_string(),
+                "Machado de Assis".to_string(),
+                1908,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            biblioteca.emprestar_intercambio(id),
+            Err(ErroBiblioteca::EstadoInvalido(_))
+        ));
+        assert!(matches!(
+            biblioteca.enviar_solicitacao(id, Uuid::new_v4()),
+            Err(ErroBiblioteca::ParceiraNaoEncontrada(_))
+        ));
+        biblioteca.enviar_solicitacao(id, parceira).unwrap();
+        assert!(matches!(
+            biblioteca.receber_intercambio(id, data(3, 4)),
+            Err(ErroBiblioteca::DadosInvalidos(_))
+        ));
+        let livro = biblioteca.receber_intercambio(id, data(4, 15)).unwrap();
+        assert_eq!(biblioteca.livros()[&livro].intercambio(), Some(id));
+
+        // Só o leitor que pediu leva o livro, e o prazo é o de sempre (18/03)
+        recusa(
+            biblioteca.realizar_emprestimo(outro, livro).map(|_| ()),
+            "só vai para o leitor que o pediu",
+        );
+        recusa(
+            biblioteca.remover_livro(livro).map(|_| ()),
+            "devolvido à parceira",
+        );
+        let emprestimo = biblioteca.emprestar_intercambio(id).unwrap();
+        assert_eq!(
+            biblioteca.emprestimos()[&emprestimo].get_data_devolucao(),
+            data(3, 18)
+        );
+        recusa(
+            biblioteca.renovar(emprestimo).map(|_| ()),
+            "não pode ser renovado",
+        );
+        recusa(biblioteca.devolver_a_parceira(id), "ainda não devolveu");
+
+        biblioteca.devolver(emprestimo).unwrap();
+        biblioteca.devolver_a_parceira(id).unwrap();
+        assert_eq!(biblioteca.solicitacoes()[&id].etapa(), Etapa::Devolvida);
+        // Com empréstimo no histórico, o registro temporário fica arquivado
+        assert!(biblioteca.livros()[&livro].arquivado);
+        recusa(biblioteca.devolver_a_parceira(id), "devolver");
+        recusa(biblioteca.cancelar_solicitacao(id), "cancelar");
+    }
+
+    #[test]
+    fn vencimento_curto_demais_nao_deixa_emprestar() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca.definir_relogio(Arc::new(RelogioFixo::new(momento(4))));
+        let leitor = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        let parceira = biblioteca
+            .cadastrar_parceira("Biblioteca Municipal".to_string(), None)
+            .unwrap();
+        let id = biblioteca
+            .solicitar_intercambio(
+                leitor,
+                "Esaú e Jacó".to_string(),
+                "Machado de Assis".to_string(),
+                1904,
+            )
+            .unwrap();
+        biblioteca.enviar_solicitacao(id, parceira).unwrap();
+
+        // Com a folga de três dias, o prazo cairia antes de amanhã
+        let livro = biblioteca.receber_intercambio(id, data(3, 6)).unwrap();
+        recusa(
+            biblioteca.emprestar_intercambio(id).map(|_| ()),
+            "não deixa tempo",
+        );
+        assert_eq!(biblioteca.solicitacoes()[&id].etapa(), Etapa::Recebida);
+        biblioteca.devolver_a_parceira(id).unwrap();
+        assert!(!biblioteca.livros().contains_key(&livro));
+    }
+}
@@ -1,25 +1,116 @@
-use std::fmt;
+use std::{fmt, ops::RangeInclusive};
 
-use crate::traits::Identificavel;
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+
+use super::carimbos::Carimbos;
+use chrono::{DateTime, Datelike, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+// Menor ano de publicação aceito (prensa de Gutenberg)
+pub const ANO_MINIMO: u16 = 1450;
+
+// Anos aceitos para publicação: até o ano que vem, para livros em pré-venda
+pub fn faixa_ano(hoje: NaiveDate) -> RangeInclusive<u16> {
+    ANO_MINIMO..=(hoje.year() + 1) as u16
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Livro {
     id: Uuid,
     titulo: String,
     autor: String,
     ano: u16,
-    pub status: StatusLivro,
+    // Reflexo dos empréstimos ativos; só a circulação altera
+    status: StatusLivro,
+    // Livros com histórico de empréstimos não são apagados, só saem das listagens
+    #[serde(default)]
+    pub arquivado: bool,
+    // Unidade dona do exemplar, para onde ele volta depois de cada empréstimo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unidade: Option<Uuid>,
+    // Onde o exemplar está; em trânsito, a unidade de onde saiu
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    local: Option<Uuid>,
+    // Desde quando está a caminho da unidade dona
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    em_transito: Option<NaiveDate>,
+    // Livro emprestado por uma parceira: o pedido de intercâmbio que o trouxe.
+    // O registro é temporário e sai do catálogo quando o livro volta à dona.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    intercambio: Option<Uuid>,
+    // Não encontrado na conferência do acervo desde esta data; não circula
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extraviado: Option<NaiveDate>,
+    #[serde(flatten)]
+    catalogacao: Catalogacao,
+    #[serde(flatten)]
+    carimbos: Carimbos,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Dados catalográficos opcionais, em geral vindos de registros MARC.
+// Ficam fora do JSON quando vazios, então arquivos antigos não mudam.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Catalogacao {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    // Autores além do principal
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coautores: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editora: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assuntos: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 
 pub enum StatusLivro {
     Disponivel,
     Emprestado,
 }
 
+// Critérios da pesquisa de livros; o que não for informado não filtra.
+// Textos são comparados por trecho, sem diferenciar maiúsculas.
+#[derive(Debug, Default)]
+pub struct FiltroLivros {
+    // Cada termo precisa aparecer no título ou em algum autor
+    pub termos: Vec<String>,
+    pub titulo: Option<String>,
+    pub autor: Option<String>,
+    pub ano: Option<u16>,
+    pub assunto: Option<String>,
+}
+
+impl FiltroLivros {
+    pub fn aceita(&self, livro: &Livro) -> bool {
+        let contem =
+            |texto: &str, trecho: &str| texto.to_lowercase().contains(&trecho.to_lowercase());
+        let algum_autor = |trecho: &str| {
+            contem(&livro.autor, trecho)
+                || livro
+                    .catalogacao
+                    .coautores
+                    .iter()
+                    .any(|c| contem(c, trecho))
+        };
+
+        self.termos
+            .iter()
+            .all(|termo| contem(&livro.titulo, termo) || algum_autor(termo))
+            && self
+                .titulo
+                .as_ref()
+                .is_none_or(|t| contem(&livro.titulo, t))
+            && self.autor.as_deref().is_none_or(algum_autor)
+            && self.ano.is_none_or(|ano| livro.ano == ano)
+            && self
+                .assunto
+                .as_ref()
+                .is_none_or(|a| livro.catalogacao.assuntos.iter().any(|s| contem(s, a)))
+    }
+}
+
 impl Identificavel for Livro {
     fn id(&self) -> Uuid {
         self.id
@@ -43,16 +134,79 @@ impl fmt::Display for Livro {
 }
 
 impl Livro {
-    pub fn new(titulo: String, autor: String, ano: u16) -> Self {
+    pub fn new(titulo: String, autor: String, ano: u16, momento: DateTime<Local>) -> Self {
         Livro {
             id: Uuid::new_v4(),
             titulo,
             autor,
             ano,
             status: StatusLivro::Disponivel,
+            arquivado: false,
+            unidade: None,
+            local: None,
+            em_transito: None,
+            intercambio: None,
+            extraviado: None,
+            catalogacao: Catalogacao::default(),
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    // Para recriar um livro que sumiu do cadastro mas ainda é citado por empréstimos
+    pub fn substituto(id: Uuid) -> Self {
+        Livro {
+            id,
+            titulo: "Livro desconhecido".to_string(),
+            autor: "Desconhecido".to_string(),
+            ano: ANO_MINIMO,
+            status: StatusLivro::Disponivel,
+            arquivado: true,
+            unidade: None,
+            local: None,
+            em_transito: None,
+            intercambio: None,
+            extraviado: None,
+            catalogacao: Catalogacao::default(),
+            carimbos: Carimbos::default(),
         }
     }
 
+    // O mapa de livros é indexado pelo id; os dois precisam coincidir
+    pub fn corrigir_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+
+    pub fn validar(
+        titulo: &str,
+        autor: &str,
+        ano: u16,
+        hoje: NaiveDate,
+    ) -> Result<(), ErroBiblioteca> {
+        if titulo.trim().is_empty() || autor.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Título e autor não podem ficar vazios.".to_string(),
+            ));
+        }
+
+        let faixa = faixa_ano(hoje);
+        if !faixa.contains(&ano) {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Ano deve estar entre {} e {}.",
+                faixa.start(),
+                faixa.end()
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn atualizar(&mut self, titulo: String, autor: String, ano: u16, momento: DateTime<Local>) {
+        self.titulo = titulo;
+        self.autor = autor;
+        self.ano = ano;
+        self.carimbos.tocar(momento);
+    }
+
     pub fn get_titulo(&self) -> &String {
         &self.titulo
     }
@@ -60,4 +214,109 @@ impl Livro {
     pub fn get_autor(&self) -> &String {
         &self.autor
     }
+
+    pub fn get_ano(&self) -> u16 {
+        self.ano
+    }
+
+    pub fn catalogacao(&self) -> &Catalogacao {
+        &self.catalogacao
+    }
+
+    pub fn catalogar(&mut self, catalogacao: Catalogacao, momento: DateTime<Local>) {
+        self.catalogacao = catalogacao;
+        self.carimbos.tocar(momento);
+    }
+
+    pub fn carimbos(&self) -> &Carimbos {
+        &self.carimbos
+    }
+
+    // Para alterações feitas direto nos campos públicos, como `arquivado`
+    pub fn tocar(&mut self, momento: DateTime<Local>) {
+        self.carimbos.tocar(momento);
+    }
+
+    pub fn status(&self) -> StatusLivro {
+        self.status
+    }
+
+    pub fn esta_disponivel(&self) -> bool {
+        self.status == StatusLivro::Disponivel
+    }
+
+    pub(super) fn refletir(&mut self, status: StatusLivro) {
+        self.status = status;
+    }
+
+    pub fn get_unidade(&self) -> Option<Uuid> {
+        self.unidade
+    }
+
+    pub fn get_local(&self) -> Option<Uuid> {
+        self.local
+    }
+
+    pub fn em_transito(&self) -> Option<NaiveDate> {
+        self.em_transito
+    }
+
+    pub fn intercambio(&self) -> Option<Uuid> {
+        self.intercambio
+    }
+
+    pub(super) fn marcar_intercambio(&mut self, id_solicitacao: Uuid) {
+        self.intercambio = Some(id_solicitacao);
+    }
+
+    pub fn extraviado(&self) -> Option<NaiveDate> {
+        self.extraviado
+    }
+
+    // Com `false`, o exemplar foi achado e volta a circular
+    pub(super) fn marcar_extravio(&mut self, extraviado: bool, momento: DateTime<Local>) {
+        self.extraviado = extraviado.then_some(momento.date_naive());
+        self.carimbos.tocar(momento);
+    }
+
+    // Troca a unidade dona. Um exemplar sem local conhecido passa a estar nela;
+    // um que está em outra unidade segue em trânsito até ser recebido.
+    pub fn definir_unidade(&mut self, unidade: Option<Uuid>, momento: DateTime<Local>) {
+        self.unidade = unidade;
+        let local = self.local.or(unidade);
+        self.localizar(local, momento.date_naive());
+        self.carimbos.tocar(momento);
+    }
+
+    // O exemplar em trânsito chegou à unidade dona
+    pub fn receber(&mut self, momento: DateTime<Local>) {
+        self.local = self.unidade;
+        self.em_transito = None;
+        self.carimbos.tocar(momento);
+    }
+
+    // A unidade sumiu do cadastro: o exemplar deixa de pertencer a ela ou de estar nela
+    pub(super) fn esquecer_unidade(&mut self, id_unidade: Uuid) {
+        if self.unidade == Some(id_unidade) {
+            self.unidade = None;
+            self.em_transito = None;
+        }
+        if self.local == Some(id_unidade) {
+            self.local = self.unidade;
+            self.em_transito = None;
+        }
+    }
+
+    // Só a circulação move o exemplar (saída e devolução). Fora da unidade
+    // dona, ele fica em trânsito a partir de `desde`.
+    pub(super) fn localizar(&mut self, local: Option<Uuid>, desde: NaiveDate) {
+        let Some(local) = local else {
+            return;
+        };
+        self.local = Some(local);
+        self.em_transito = match self.unidade {
+            Some(unidade) if unidade != local => Some(desde),
+            _ => None,
+        };
+    }
 }
@@ -1,39 +1,135 @@
+pub mod auditoria;
+pub mod autosalvamento;
+pub mod calendario;
+pub mod carimbos;
+pub mod circulacao;
+pub mod contas;
+pub mod desfazer;
+pub mod diario;
 pub mod emprestimos;
+pub mod intercambio;
 pub mod livros;
+pub mod politicas;
+pub mod relogio;
+pub mod unidades;
 pub mod usuarios;
+pub mod verificacao;
 
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    fs::{File, OpenOptions},
-    io::{self, BufReader, BufWriter},
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    mem,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use uuid::Uuid;
 
 use livros::*;
 
+use crate::entrada::{
+    ler_ano, ler_ano_ou, ler_confirmacao, ler_data_ou, ler_indice, ler_numero, ler_string,
+    ler_string_opcional, ler_string_ou, ler_uuid,
+};
 use crate::errors::ErroBiblioteca;
 use crate::{
     biblioteca::{
+        auditoria::{FiltroAuditoria, RegistroAuditoria},
+        autosalvamento::{ModoAutosalvamento, Salvaguarda},
+        calendario::Calendario,
+        circulacao::Circulacao,
+        contas::{Permissao, Sessao},
+        desfazer::{Comando, PilhaDesfazer},
+        diario::{Evento, RegistroEvento},
         emprestimos::{Emprestimo, StatusEmprestimo},
+        intercambio::{Parceira, Solicitacao},
+        politicas::Politicas,
+        relogio::{Relogio, RelogioSistema},
+        unidades::Unidade,
         usuarios::Usuario,
+        verificacao::{Gravidade, Problema, Reparo},
     },
     traits::Identificavel,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct DadosPersistencia {
     livros: HashMap<Uuid, Livro>,
     usuarios: HashMap<Uuid, Usuario>,
     emprestimos: HashMap<Uuid, Emprestimo>,
+    // Filiais; bibliotecas de uma unidade só não têm nenhuma
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    unidades: HashMap<Uuid, Unidade>,
+    // Intercâmbio com outras bibliotecas
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    parceiras: HashMap<Uuid, Parceira>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    solicitacoes: HashMap<Uuid, Solicitacao>,
+    // Último evento do diário já incluído neste retrato (0 em arquivos antigos)
+    #[serde(default)]
+    ultima_sequencia: u64,
 }
 
 pub struct Biblioteca {
     livros: HashMap<Uuid, Livro>,
     usuarios: HashMap<Uuid, Usuario>,
     emprestimos: HashMap<Uuid, Emprestimo>,
-    caminho_arquivo: PathBuf,
+    unidades: HashMap<Uuid, Unidade>,
+    parceiras: HashMap<Uuid, Parceira>,
+    solicitacoes: HashMap<Uuid, Solicitacao>,
+    ultima_sequencia: u64,
+    autosalvamento: ModoAutosalvamento,
+    // Também funciona como a marca de "há alterações não salvas"
+    salvaguarda: Salvaguarda,
+    // Quem está operando o sistema; vai para cada evento do diário.
+    // Sem sessão, nenhuma operação que exige permissão é aceita.
+    sessao: Option<Sessao>,
+    desfazer: PilhaDesfazer,
+    // Eventos da transação em andamento; só vão para o diário na confirmação
+    lote: Option<Vec<Evento>>,
+    // Dias em que a biblioteca abre, para os prazos de devolução
+    calendario: Calendario,
+    // Prazo, limite de empréstimos e multa, vindos da configuração
+    politicas: Politicas,
+    // Toda data que a biblioteca decide (prazos, devoluções, carimbos) sai daqui
+    relogio: Arc<dyn Relogio>,
+    // Unidade onde se está operando: empréstimos e devoluções são registrados nela
+    unidade_atual: Option<Uuid>,
+}
+
+// Operador usado quando ninguém entrou no sistema (ex.: importação de dados antigos)
+pub const OPERADOR_PADRAO: &str = "sistema";
+
+// O retrato (ou o arquivo de contas) é gravado num arquivo ao lado e só então
+// colocado no lugar do antigo: uma queda no meio da escrita deixa o anterior intacto
+fn escrever_dados<T: Serialize + ?Sized>(caminho: &Path, dados: &T) -> Result<(), ErroBiblioteca> {
+    let mut nome = caminho.as_os_str().to_owned();
+    nome.push(".tmp");
+    let temporario = PathBuf::from(nome);
+    let erro = |e: std::io::Error| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar {}: {}", temporario.display(), e))
+    };
+
+    let mut escritor = BufWriter::new(File::create(&temporario).map_err(erro)?);
+    serde_json::to_writer_pretty(&mut escritor, dados)
+        .map_err(|e| ErroBiblioteca::ErroPersistencia(format!("Erro ao serializar JSON: {}", e)))?;
+    let arquivo = escritor.into_inner().map_err(|e| erro(e.into_error()))?;
+    arquivo.sync_all().map_err(erro)?;
+
+    fs::rename(&temporario, caminho).map_err(|e| {
+        ErroBiblioteca::ErroPersistencia(format!("Erro ao substituir {}: {}", caminho.display(), e))
+    })?;
+    // Para a troca de nome também sobreviver a uma queda de energia (onde o
+    // sistema permite abrir a pasta)
+    if let Some(pasta) = caminho.parent().filter(|p| !p.as_os_str().is_empty())
+        && let Ok(pasta) = File::open(pasta)
+    {
+        let _ = pasta.sync_all();
+    }
+
+    Ok(())
 }
 
 impl Biblioteca {
@@ -42,209 +138,1611 @@ impl Biblioteca {
             livros: HashMap::new(),
             usuarios: HashMap::new(),
             emprestimos: HashMap::new(),
-            caminho_arquivo: caminho.as_ref().to_path_buf(),
+            unidades: HashMap::new(),
+            parceiras: HashMap::new(),
+            solicitacoes: HashMap::new(),
+            ultima_sequencia: 0,
+            autosalvamento: ModoAutosalvamento::Desligado,
+            salvaguarda: Salvaguarda::nova(caminho.as_ref().to_path_buf()),
+            sessao: None,
+            desfazer: PilhaDesfazer::default(),
+            lote: None,
+            calendario: Calendario::default(),
+            politicas: Politicas::default(),
+            relogio: Arc::new(RelogioSistema),
+            unidade_atual: None,
         }
     }
 
-    pub fn carregar<P: AsRef<Path>>(caminho: P) -> Result<Self, ErroBiblioteca> {
-        let caminho_path = caminho.as_ref().to_path_buf();
-
-        if !caminho_path.exists() {
-            return Ok(Self::nova(caminho_path));
-        }
-
-        let arquivo = File::open(&caminho_path).map_err(|e| {
+    fn ler_retrato(caminho: &Path) -> Result<DadosPersistencia, ErroBiblioteca> {
+        let arquivo = File::open(caminho).map_err(|e| {
             ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir arquivo: {}", e))
         })?;
 
         let leitor = BufReader::new(arquivo);
-        let dados: DadosPersistencia = serde_json::from_reader(leitor).map_err(|e| {
+        serde_json::from_reader(leitor).map_err(|e| {
             ErroBiblioteca::ErroPersistencia(format!("Erro ao deserializar JSON: {}", e))
-        })?;
+        })
+    }
 
-        Ok(Self {
+    fn com_dados(dados: DadosPersistencia, caminho: PathBuf) -> Self {
+        Self {
             livros: dados.livros,
             usuarios: dados.usuarios,
             emprestimos: dados.emprestimos,
-            caminho_arquivo: caminho_path,
+            unidades: dados.unidades,
+            parceiras: dados.parceiras,
+            solicitacoes: dados.solicitacoes,
+            ultima_sequencia: dados.ultima_sequencia,
+            autosalvamento: ModoAutosalvamento::Desligado,
+            salvaguarda: Salvaguarda::nova(caminho),
+            sessao: None,
+            desfazer: PilhaDesfazer::default(),
+            lote: None,
+            calendario: Calendario::default(),
+            politicas: Politicas::default(),
+            relogio: Arc::new(RelogioSistema),
+            unidade_atual: None,
+        }
+    }
+
+    pub fn carregar<P: AsRef<Path>>(caminho: P) -> Result<Self, ErroBiblioteca> {
+        let caminho_path = caminho.as_ref().to_path_buf();
+        let existe_historico = diario::existe_historico(&caminho_path)?;
+
+        // Sem retrato, mas com diário: reconstrói tudo a partir do histórico
+        let (mut dados, registros) = if caminho_path.exists() {
+            match Self::ler_retrato(&caminho_path) {
+                Ok(dados) => (dados, diario::ler_atual(&caminho_path)?),
+                // Retrato ilegível: o histórico inteiro refaz o mesmo estado
+                Err(e) if existe_historico => {
+                    eprintln!(
+                        "Aviso: {}. Reconstruindo os dados a partir do diário de eventos.",
+                        e
+                    );
+                    (
+                        DadosPersistencia::default(),
+                        diario::ler_historico(&caminho_path)?,
+                    )
+                }
+                Err(e) => return Err(e),
+            }
+        } else if existe_historico {
+            (
+                DadosPersistencia::default(),
+                diario::ler_historico(&caminho_path)?,
+            )
+        } else {
+            return Ok(Self::nova(caminho_path));
+        };
+
+        // Eventos que foram para o diário mas não chegaram ao retrato
+        for registro in registros {
+            if registro.sequencia > dados.ultima_sequencia {
+                diario::aplicar(&mut dados, &registro.evento);
+                dados.ultima_sequencia = registro.sequencia;
+            }
+        }
+
+        let mut biblioteca = Self::com_dados(dados, caminho_path);
+
+        // Arquivo de antes do diário existir: o estado atual vira o primeiro evento
+        if !existe_historico && !biblioteca.esta_vazia() {
+            biblioteca.registrar_alteracao(Evento::EstadoImportado {
+                livros: biblioteca.livros.clone(),
+                usuarios: biblioteca.usuarios.clone(),
+                emprestimos: biblioteca.emprestimos.clone(),
+            });
+            if let Err(e) = biblioteca.salvar() {
+                eprintln!("Aviso: não foi possível iniciar o diário de eventos: {}", e);
+            }
+        }
+
+        // Só avisa; reparar é uma decisão de quem opera (`verificar --reparar`)
+        let problemas = biblioteca.verificar();
+        if !problemas.is_empty() {
+            let criticos = problemas
+                .iter()
+                .filter(|p| p.gravidade == Gravidade::Critico)
+                .count();
+            eprintln!(
+                "Aviso: {} problema(s) de integridade nos dados ({} crítico(s)). \
+                 Rode o comando `verificar` para ver os detalhes.",
+                problemas.len(),
+                criticos
+            );
+        }
+
+        Ok(biblioteca)
+    }
+
+    pub fn verificar(&self) -> Vec<Problema> {
+        verificacao::verificar(&self.dados(), self.agora())
+    }
+
+    // Com `simular`, só calcula o que mudaria
+    pub fn reparar(&mut self, simular: bool) -> Result<Reparo, ErroBiblioteca> {
+        self.exigir(Permissao::RepararDados)?;
+
+        let antes = self.dados();
+        let (problemas, reparados) = verificacao::reparar(&antes, self.agora());
+        let diferencas = verificacao::diferencas(&antes, &reparados);
+
+        if !simular && !diferencas.is_empty() {
+            self.livros = reparados.livros.clone();
+            self.usuarios = reparados.usuarios.clone();
+            self.emprestimos = reparados.emprestimos.clone();
+            self.registrar_alteracao(Evento::DadosReparados {
+                problemas: problemas
+                    .iter()
+                    .filter(|p| p.reparo.is_some())
+                    .map(|p| p.descricao.clone())
+                    .collect(),
+                livros: reparados.livros,
+                usuarios: reparados.usuarios,
+                emprestimos: reparados.emprestimos,
+            });
+        }
+
+        Ok(Reparo {
+            problemas,
+            diferencas,
         })
     }
 
-    pub fn salvar(&self) -> Result<(), ErroBiblioteca> {
-        let dados = DadosPersistencia {
+    fn esta_vazia(&self) -> bool {
+        self.livros.is_empty() && self.usuarios.is_empty() && self.emprestimos.is_empty()
+    }
+
+    // Estado da biblioteca no fim de `momento`, refeito a partir de todo o diário.
+    // O resultado é só para consulta: não deve ser salvo por cima dos dados atuais.
+    pub fn reconstruir_em<P: AsRef<Path>>(
+        caminho: P,
+        momento: DateTime<Local>,
+    ) -> Result<Self, ErroBiblioteca> {
+        let caminho_path = caminho.as_ref().to_path_buf();
+        let mut dados = DadosPersistencia::default();
+
+        for registro in diario::ler_historico(&caminho_path)? {
+            if registro.momento > momento {
+                break;
+            }
+            diario::aplicar(&mut dados, &registro.evento);
+            dados.ultima_sequencia = registro.sequencia;
+        }
+
+        Ok(Self::com_dados(dados, caminho_path))
+    }
+
+    // Salva um retrato novo e arquiva o diário atual, para que o próximo
+    // carregamento não precise reaplicar nada. O histórico arquivado continua
+    // disponível para `reconstruir_em`.
+    pub fn compactar(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Compactar)?;
+        self.salvar()?;
+        diario::arquivar(self.salvaguarda.caminho(), self.ultima_sequencia)
+    }
+
+    // Momento do evento mais antigo do histórico (antes dele nada pode ser reconstruído)
+    pub fn inicio_do_historico(&self) -> Result<Option<DateTime<Local>>, ErroBiblioteca> {
+        Ok(diario::ler_historico(self.salvaguarda.caminho())?
+            .first()
+            .map(|registro| registro.momento))
+    }
+
+    fn dados(&self) -> DadosPersistencia {
+        DadosPersistencia {
             livros: self.livros.clone(),
             usuarios: self.usuarios.clone(),
             emprestimos: self.emprestimos.clone(),
+            unidades: self.unidades.clone(),
+            parceiras: self.parceiras.clone(),
+            solicitacoes: self.solicitacoes.clone(),
+            ultima_sequencia: self.ultima_sequencia,
+        }
+    }
+
+    // Depois de salvar, o que foi feito não pode mais ser desfeito
+    pub fn salvar(&mut self) -> Result<(), ErroBiblioteca> {
+        self.salvaguarda.gravar(&self.dados())?;
+        self.desfazer.limpar();
+        Ok(())
+    }
+
+    pub fn definir_relogio(&mut self, relogio: Arc<dyn Relogio>) {
+        self.relogio = relogio;
+    }
+
+    pub fn agora(&self) -> DateTime<Local> {
+        self.relogio.agora()
+    }
+
+    pub fn hoje(&self) -> NaiveDate {
+        self.relogio.hoje()
+    }
+
+    pub fn definir_calendario(&mut self, calendario: Calendario) {
+        self.calendario = calendario;
+    }
+
+    pub fn calendario(&self) -> &Calendario {
+        &self.calendario
+    }
+
+    pub fn definir_politicas(&mut self, politicas: Politicas) {
+        self.politicas = politicas;
+    }
+
+    pub fn politicas(&self) -> &Politicas {
+        &self.politicas
+    }
+
+    // Prazo de um empréstimo ou renovação feito hoje: se cair num dia em que a
+    // biblioteca está fechada, passa para o próximo dia aberto
+    fn prazo_de_devolucao(&self) -> NaiveDate {
+        let prazo = self.hoje() + chrono::Duration::days(self.politicas.prazo_dias);
+        self.calendario.proximo_dia_aberto(prazo)
+    }
+
+    pub fn definir_autosalvamento(&mut self, modo: ModoAutosalvamento) {
+        if let ModoAutosalvamento::Intervalo(intervalo) = modo {
+            self.salvaguarda.iniciar_timer(intervalo);
+        }
+        self.autosalvamento = modo;
+    }
+
+    pub fn tem_alteracoes(&self) -> bool {
+        self.salvaguarda.tem_pendencias()
+    }
+
+    // Para quem precisa salvar de fora da thread principal (sinais, pânico)
+    pub fn salvaguarda(&self) -> Salvaguarda {
+        self.salvaguarda.clone()
+    }
+
+    pub fn descartar_alteracoes(&self) {
+        self.salvaguarda.descartar();
+    }
+
+    pub fn entrar(&mut self, sessao: Sessao) {
+        self.sessao = Some(sessao);
+    }
+
+    pub fn sessao(&self) -> Option<&Sessao> {
+        self.sessao.as_ref()
+    }
+
+    pub fn operador(&self) -> &str {
+        self.sessao
+            .as_ref()
+            .map_or(OPERADOR_PADRAO, |sessao| sessao.login.as_str())
+    }
+
+    fn exigir(&self, permissao: Permissao) -> Result<(), ErroBiblioteca> {
+        match &self.sessao {
+            Some(sessao) => sessao.exigir(permissao),
+            None => Err(ErroBiblioteca::PermissaoNegada(format!(
+                "é preciso entrar no sistema para {}",
+                permissao
+            ))),
+        }
+    }
+
+    // Quem fez o quê e quando, com o estado das entidades antes e depois
+    pub fn auditoria(
+        &self,
+        filtro: &FiltroAuditoria,
+    ) -> Result<Vec<RegistroAuditoria>, ErroBiblioteca> {
+        self.exigir(Permissao::ConsultarAuditoria)?;
+        auditoria::consultar(self.salvaguarda.caminho(), filtro)
+    }
+
+    // Chamada depois de toda operação que altera livros, usuários ou empréstimos
+    fn registrar_alteracao(&mut self, evento: Evento) {
+        if let Some(comando) = Comando::do_evento(&evento) {
+            self.desfazer.registrar(comando);
+        }
+        self.anotar_evento(evento);
+    }
+
+    // Como `registrar_alteracao`, mas sem mexer nas pilhas de desfazer/refazer
+    fn anotar_evento(&mut self, evento: Evento) {
+        if let Some(lote) = self.lote.as_mut() {
+            lote.push(evento);
+            return;
+        }
+
+        self.ultima_sequencia += 1;
+        let registro = RegistroEvento {
+            sequencia: self.ultima_sequencia,
+            momento: self.agora(),
+            operador: self.operador().to_string(),
+            evento,
         };
+        self.salvaguarda.marcar(registro);
 
-        let arquivo = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&self.caminho_arquivo)
-            .map_err(|e| {
-                ErroBiblioteca::ErroPersistencia(format!("Erro ao criar arquivo: {}", e))
-            })?;
-
-        let escritor = BufWriter::new(arquivo);
-        serde_json::to_writer_pretty(escritor, &dados).map_err(|e| {
-            ErroBiblioteca::ErroPersistencia(format!("Erro ao serializar JSON: {}", e))
-        })?;
+        if self.autosalvamento == ModoAutosalvamento::ACadaAlteracao
+            && let Err(e) = self.salvaguarda.salvar_pendencias()
+        {
+            eprintln!("Erro no autosalvamento: {}", e);
+        }
+    }
 
+    // ======== Transações ========
+
+    // Executa várias operações como uma só. Cada uma valida suas regras já
+    // enxergando as anteriores; no fim, os dados passam pela verificação de
+    // integridade. Se algo falhar, tudo volta a ser como antes e nada chega ao
+    // disco. Se der certo, o diário recebe um único evento `Lote`.
+    pub fn transacao<T>(
+        &mut self,
+        operacoes: impl FnOnce(&mut Biblioteca) -> Result<T, ErroBiblioteca>,
+    ) -> Result<T, ErroBiblioteca> {
+        // Transação dentro de transação faz parte da de fora
+        if self.lote.is_some() {
+            return operacoes(self);
+        }
+
+        let antes = self.dados();
+        let desfazer = self.desfazer.clone();
+        self.lote = Some(Vec::new());
+
+        let resultado = operacoes(self).and_then(|valor| {
+            self.validar_transacao(&antes)?;
+            Ok(valor)
+        });
+        let mut eventos = self.lote.take().unwrap_or_default();
+
+        match resultado {
+            Ok(valor) => {
+                match eventos.len() {
+                    0 => {}
+                    1 => self.anotar_evento(eventos.remove(0)),
+                    _ => self.anotar_evento(Evento::Lote(eventos)),
+                }
+                Ok(valor)
+            }
+            Err(erro) => {
+                self.restaurar(antes);
+                self.desfazer = desfazer;
+                Err(erro)
+            }
+        }
+    }
+
+    fn retirar(&mut self) -> DadosPersistencia {
+        DadosPersistencia {
+            livros: mem::take(&mut self.livros),
+            usuarios: mem::take(&mut self.usuarios),
+            emprestimos: mem::take(&mut self.emprestimos),
+            unidades: mem::take(&mut self.unidades),
+            parceiras: mem::take(&mut self.parceiras),
+            solicitacoes: mem::take(&mut self.solicitacoes),
+            ultima_sequencia: self.ultima_sequencia,
+        }
+    }
+
+    fn restaurar(&mut self, dados: DadosPersistencia) {
+        self.livros = dados.livros;
+        self.usuarios = dados.usuarios;
+        self.emprestimos = dados.emprestimos;
+        self.unidades = dados.unidades;
+        self.parceiras = dados.parceiras;
+        self.solicitacoes = dados.solicitacoes;
+        self.ultima_sequencia = dados.ultima_sequencia;
+    }
+
+    // Para quem responde "feito" ou "não feito" a cada alteração (o servidor):
+    // executa e salva. Se a operação falhar, ou se nada chegar ao disco, tudo
+    // volta a ser como antes e quem chamou pode tentar de novo sem aplicar a
+    // alteração duas vezes. Se só o retrato falhar, o diário já garante a
+    // alteração e ela conta como feita.
+    pub fn alterar_e_salvar<T>(
+        &mut self,
+        operacao: impl FnOnce(&mut Biblioteca) -> Result<T, ErroBiblioteca>,
+    ) -> Result<T, ErroBiblioteca> {
+        let antes = self.dados();
+        let desfazer = self.desfazer.clone();
+        let pendencias = self.salvaguarda.pendencias();
+
+        let resultado = operacao(self).and_then(|valor| match self.salvar() {
+            Ok(()) => Ok(valor),
+            Err(erro) if self.salvaguarda.diario_em_dia() => {
+                eprintln!("Aviso: {} (a alteração está no diário de eventos)", erro);
+                Ok(valor)
+            }
+            Err(erro) => Err(erro),
+        });
+        if resultado.is_err() {
+            self.restaurar(antes);
+            self.desfazer = desfazer;
+            self.salvaguarda.restaurar(pendencias);
+        }
+        resultado
+    }
+
+    // Problemas de integridade que a transação criou (os que já existiam não
+    // contam). Só o que os eventos do lote tocaram é verificado; importação e
+    // reparo mexem em tudo, então com eles a verificação é completa.
+    fn validar_transacao(&mut self, antes: &DadosPersistencia) -> Result<(), ErroBiblioteca> {
+        let eventos = self.lote.as_deref().unwrap_or_default();
+        let completa = eventos.iter().any(|evento| {
+            matches!(
+                evento,
+                Evento::EstadoImportado { .. } | Evento::DadosReparados { .. }
+            )
+        });
+        let ids: HashSet<Uuid> = eventos.iter().flat_map(Evento::entidades).collect();
+        let agora = self.agora();
+        let graves = |dados: &DadosPersistencia| -> Vec<String> {
+            verificacao::verificar(dados, agora)
+                .into_iter()
+                .filter(|p| p.gravidade >= Gravidade::Erro)
+                .map(|p| p.descricao)
+                .collect()
+        };
+
+        let (existentes, depois) = if completa {
+            (graves(antes), graves(&self.dados()))
+        } else {
+            // Os mapas atuais saem emprestados para o recorte e voltam em seguida
+            let atuais = self.retirar();
+            let depois = graves(&verificacao::recorte(&atuais, &ids));
+            self.restaurar(atuais);
+            (graves(&verificacao::recorte(antes, &ids)), depois)
+        };
+        let novos: Vec<String> = depois
+            .into_iter()
+            .filter(|descricao| !existentes.contains(descricao))
+            .collect();
+
+        if novos.is_empty() {
+            Ok(())
+        } else {
+            Err(ErroBiblioteca::EstadoInvalido(format!(
+                "A transação deixaria os dados inconsistentes: {}",
+                novos.join(" ")
+            )))
+        }
+    }
+
+    // ======== Desfazer / refazer ========
+
+    pub fn pilha_desfazer(&self) -> &PilhaDesfazer {
+        &self.desfazer
+    }
+
+    // Devolve o comando desfeito. Se ele não puder mais ser desfeito (algo mudou
+    // depois, por outra interface), sai da pilha; sem permissão, fica onde está.
+    pub fn desfazer(&mut self) -> Result<Comando, ErroBiblioteca> {
+        let comando = self
+            .desfazer
+            .retirar_desfazer()
+            .ok_or_else(|| ErroBiblioteca::EstadoInvalido("Nada para desfazer.".to_string()))?;
+
+        match self.reverter(&comando) {
+            Ok(()) => {
+                self.desfazer.devolver_refazer(comando.clone());
+                Ok(comando)
+            }
+            Err(e @ ErroBiblioteca::PermissaoNegada(_)) => {
+                self.desfazer.devolver_desfazer(comando);
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn refazer(&mut self) -> Result<Comando, ErroBiblioteca> {
+        let comando = self
+            .desfazer
+            .retirar_refazer()
+            .ok_or_else(|| ErroBiblioteca::EstadoInvalido("Nada para refazer.".to_string()))?;
+
+        match self.reaplicar(&comando) {
+            Ok(()) => {
+                self.desfazer.devolver_desfazer(comando.clone());
+                Ok(comando)
+            }
+            Err(e @ ErroBiblioteca::PermissaoNegada(_)) => {
+                self.desfazer.devolver_refazer(comando);
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn tem_emprestimos(&self, id: Uuid) -> bool {
+        self.emprestimos
+            .values()
+            .any(|e| e.get_id_livro() == id || e.get_id_usuario() == id)
+    }
+
+    fn reverter(&mut self, comando: &Comando) -> Result<(), ErroBiblioteca> {
+        match comando {
+            Comando::AdicionarLivro(livro) => {
+                self.exigir(Permissao::CadastrarLivro)?;
+                let id = livro.id();
+                if !self.livros.contains_key(&id) {
+                    return Err(ErroBiblioteca::LivroNaoEncontrado(id));
+                }
+                if self.tem_emprestimos(id) {
+                    return Err(ErroBiblioteca::EstadoInvalido(
+                        "O livro já tem empréstimos registrados.".to_string(),
+                    ));
+                }
+                self.livros.remove(&id);
+                self.anotar_evento(Evento::LivroRemovido(id));
+            }
+            Comando::AdicionarUsuario(usuario) => {
+                self.exigir(Permissao::CadastrarUsuario)?;
+                let id = usuario.id();
+                if !self.usuarios.contains_key(&id) {
+                    return Err(ErroBiblioteca::UsuarioNaoEncontrado(id));
+                }
+                if self.tem_emprestimos(id) {
+                    return Err(ErroBiblioteca::EstadoInvalido(
+                        "O usuário já tem empréstimos registrados.".to_string(),
+                    ));
+                }
+                self.usuarios.remove(&id);
+                self.anotar_evento(Evento::UsuarioRemovido(id));
+            }
+            Comando::RegistrarEmprestimo(emprestimo) => {
+                self.exigir(Permissao::Emprestar)?;
+                let id = emprestimo.id();
+                self.circulacao()
+                    .ativo(id, "O empréstimo já foi devolvido.")?;
+                let emprestimo = self.circulacao().cancelamento(id)?;
+                self.anotar_evento(Evento::EmprestimoCancelado(emprestimo));
+            }
+            Comando::RegistrarDevolucao(emprestimo) => {
+                self.exigir(Permissao::Devolver)?;
+                let agora = self.agora();
+                let reaberto = self.circulacao().reabertura(emprestimo.id(), agora)?;
+                self.anotar_evento(Evento::DevolucaoDesfeita(reaberto));
+            }
+        }
         Ok(())
     }
 
-    pub fn emprestar(
-        livro: &mut Livro,
-        id_usuario: Uuid,
+    fn reaplicar(&mut self, comando: &Comando) -> Result<(), ErroBiblioteca> {
+        match comando {
+            Comando::AdicionarLivro(livro) => {
+                self.exigir(Permissao::CadastrarLivro)?;
+                self.livros.insert(livro.id(), livro.clone());
+                self.anotar_evento(Evento::LivroAdicionado(livro.clone()));
+            }
+            Comando::AdicionarUsuario(usuario) => {
+                self.exigir(Permissao::CadastrarUsuario)?;
+                self.usuarios.insert(usuario.id(), usuario.clone());
+                self.anotar_evento(Evento::UsuarioAdicionado(usuario.clone()));
+            }
+            Comando::RegistrarEmprestimo(emprestimo) => {
+                self.exigir(Permissao::Emprestar)?;
+                self.usuario_ativo(emprestimo.get_id_usuario())?;
+                self.circulacao().saida(emprestimo.clone())?;
+                self.anotar_evento(Evento::EmprestimoRealizado(emprestimo.clone()));
+            }
+            Comando::RegistrarDevolucao(emprestimo) => {
+                self.exigir(Permissao::Devolver)?;
+                let agora = self.agora();
+                let devolvido = self.circulacao().retorno(
+                    emprestimo.id(),
+                    emprestimo.get_unidade_devolucao(),
+                    agora,
+                )?;
+                self.anotar_evento(Evento::EmprestimoDevolvido(devolvido));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn devolver(&mut self, id_emprestimo: Uuid) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Devolver)?;
+        let agora = self.agora();
+        let unidade = self.unidade_atual;
+        let emprestimo = self.circulacao().retorno(id_emprestimo, unidade, agora)?;
+        self.registrar_alteracao(Evento::EmprestimoDevolvido(emprestimo));
+        Ok(())
+    }
+
+    pub fn renovar(&mut self, id_emprestimo: Uuid) -> Result<NaiveDate, ErroBiblioteca> {
+        self.exigir(Permissao::Renovar)?;
+        let prazo = self.prazo_de_devolucao();
+        let agora = self.agora();
+        let emprestimo = self
+            .emprestimos
+            .get_mut(&id_emprestimo)
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))?;
+
+        if !emprestimo.esta_ativo() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Não é possível renovar um empréstimo já devolvido!".to_string(),
+            ));
+        }
+        if self
+            .livros
+            .get(&emprestimo.get_id_livro())
+            .is_some_and(|livro| livro.intercambio().is_some())
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro de intercâmbio não pode ser renovado: o prazo é da biblioteca parceira."
+                    .to_string(),
+            ));
+        }
+
+        let nova_data = emprestimo.renovar(prazo, agora);
+        let evento = Evento::EmprestimoRenovado(emprestimo.clone());
+        self.registrar_alteracao(evento);
+        Ok(nova_data)
+    }
+
+    // ======== Unidades ========
+
+    pub fn unidades(&self) -> &HashMap<Uuid, Unidade> {
+        &self.unidades
+    }
+
+    // Sem diferenciar maiúsculas; também aceita o id
+    pub fn unidade(&self, sigla_ou_id: &str) -> Result<&Unidade, ErroBiblioteca> {
+        let sigla = sigla_ou_id.trim().to_uppercase();
+        let id = Uuid::parse_str(sigla_ou_id.trim()).ok();
+        self.unidades
+            .values()
+            .find(|u| u.sigla == sigla || Some(u.id) == id)
+            .ok_or_else(|| ErroBiblioteca::UnidadeNaoEncontrada(sigla_ou_id.to_string()))
+    }
+
+    pub fn definir_unidade_atual(&mut self, unidade: Option<Uuid>) {
+        self.unidade_atual = unidade;
+    }
+
+    pub fn unidade_atual(&self) -> Option<Uuid> {
+        self.unidade_atual
+    }
+
+    pub fn cadastrar_unidade(&mut self, sigla: &str, nome: String) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::GerenciarUnidades)?;
+        let sigla = Unidade::validar(sigla, &nome)?;
+        if self.unidades.values().any(|u| u.sigla == sigla) {
+            return Err(ErroBiblioteca::EstadoInvalido(format!(
+                "Já existe uma unidade com a sigla {}.",
+                sigla
+            )));
+        }
+
+        let unidade = Unidade::new(sigla, nome, self.agora());
+        let id = unidade.id;
+        self.unidades.insert(id, unidade.clone());
+        self.registrar_alteracao(Evento::UnidadeAdicionada(unidade));
+        Ok(id)
+    }
+
+    pub fn renomear_unidade(
+        &mut self,
+        id_unidade: Uuid,
+        nome: String,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::GerenciarUnidades)?;
+        let agora = self.agora();
+        let unidade = self
+            .unidades
+            .get_mut(&id_unidade)
+            .ok_or_else(|| ErroBiblioteca::UnidadeNaoEncontrada(id_unidade.to_string()))?;
+        Unidade::validar(&unidade.sigla, &nome)?;
+
+        unidade.nome = nome;
+        unidade.carimbos.tocar(agora);
+        let evento = Evento::UnidadeAtualizada(unidade.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // Só unidades sem exemplares e sem empréstimos registrados nelas
+    pub fn remover_unidade(&mut self, id_unidade: Uuid) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::GerenciarUnidades)?;
+        if !self.unidades.contains_key(&id_unidade) {
+            return Err(ErroBiblioteca::UnidadeNaoEncontrada(id_unidade.to_string()));
+        }
+        let referencia = Some(id_unidade);
+        let em_uso =
+            self.livros
+                .values()
+                .any(|l| l.get_unidade() == referencia || l.get_local() == referencia)
+                || self.emprestimos.values().any(|e| {
+                    e.get_unidade() == referencia || e.get_unidade_devolucao() == referencia
+                });
+        if em_uso {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "A unidade tem exemplares ou empréstimos registrados; transfira os exemplares antes."
+                    .to_string(),
+            ));
+        }
+
+        self.unidades.remove(&id_unidade);
+        self.registrar_alteracao(Evento::UnidadeRemovida(id_unidade));
+        Ok(())
+    }
+
+    // Muda a unidade dona do exemplar; se ele não estiver lá, fica em trânsito
+    pub fn transferir_livro(
+        &mut self,
         id_livro: Uuid,
-    ) -> Result<Emprestimo, ErroBiblioteca> {
-        match livro.status {
-            StatusLivro::Emprestado => Err(ErroBiblioteca::EstadoInvalido(
-                "Livro já está emprestado!".to_string(),
-            )),
-            StatusLivro::Disponivel => {
-                livro.status = StatusLivro::Emprestado;
-                let emprestimo = Emprestimo::new(id_usuario, id_livro);
-                Ok(emprestimo)
+        destino: Uuid,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::GerenciarUnidades)?;
+        if !self.unidades.contains_key(&destino) {
+            return Err(ErroBiblioteca::UnidadeNaoEncontrada(destino.to_string()));
+        }
+        let agora = self.agora();
+        let livro = self
+            .livros
+            .get_mut(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+        if livro.get_unidade() == Some(destino) {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O exemplar já pertence a essa unidade.".to_string(),
+            ));
+        }
+
+        livro.definir_unidade(Some(destino), agora);
+        let evento = Evento::LivroAtualizado(livro.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // Chegada de um exemplar em trânsito à unidade dona
+    pub fn receber_livro(&mut self, id_livro: Uuid) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Devolver)?;
+        let agora = self.agora();
+        let unidade_atual = self.unidade_atual;
+        let livro = self
+            .livros
+            .get_mut(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+        if livro.em_transito().is_none() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O exemplar não está em trânsito.".to_string(),
+            ));
+        }
+        if unidade_atual.is_some_and(|aqui| livro.get_unidade() != Some(aqui)) {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O exemplar pertence a outra unidade; só ela pode recebê-lo.".to_string(),
+            ));
+        }
+
+        livro.receber(agora);
+        let evento = Evento::LivroAtualizado(livro.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // ======== Intercâmbio com outras bibliotecas ========
+
+    pub fn parceiras(&self) -> &HashMap<Uuid, Parceira> {
+        &self.parceiras
+    }
+
+    pub fn solicitacoes(&self) -> &HashMap<Uuid, Solicitacao> {
+        &self.solicitacoes
+    }
+
+    // Pelo nome (sem diferenciar maiúsculas) ou pelo id
+    pub fn parceira(&self, nome_ou_id: &str) -> Result<&Parceira, ErroBiblioteca> {
+        let nome = nome_ou_id.trim().to_lowercase();
+        let id = Uuid::parse_str(nome_ou_id.trim()).ok();
+        self.parceiras
+            .values()
+            .find(|p| p.nome.to_lowercase() == nome || Some(p.id) == id)
+            .ok_or_else(|| ErroBiblioteca::ParceiraNaoEncontrada(nome_ou_id.to_string()))
+    }
+
+    pub fn cadastrar_parceira(
+        &mut self,
+        nome: String,
+        email: Option<String>,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::Intercambio)?;
+        Parceira::validar(&nome)?;
+        let email = match email {
+            Some(email) => Usuario::validar_email(&email)?,
+            None => None,
+        };
+        if self.parceira(&nome).is_ok() {
+            return Err(ErroBiblioteca::EstadoInvalido(format!(
+                "Já existe uma parceira chamada {}.",
+                nome.trim()
+            )));
+        }
+
+        let parceira = Parceira::new(nome.trim().to_string(), email, self.agora());
+        let id = parceira.id;
+        self.parceiras.insert(id, parceira.clone());
+        self.registrar_alteracao(Evento::ParceiraAdicionada(parceira));
+        Ok(id)
+    }
+
+    // Pedido feito no balcão por um leitor
+    pub fn solicitar_intercambio(
+        &mut self,
+        id_usuario: Uuid,
+        titulo: String,
+        autor: String,
+        ano: u16,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::Emprestar)?;
+        self.usuario_ativo(id_usuario)?;
+        Livro::validar(&titulo, &autor, ano, self.hoje())?;
+
+        let solicitacao = Solicitacao::new(id_usuario, titulo, autor, ano, self.agora());
+        let id = solicitacao.id;
+        self.solicitacoes.insert(id, solicitacao.clone());
+        self.registrar_alteracao(Evento::SolicitacaoRegistrada(solicitacao));
+        Ok(id)
+    }
+
+    fn solicitacao_mut(&mut self, id: Uuid) -> Result<&mut Solicitacao, ErroBiblioteca> {
+        self.solicitacoes
+            .get_mut(&id)
+            .ok_or(ErroBiblioteca::SolicitacaoNaoEncontrada(id))
+    }
+
+    pub fn enviar_solicitacao(
+        &mut self,
+        id_solicitacao: Uuid,
+        id_parceira: Uuid,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Intercambio)?;
+        if !self.parceiras.contains_key(&id_parceira) {
+            return Err(ErroBiblioteca::ParceiraNaoEncontrada(
+                id_parceira.to_string(),
+            ));
+        }
+        let agora = self.agora();
+        let solicitacao = self.solicitacao_mut(id_solicitacao)?;
+        solicitacao.enviar(id_parceira, agora)?;
+        let evento = Evento::SolicitacaoAtualizada(solicitacao.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // O livro chegou da parceira: entra no catálogo como registro temporário.
+    // Devolve o id desse registro.
+    pub fn receber_intercambio(
+        &mut self,
+        id_solicitacao: Uuid,
+        vencimento: NaiveDate,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::Intercambio)?;
+        if vencimento <= self.hoje() {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "O vencimento dado pela parceira ({}) já passou.",
+                vencimento.format("%d/%m/%Y")
+            )));
+        }
+
+        self.transacao(|biblioteca| {
+            let agora = biblioteca.agora();
+            let solicitacao = biblioteca
+                .solicitacoes
+                .get(&id_solicitacao)
+                .ok_or(ErroBiblioteca::SolicitacaoNaoEncontrada(id_solicitacao))?;
+            let mut livro = Livro::new(
+                solicitacao.titulo.clone(),
+                solicitacao.autor.clone(),
+                solicitacao.ano,
+                agora,
+            );
+            livro.marcar_intercambio(id_solicitacao);
+            if biblioteca.unidade_atual.is_some() {
+                livro.definir_unidade(biblioteca.unidade_atual, agora);
             }
+            let id_livro = livro.id();
+
+            let solicitacao = biblioteca.solicitacao_mut(id_solicitacao)?;
+            solicitacao.receber(vencimento, id_livro, agora)?;
+            let evento = Evento::SolicitacaoAtualizada(solicitacao.clone());
+
+            // Sem desfazer: o registro temporário acompanha o pedido até o fim
+            biblioteca.livros.insert(id_livro, livro.clone());
+            biblioteca.anotar_evento(Evento::LivroAdicionado(livro));
+            biblioteca.anotar_evento(evento);
+            Ok(id_livro)
+        })
+    }
+
+    // Empresta o livro recebido ao leitor que o pediu. O prazo é o de sempre,
+    // mas nunca passa do vencimento da parceira menos a folga para devolvê-lo.
+    pub fn emprestar_intercambio(&mut self, id_solicitacao: Uuid) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::Emprestar)?;
+        let solicitacao = self
+            .solicitacoes
+            .get(&id_solicitacao)
+            .ok_or(ErroBiblioteca::SolicitacaoNaoEncontrada(id_solicitacao))?;
+        let (Some(id_livro), Some(vencimento)) =
+            (solicitacao.get_livro(), solicitacao.vencimento())
+        else {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O livro desse pedido ainda não chegou.".to_string(),
+            ));
+        };
+        let id_usuario = solicitacao.id_usuario;
+        self.usuario_ativo(id_usuario)?;
+        self.verificar_limite(id_usuario)?;
+
+        let hoje = self.hoje();
+        let mut prazo = self
+            .prazo_de_devolucao()
+            .min(vencimento - chrono::Duration::days(intercambio::FOLGA_DEVOLUCAO_DIAS));
+        while prazo > hoje && self.calendario.fechamento(prazo).is_some() {
+            prazo -= chrono::Duration::days(1);
         }
+        if prazo <= hoje {
+            return Err(ErroBiblioteca::EstadoInvalido(format!(
+                "O vencimento da parceira ({}) não deixa tempo para emprestar o livro.",
+                vencimento.format("%d/%m/%Y")
+            )));
+        }
+
+        self.transacao(|biblioteca| {
+            let agora = biblioteca.agora();
+            let emprestimo =
+                Emprestimo::new(id_usuario, id_livro, prazo, biblioteca.unidade_atual, agora);
+            let id_emprestimo = emprestimo.id();
+            let solicitacao = biblioteca.solicitacao_mut(id_solicitacao)?;
+            solicitacao.emprestar(id_emprestimo, agora)?;
+            let evento = Evento::SolicitacaoAtualizada(solicitacao.clone());
+
+            biblioteca.circulacao().saida(emprestimo.clone())?;
+            biblioteca.anotar_evento(Evento::EmprestimoRealizado(emprestimo));
+            biblioteca.anotar_evento(evento);
+            Ok(id_emprestimo)
+        })
     }
 
-    // pub fn devolver(
-    //     &mut self,
-    //     livro: &mut Livro,
-    //     emprestimo: &mut Emprestimo,
-    // ) -> Result<(), ErroBiblioteca> {
-    //     match livro.status {
-    //         StatusLivro::Disponivel => Err(ErroBiblioteca::EstadoInvalido(
-    //             "Livro já está disponível!".to_string(),
-    //         )),
-    //         StatusLivro::Emprestado => {
-    //             livro.status = StatusLivro::Disponivel;
-    //             emprestimo.status = StatusEmprestimo::Devolvido;
+    // O livro voltou para a parceira; o registro temporário sai do catálogo
+    pub fn devolver_a_parceira(&mut self, id_solicitacao: Uuid) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Intercambio)?;
+        let solicitacao = self
+            .solicitacoes
+            .get(&id_solicitacao)
+            .ok_or(ErroBiblioteca::SolicitacaoNaoEncontrada(id_solicitacao))?;
+        let id_livro = solicitacao.get_livro();
+        if let Some(id_emprestimo) = solicitacao.get_emprestimo()
+            && self
+                .emprestimos
+                .get(&id_emprestimo)
+                .is_some_and(Emprestimo::esta_ativo)
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "O leitor ainda não devolveu o livro.".to_string(),
+            ));
+        }
 
-    //             Ok(())
-    //         }
-    //     }
-    // }
+        self.transacao(|biblioteca| {
+            let agora = biblioteca.agora();
+            let solicitacao = biblioteca.solicitacao_mut(id_solicitacao)?;
+            solicitacao.devolver(agora)?;
+            let evento = Evento::SolicitacaoAtualizada(solicitacao.clone());
 
-    pub fn adicionar_livro(&mut self) -> Result<Uuid, ErroBiblioteca> {
-        let titulo = ler_string("Titulo: ".to_string());
-        let autor = ler_string("Autor: ".to_string());
-        let ano = ler_u16("Ano: ".to_string());
+            if let Some(id_livro) = id_livro
+                && biblioteca.livros.contains_key(&id_livro)
+            {
+                biblioteca.retirar_livro(id_livro)?;
+            }
+            biblioteca.anotar_evento(evento);
+            Ok(())
+        })
+    }
+
+    pub fn cancelar_solicitacao(&mut self, id_solicitacao: Uuid) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::Intercambio)?;
+        let agora = self.agora();
+        let solicitacao = self.solicitacao_mut(id_solicitacao)?;
+        solicitacao.cancelar(agora)?;
+        let evento = Evento::SolicitacaoAtualizada(solicitacao.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // ======== Inventário ========
+
+    // Marca (ou desmarca, quando achados) exemplares como extraviados, todos ou nenhum
+    pub fn definir_extravio(
+        &mut self,
+        ids_livros: &[Uuid],
+        extraviado: bool,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarLivro)?;
+        self.transacao(|biblioteca| {
+            let agora = biblioteca.agora();
+            for id_livro in ids_livros {
+                let livro = biblioteca
+                    .livros
+                    .get_mut(id_livro)
+                    .ok_or(ErroBiblioteca::LivroNaoEncontrado(*id_livro))?;
+                if livro.extraviado().is_some() == extraviado {
+                    continue;
+                }
+                livro.marcar_extravio(extraviado, agora);
+                let evento = Evento::LivroAtualizado(livro.clone());
+                biblioteca.registrar_alteracao(evento);
+            }
+            Ok(())
+        })
+    }
+
+    // ======== Operações sem interação com o terminal ========
+    // Usadas pelas interfaces que não são o menu de console (TUI, servidor HTTP)
 
-        let livro = Livro::new(titulo, autor, ano);
+    pub fn cadastrar_livro(
+        &mut self,
+        titulo: String,
+        autor: String,
+        ano: u16,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.cadastrar_livro_catalogado(titulo, autor, ano, Catalogacao::default())
+    }
+
+    pub fn cadastrar_livro_catalogado(
+        &mut self,
+        titulo: String,
+        autor: String,
+        ano: u16,
+        catalogacao: Catalogacao,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::CadastrarLivro)?;
+        Livro::validar(&titulo, &autor, ano, self.hoje())?;
+
+        let mut livro = Livro::new(titulo, autor, ano, self.agora());
+        livro.catalogar(catalogacao, self.agora());
+        // O exemplar passa a ser da unidade onde foi cadastrado
+        if self.unidade_atual.is_some() {
+            livro.definir_unidade(self.unidade_atual, self.agora());
+        }
         let id = livro.id();
 
-        self.livros.insert(livro.id(), livro);
+        self.livros.insert(id, livro.clone());
+        self.registrar_alteracao(Evento::LivroAdicionado(livro));
+        Ok(id)
+    }
+
+    pub fn cadastrar_usuario(&mut self, nome: String) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::CadastrarUsuario)?;
+        Usuario::validar(&nome)?;
+
+        let usuario = Usuario::new(nome, self.agora());
+        let id = usuario.id();
 
+        self.usuarios.insert(id, usuario.clone());
+        self.registrar_alteracao(Evento::UsuarioAdicionado(usuario));
         Ok(id)
     }
 
-    pub fn adicionar_usuario(&mut self) -> Result<Uuid, ErroBiblioteca> {
-        let nome = ler_string("Nome: ".to_string());
-        let usuario = Usuario::new(nome);
+    pub fn atualizar_livro(
+        &mut self,
+        id_livro: Uuid,
+        titulo: String,
+        autor: String,
+        ano: u16,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarLivro)?;
+        Livro::validar(&titulo, &autor, ano, self.hoje())?;
+
+        let agora = self.agora();
+        let livro = self
+            .livros
+            .get_mut(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+
+        livro.atualizar(titulo, autor, ano, agora);
+        let evento = Evento::LivroAtualizado(livro.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    pub fn atualizar_usuario(
+        &mut self,
+        id_usuario: Uuid,
+        nome: String,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarUsuario)?;
+        Usuario::validar(&nome)?;
+
+        let agora = self.agora();
+        let usuario = self
+            .usuarios
+            .get_mut(&id_usuario)
+            .ok_or(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario))?;
+
+        usuario.nome = nome;
+        usuario.carimbos.tocar(agora);
+        let evento = Evento::UsuarioAtualizado(usuario.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // `None` (ou texto vazio) remove o endereço
+    pub fn definir_email(
+        &mut self,
+        id_usuario: Uuid,
+        email: Option<String>,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarUsuario)?;
+        let email = match email {
+            Some(email) => Usuario::validar_email(&email)?,
+            None => None,
+        };
+
+        let agora = self.agora();
+        let usuario = self
+            .usuarios
+            .get_mut(&id_usuario)
+            .ok_or(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario))?;
+        if usuario.email == email {
+            return Ok(());
+        }
+
+        usuario.email = email;
+        usuario.carimbos.tocar(agora);
+        let evento = Evento::UsuarioAtualizado(usuario.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
+
+    // Livro emprestado não pode sair. Se já teve empréstimos, é arquivado
+    // (continua existindo para o histórico); senão, é apagado de vez.
+    // O livro devolvido indica qual dos dois aconteceu em `arquivado`.
+    pub fn remover_livro(&mut self, id_livro: Uuid) -> Result<Livro, ErroBiblioteca> {
+        self.exigir(Permissao::RemoverLivro)?;
+        let livro = self
+            .livros
+            .get(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+        if livro.intercambio().is_some() {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro de intercâmbio sai do catálogo quando é devolvido à parceira.".to_string(),
+            ));
+        }
+        self.retirar_livro(id_livro)
+    }
+
+    // Sem checar permissão: a devolução à parceira também tira o registro temporário
+    fn retirar_livro(&mut self, id_livro: Uuid) -> Result<Livro, ErroBiblioteca> {
+        if self
+            .emprestimos
+            .values()
+            .any(|e| e.get_id_livro() == id_livro && e.esta_ativo())
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro está emprestado e não pode ser removido!".to_string(),
+            ));
+        }
+
+        if self.tem_emprestimos(id_livro) {
+            let agora = self.agora();
+            let livro = self
+                .livros
+                .get_mut(&id_livro)
+                .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+            livro.arquivado = true;
+            livro.tocar(agora);
+            let livro = livro.clone();
+            self.registrar_alteracao(Evento::LivroArquivado(livro.clone()));
+            return Ok(livro);
+        }
+
+        let livro = self
+            .livros
+            .remove(&id_livro)
+            .ok_or(ErroBiblioteca::LivroNaoEncontrado(id_livro))?;
+        self.registrar_alteracao(Evento::LivroRemovido(id_livro));
+        Ok(livro)
+    }
 
-        let id = usuario.id;
+    // Mesmas regras de `remover_livro`
+    pub fn remover_usuario(&mut self, id_usuario: Uuid) -> Result<Usuario, ErroBiblioteca> {
+        self.exigir(Permissao::RemoverUsuario)?;
+        if !self.usuarios.contains_key(&id_usuario) {
+            return Err(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario));
+        }
+        if self
+            .emprestimos
+            .values()
+            .any(|e| e.get_id_usuario() == id_usuario && e.esta_ativo())
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Usuário tem livros emprestados e não pode ser removido!".to_string(),
+            ));
+        }
+
+        if self.tem_emprestimos(id_usuario) {
+            let agora = self.agora();
+            let usuario = self
+                .usuarios
+                .get_mut(&id_usuario)
+                .ok_or(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario))?;
+            usuario.arquivado = true;
+            usuario.carimbos.tocar(agora);
+            let usuario = usuario.clone();
+            self.registrar_alteracao(Evento::UsuarioArquivado(usuario.clone()));
+            return Ok(usuario);
+        }
+
+        let usuario = self
+            .usuarios
+            .remove(&id_usuario)
+            .ok_or(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario))?;
+        self.registrar_alteracao(Evento::UsuarioRemovido(id_usuario));
+        Ok(usuario)
+    }
+
+    // Usuário que pode pegar livros: existe e não foi arquivado
+    fn usuario_ativo(&self, id_usuario: Uuid) -> Result<&Usuario, ErroBiblioteca> {
+        match self.usuarios.get(&id_usuario) {
+            None => Err(ErroBiblioteca::UsuarioNaoEncontrado(id_usuario)),
+            Some(usuario) if usuario.arquivado => Err(ErroBiblioteca::EstadoInvalido(format!(
+                "O usuário {} está arquivado.",
+                usuario.get_nome()
+            ))),
+            Some(usuario) => Ok(usuario),
+        }
+    }
+
+    pub fn atualizar_emprestimo(
+        &mut self,
+        id_emprestimo: Uuid,
+        id_usuario: Uuid,
+        data_devolucao: NaiveDate,
+    ) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarEmprestimo)?;
+
+        let atual = self
+            .emprestimos
+            .get(&id_emprestimo)
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))?;
+        if atual.get_id_usuario() != id_usuario {
+            self.usuario_ativo(id_usuario)?;
+        }
+
+        let agora = self.agora();
+        let emprestimo = self
+            .emprestimos
+            .get_mut(&id_emprestimo)
+            .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id_emprestimo))?;
+        emprestimo.alterar(id_usuario, data_devolucao, agora)?;
+        let evento = Evento::EmprestimoAtualizado(emprestimo.clone());
+        self.registrar_alteracao(evento);
+        Ok(())
+    }
 
-        self.usuarios.insert(id, usuario);
+    // Apaga o registro (ex.: lançado por engano); se estava ativo, o livro volta a ficar disponível
+    pub fn remover_emprestimo(
+        &mut self,
+        id_emprestimo: Uuid,
+    ) -> Result<Emprestimo, ErroBiblioteca> {
+        self.exigir(Permissao::RemoverEmprestimo)?;
+
+        let emprestimo = self.circulacao().cancelamento(id_emprestimo)?;
+        self.registrar_alteracao(Evento::EmprestimoCancelado(emprestimo.clone()));
+        Ok(emprestimo)
+    }
+
+    pub fn realizar_emprestimo(
+        &mut self,
+        id_usuario: Uuid,
+        id_livro: Uuid,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::Emprestar)?;
+        self.usuario_ativo(id_usuario)?;
+        self.efetivar_emprestimo(id_usuario, id_livro)
+    }
+
+    fn verificar_limite(&self, id_usuario: Uuid) -> Result<(), ErroBiblioteca> {
+        if let Some(limite) = self.politicas.limite_por_usuario {
+            let ativos = self
+                .emprestimos
+                .values()
+                .filter(|e| e.esta_ativo() && e.get_id_usuario() == id_usuario)
+                .count();
+            if ativos >= limite {
+                return Err(ErroBiblioteca::EstadoInvalido(format!(
+                    "O usuário já tem {} empréstimo(s) ativo(s); o limite é {}.",
+                    ativos, limite
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // A saída do livro e o registro do empréstimo acontecem juntos ou não acontecem
+    fn efetivar_emprestimo(
+        &mut self,
+        id_usuario: Uuid,
+        id_livro: Uuid,
+    ) -> Result<Uuid, ErroBiblioteca> {
+        self.verificar_limite(id_usuario)?;
+        if self
+            .livros
+            .get(&id_livro)
+            .is_some_and(|livro| livro.intercambio().is_some())
+        {
+            return Err(ErroBiblioteca::EstadoInvalido(
+                "Livro de intercâmbio só vai para o leitor que o pediu (intercambio emprestar)."
+                    .to_string(),
+            ));
+        }
+        let emprestimo = Emprestimo::new(
+            id_usuario,
+            id_livro,
+            self.prazo_de_devolucao(),
+            self.unidade_atual,
+            self.agora(),
+        );
+        let id = emprestimo.id();
+        self.circulacao().saida(emprestimo.clone())?;
+        self.registrar_alteracao(Evento::EmprestimoRealizado(emprestimo));
         Ok(id)
     }
 
+    fn circulacao(&mut self) -> Circulacao<'_> {
+        Circulacao::new(&mut self.livros, &mut self.emprestimos)
+    }
+
+    pub fn livros(&self) -> &HashMap<Uuid, Livro> {
+        &self.livros
+    }
+
+    pub fn usuarios(&self) -> &HashMap<Uuid, Usuario> {
+        &self.usuarios
+    }
+
+    pub fn emprestimos(&self) -> &HashMap<Uuid, Emprestimo> {
+        &self.emprestimos
+    }
+
+    pub fn adicionar_livro(&mut self) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::CadastrarLivro)?;
+        let titulo = ler_string("Titulo: ")?;
+        let autor = ler_string("Autor: ")?;
+        let ano = ler_ano("Ano: ", self.hoje())?;
+
+        self.cadastrar_livro(titulo, autor, ano)
+    }
+
+    pub fn adicionar_usuario(&mut self) -> Result<Uuid, ErroBiblioteca> {
+        self.exigir(Permissao::CadastrarUsuario)?;
+        let nome = ler_string("Nome: ")?;
+        let email = ler_string_opcional("E-mail (opcional): ")?;
+        Usuario::validar_email(&email)?;
+
+        self.transacao(|biblioteca| {
+            let id = biblioteca.cadastrar_usuario(nome)?;
+            biblioteca.definir_email(id, Some(email))?;
+            Ok(id)
+        })
+    }
+
+    // ======== Escolha de itens em listas numeradas (menu de console) ========
+
+    fn escolher_livro(&self) -> Result<Uuid, ErroBiblioteca> {
+        let mut livros: Vec<&Livro> = self.livros.values().filter(|l| !l.arquivado).collect();
+        livros.sort_by(|a, b| a.get_titulo().cmp(b.get_titulo()));
+
+        println!("\n=== Lista de Livros ===");
+        for (indice, livro) in livros.iter().enumerate() {
+            println!("ID: {}", indice);
+            println!("{}", livro);
+            println!("---------------------------");
+        }
+
+        let indice = ler_indice("Digite o ID do livro: ", livros.len())?;
+        Ok(livros[indice].id())
+    }
+
+    fn escolher_usuario(&self) -> Result<Uuid, ErroBiblioteca> {
+        let mut usuarios: Vec<&Usuario> = self.usuarios.values().filter(|u| !u.arquivado).collect();
+        usuarios.sort_by(|a, b| a.nome.cmp(&b.nome));
+
+        println!("\n=== Lista de Usuários ===");
+        for (indice, usuario) in usuarios.iter().enumerate() {
+            println!("ID: {}", indice);
+            println!("{}", usuario);
+            println!("---------------------------");
+        }
+
+        let indice = ler_indice("Digite o ID do usuário: ", usuarios.len())?;
+        Ok(usuarios[indice].id())
+    }
+
+    fn escolher_emprestimo(&self) -> Result<Uuid, ErroBiblioteca> {
+        let mut emprestimos: Vec<&Emprestimo> = self.emprestimos.values().collect();
+        emprestimos.sort_by_key(|e| e.get_data_emprestimo());
+
+        println!("\n=== Lista de Empréstimos ===");
+        for (indice, emprestimo) in emprestimos.iter().enumerate() {
+            println!("ID: {}", indice);
+            self.exibir_emprestimo(emprestimo);
+            println!("---------------------------");
+        }
+
+        let indice = ler_indice("Digite o ID do empréstimo: ", emprestimos.len())?;
+        Ok(emprestimos[indice].id())
+    }
+
+    // ======== Edição e remoção pelo menu de console ========
+
+    pub fn editar_livro(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarLivro)?;
+        let id = self.escolher_livro()?;
+        let livro = &self.livros[&id];
+
+        let titulo = ler_string_ou("Título", livro.get_titulo())?;
+        let autor = ler_string_ou("Autor", livro.get_autor())?;
+        let ano = ler_ano_ou("Ano", livro.get_ano(), self.hoje())?;
+
+        self.atualizar_livro(id, titulo, autor, ano)?;
+        println!("✅ Livro atualizado com sucesso!");
+        Ok(())
+    }
+
+    pub fn excluir_livro(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::RemoverLivro)?;
+        let id = self.escolher_livro()?;
+
+        let pergunta = format!("Remover o livro \"{}\"?", self.livros[&id].get_titulo());
+        if !ler_confirmacao(&pergunta)? {
+            return Err(ErroBiblioteca::OperacaoCancelada);
+        }
+
+        if self.remover_livro(id)?.arquivado {
+            println!("✅ O livro tem histórico de empréstimos e foi arquivado.");
+        } else {
+            println!("✅ Livro removido com sucesso!");
+        }
+        Ok(())
+    }
+
+    pub fn editar_usuario(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarUsuario)?;
+        let id = self.escolher_usuario()?;
+
+        let nome = ler_string_ou("Nome", &self.usuarios[&id].nome)?;
+        let atual = self.usuarios[&id].email.clone().unwrap_or_default();
+        let email = ler_string_ou("E-mail (\"-\" para remover)", &atual)?;
+        let email = if email == "-" { None } else { Some(email) };
+
+        self.transacao(|biblioteca| {
+            biblioteca.atualizar_usuario(id, nome)?;
+            biblioteca.definir_email(id, email)
+        })?;
+        println!("✅ Usuário atualizado com sucesso!");
+        Ok(())
+    }
+
+    pub fn excluir_usuario(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::RemoverUsuario)?;
+        let id = self.escolher_usuario()?;
+
+        let pergunta = format!("Remover o usuário \"{}\"?", self.usuarios[&id].nome);
+        if !ler_confirmacao(&pergunta)? {
+            return Err(ErroBiblioteca::OperacaoCancelada);
+        }
+
+        if self.remover_usuario(id)?.arquivado {
+            println!("✅ O usuário tem histórico de empréstimos e foi arquivado.");
+        } else {
+            println!("✅ Usuário removido com sucesso!");
+        }
+        Ok(())
+    }
+
+    pub fn editar_emprestimo(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::EditarEmprestimo)?;
+        let id = self.escolher_emprestimo()?;
+
+        let id_usuario = if ler_confirmacao("Trocar o usuário do empréstimo?")? {
+            self.escolher_usuario()?
+        } else {
+            self.emprestimos[&id].get_id_usuario()
+        };
+        let data_devolucao = ler_data_ou(
+            "Data de devolução",
+            self.emprestimos[&id].get_data_devolucao(),
+        )?;
+
+        self.atualizar_emprestimo(id, id_usuario, data_devolucao)?;
+        println!("✅ Empréstimo atualizado com sucesso!");
+        Ok(())
+    }
+
+    pub fn excluir_emprestimo(&mut self) -> Result<(), ErroBiblioteca> {
+        self.exigir(Permissao::RemoverEmprestimo)?;
+        let id = self.escolher_emprestimo()?;
+
+        if !ler_confirmacao("Remover este empréstimo? O registro será apagado.")? {
+            return Err(ErroBiblioteca::OperacaoCancelada);
+        }
+
+        self.remover_emprestimo(id)?;
+        println!("✅ Empréstimo removido com sucesso!");
+        Ok(())
+    }
+
     pub fn registrar_emprestimo(&mut self) -> Result<(), ErroBiblioteca> {
-        let mensagem = String::from(
-            "Como deseja buscar o livro: 
+        self.exigir(Permissao::Emprestar)?;
+        let mensagem = "Como deseja buscar o livro: 
         [1] Buscar pelo ID (Listar todos os Livros) 
         [2] Buscar pelo Título 
         [3] Buscar pelo Nome do Autor 
-        Opção: ",
-        );
+        Opção: ";
 
         self.listar_usuarios();
 
-        let id_usuario = ler_uuid("Digite o UUID do usuário que vai fazer o empréstimo: ");
+        let id_usuario = ler_uuid("Digite o UUID do usuário que vai fazer o empréstimo: ")?;
+        self.usuario_ativo(id_usuario)?;
         println!("***** Realizar Empréstimo ******");
 
-        let opcao = ler_i32(mensagem);
+        let opcao = ler_numero(mensagem, 1..=3)?;
 
         if opcao == 1 {
             // ======== BUSCA POR ID (LISTAR TODOS OS LIVROS) ========
 
             // Coleta todos os livros em um vetor para exibir e acessar por índice
-            let mut livros_vec: Vec<(&Uuid, &mut Livro)> = self.livros.iter_mut().collect();
+            let livros_vec: Vec<(&Uuid, &Livro)> = self
+                .livros
+                .iter()
+                .filter(|(_, livro)| !livro.arquivado)
+                .collect();
 
             // Exibe a lista com índices numéricos
             Biblioteca::listar_livros_vec(&livros_vec);
 
-            // Usuário escolhe o livro pelo índice mostrado na lista
-            let id_livro =
-                ler_i32("Digite o ID do livro que deseja pegar emprestado: ".to_string()) as usize;
+            // Usuário escolhe o livro pelo índice mostrado na lista (já validado)
+            let id_livro = ler_indice(
+                "Digite o ID do livro que deseja pegar emprestado: ",
+                livros_vec.len(),
+            )?;
 
-            // Verifica se o índice é válido
-            if id_livro >= livros_vec.len() {
-                println!("ID inválido!");
-                return Ok(());
-            }
-
-            // Obtém o UUID e referência mutável do livro escolhido
-            let (uuid_livro, livro_mut) = &mut livros_vec[id_livro];
-            let uuid_livro = **uuid_livro; // desreferencia o ponteiro duplo para copiar o UUID
+            // Obtém o UUID do livro escolhido
+            let uuid_livro = *livros_vec[id_livro].0;
 
             // Cria o empréstimo
-            match Biblioteca::emprestar(livro_mut, id_usuario, uuid_livro) {
-                Ok(emprestimo) => {
-                    self.emprestimos.insert(emprestimo.id(), emprestimo);
-                    println!("✅ Empréstimo registrado com sucesso!");
-                }
+            match self.efetivar_emprestimo(id_usuario, uuid_livro) {
+                Ok(_) => println!("✅ Empréstimo registrado com sucesso!"),
                 Err(e) => println!("❌ Erro: {}", e),
             }
         } else if opcao == 2 {
-            let titulo = ler_string("Nome do titulo que deseja buscar: ".to_string());
+            let titulo = ler_string("Nome do titulo que deseja buscar: ")?;
 
             if let Some(livros) = self.buscar_livro_por_titulo(&titulo) {
-                let id_livro =
-                    ler_i32("Confirme o ID do livro que deseja pegar emprestado: ".to_string())
-                        as usize;
-
-                if id_livro >= livros.len() {
-                    println!("ID inválido!");
-                    return Ok(());
-                }
+                let id_livro = ler_indice(
+                    "Confirme o ID do livro que deseja pegar emprestado: ",
+                    livros.len(),
+                )?;
 
                 let (uuid_livro, _) = &livros[id_livro];
                 let uuid_livro = *uuid_livro; // copia o UUID (tipo Copy)
 
-                if let Some(livro_mut) = self.livros.get_mut(&uuid_livro) {
-                    let emprestimo = Biblioteca::emprestar(livro_mut, id_usuario, uuid_livro)?;
-                    livro_mut.status = StatusLivro::Emprestado;
-                    self.emprestimos.insert(emprestimo.id(), emprestimo);
-                }
+                self.efetivar_emprestimo(id_usuario, uuid_livro)?;
             } else {
                 println!("Nenhum livro encontrado para o autor '{}'.", titulo);
             }
         } else if opcao == 3 {
             // ======== BUSCA POR AUTOR ========
-            let nome_autor = ler_string("Nome do autor que deseja buscar: ".to_string());
+            let nome_autor = ler_string("Nome do autor que deseja buscar: ")?;
 
             if let Some(livros) = self.buscar_livro_por_autor(&nome_autor) {
-                let id_livro =
-                    ler_i32("Confirme o ID do livro que deseja pegar emprestado: ".to_string())
-                        as usize;
-
-                if id_livro >= livros.len() {
-                    println!("ID inválido!");
-                    return Ok(());
-                }
+                let id_livro = ler_indice(
+                    "Confirme o ID do livro que deseja pegar emprestado: ",
+                    livros.len(),
+                )?;
 
                 let (uuid_livro, _) = &livros[id_livro];
                 let uuid_livro = *uuid_livro; // copia o UUID (tipo Copy)
 
-                if let Some(livro_mut) = self.livros.get_mut(&uuid_livro) {
-                    let emprestimo = Biblioteca::emprestar(livro_mut, id_usuario, uuid_livro)?;
-                    livro_mut.status = StatusLivro::Emprestado;
-                    self.emprestimos.insert(emprestimo.id(), emprestimo);
-                }
+                self.efetivar_emprestimo(id_usuario, uuid_livro)?;
             } else {
                 println!("Nenhum livro encontrado para o autor '{}'.", nome_autor);
             }
@@ -262,26 +1760,24 @@ impl Biblioteca {
         }
 
         println!("\n=== Lista de Livros ===");
-        for (id, livro) in &self.livros {
+        for (id, livro) in self.livros.iter().filter(|(_, livro)| !livro.arquivado) {
             println!("ID: {}", id);
             println!("{}", livro);
             println!("---------------------------");
         }
     }
 
-    pub fn listar_livros_vec(livros: &Vec<(&Uuid, &mut Livro)>) {
+    pub fn listar_livros_vec(livros: &[(&Uuid, &Livro)]) {
         if livros.is_empty() {
             println!("Nenhum livro cadastrado.");
             return;
         }
 
-        let mut id = 0;
         println!("\n=== Lista de Livros ===");
-        for (_, livro) in livros {
+        for (id, (_, livro)) in livros.iter().enumerate() {
             println!("ID: {}", id);
             println!("{}", livro);
             println!("---------------------------");
-            id += 1;
         }
     }
 
@@ -292,7 +1788,11 @@ impl Biblioteca {
         }
 
         println!("\n=== Lista de Usuários ===");
-        for (id, usuario) in &self.usuarios {
+        for (id, usuario) in self
+            .usuarios
+            .iter()
+            .filter(|(_, usuario)| !usuario.arquivado)
+        {
             println!("ID: {}", id);
             println!("{}", usuario); // usa Display de Usuario
             println!("---------------------------");
@@ -314,20 +1814,43 @@ impl Biblioteca {
         }
     }
 
+    pub fn listar_emprestimos_ativos(&self) {
+        let ativos: Vec<_> = self
+            .emprestimos
+            .iter()
+            .filter(|(_, emprestimo)| emprestimo.esta_ativo())
+            .collect();
+
+        if ativos.is_empty() {
+            println!("Nenhum livro emprestado.");
+            return;
+        }
+
+        println!("\n=== Empréstimos Ativos ===");
+        for (id, emprestimo) in ativos {
+            println!("ID: {}", id);
+            self.exibir_emprestimo(emprestimo);
+            println!("---------------------------");
+        }
+    }
+
     pub fn exibir_emprestimo(&self, emprestimo: &Emprestimo) {
         let livro = self.livros.get(&emprestimo.get_id_livro());
         let usuario = self.usuarios.get(&emprestimo.get_id_usuario());
 
+        // Livros e usuários com empréstimos nunca são apagados, só arquivados
         match livro {
+            Some(l) if l.arquivado => println!("Livro: {} (arquivado)", l.get_titulo()),
             Some(l) => println!("Livro: {}", l.get_titulo()),
             None => println!("Livro não encontrado."),
         }
         match usuario {
+            Some(u) if u.arquivado => println!("Usuário: {} (arquivado)", u.get_nome()),
             Some(u) => println!("Usuário: {}", u.get_nome()),
             None => println!("Usuário não encontrado."),
         }
 
-        println!("Data do Empréstimo: {}", emprestimo.get_data_devolucao());
+        println!("Data do Empréstimo: {}", emprestimo.get_data_emprestimo());
         println!("Data de Devolução: {}", emprestimo.get_data_devolucao());
         println!(
             "Status: {}",
@@ -338,11 +1861,23 @@ impl Biblioteca {
         );
     }
 
+    // Pesquisa sem efeitos na tela, em ordem de título (arquivados ficam de fora)
+    pub fn pesquisar_livros(&self, filtro: &FiltroLivros) -> Vec<(Uuid, &Livro)> {
+        let mut encontrados: Vec<(Uuid, &Livro)> = self
+            .livros
+            .iter()
+            .filter(|(_, livro)| !livro.arquivado && filtro.aceita(livro))
+            .map(|(id, livro)| (*id, livro))
+            .collect();
+        encontrados.sort_by_key(|(id, livro)| (livro.get_titulo().to_lowercase(), *id));
+        encontrados
+    }
+
     pub fn buscar_livro_por_titulo(&self, titulo_livro: &str) -> Option<Vec<(Uuid, &Livro)>> {
         let encontrados: Vec<_> = self
             .livros
             .iter()
-            .filter(|(_, livro)| livro.get_titulo() == titulo_livro)
+            .filter(|(_, livro)| !livro.arquivado && livro.get_titulo() == titulo_livro)
             .map(|(id, livro)| (*id, livro))
             .collect();
 
@@ -364,7 +1899,7 @@ impl Biblioteca {
         let mut encontrados: Vec<(Uuid, &Livro)> = Vec::new();
 
         for (id_livro, livro) in &self.livros {
-            if livro.get_autor() == autor_livro {
+            if !livro.arquivado && livro.get_autor() == autor_livro {
                 encontrados.push((*id_livro, livro)); // copia o UUID, referencia o livro
             }
         }
@@ -384,69 +1919,372 @@ impl Biblioteca {
     }
 }
 
-pub fn ler_string(mensagem: String) -> String {
-    let mut entrada = String::new();
-    println!("{}", mensagem);
-
-    io::stdin()
-        .read_line(&mut entrada)
-        .expect("Falha ao ler entrada");
+#[cfg(test)]
+mod testes {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{contas::Papel, relogio::RelogioFixo};
+    use crate::notificacoes::{self, RegistroAvisos, TipoAviso};
+
+    fn com_sessao(caminho: &Path) -> Biblioteca {
+        let mut biblioteca = Biblioteca::carregar(caminho).expect("carregar");
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca
+    }
 
-    entrada.trim().to_string()
-}
+    #[test]
+    fn retrato_corrompido_e_refeito_pelo_diario() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+
+        let mut biblioteca = com_sessao(&caminho);
+        let id = biblioteca
+            .cadastrar_livro(
+                "Quincas Borba".to_string(),
+                "Machado de Assis".to_string(),
+                1891,
+            )
+            .expect("cadastro");
+        biblioteca.salvar().expect("salvar");
+        assert!(!pasta.path().join("dados.json.tmp").exists());
+
+        // Simula uma gravação interrompida no meio
+        fs::write(&caminho, "{\"livros\": {").expect("corromper");
+
+        let mut biblioteca = com_sessao(&caminho);
+        assert!(biblioteca.livros().contains_key(&id));
+
+        // A numeração do diário continua de onde parou
+        biblioteca
+            .cadastrar_usuario("Rubião".to_string())
+            .expect("cadastro");
+        biblioteca.salvar().expect("salvar");
+        let sequencias: Vec<u64> = diario::ler_historico(&caminho)
+            .expect("histórico")
+            .iter()
+            .map(|registro| registro.sequencia)
+            .collect();
+        assert_eq!(sequencias, vec![1, 2]);
+    }
 
-pub fn ler_u16(mensagem: String) -> u16 {
-    let mut entrada = String::new();
+    #[test]
+    fn retrato_corrompido_sem_diario_e_erro() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        fs::write(&caminho, "não é JSON").expect("arquivo");
 
-    println!("{}", mensagem);
+        assert!(Biblioteca::carregar(&caminho).is_err());
+    }
 
-    io::stdin()
-        .read_line(&mut entrada)
-        .expect("Erro ao ler entrada");
+    fn leitor_e_livro(biblioteca: &mut Biblioteca) -> (Uuid, Uuid) {
+        let leitor = biblioteca
+            .cadastrar_usuario("Capitu".to_string())
+            .expect("cadastro");
+        let livro = biblioteca
+            .cadastrar_livro(
+                "Dom Casmurro".to_string(),
+                "Machado de Assis".to_string(),
+                1899,
+            )
+            .expect("cadastro");
+        (leitor, livro)
+    }
 
-    let numero: u16 = entrada
-        .trim() // remove \n e espaços
-        .parse() // tenta converter para número
-        .expect("Digite um número válido!");
+    #[test]
+    fn transacao_com_falha_desfaz_tudo() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        let mut biblioteca = com_sessao(&caminho);
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        biblioteca.salvar().expect("salvar");
+        let desfazer = format!("{:?}", biblioteca.pilha_desfazer().proximo_desfazer());
+
+        let resultado = biblioteca.transacao(|b| {
+            b.realizar_emprestimo(leitor, livro)?;
+            b.cadastrar_livro("Helena".to_string(), "Machado de Assis".to_string(), 1876)?;
+            b.realizar_emprestimo(Uuid::new_v4(), livro)
+        });
+
+        assert!(resultado.is_err());
+        assert!(biblioteca.emprestimos().is_empty());
+        assert_eq!(biblioteca.livros().len(), 1);
+        assert!(biblioteca.livros()[&livro].esta_disponivel());
+        assert!(!biblioteca.tem_alteracoes());
+        assert_eq!(
+            format!("{:?}", biblioteca.pilha_desfazer().proximo_desfazer()),
+            desfazer
+        );
+    }
 
-    numero
-}
+    #[test]
+    fn transacao_grava_um_unico_lote() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        let mut biblioteca = com_sessao(&caminho);
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        biblioteca.salvar().expect("salvar");
+
+        biblioteca
+            .transacao(|b| {
+                let id = b.realizar_emprestimo(leitor, livro)?;
+                b.renovar(id)
+            })
+            .expect("transação");
+        biblioteca.salvar().expect("salvar");
+
+        let historico = diario::ler_historico(&caminho).expect("histórico");
+        let ultimo = historico.last().expect("evento");
+        assert_eq!(historico.len(), 3);
+        assert!(matches!(&ultimo.evento, Evento::Lote(eventos) if eventos.len() == 2));
+    }
 
-pub fn ler_i32(mensagem: String) -> i32 {
-    let mut entrada = String::new();
+    #[test]
+    fn transacao_recusa_inconsistencia_so_no_que_tocou() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        let mut biblioteca = com_sessao(&caminho);
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+
+        // Um problema antigo, em outro livro, não impede transações
+        let antigo = biblioteca
+            .cadastrar_livro(
+                "Iaiá Garcia".to_string(),
+                "Machado de Assis".to_string(),
+                1878,
+            )
+            .expect("cadastro");
+        biblioteca
+            .livros
+            .get_mut(&antigo)
+            .expect("livro")
+            .refletir(StatusLivro::Emprestado);
+        biblioteca
+            .transacao(|b| b.cadastrar_usuario("Bentinho".to_string()))
+            .expect("transação sem relação com o problema");
+
+        // Empréstimo ativo com o livro ainda disponível: só a verificação pega
+        let resultado = biblioteca.transacao(|b| {
+            let prazo = b.prazo_de_devolucao();
+            let emprestimo = Emprestimo::new(leitor, livro, prazo, None, b.agora());
+            b.emprestimos.insert(emprestimo.id(), emprestimo.clone());
+            b.anotar_evento(Evento::EmprestimoRealizado(emprestimo));
+            Ok(())
+        });
+
+        match resultado {
+            Err(ErroBiblioteca::EstadoInvalido(mensagem)) => {
+                assert!(mensagem.contains("inconsistentes"), "{}", mensagem)
+            }
+            outro => panic!("esperava recusa, veio {:?}", outro),
+        }
+        assert!(biblioteca.emprestimos().is_empty());
+    }
 
-    println!("{}", mensagem);
+    #[test]
+    fn renovacao_de_emprestimo_devolvido_e_recusada() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = com_sessao(&pasta.path().join("dados.json"));
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        let id = biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo");
+        biblioteca.devolver(id).expect("devolução");
+
+        assert!(matches!(
+            biblioteca.renovar(id),
+            Err(ErroBiblioteca::EstadoInvalido(_))
+        ));
+    }
 
-    io::stdin()
-        .read_line(&mut entrada)
-        .expect("Erro ao ler entrada");
+    fn com_relogio(caminho: &Path, data: NaiveDate) -> (Biblioteca, Arc<RelogioFixo>) {
+        let relogio = Arc::new(RelogioFixo::no_dia(data).expect("dia válido"));
+        let mut biblioteca = com_sessao(caminho);
+        biblioteca.definir_relogio(relogio.clone());
+        (biblioteca, relogio)
+    }
 
-    let numero: i32 = entrada
-        .trim() // remove \n e espaços
-        .parse() // tenta converter para número
-        .expect("Digite um número válido!");
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).expect("data válida")
+    }
 
-    numero
-}
+    #[test]
+    fn prazo_e_diario_seguem_o_relogio() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        // Sexta; 14 dias depois é Sexta-feira Santa, e o prazo passa para o sábado
+        let (mut biblioteca, relogio) = com_relogio(&caminho, data(2024, 3, 15));
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        relogio.avancar(chrono::Duration::hours(10));
+        let id = biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo");
+
+        let emprestimo = &biblioteca.emprestimos()[&id];
+        assert_eq!(emprestimo.get_data_emprestimo(), data(2024, 3, 15));
+        assert_eq!(emprestimo.get_data_devolucao(), data(2024, 3, 30));
+
+        biblioteca.salvar().expect("salvar");
+        let historico = diario::ler_historico(&caminho).expect("histórico");
+        assert_eq!(historico.last().map(|r| r.momento), Some(relogio.agora()));
+    }
 
-pub fn ler_uuid(mensagem: &str) -> Uuid {
-    loop {
-        println!("{}", mensagem);
+    #[test]
+    fn renovacao_conta_o_prazo_a_partir_de_hoje() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, relogio) =
+            com_relogio(&pasta.path().join("dados.json"), data(2024, 3, 15));
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        let id = biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo");
+
+        relogio.avancar(chrono::Duration::days(10));
+        assert_eq!(biblioteca.renovar(id).expect("renovação"), data(2024, 4, 8));
+        assert_eq!(
+            biblioteca.emprestimos()[&id].get_data_devolucao(),
+            data(2024, 4, 8)
+        );
+    }
 
-        let mut entrada = String::new();
+    #[test]
+    fn atraso_so_depois_do_prazo_e_em_dia_aberto() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        let (mut biblioteca, relogio) = com_relogio(&caminho, data(2024, 3, 15));
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        biblioteca
+            .definir_email(leitor, Some("capitu@exemplo.com".to_string()))
+            .expect("e-mail");
+        biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo");
+        let registro = RegistroAvisos::carregar(&caminho).expect("registro de avisos");
+        let tipos = |biblioteca: &Biblioteca| -> Vec<(TipoAviso, i64)> {
+            notificacoes::pendencias(biblioteca, &registro, biblioteca.hoje(), 3)
+                .avisos
+                .iter()
+                .flat_map(|aviso| aviso.itens.iter().map(|item| (aviso.tipo, item.atraso)))
+                .collect()
+        };
 
-        match io::stdin().read_line(&mut entrada) {
-            Ok(_) => {
-                let entrada = entrada.trim();
+        // Prazo no sábado, 30/03
+        relogio.avancar(chrono::Duration::days(12));
+        assert_eq!(tipos(&biblioteca), [(TipoAviso::Lembrete, 0)]);
+        relogio.avancar(chrono::Duration::days(3));
+        assert_eq!(biblioteca.hoje(), data(2024, 3, 30));
+        assert_eq!(tipos(&biblioteca), [(TipoAviso::Lembrete, 0)]);
+        // Domingo a biblioteca não abre: ainda não há atraso
+        relogio.avancar(chrono::Duration::days(1));
+        assert!(tipos(&biblioteca).is_empty());
+        relogio.avancar(chrono::Duration::days(1));
+        assert_eq!(tipos(&biblioteca), [(TipoAviso::Atraso, 1)]);
+    }
 
-                match Uuid::parse_str(entrada) {
-                    Ok(uuid) => return uuid, // ✅ retorna o UUID válido
-                    Err(_) => println!("❌ UUID inválido! Tente novamente."),
-                }
-            }
-            Err(_) => {
-                println!("❌ Erro ao ler a entrada. Tente novamente.");
+    fn recusa<T: std::fmt::Debug>(resultado: Result<T, ErroBiblioteca>, trecho: &str) {
+        match resultado {
+            Err(ErroBiblioteca::EstadoInvalido(mensagem)) => {
+                assert!(
+                    mensagem.contains(trecho),
+                    "mensagem inesperada: {}",
+                    mensagem
+                )
             }
+            outro => panic!("esperava recusa com \"{}\", veio {:?}", trecho, outro),
         }
     }
+
+    // Duas unidades; o livro é cadastrado no CENTRO
+    fn com_unidades(caminho: &Path) -> (Biblioteca, Uuid, Uuid, Uuid, Uuid) {
+        let (mut biblioteca, _) = com_relogio(caminho, data(2024, 3, 4));
+        let centro = biblioteca
+            .cadastrar_unidade("centro", "Biblioteca Central".to_string())
+            .expect("unidade");
+        let norte = biblioteca
+            .cadastrar_unidade("NORTE", "Zona Norte".to_string())
+            .expect("unidade");
+        biblioteca.definir_unidade_atual(Some(centro));
+        let (leitor, livro) = leitor_e_livro(&mut biblioteca);
+        (biblioteca, centro, norte, leitor, livro)
+    }
+
+    #[test]
+    fn transferencia_fica_em_transito_ate_a_unidade_receber() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, centro, norte, leitor, livro) =
+            com_unidades(&pasta.path().join("dados.json"));
+        assert_eq!(biblioteca.livros()[&livro].get_unidade(), Some(centro));
+        assert_eq!(biblioteca.livros()[&livro].get_local(), Some(centro));
+
+        biblioteca
+            .transferir_livro(livro, norte)
+            .expect("transferência");
+        let exemplar = &biblioteca.livros()[&livro];
+        assert_eq!(exemplar.get_unidade(), Some(norte));
+        assert_eq!(exemplar.get_local(), Some(centro));
+        assert_eq!(exemplar.em_transito(), Some(data(2024, 3, 4)));
+        recusa(biblioteca.transferir_livro(livro, norte), "já pertence");
+        assert!(matches!(
+            biblioteca.transferir_livro(livro, Uuid::new_v4()),
+            Err(ErroBiblioteca::UnidadeNaoEncontrada(_))
+        ));
+
+        // Em trânsito não sai, nem onde está; só a unidade dona recebe
+        recusa(biblioteca.realizar_emprestimo(leitor, livro), "em trânsito");
+        recusa(biblioteca.receber_livro(livro), "outra unidade");
+        recusa(biblioteca.remover_unidade(centro), "transfira");
+
+        biblioteca.definir_unidade_atual(Some(norte));
+        biblioteca.receber_livro(livro).expect("recebimento");
+        let exemplar = &biblioteca.livros()[&livro];
+        assert_eq!(exemplar.get_local(), Some(norte));
+        assert_eq!(exemplar.em_transito(), None);
+        recusa(biblioteca.receber_livro(livro), "não está em trânsito");
+        biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo no NORTE");
+        biblioteca.remover_unidade(centro).expect("CENTRO vazia");
+    }
+
+    #[test]
+    fn emprestimo_so_onde_esta_o_exemplar_e_devolucao_em_qualquer_unidade() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, centro, norte, leitor, livro) =
+            com_unidades(&pasta.path().join("dados.json"));
+
+        biblioteca.definir_unidade_atual(Some(norte));
+        recusa(
+            biblioteca.realizar_emprestimo(leitor, livro),
+            "não está nesta unidade",
+        );
+        biblioteca.definir_unidade_atual(Some(centro));
+        let id = biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo no CENTRO");
+        assert_eq!(biblioteca.emprestimos()[&id].get_unidade(), Some(centro));
+
+        // Devolvido no NORTE: continua do CENTRO, em trânsito de volta
+        biblioteca.definir_unidade_atual(Some(norte));
+        biblioteca.devolver(id).expect("devolução");
+        assert_eq!(
+            biblioteca.emprestimos()[&id].get_unidade_devolucao(),
+            Some(norte)
+        );
+        let exemplar = &biblioteca.livros()[&livro];
+        assert!(exemplar.esta_disponivel());
+        assert_eq!(exemplar.get_unidade(), Some(centro));
+        assert_eq!(exemplar.get_local(), Some(norte));
+        assert_eq!(exemplar.em_transito(), Some(data(2024, 3, 4)));
+        recusa(biblioteca.realizar_emprestimo(leitor, livro), "em trânsito");
+
+        biblioteca.definir_unidade_atual(Some(centro));
+        biblioteca.receber_livro(livro).expect("recebimento");
+        assert_eq!(biblioteca.livros()[&livro].get_local(), Some(centro));
+        biblioteca
+            .realizar_emprestimo(leitor, livro)
+            .expect("empréstimo depois de receber");
+    }
 }
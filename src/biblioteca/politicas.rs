@@ -0,0 +1,165 @@
+// Regras de circulação que cada biblioteca ajusta na configuração: prazo,
+// quantos livros um leitor pode ter ao mesmo tempo e a multa por dia de atraso.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::ErroBiblioteca;
+
+use super::emprestimos::PRAZO_EMPRESTIMO_DIAS;
+
+// Um ano de prazo já é mais que qualquer biblioteca empresta
+const PRAZO_MAXIMO_DIAS: i64 = 365;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Politicas {
+    // Dias corridos; o prazo ainda passa para o próximo dia aberto do calendário
+    pub prazo_dias: i64,
+    // Empréstimos ativos por leitor; sem valor, não há limite
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limite_por_usuario: Option<usize>,
+    // Por dia de atraso (contado só nos dias em que a biblioteca abre); no TOML, em reais
+    pub multa_por_dia: Centavos,
+}
+
+impl Default for Politicas {
+    fn default() -> Self {
+        Politicas {
+            prazo_dias: PRAZO_EMPRESTIMO_DIAS,
+            limite_por_usuario: None,
+            multa_por_dia: Centavos(0),
+        }
+    }
+}
+
+impl Politicas {
+    pub fn validar(&self) -> Result<(), ErroBiblioteca> {
+        if !(1..=PRAZO_MAXIMO_DIAS).contains(&self.prazo_dias) {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "emprestimos.prazo_dias deve ficar entre 1 e {} (veio {}).",
+                PRAZO_MAXIMO_DIAS, self.prazo_dias
+            )));
+        }
+        if self.limite_por_usuario == Some(0) {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "emprestimos.limite_por_usuario deve ser pelo menos 1 (para não limitar, apague a linha)."
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Sem multa configurada ou sem atraso, não há o que cobrar
+    pub fn multa(&self, dias_de_atraso: i64) -> Option<Centavos> {
+        let dias = u64::try_from(dias_de_atraso)
+            .ok()
+            .filter(|dias| *dias > 0)?;
+        (self.multa_por_dia.0 > 0).then(|| Centavos(self.multa_por_dia.0.saturating_mul(dias)))
+    }
+}
+
+// Dinheiro em centavos inteiros, para a multa de muitos dias não acumular o
+// erro de arredondamento de um f64. Na configuração continua sendo escrito em
+// reais (`multa_por_dia = 0.50`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Centavos(pub u64);
+
+impl Centavos {
+    pub fn de_reais(reais: f64) -> Result<Self, ErroBiblioteca> {
+        let centavos = reais * 100.0;
+        if !centavos.is_finite() || centavos < 0.0 || centavos > u64::MAX as f64 {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "emprestimos.multa_por_dia deve ser um valor de 0 para cima (veio {}).",
+                reais
+            )));
+        }
+        // 0.1 * 100 dá 10.000000000000002; mais casas que os centavos é erro de digitação
+        if (centavos - centavos.round()).abs() > 1e-6 {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "emprestimos.multa_por_dia tem mais de duas casas decimais (veio {}).",
+                reais
+            )));
+        }
+        Ok(Centavos(centavos.round() as u64))
+    }
+}
+
+// 1250 -> "R$ 12,50"
+impl fmt::Display for Centavos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "R$ {},{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+impl Serialize for Centavos {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.0 as f64 / 100.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Centavos {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // `multa_por_dia = 2` também vale, não só `2.0`
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Reais {
+            Inteiro(i64),
+            Decimal(f64),
+        }
+        let reais = match Reais::deserialize(deserializer)? {
+            Reais::Inteiro(reais) => reais as f64,
+            Reais::Decimal(reais) => reais,
+        };
+        Centavos::de_reais(reais).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    #[test]
+    fn multa_e_calculada_em_centavos() {
+        let politicas = Politicas {
+            multa_por_dia: Centavos::de_reais(0.1).unwrap(),
+            ..Politicas::default()
+        };
+        // Em f64, 0.1 * 3 dá 0.30000000000000004
+        assert_eq!(politicas.multa(3), Some(Centavos(30)));
+        assert_eq!(politicas.multa(1_000), Some(Centavos(10_000)));
+        assert_eq!(politicas.multa(0), None);
+        assert_eq!(politicas.multa(-2), None);
+        assert_eq!(Politicas::default().multa(5), None);
+    }
+
+    #[test]
+    fn valor_em_reais_vira_centavos() {
+        assert_eq!(Centavos::de_reais(12.5).unwrap(), Centavos(1250));
+        assert_eq!(Centavos::de_reais(0.29).unwrap(), Centavos(29));
+        assert!(Centavos::de_reais(-1.0).is_err());
+        assert!(Centavos::de_reais(0.125).is_err());
+        assert!(Centavos::de_reais(f64::NAN).is_err());
+
+        assert_eq!(Centavos(1250).to_string(), "R$ 12,50");
+        assert_eq!(Centavos(7).to_string(), "R$ 0,07");
+    }
+
+    #[test]
+    fn configuracao_aceita_reais_inteiros_ou_decimais() {
+        let ler = |texto: &str| toml::from_str::<Politicas>(texto);
+        assert_eq!(
+            ler("multa_por_dia = 2").unwrap().multa_por_dia,
+            Centavos(200)
+        );
+        assert_eq!(
+            ler("multa_por_dia = 0.75").unwrap().multa_por_dia,
+            Centavos(75)
+        );
+        assert!(ler("multa_por_dia = -0.5").is_err());
+
+        let texto = toml::to_string(&ler("multa_por_dia = 1.5").unwrap()).unwrap();
+        assert!(texto.contains("multa_por_dia = 1.5"), "{}", texto);
+    }
+}
@@ -0,0 +1,57 @@
+// De onde a biblioteca tira "agora": datas de empréstimo e de devolução,
+// prazos, atrasos e carimbos de criação/alteração. O relógio do sistema é o
+// padrão; o fixo serve para testes e para simular outra data (`--data-atual`).
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+
+pub trait Relogio: Send + Sync {
+    fn agora(&self) -> DateTime<Local>;
+
+    fn hoje(&self) -> NaiveDate {
+        self.agora().date_naive()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RelogioSistema;
+
+impl Relogio for RelogioSistema {
+    fn agora(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+// Parado no momento dado até alguém avançá-lo
+#[derive(Debug)]
+pub struct RelogioFixo {
+    momento: Mutex<DateTime<Local>>,
+}
+
+impl RelogioFixo {
+    pub fn new(momento: DateTime<Local>) -> Self {
+        RelogioFixo {
+            momento: Mutex::new(momento),
+        }
+    }
+
+    // Meia-noite do dia dado (ou o primeiro instante válido dele, em dias de horário de verão)
+    pub fn no_dia(data: NaiveDate) -> Option<Self> {
+        data.and_hms_opt(0, 0, 0)?
+            .and_local_timezone(Local)
+            .earliest()
+            .map(Self::new)
+    }
+
+    pub fn avancar(&self, intervalo: Duration) {
+        let mut momento = self.momento.lock().unwrap_or_else(|e| e.into_inner());
+        *momento += intervalo;
+    }
+}
+
+impl Relogio for RelogioFixo {
+    fn agora(&self) -> DateTime<Local> {
+        *self.momento.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,92 @@
+// Unidades (filiais) que dividem o mesmo catálogo. Cada exemplar pertence a uma
+// unidade e pode estar fisicamente em outra; bibliotecas de uma unidade só não
+// precisam cadastrar nenhuma.
+
+use std::fmt;
+
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::carimbos::Carimbos;
+
+// Siglas vão em etiquetas e na linha de comando; precisam ser curtas
+const TAMANHO_MAXIMO_SIGLA: usize = 12;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Unidade {
+    pub id: Uuid,
+    // Como a unidade é chamada nos comandos: CENTRO, NORTE...
+    pub sigla: String,
+    pub nome: String,
+    #[serde(flatten)]
+    pub carimbos: Carimbos,
+}
+
+impl Unidade {
+    pub fn new(sigla: String, nome: String, momento: DateTime<Local>) -> Self {
+        Unidade {
+            id: Uuid::new_v4(),
+            sigla,
+            nome,
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    // Devolve a sigla normalizada (maiúsculas, sem espaços nas pontas)
+    pub fn validar(sigla: &str, nome: &str) -> Result<String, ErroBiblioteca> {
+        let sigla = sigla.trim().to_uppercase();
+        if sigla.is_empty()
+            || sigla.chars().count() > TAMANHO_MAXIMO_SIGLA
+            || !sigla.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Sigla inválida: \"{}\" (até {} letras, números ou hífens).",
+                sigla, TAMANHO_MAXIMO_SIGLA
+            )));
+        }
+        if nome.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Nome da unidade não pode ficar vazio.".to_string(),
+            ));
+        }
+        Ok(sigla)
+    }
+}
+
+impl Identificavel for Unidade {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl fmt::Display for Unidade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.nome, self.sigla)
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    #[test]
+    fn sigla_e_normalizada_e_validada() {
+        assert_eq!(
+            Unidade::validar(" centro-2 ", "Central").unwrap(),
+            "CENTRO-2"
+        );
+        for sigla in ["", "   ", "ZONA NORTE", "SÃO-PAULO", "UMA-SIGLA-LONGA"] {
+            assert!(
+                matches!(
+                    Unidade::validar(sigla, "Nome"),
+                    Err(ErroBiblioteca::DadosInvalidos(_))
+                ),
+                "{}",
+                sigla
+            );
+        }
+        assert!(Unidade::validar("NORTE", "  ").is_err());
+    }
+}
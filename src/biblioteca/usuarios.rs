@@ -1,23 +1,75 @@
 use std::fmt;
 
-use crate::traits::Identificavel;
+use crate::{errors::ErroBiblioteca, traits::Identificavel};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::carimbos::Carimbos;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 
 pub struct Usuario {
     pub id: Uuid,
     pub nome: String,
+    // Usuários com histórico de empréstimos não são apagados, só saem das listagens
+    #[serde(default)]
+    pub arquivado: bool,
+    // Endereço para lembretes de devolução e avisos de atraso
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub carimbos: Carimbos,
 }
 
 impl Usuario {
-    pub fn new(nome: String) -> Self {
-        let usuario = Usuario {
+    pub fn new(nome: String, momento: DateTime<Local>) -> Self {
+        Usuario {
             id: Uuid::new_v4(),
             nome,
+            arquivado: false,
+            email: None,
+            carimbos: Carimbos::em(momento),
+        }
+    }
+
+    pub fn validar(nome: &str) -> Result<(), ErroBiblioteca> {
+        if nome.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Nome não pode ficar vazio.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // Vazio remove o endereço. Quebras de linha e espaços são recusados porque o
+    // endereço vai direto para o cabeçalho da mensagem e para o comando SMTP.
+    pub fn validar_email(email: &str) -> Result<Option<String>, ErroBiblioteca> {
+        let email = email.trim();
+        if email.is_empty() {
+            return Ok(None);
+        }
+        let valido = match email.split_once('@') {
+            Some((local, dominio)) => {
+                !local.is_empty()
+                    && dominio.contains('.')
+                    && !dominio.starts_with('.')
+                    && !dominio.ends_with('.')
+                    && !dominio.contains('@')
+                    && !email
+                        .chars()
+                        .any(|c| c.is_whitespace() || c.is_control() || "<>,;\"".contains(c))
+            }
+            None => false,
         };
-        usuario
+        if valido {
+            Ok(Some(email.to_string()))
+        } else {
+            Err(ErroBiblioteca::DadosInvalidos(format!(
+                "E-mail inválido: {}",
+                email
+            )))
+        }
     }
 
     pub fn get_nome(&self) -> String {
@@ -0,0 +1,759 @@
+// Verificação de integridade dos dados: procura estados que as operações
+// normais não deveriam produzir (arquivos editados à mão, versões antigas,
+// quedas no meio de uma escrita) e, se pedido, corrige o que for possível.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::traits::Identificavel;
+
+use super::{
+    DadosPersistencia,
+    carimbos::Carimbos,
+    circulacao::Circulacao,
+    emprestimos::{Emprestimo, PRAZO_EMPRESTIMO_DIAS, StatusEmprestimo},
+    intercambio::Etapa,
+    livros::Livro,
+    usuarios::Usuario,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Gravidade {
+    // Estranho, mas não atrapalha nenhuma operação
+    Aviso,
+    // Dados contraditórios; alguma operação vai se comportar errado
+    Erro,
+    // Referências quebradas: há registros que não podem ser exibidos
+    Critico,
+}
+
+impl fmt::Display for Gravidade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gravidade::Aviso => write!(f, "AVISO"),
+            Gravidade::Erro => write!(f, "ERRO"),
+            Gravidade::Critico => write!(f, "CRÍTICO"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Problema {
+    pub gravidade: Gravidade,
+    pub descricao: String,
+    // Como o reparo automático resolve (ou `None` se precisa de alguém olhar)
+    pub reparo: Option<String>,
+}
+
+impl fmt::Display for Problema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.gravidade, self.descricao)?;
+        match &self.reparo {
+            Some(reparo) => write!(f, "\n    Reparo: {}", reparo),
+            None => write!(f, "\n    Reparo: manual"),
+        }
+    }
+}
+
+// Resultado de `Biblioteca::reparar`: o que estava errado e o que mudou (ou mudaria)
+#[derive(Debug)]
+pub struct Reparo {
+    pub problemas: Vec<Problema>,
+    pub diferencas: Vec<String>,
+}
+
+struct Analise<'a> {
+    dados: &'a mut DadosPersistencia,
+    reparar: bool,
+    // Carimbo dos reparos e referência para validar os cadastros
+    agora: DateTime<Local>,
+    problemas: Vec<Problema>,
+}
+
+impl Analise<'_> {
+    fn relatar(&mut self, gravidade: Gravidade, descricao: String, reparo: Option<&str>) {
+        self.problemas.push(Problema {
+            gravidade,
+            descricao,
+            reparo: reparo.map(str::to_string),
+        });
+    }
+
+    // O mapa é indexado pelo id, e os empréstimos apontam para a chave do mapa;
+    // então é o id interno que é corrigido
+    fn chaves(&mut self) {
+        let livros: Vec<(Uuid, Uuid)> = self
+            .dados
+            .livros
+            .iter()
+            .filter(|(chave, livro)| **chave != livro.id())
+            .map(|(chave, livro)| (*chave, livro.id()))
+            .collect();
+        for (chave, id) in livros {
+            self.relatar(
+                Gravidade::Erro,
+                format!("Livro guardado sob a chave {} tem id {}.", chave, id),
+                Some("usar a chave como id"),
+            );
+            if self.reparar
+                && let Some(livro) = self.dados.livros.get_mut(&chave)
+            {
+                livro.corrigir_id(chave);
+            }
+        }
+
+        let usuarios: Vec<(Uuid, Uuid)> = self
+            .dados
+            .usuarios
+            .iter()
+            .filter(|(chave, usuario)| **chave != usuario.id)
+            .map(|(chave, usuario)| (*chave, usuario.id))
+            .collect();
+        for (chave, id) in usuarios {
+            self.relatar(
+                Gravidade::Erro,
+                format!("Usuário guardado sob a chave {} tem id {}.", chave, id),
+                Some("usar a chave como id"),
+            );
+            if self.reparar
+                && let Some(usuario) = self.dados.usuarios.get_mut(&chave)
+            {
+                usuario.id = chave;
+            }
+        }
+
+        let emprestimos: Vec<(Uuid, Uuid)> = self
+            .dados
+            .emprestimos
+            .iter()
+            .filter(|(chave, emprestimo)| **chave != emprestimo.id())
+            .map(|(chave, emprestimo)| (*chave, emprestimo.id()))
+            .collect();
+        for (chave, id) in emprestimos {
+            self.relatar(
+                Gravidade::Erro,
+                format!("Empréstimo guardado sob a chave {} tem id {}.", chave, id),
+                Some("usar a chave como id"),
+            );
+            if self.reparar
+                && let Some(emprestimo) = self.dados.emprestimos.get_mut(&chave)
+            {
+                emprestimo.corrigir_id(chave);
+            }
+        }
+    }
+
+    // Empréstimos que citam livros ou usuários inexistentes. Apagar o empréstimo
+    // perderia histórico, então o reparo recria o cadastro como arquivado.
+    fn referencias(&mut self) {
+        let mut livros_faltando = BTreeSet::new();
+        let mut usuarios_faltando = BTreeSet::new();
+        for (id, emprestimo) in &self.dados.emprestimos {
+            if !self.dados.livros.contains_key(&emprestimo.get_id_livro()) {
+                livros_faltando.insert((emprestimo.get_id_livro(), *id));
+            }
+            if !self
+                .dados
+                .usuarios
+                .contains_key(&emprestimo.get_id_usuario())
+            {
+                usuarios_faltando.insert((emprestimo.get_id_usuario(), *id));
+            }
+        }
+
+        for (id_livro, id_emprestimo) in livros_faltando {
+            self.relatar(
+                Gravidade::Critico,
+                format!(
+                    "Empréstimo {} aponta para o livro {}, que não existe.",
+                    id_emprestimo, id_livro
+                ),
+                Some("recriar o livro como \"Livro desconhecido\", arquivado"),
+            );
+            if self.reparar {
+                self.dados
+                    .livros
+                    .entry(id_livro)
+                    .or_insert_with(|| Livro::substituto(id_livro));
+            }
+        }
+
+        for (id_usuario, id_emprestimo) in usuarios_faltando {
+            self.relatar(
+                Gravidade::Critico,
+                format!(
+                    "Empréstimo {} aponta para o usuário {}, que não existe.",
+                    id_emprestimo, id_usuario
+                ),
+                Some("recriar o usuário como \"Usuário desconhecido\", arquivado"),
+            );
+            if self.reparar {
+                self.dados.usuarios.entry(id_usuario).or_insert(Usuario {
+                    id: id_usuario,
+                    nome: "Usuário desconhecido".to_string(),
+                    arquivado: true,
+                    email: None,
+                    carimbos: Carimbos::default(),
+                });
+            }
+        }
+    }
+
+    // Exemplares que pertencem a (ou estão em) uma unidade que não existe mais
+    fn unidades(&mut self) {
+        let mut faltando = BTreeSet::new();
+        for (id, livro) in &self.dados.livros {
+            for unidade in [livro.get_unidade(), livro.get_local()]
+                .into_iter()
+                .flatten()
+            {
+                if !self.dados.unidades.contains_key(&unidade) {
+                    faltando.insert((*id, unidade));
+                }
+            }
+        }
+
+        for (id_livro, id_unidade) in faltando {
+            self.relatar(
+                Gravidade::Erro,
+                format!(
+                    "Livro {} aponta para a unidade {}, que não existe.",
+                    id_livro, id_unidade
+                ),
+                Some("tirar a unidade do exemplar"),
+            );
+            if self.reparar
+                && let Some(livro) = self.dados.livros.get_mut(&id_livro)
+            {
+                livro.esquecer_unidade(id_unidade);
+                livro.tocar(self.agora);
+            }
+        }
+    }
+
+    // Pedidos de intercâmbio em andamento precisam do registro temporário e da parceira
+    fn intercambio(&mut self) {
+        let mut relatos = Vec::new();
+        for (id, solicitacao) in &self.dados.solicitacoes {
+            if let Some(id_parceira) = solicitacao.get_parceira()
+                && !self.dados.parceiras.contains_key(&id_parceira)
+            {
+                relatos.push(format!(
+                    "Pedido de intercâmbio {} aponta para a parceira {}, que não existe.",
+                    id, id_parceira
+                ));
+            }
+            if matches!(solicitacao.etapa(), Etapa::Recebida | Etapa::Emprestada)
+                && solicitacao
+                    .get_livro()
+                    .is_none_or(|id_livro| !self.dados.livros.contains_key(&id_livro))
+            {
+                relatos.push(format!(
+                    "Pedido de intercâmbio {} está {}, mas o livro recebido não está no catálogo.",
+                    id,
+                    solicitacao.etapa()
+                ));
+            }
+        }
+        relatos.sort();
+
+        for descricao in relatos {
+            self.relatar(Gravidade::Erro, descricao, None);
+        }
+    }
+
+    // Só o empréstimo mais recente continua ativo
+    fn emprestimos_duplicados(&mut self) {
+        let mut ativos_por_livro: HashMap<Uuid, Vec<&Emprestimo>> = HashMap::new();
+        for emprestimo in self.dados.emprestimos.values() {
+            if emprestimo.esta_ativo() {
+                ativos_por_livro
+                    .entry(emprestimo.get_id_livro())
+                    .or_default()
+                    .push(emprestimo);
+            }
+        }
+
+        let mut encerrar = Vec::new();
+        for (id_livro, mut ativos) in ativos_por_livro {
+            if ativos.len() < 2 {
+                continue;
+            }
+            ativos.sort_by_key(|e| (e.get_data_emprestimo(), e.id()));
+            let mais_recente = ativos.pop().expect("há pelo menos dois");
+            encerrar.push((
+                id_livro,
+                ativos.len() + 1,
+                mais_recente.id(),
+                ativos.iter().map(|e| e.id()).collect::<Vec<_>>(),
+            ));
+        }
+        encerrar.sort();
+
+        for (id_livro, quantidade, mantido, antigos) in encerrar {
+            self.relatar(
+                Gravidade::Critico,
+                format!(
+                    "Livro {} tem {} empréstimos ativos ao mesmo tempo.",
+                    id_livro, quantidade
+                ),
+                Some(&format!(
+                    "manter só o mais recente ({}) e marcar os outros como devolvidos",
+                    mantido
+                )),
+            );
+            if self.reparar {
+                for id in antigos {
+                    if let Some(emprestimo) = self.dados.emprestimos.get_mut(&id) {
+                        emprestimo.status = StatusEmprestimo::Devolvido;
+                        emprestimo.carimbos.tocar(self.agora);
+                    }
+                }
+            }
+        }
+    }
+
+    // O status do livro tem que bater com a existência de um empréstimo ativo
+    fn status_dos_livros(&mut self) {
+        let emprestados: BTreeSet<Uuid> = self
+            .dados
+            .emprestimos
+            .values()
+            .filter(|e| e.esta_ativo())
+            .map(|e| e.get_id_livro())
+            .collect();
+
+        let mut divergentes: Vec<(Uuid, bool)> = self
+            .dados
+            .livros
+            .iter()
+            .filter_map(|(id, livro)| {
+                let deveria_estar_emprestado = emprestados.contains(id);
+                let esta_emprestado = !livro.esta_disponivel();
+                (deveria_estar_emprestado != esta_emprestado)
+                    .then_some((*id, deveria_estar_emprestado))
+            })
+            .collect();
+        divergentes.sort();
+
+        for (id, deveria_estar_emprestado) in divergentes {
+            if deveria_estar_emprestado {
+                self.relatar(
+                    Gravidade::Erro,
+                    format!(
+                        "Livro {} está marcado como disponível, mas tem empréstimo ativo.",
+                        id
+                    ),
+                    Some("marcar como emprestado"),
+                );
+            } else {
+                self.relatar(
+                    Gravidade::Erro,
+                    format!(
+                        "Livro {} está marcado como emprestado, mas não tem empréstimo ativo.",
+                        id
+                    ),
+                    Some("marcar como disponível"),
+                );
+            }
+            if self.reparar {
+                Circulacao::new(&mut self.dados.livros, &mut self.dados.emprestimos)
+                    .sincronizar(id);
+            }
+        }
+    }
+
+    // Quem ainda tem empréstimo ativo não deveria estar arquivado
+    fn arquivados_com_emprestimo(&mut self) {
+        let mut livros = BTreeSet::new();
+        let mut usuarios = BTreeSet::new();
+        for emprestimo in self.dados.emprestimos.values().filter(|e| e.esta_ativo()) {
+            if self
+                .dados
+                .livros
+                .get(&emprestimo.get_id_livro())
+                .is_some_and(|l| l.arquivado)
+            {
+                livros.insert(emprestimo.get_id_livro());
+            }
+            if self
+                .dados
+                .usuarios
+                .get(&emprestimo.get_id_usuario())
+                .is_some_and(|u| u.arquivado)
+            {
+                usuarios.insert(emprestimo.get_id_usuario());
+            }
+        }
+
+        for id in livros {
+            self.relatar(
+                Gravidade::Aviso,
+                format!("Livro {} está arquivado, mas tem empréstimo ativo.", id),
+                Some("desarquivar o livro"),
+            );
+            if self.reparar
+                && let Some(livro) = self.dados.livros.get_mut(&id)
+            {
+                livro.arquivado = false;
+                livro.tocar(self.agora);
+            }
+        }
+        for id in usuarios {
+            self.relatar(
+                Gravidade::Aviso,
+                format!("Usuário {} está arquivado, mas tem empréstimo ativo.", id),
+                Some("desarquivar o usuário"),
+            );
+            if self.reparar
+                && let Some(usuario) = self.dados.usuarios.get_mut(&id)
+            {
+                usuario.arquivado = false;
+                usuario.carimbos.tocar(self.agora);
+            }
+        }
+    }
+
+    fn datas(&mut self) {
+        let mut invertidos: Vec<Uuid> = self
+            .dados
+            .emprestimos
+            .iter()
+            .filter(|(_, e)| e.get_data_devolucao() < e.get_data_emprestimo())
+            .map(|(id, _)| *id)
+            .collect();
+        invertidos.sort();
+
+        for id in invertidos {
+            self.relatar(
+                Gravidade::Aviso,
+                format!(
+                    "Empréstimo {} tem data de devolução anterior à do empréstimo.",
+                    id
+                ),
+                Some(&format!(
+                    "prazo de {} dias a partir do empréstimo",
+                    PRAZO_EMPRESTIMO_DIAS
+                )),
+            );
+            if self.reparar
+                && let Some(emprestimo) = self.dados.emprestimos.get_mut(&id)
+            {
+                let prazo = emprestimo.get_data_emprestimo()
+                    + chrono::Duration::days(PRAZO_EMPRESTIMO_DIAS);
+                let _ = emprestimo.alterar(emprestimo.get_id_usuario(), prazo, self.agora);
+            }
+        }
+    }
+
+    // Cadastros que não passariam na validação de hoje; só alguém pode decidir o valor certo
+    fn cadastros(&mut self) {
+        let mut invalidos: Vec<(Uuid, String)> = self
+            .dados
+            .livros
+            .iter()
+            .filter_map(|(id, livro)| {
+                Livro::validar(
+                    livro.get_titulo(),
+                    livro.get_autor(),
+                    livro.get_ano(),
+                    self.agora.date_naive(),
+                )
+                .err()
+                .map(|e| (*id, e.to_string()))
+            })
+            .chain(self.dados.usuarios.iter().filter_map(|(id, usuario)| {
+                Usuario::validar(&usuario.nome)
+                    .err()
+                    .map(|e| (*id, e.to_string()))
+            }))
+            .collect();
+        invalidos.sort();
+
+        for (id, erro) in invalidos {
+            self.relatar(Gravidade::Aviso, format!("Cadastro {}: {}", id, erro), None);
+        }
+    }
+}
+
+// A ordem importa no reparo: ids primeiro, depois referências, e só então os status
+fn analisar(dados: &mut DadosPersistencia, reparar: bool, agora: DateTime<Local>) -> Vec<Problema> {
+    let mut analise = Analise {
+        dados,
+        reparar,
+        agora,
+        problemas: Vec::new(),
+    };
+    analise.chaves();
+    analise.referencias();
+    analise.unidades();
+    analise.intercambio();
+    analise.emprestimos_duplicados();
+    analise.status_dos_livros();
+    analise.arquivados_com_emprestimo();
+    analise.datas();
+    analise.cadastros();
+    analise.problemas
+}
+
+// Só as entidades dadas e aquilo de que a verificação delas depende: os
+// empréstimos de um livro (para o status dele), o livro e o leitor de cada
+// empréstimo, a unidade de cada exemplar, a parceira de cada pedido. Problemas
+// de entidades fora do recorte não aparecem, mas os das que estão nele são os
+// mesmos que a verificação completa acharia.
+pub(super) fn recorte(dados: &DadosPersistencia, ids: &HashSet<Uuid>) -> DadosPersistencia {
+    let mut livros = ids.clone();
+    for (id, livro) in &dados.livros {
+        if [livro.get_unidade(), livro.get_local()]
+            .into_iter()
+            .flatten()
+            .any(|unidade| ids.contains(&unidade))
+        {
+            livros.insert(*id);
+        }
+    }
+    let solicitacoes: HashMap<Uuid, _> = dados
+        .solicitacoes
+        .iter()
+        .filter(|(id, s)| ids.contains(id) || s.get_livro().is_some_and(|l| ids.contains(&l)))
+        .map(|(id, s)| (*id, s.clone()))
+        .collect();
+    livros.extend(solicitacoes.values().filter_map(|s| s.get_livro()));
+
+    // Duas passadas: os livros dos empréstimos tocados trazem os outros
+    // empréstimos desses livros
+    let envolve = |livros: &HashSet<Uuid>, id: &Uuid, e: &Emprestimo| {
+        ids.contains(id) || ids.contains(&e.get_id_usuario()) || livros.contains(&e.get_id_livro())
+    };
+    let tocados: Vec<Uuid> = dados
+        .emprestimos
+        .iter()
+        .filter(|(id, e)| envolve(&livros, id, e))
+        .map(|(_, e)| e.get_id_livro())
+        .collect();
+    livros.extend(tocados);
+    let emprestimos: HashMap<Uuid, Emprestimo> = dados
+        .emprestimos
+        .iter()
+        .filter(|(id, e)| envolve(&livros, id, e))
+        .map(|(id, e)| (*id, e.clone()))
+        .collect();
+
+    let mut usuarios = ids.clone();
+    usuarios.extend(emprestimos.values().map(|e| e.get_id_usuario()));
+    let livros = copiar(&dados.livros, &livros);
+    let mut unidades = ids.clone();
+    for livro in livros.values() {
+        unidades.extend(
+            [livro.get_unidade(), livro.get_local()]
+                .into_iter()
+                .flatten(),
+        );
+    }
+    let mut parceiras = ids.clone();
+    parceiras.extend(solicitacoes.values().filter_map(|s| s.get_parceira()));
+
+    DadosPersistencia {
+        livros,
+        usuarios: copiar(&dados.usuarios, &usuarios),
+        emprestimos,
+        unidades: copiar(&dados.unidades, &unidades),
+        parceiras: copiar(&dados.parceiras, &parceiras),
+        solicitacoes,
+        ultima_sequencia: dados.ultima_sequencia,
+    }
+}
+
+fn copiar<T: Clone>(mapa: &HashMap<Uuid, T>, ids: &HashSet<Uuid>) -> HashMap<Uuid, T> {
+    ids.iter()
+        .filter_map(|id| mapa.get(id).map(|valor| (*id, valor.clone())))
+        .collect()
+}
+
+pub(super) fn verificar(dados: &DadosPersistencia, agora: DateTime<Local>) -> Vec<Problema> {
+    let mut copia = dados.clone();
+    analisar(&mut copia, false, agora)
+}
+
+// Devolve os problemas encontrados (antes do reparo) e os dados já reparados
+pub(super) fn reparar(
+    dados: &DadosPersistencia,
+    agora: DateTime<Local>,
+) -> (Vec<Problema>, DadosPersistencia) {
+    let mut reparados = dados.clone();
+    let problemas = analisar(&mut reparados, true, agora);
+    (problemas, reparados)
+}
+
+// Diferença entidade por entidade, no estilo de um diff: `-` antes, `+` depois
+pub(super) fn diferencas(antes: &DadosPersistencia, depois: &DadosPersistencia) -> Vec<String> {
+    let mut linhas = Vec::new();
+    comparar("livros", &antes.livros, &depois.livros, &mut linhas);
+    comparar("usuarios", &antes.usuarios, &depois.usuarios, &mut linhas);
+    comparar(
+        "emprestimos",
+        &antes.emprestimos,
+        &depois.emprestimos,
+        &mut linhas,
+    );
+    linhas
+}
+
+fn comparar<T: Serialize>(
+    nome: &str,
+    antes: &HashMap<Uuid, T>,
+    depois: &HashMap<Uuid, T>,
+    linhas: &mut Vec<String>,
+) {
+    let texto = |valor: Option<&T>| valor.and_then(|v| serde_json::to_string(v).ok());
+    let chaves: BTreeSet<&Uuid> = antes.keys().chain(depois.keys()).collect();
+
+    for chave in chaves {
+        let (velho, novo) = (texto(antes.get(chave)), texto(depois.get(chave)));
+        if velho == novo {
+            continue;
+        }
+        if let Some(velho) = velho {
+            linhas.push(format!("- {}[{}] {}", nome, chave, velho));
+        }
+        if let Some(novo) = novo {
+            linhas.push(format!("+ {}[{}] {}", nome, chave, novo));
+        }
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use chrono::{Days, TimeZone};
+
+    use super::*;
+
+    fn agora() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 20, 9, 0, 0).unwrap()
+    }
+
+    // Um leitor e dois livros, o primeiro emprestado a ele
+    fn dados() -> (DadosPersistencia, Uuid, Uuid) {
+        let leitor = Usuario::new("Sofia".to_string(), agora());
+        let livro = Livro::new(
+            "Quincas Borba".to_string(),
+            "Machado de Assis".to_string(),
+            1891,
+            agora(),
+        );
+        let outro = Livro::new(
+            "Esaú e Jacó".to_string(),
+            "Machado de Assis".to_string(),
+            1904,
+            agora(),
+        );
+        let mut dados = DadosPersistencia {
+            usuarios: HashMap::from([(leitor.id, leitor.clone())]),
+            livros: HashMap::from([(livro.id(), livro.clone()), (outro.id(), outro)]),
+            ..Default::default()
+        };
+        let emprestimo = emprestimo(leitor.id, livro.id(), agora());
+        let id = emprestimo.id();
+        Circulacao::new(&mut dados.livros, &mut dados.emprestimos).abrir(emprestimo);
+        (dados, livro.id(), id)
+    }
+
+    fn emprestimo(id_usuario: Uuid, id_livro: Uuid, momento: DateTime<Local>) -> Emprestimo {
+        let prazo = momento.date_naive() + Days::new(14);
+        Emprestimo::new(id_usuario, id_livro, prazo, None, momento)
+    }
+
+    fn graves(problemas: &[Problema]) -> Vec<&str> {
+        problemas
+            .iter()
+            .filter(|p| p.gravidade >= Gravidade::Erro)
+            .map(|p| p.descricao.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn dados_coerentes_nao_tem_problemas() {
+        let (dados, _, _) = dados();
+        assert!(verificar(&dados, agora()).is_empty());
+    }
+
+    #[test]
+    fn emprestimos_duplicados_ficam_so_com_o_mais_recente() {
+        let (mut dados, livro, antigo) = dados();
+        let leitor = dados.emprestimos[&antigo].get_id_usuario();
+        let recente = emprestimo(leitor, livro, agora() + Days::new(1));
+        let id_recente = recente.id();
+        dados.emprestimos.insert(id_recente, recente);
+
+        let problemas = verificar(&dados, agora());
+        assert_eq!(problemas.len(), 1);
+        assert_eq!(problemas[0].gravidade, Gravidade::Critico);
+
+        let (_, reparados) = reparar(&dados, agora());
+        assert!(!reparados.emprestimos[&antigo].esta_ativo());
+        assert!(reparados.emprestimos[&id_recente].esta_ativo());
+        assert!(verificar(&reparados, agora()).is_empty());
+    }
+
+    #[test]
+    fn status_divergente_e_sincronizado_com_os_emprestimos() {
+        let (mut dados, livro, id) = dados();
+        dados.emprestimos.get_mut(&id).unwrap().status = StatusEmprestimo::Devolvido;
+
+        let problemas = verificar(&dados, agora());
+        assert_eq!(graves(&problemas).len(), 1);
+        assert!(problemas[0].descricao.contains("não tem empréstimo ativo"));
+
+        let (_, reparados) = reparar(&dados, agora());
+        assert!(reparados.livros[&livro].esta_disponivel());
+        assert!(verificar(&reparados, agora()).is_empty());
+    }
+
+    #[test]
+    fn emprestimo_de_livro_inexistente_recria_o_livro() {
+        let (mut dados, livro, _) = dados();
+        dados.livros.remove(&livro);
+
+        let problemas = verificar(&dados, agora());
+        assert!(problemas.iter().any(|p| p.gravidade == Gravidade::Critico));
+
+        let (_, reparados) = reparar(&dados, agora());
+        // Recriado arquivado, mas o empréstimo ativo o desarquiva
+        assert_eq!(reparados.livros[&livro].get_titulo(), "Livro desconhecido");
+        assert!(graves(&verificar(&reparados, agora())).is_empty());
+    }
+
+    #[test]
+    fn recorte_traz_o_que_a_verificacao_precisa() {
+        let (mut dados, livro, antigo) = dados();
+        let leitor = dados.emprestimos[&antigo].get_id_usuario();
+        let outro_leitor = Usuario::new("Rubião".to_string(), agora());
+        let recente = emprestimo(outro_leitor.id, livro, agora() + Days::new(1));
+        let id_recente = recente.id();
+        dados.usuarios.insert(outro_leitor.id, outro_leitor.clone());
+        dados.emprestimos.insert(id_recente, recente);
+
+        // Só o empréstimo novo foi tocado, mas o antigo do mesmo livro entra
+        let recorte = recorte(&dados, &HashSet::from([id_recente]));
+        assert_eq!(recorte.emprestimos.len(), 2);
+        assert_eq!(recorte.livros.len(), 1);
+        assert!(recorte.usuarios.contains_key(&leitor));
+        assert!(recorte.usuarios.contains_key(&outro_leitor.id));
+        assert_eq!(
+            graves(&verificar(&recorte, agora())),
+            graves(&verificar(&dados, agora()))
+        );
+
+        // O outro livro não tem nada a ver com a alteração
+        let sem_relacao = dados.livros.keys().find(|id| **id != livro).copied();
+        let recorte = super::recorte(&dados, &HashSet::from_iter(sem_relacao));
+        assert!(recorte.emprestimos.is_empty());
+        assert!(verificar(&recorte, agora()).is_empty());
+    }
+}
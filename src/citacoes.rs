@@ -0,0 +1,361 @@
+// Exportação de referências para gerenciadores de citação: BibTeX, RIS e CSL-JSON.
+// As chaves de citação (sobrenome + ano) são calculadas sobre o acervo inteiro,
+// então o mesmo livro recebe sempre a mesma chave, qualquer que seja a pesquisa.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::biblioteca::livros::Livro;
+use crate::errors::ErroBiblioteca;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatoCitacao {
+    Bibtex,
+    Ris,
+    CslJson,
+}
+
+impl FormatoCitacao {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "bibtex" | "bib" => Ok(FormatoCitacao::Bibtex),
+            "ris" => Ok(FormatoCitacao::Ris),
+            "csl-json" | "csl" => Ok(FormatoCitacao::CslJson),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Formato de citação desconhecido: {} (use bibtex, ris ou csl-json).",
+                outro
+            ))),
+        }
+    }
+}
+
+// Tira os acentos mais comuns em português, para chaves só com ASCII
+fn sem_acentos(texto: &str) -> String {
+    texto
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            outro => outro,
+        })
+        .collect()
+}
+
+// "Assis, Machado de" ou "Machado de Assis" -> ("Assis", "Machado de")
+fn separar_nome(nome: &str) -> (String, Option<String>) {
+    if let Some((sobrenome, prenomes)) = nome.split_once(',') {
+        let prenomes = prenomes.trim();
+        return (
+            sobrenome.trim().to_string(),
+            (!prenomes.is_empty()).then(|| prenomes.to_string()),
+        );
+    }
+    match nome.trim().rsplit_once(' ') {
+        Some((prenomes, sobrenome)) => (sobrenome.to_string(), Some(prenomes.trim().to_string())),
+        None => (nome.trim().to_string(), None),
+    }
+}
+
+fn autores(livro: &Livro) -> Vec<&String> {
+    std::iter::once(livro.get_autor())
+        .chain(&livro.catalogacao().coautores)
+        .collect()
+}
+
+// Sobrenome do autor principal + ano, em minúsculas: "assis1899".
+// Livros com a mesma base ganham "a", "b", "c"... pela ordem de título.
+pub fn chaves<'a>(livros: impl Iterator<Item = (&'a Uuid, &'a Livro)>) -> HashMap<Uuid, String> {
+    let mut por_base: HashMap<String, Vec<(&Livro, Uuid)>> = HashMap::new();
+    for (id, livro) in livros {
+        let (sobrenome, _) = separar_nome(livro.get_autor());
+        let mut base: String = sem_acentos(&sobrenome.to_lowercase())
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        if base.is_empty() {
+            base = "anonimo".to_string();
+        }
+        base.push_str(&livro.get_ano().to_string());
+        por_base.entry(base).or_default().push((livro, *id));
+    }
+
+    let mut chaves = HashMap::new();
+    for (base, mut livros) in por_base {
+        if livros.len() == 1 {
+            chaves.insert(livros[0].1, base);
+            continue;
+        }
+        livros.sort_by_key(|(livro, id)| (livro.get_titulo().to_lowercase(), *id));
+        for (indice, (_, id)) in livros.into_iter().enumerate() {
+            chaves.insert(id, format!("{}{}", base, sufixo(indice)));
+        }
+    }
+    chaves
+}
+
+// 0 -> "a", 25 -> "z", 26 -> "aa"...
+fn sufixo(mut indice: usize) -> String {
+    let mut letras = Vec::new();
+    loop {
+        letras.push((b'a' + (indice % 26) as u8) as char);
+        if indice < 26 {
+            break;
+        }
+        indice = indice / 26 - 1;
+    }
+    letras.iter().rev().collect()
+}
+
+// Caracteres com significado especial no LaTeX
+fn escapar_bibtex(texto: &str) -> String {
+    let mut saida = String::new();
+    for c in texto.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                saida.push('\\');
+                saida.push(c);
+            }
+            '~' => saida.push_str("\\textasciitilde{}"),
+            '^' => saida.push_str("\\textasciicircum{}"),
+            '\\' => saida.push_str("\\textbackslash{}"),
+            '\n' | '\r' => saida.push(' '),
+            _ => saida.push(c),
+        }
+    }
+    saida
+}
+
+// " and " separa autores no BibTeX; entre chaves, fica como parte do nome
+fn escapar_autor_bibtex(autor: &str) -> String {
+    let escapado = escapar_bibtex(autor);
+    if escapado.to_lowercase().contains(" and ") {
+        format!("{{{}}}", escapado)
+    } else {
+        escapado
+    }
+}
+
+fn bibtex(chave: &str, livro: &Livro) -> String {
+    let catalogacao = livro.catalogacao();
+    let mut campos = vec![
+        (
+            "author",
+            autores(livro)
+                .iter()
+                .map(|autor| escapar_autor_bibtex(autor))
+                .collect::<Vec<_>>()
+                .join(" and "),
+        ),
+        ("title", escapar_bibtex(livro.get_titulo())),
+        ("year", livro.get_ano().to_string()),
+    ];
+    if let Some(editora) = &catalogacao.editora {
+        campos.push(("publisher", escapar_bibtex(editora)));
+    }
+    if let Some(isbn) = &catalogacao.isbn {
+        campos.push(("isbn", escapar_bibtex(isbn)));
+    }
+    if !catalogacao.assuntos.is_empty() {
+        campos.push(("keywords", escapar_bibtex(&catalogacao.assuntos.join(", "))));
+    }
+
+    let corpo: Vec<String> = campos
+        .into_iter()
+        .map(|(nome, valor)| format!("  {} = {{{}}}", nome, valor))
+        .collect();
+    format!("@book{{{},\n{}\n}}\n", chave, corpo.join(",\n"))
+}
+
+// RIS não tem escape: basta não deixar quebra de linha no valor
+fn linha_ris(tag: &str, valor: &str) -> String {
+    format!("{}  - {}\r\n", tag, valor.replace(['\r', '\n'], " "))
+}
+
+fn ris(chave: &str, livro: &Livro) -> String {
+    let catalogacao = livro.catalogacao();
+    let mut saida = linha_ris("TY", "BOOK");
+    saida.push_str(&linha_ris("ID", chave));
+    for autor in autores(livro) {
+        saida.push_str(&linha_ris("AU", autor));
+    }
+    saida.push_str(&linha_ris("TI", livro.get_titulo()));
+    saida.push_str(&linha_ris("PY", &livro.get_ano().to_string()));
+    if let Some(editora) = &catalogacao.editora {
+        saida.push_str(&linha_ris("PB", editora));
+    }
+    if let Some(isbn) = &catalogacao.isbn {
+        saida.push_str(&linha_ris("SN", isbn));
+    }
+    for assunto in &catalogacao.assuntos {
+        saida.push_str(&linha_ris("KW", assunto));
+    }
+    saida.push_str(&linha_ris("ER", ""));
+    saida
+}
+
+fn csl_json(chave: &str, livro: &Livro) -> Value {
+    let catalogacao = livro.catalogacao();
+    let autores: Vec<Value> = autores(livro)
+        .into_iter()
+        .map(|autor| match separar_nome(autor) {
+            (sobrenome, Some(prenomes)) => json!({ "family": sobrenome, "given": prenomes }),
+            (nome, None) => json!({ "literal": nome }),
+        })
+        .collect();
+
+    let mut item = json!({
+        "id": chave,
+        "type": "book",
+        "title": livro.get_titulo(),
+        "author": autores,
+        "issued": { "date-parts": [[livro.get_ano()]] },
+    });
+    if let Some(editora) = &catalogacao.editora {
+        item["publisher"] = json!(editora);
+    }
+    if let Some(isbn) = &catalogacao.isbn {
+        item["ISBN"] = json!(isbn);
+    }
+    if !catalogacao.assuntos.is_empty() {
+        item["keyword"] = json!(catalogacao.assuntos.join(", "));
+    }
+    item
+}
+
+// Os livros já vêm na ordem em que devem aparecer
+pub fn exportar(
+    formato: FormatoCitacao,
+    livros: &[(Uuid, &Livro)],
+    chaves: &HashMap<Uuid, String>,
+) -> Result<String, ErroBiblioteca> {
+    let chave = |id: &Uuid| chaves.get(id).map(String::as_str).unwrap_or("sem-chave");
+
+    match formato {
+        FormatoCitacao::Bibtex => Ok(livros
+            .iter()
+            .map(|(id, livro)| bibtex(chave(id), livro))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        FormatoCitacao::Ris => Ok(livros
+            .iter()
+            .map(|(id, livro)| ris(chave(id), livro))
+            .collect()),
+        FormatoCitacao::CslJson => {
+            let itens: Vec<Value> = livros
+                .iter()
+                .map(|(id, livro)| csl_json(chave(id), livro))
+                .collect();
+            serde_json::to_string_pretty(&itens)
+                .map(|json| json + "\n")
+                .map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!("Erro ao gerar CSL-JSON: {}", e))
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use chrono::Local;
+
+    use super::*;
+    use crate::biblioteca::livros::Catalogacao;
+
+    fn livro(titulo: &str, autor: &str, catalogacao: Catalogacao) -> Livro {
+        let mut livro = Livro::new(titulo.to_string(), autor.to_string(), 1899, Local::now());
+        livro.catalogar(catalogacao, Local::now());
+        livro
+    }
+
+    #[test]
+    fn bibtex_escapa_caracteres_do_latex() {
+        assert_eq!(
+            escapar_bibtex("50% & {chaves} #1 $5 a_b"),
+            "50\\% \\& \\{chaves\\} \\#1 \\$5 a\\_b"
+        );
+        assert_eq!(
+            escapar_bibtex("~^\\"),
+            "\\textasciitilde{}\\textasciicircum{}\\textbackslash{}"
+        );
+        assert_eq!(escapar_bibtex("duas\nlinhas\r"), "duas linhas ");
+        assert_eq!(escapar_bibtex("Memórias"), "Memórias");
+    }
+
+    #[test]
+    fn bibtex_nao_quebra_o_registro() {
+        let livro = livro(
+            "Contos} @book{falso,",
+            "Laemmert and Cia.",
+            Catalogacao {
+                coautores: vec!["Assis, Machado de".to_string()],
+                editora: Some("Garnier & Irmãos".to_string()),
+                ..Default::default()
+            },
+        );
+        let entrada = bibtex("laemmert1899", &livro);
+
+        assert!(
+            entrada.contains("title = {Contos\\} @book\\{falso,}"),
+            "{}",
+            entrada
+        );
+        assert!(
+            entrada.contains("author = {{Laemmert and Cia.} and Assis, Machado de}"),
+            "{}",
+            entrada
+        );
+        assert!(
+            entrada.contains("publisher = {Garnier \\& Irmãos}"),
+            "{}",
+            entrada
+        );
+        // Chaves sem escape balanceadas: o registro termina onde deveria
+        let sem_escape = entrada.replace("\\{", "").replace("\\}", "");
+        assert_eq!(
+            sem_escape.matches('{').count(),
+            sem_escape.matches('}').count()
+        );
+    }
+
+    #[test]
+    fn ris_nao_deixa_valor_criar_outra_linha() {
+        let livro = livro(
+            "Primeira linha\r\nER  - ",
+            "Assis, Machado de",
+            Catalogacao {
+                assuntos: vec!["Romance\nTY  - JOUR".to_string()],
+                ..Default::default()
+            },
+        );
+        let registro = ris("assis1899", &livro);
+        let linhas: Vec<&str> = registro.split_terminator("\r\n").collect();
+
+        assert_eq!(linhas.first(), Some(&"TY  - BOOK"));
+        assert_eq!(linhas.last(), Some(&"ER  - "));
+        assert_eq!(linhas.iter().filter(|l| l.starts_with("ER  -")).count(), 1);
+        assert_eq!(linhas.iter().filter(|l| l.starts_with("TY  -")).count(), 1);
+        assert!(linhas.contains(&"TI  - Primeira linha  ER  - "));
+        assert!(!registro.replace("\r\n", "").contains(['\r', '\n']));
+    }
+
+    #[test]
+    fn chaves_repetidas_ganham_sufixo_pela_ordem_do_titulo() {
+        let a = livro("Quincas Borba", "Machado de Assis", Catalogacao::default());
+        let b = livro("Dom Casmurro", "Assis, Machado de", Catalogacao::default());
+        let c = livro("Sem autor", "", Catalogacao::default());
+        let ids = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let chaves = chaves(ids.iter().zip([&a, &b, &c]));
+
+        assert_eq!(chaves[&ids[1]], "assis1899a");
+        assert_eq!(chaves[&ids[0]], "assis1899b");
+        assert_eq!(chaves[&ids[2]], "anonimo1899");
+        assert_eq!(sufixo(26), "aa");
+    }
+}
@@ -0,0 +1,63 @@
+// `ajuda [COMANDO]`: o uso geral com as opções que valem para todos os
+// comandos, ou o uso de um comando com as opções só dele.
+
+use super::{
+    auditoria, calendario, citar, config, contas, documento, intercambio, inventario, notificar,
+    planilha, reconstruir, relatorio, servidor, unidades, verificar,
+};
+
+pub const USO_GERAL: &str = "projeto2 [OPÇÕES] [COMANDO ...]
+
+Sem comando, abre o menu no terminal.
+
+Opções (valem para todos os comandos):
+  --config ARQUIVO                  configuração (padrão: biblioteca.toml)
+  --login NOME                      conta usada no login
+  --unidade SIGLA                   unidade onde o programa está rodando
+  --autosalvar sempre|nunca|SEGUNDOS
+  --data-atual AAAA-MM-DD           simula outro dia (para depuração)
+
+Comandos:
+  tui, config, serve, compactar, reconstruir, auditoria, contas, verificar,
+  importar, exportar, citar, relatorio, documento, notificar, calendario,
+  unidades, intercambio, inventario
+
+Use `projeto2 ajuda COMANDO` para ver as opções de um comando.";
+
+pub fn uso(comando: &str) -> Option<&'static str> {
+    let uso = match comando {
+        "tui" => "tui",
+        "config" => config::USO,
+        "serve" => servidor::USO,
+        "compactar" => "compactar",
+        "reconstruir" => reconstruir::USO,
+        "auditoria" => auditoria::USO,
+        "contas" => contas::USO,
+        "verificar" => verificar::USO,
+        "importar" => planilha::USO_IMPORTAR,
+        "exportar" => planilha::USO_EXPORTAR,
+        "citar" => citar::USO,
+        "relatorio" => relatorio::USO,
+        "documento" => documento::USO,
+        "notificar" => notificar::USO,
+        "calendario" => calendario::USO,
+        "unidades" => unidades::USO,
+        "intercambio" => intercambio::USO,
+        "inventario" => inventario::USO,
+        _ => return None,
+    };
+    Some(uso)
+}
+
+pub fn executar(comando: Option<&str>) {
+    match comando {
+        None => println!("Uso: {}", USO_GERAL),
+        Some(comando) => match uso(comando) {
+            Some(uso) => println!("Uso: {}", uso),
+            None => {
+                eprintln!("Comando desconhecido: {}\n\nUso: {}", comando, USO_GERAL);
+                std::process::exit(2);
+            }
+        },
+    }
+}
@@ -0,0 +1,74 @@
+use uuid::Uuid;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{Biblioteca, auditoria::FiltroAuditoria};
+
+use super::momento_do_dia;
+
+pub const USO: &str = "auditoria [--de AAAA-MM-DD] [--ate AAAA-MM-DD] [--feito-por LOGIN] \
+     [--entidade UUID]\n     \
+     [--formato texto|json] [--saida ARQUIVO]";
+
+// Lista a trilha de auditoria filtrada, em texto ou JSON, na tela ou num arquivo
+pub fn executar(biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let mut filtro = FiltroAuditoria::default();
+
+    for (opcao, fim) in [("de", false), ("ate", true)] {
+        if let Some(texto) = argumentos.opcao(opcao) {
+            let Some(momento) = momento_do_dia(texto, fim) else {
+                eprintln!("--{} precisa de uma data no formato AAAA-MM-DD", opcao);
+                return;
+            };
+            if fim {
+                filtro.ate = Some(momento);
+            } else {
+                filtro.de = Some(momento);
+            }
+        }
+    }
+
+    // Quem fez as operações; `--login` é só de quem está consultando
+    filtro.operador = argumentos.opcao("feito-por").map(str::to_string);
+
+    if let Some(texto) = argumentos.opcao("entidade") {
+        match Uuid::parse_str(texto) {
+            Ok(id) => filtro.entidade = Some(id),
+            Err(_) => {
+                eprintln!("--entidade precisa de um UUID válido");
+                return;
+            }
+        }
+    }
+
+    let registros = match biblioteca.auditoria(&filtro) {
+        Ok(registros) => registros,
+        Err(e) => {
+            eprintln!("Erro ao consultar auditoria: {}", e);
+            return;
+        }
+    };
+
+    let texto = match argumentos.opcao("formato").unwrap_or("texto") {
+        "texto" if registros.is_empty() => "Nenhum registro encontrado.\n".to_string(),
+        "texto" => registros.iter().map(|r| format!("{}\n\n", r)).collect(),
+        "json" => match serde_json::to_string_pretty(&registros) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                eprintln!("Erro ao gerar JSON: {}", e);
+                return;
+            }
+        },
+        outro => {
+            eprintln!("Formato desconhecido: {} (use texto ou json)", outro);
+            return;
+        }
+    };
+
+    match argumentos.opcao("saida") {
+        None => print!("{}", texto),
+        Some(arquivo) => match std::fs::write(arquivo, texto) {
+            Ok(()) => println!("{} registro(s) gravado(s) em {}", registros.len(), arquivo),
+            Err(e) => eprintln!("Erro ao gravar {}: {}", arquivo, e),
+        },
+    }
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+
+use super::ler_data;
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{
+    Biblioteca,
+    calendario::{Calendario, DiaSemana, Fechamento, Feriado},
+    contas::Permissao,
+};
+use crate::errors::ErroBiblioteca;
+
+pub const USO: &str = "calendario [mostrar] [--ano N]\n     \
+     calendario dia AAAA-MM-DD\n     \
+     calendario funcionamento seg,ter,...\n     \
+     calendario nacionais sim|nao\n     \
+     calendario feriado NOME (--data MM-DD | --pascoa N)\n     \
+     calendario remover-feriado NOME\n     \
+     calendario fechar AAAA-MM-DD [AAAA-MM-DD] --motivo TEXTO\n     \
+     calendario reabrir AAAA-MM-DD";
+
+// `calendario`: mostra os dias de funcionamento, feriados e fechamentos, ou os
+// altera (o que muda os prazos dos próximos empréstimos e renovações)
+pub fn executar(caminho_arquivo: &Path, biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let Some(sessao) = biblioteca.sessao().cloned() else {
+        return;
+    };
+    let posicional = |i: usize| argumentos.posicionais.get(i).map(String::as_str);
+    let mut calendario = biblioteca.calendario().clone();
+
+    let alteracao = match (posicional(1), posicional(2)) {
+        (None | Some("mostrar"), _) => {
+            let ano = match argumentos.opcao("ano").map(str::parse::<i32>) {
+                None => biblioteca.hoje().year(),
+                Some(Ok(ano)) => ano,
+                Some(Err(_)) => {
+                    eprintln!("--ano precisa de um número");
+                    return;
+                }
+            };
+            mostrar_calendario(&calendario, ano);
+            return;
+        }
+        (Some("dia"), Some(texto)) => {
+            match ler_data(texto) {
+                Ok(data) => match calendario.fechamento(data) {
+                    None => println!("{}: aberta", data.format("%d/%m/%Y")),
+                    Some(motivo) => println!(
+                        "{}: fechada ({}); próximo dia aberto: {}",
+                        data.format("%d/%m/%Y"),
+                        motivo,
+                        calendario.proximo_dia_aberto(data).format("%d/%m/%Y")
+                    ),
+                },
+                Err(e) => eprintln!("{}", e),
+            }
+            return;
+        }
+        (Some("funcionamento"), Some(lista)) => lista
+            .split(',')
+            .map(DiaSemana::interpretar)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|dias| {
+                calendario.funcionamento = DiaSemana::TODOS
+                    .into_iter()
+                    .filter(|dia| dias.contains(dia))
+                    .collect();
+            }),
+        (Some("nacionais"), Some(valor)) => match valor {
+            "sim" | "nao" | "não" => {
+                calendario.feriados_nacionais = valor == "sim";
+                Ok(())
+            }
+            _ => Err(ErroBiblioteca::DadosInvalidos(
+                "Use `calendario nacionais sim` ou `calendario nacionais nao`.".to_string(),
+            )),
+        },
+        (Some("feriado"), Some(nome)) => {
+            let pascoa = match argumentos.opcao("pascoa").map(str::parse::<i64>) {
+                None => Ok(None),
+                Some(Ok(dias)) => Ok(Some(dias)),
+                Some(Err(_)) => Err(ErroBiblioteca::DadosInvalidos(
+                    "--pascoa precisa do número de dias em relação à Páscoa (ex.: -2).".to_string(),
+                )),
+            };
+            pascoa.map(|pascoa| {
+                // Um feriado com o mesmo nome é substituído
+                calendario.feriados.retain(|f| f.nome != nome);
+                calendario.feriados.push(Feriado {
+                    nome: nome.to_string(),
+                    data: argumentos.opcao("data").map(str::to_string),
+                    pascoa,
+                });
+            })
+        }
+        (Some("remover-feriado"), Some(nome)) => {
+            let antes = calendario.feriados.len();
+            calendario.feriados.retain(|f| f.nome != nome);
+            if calendario.feriados.len() == antes {
+                Err(ErroBiblioteca::DadosInvalidos(format!(
+                    "Nenhum feriado da biblioteca se chama {}.",
+                    nome
+                )))
+            } else {
+                Ok(())
+            }
+        }
+        (Some("fechar"), Some(de)) => (|| {
+            let de = ler_data(de)?;
+            let ate = match posicional(3) {
+                Some(ate) => ler_data(ate)?,
+                None => de,
+            };
+            let motivo = argumentos
+                .opcao("motivo")
+                .map(str::trim)
+                .filter(|motivo| !motivo.is_empty())
+                .ok_or_else(|| {
+                    ErroBiblioteca::DadosInvalidos("Informe --motivo TEXTO.".to_string())
+                })?;
+            calendario.fechamentos.push(Fechamento {
+                de,
+                ate,
+                motivo: motivo.to_string(),
+            });
+            calendario.fechamentos.sort_by_key(|f| (f.de, f.ate));
+            Ok(())
+        })(),
+        (Some("reabrir"), Some(texto)) => ler_data(texto).and_then(|data| {
+            let antes = calendario.fechamentos.len();
+            calendario
+                .fechamentos
+                .retain(|f| !(f.de <= data && data <= f.ate));
+            if calendario.fechamentos.len() == antes {
+                Err(ErroBiblioteca::DadosInvalidos(format!(
+                    "Nenhum fechamento inclui {}.",
+                    texto
+                )))
+            } else {
+                Ok(())
+            }
+        }),
+        _ => Err(ErroBiblioteca::DadosInvalidos(format!("Uso: {}", USO))),
+    };
+
+    let resultado = alteracao
+        .and_then(|()| sessao.exigir(Permissao::ConfigurarCalendario))
+        .and_then(|()| calendario.salvar(caminho_arquivo));
+    match resultado {
+        Ok(()) => {
+            biblioteca.definir_calendario(calendario);
+            println!("Calendário atualizado.");
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn mostrar_calendario(calendario: &Calendario, ano: i32) {
+    let dias: Vec<String> = calendario
+        .funcionamento
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    println!("Funcionamento: {}", dias.join(", "));
+    println!(
+        "Feriados nacionais: {}",
+        if calendario.feriados_nacionais {
+            "sim"
+        } else {
+            "não"
+        }
+    );
+
+    let mut datas: Vec<(NaiveDate, String, String)> = calendario
+        .feriados_do_ano(ano)
+        .into_iter()
+        .map(|(data, nome)| (data, data.format("%d/%m/%Y").to_string(), nome))
+        .collect();
+    for fechamento in &calendario.fechamentos {
+        if fechamento.de.year() <= ano && ano <= fechamento.ate.year() {
+            let periodo = if fechamento.de == fechamento.ate {
+                fechamento.de.format("%d/%m/%Y").to_string()
+            } else {
+                format!(
+                    "{} a {}",
+                    fechamento.de.format("%d/%m/%Y"),
+                    fechamento.ate.format("%d/%m/%Y")
+                )
+            };
+            datas.push((fechamento.de, periodo, fechamento.motivo.clone()));
+        }
+    }
+    datas.sort();
+
+    println!("Feriados e fechamentos em {}:", ano);
+    if datas.is_empty() {
+        println!("  nenhum");
+    }
+    let largura = datas.iter().map(|(_, periodo, _)| periodo.len()).max();
+    for (_, periodo, nome) in datas {
+        println!(
+            "  {:<largura$}  {}",
+            periodo,
+            nome,
+            largura = largura.unwrap_or(0)
+        );
+    }
+}
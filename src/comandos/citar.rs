@@ -0,0 +1,59 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{Biblioteca, livros::FiltroLivros};
+use crate::citacoes;
+
+pub const USO: &str = "citar [TERMOS...] [--titulo T] [--autor A] [--ano N] [--assunto S]\n     \
+     [--formato bibtex|ris|csl-json] [--saida ARQUIVO]";
+
+// `citar [TERMOS...]`: referências dos livros encontrados, para gerenciadores de citação
+pub fn executar(biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let mut filtro = FiltroLivros {
+        termos: argumentos.posicionais[1..].to_vec(),
+        titulo: argumentos.opcao("titulo").map(str::to_string),
+        autor: argumentos.opcao("autor").map(str::to_string),
+        assunto: argumentos.opcao("assunto").map(str::to_string),
+        ..FiltroLivros::default()
+    };
+    if let Some(texto) = argumentos.opcao("ano") {
+        match texto.parse() {
+            Ok(ano) => filtro.ano = Some(ano),
+            Err(_) => {
+                eprintln!("--ano precisa de um número");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let formato = match citacoes::FormatoCitacao::interpretar(
+        argumentos.opcao("formato").unwrap_or("bibtex"),
+    ) {
+        Ok(formato) => formato,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let livros = biblioteca.pesquisar_livros(&filtro);
+    if livros.is_empty() {
+        eprintln!("Nenhum livro encontrado.");
+        std::process::exit(1);
+    }
+
+    let chaves = citacoes::chaves(biblioteca.livros().iter());
+    let texto = match citacoes::exportar(formato, &livros, &chaves) {
+        Ok(texto) => texto,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    match argumentos.opcao("saida") {
+        None => print!("{}", texto),
+        Some(arquivo) => match std::fs::write(arquivo, texto) {
+            Ok(()) => println!("{} referência(s) gravada(s) em {}", livros.len(), arquivo),
+            Err(e) => eprintln!("Erro ao gravar {}: {}", arquivo, e),
+        },
+    }
+}
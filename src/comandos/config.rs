@@ -0,0 +1,45 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::calendario::Calendario;
+use crate::configuracao::Configuracao;
+
+pub const USO: &str = "config [show]";
+
+// `config show`: a configuração em vigor, em TOML, com o calendário que está
+// valendo de fato (o arquivo ao lado dos dados, depois que ele existe)
+pub fn executar(configuracao: &Configuracao, argumentos: &Argumentos) {
+    if !matches!(
+        argumentos.posicionais.get(1).map(String::as_str),
+        None | Some("show")
+    ) {
+        eprintln!("Uso: {}", USO);
+        return;
+    }
+
+    let mut efetiva = configuracao.clone();
+    let caminho_dados = configuracao.caminho_dados();
+    let arquivo_calendario = Calendario::caminho_calendario(caminho_dados);
+    if arquivo_calendario.exists() {
+        match Calendario::carregar(caminho_dados, None) {
+            Ok(calendario) => efetiva.calendario = Some(calendario),
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+    }
+
+    match &configuracao.origem {
+        Some(caminho) => println!("# Configuração lida de {}", caminho.display()),
+        None => println!("# Nenhum arquivo de configuração encontrado; valores padrão"),
+    }
+    if arquivo_calendario.exists() {
+        println!(
+            "# [calendario] vem de {} (alterado pelo comando calendario)",
+            arquivo_calendario.display()
+        );
+    }
+    match efetiva.em_toml() {
+        Ok(texto) => print!("\n{}", texto),
+        Err(e) => eprintln!("{}", e),
+    }
+}
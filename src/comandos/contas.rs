@@ -0,0 +1,102 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{
+    Biblioteca,
+    contas::{Contas, Papel, Permissao, Sessao},
+};
+use crate::entrada;
+use crate::errors::ErroBiblioteca;
+
+pub const USO: &str = "contas [listar]\n     \
+     contas criar LOGIN --papel admin|bibliotecario|assistente\n     \
+     contas remover LOGIN\n     \
+     contas senha [LOGIN]";
+
+// Senha usada no login quando definida (para scripts e tarefas agendadas)
+const VARIAVEL_SENHA: &str = "BIBLIOTECA_SENHA";
+
+// Quantas vezes a senha pode ser digitada errada antes de encerrar
+const TENTATIVAS_LOGIN: u32 = 3;
+
+// Na primeira execução cria o administrador; depois, pede login e senha.
+// A senha pode vir da variável de ambiente para uso em scripts.
+pub fn entrar(contas: &mut Contas, argumentos: &Argumentos) -> Result<Sessao, ErroBiblioteca> {
+    if contas.esta_vazia() {
+        println!("Nenhuma conta de funcionário cadastrada. Crie a conta do administrador.");
+        let login = entrada::ler_string("Login: ")?;
+        let senha = entrada::ler_nova_senha()?;
+        contas.criar_primeiro_admin(&login, &senha)?;
+        return contas.autenticar(&login, &senha);
+    }
+
+    let login = match argumentos.opcao("login") {
+        Some(login) => login.to_string(),
+        None => entrada::ler_string("Login: ")?,
+    };
+
+    if let Ok(senha) = std::env::var(VARIAVEL_SENHA) {
+        return contas.autenticar(&login, &senha);
+    }
+
+    let mut tentativas = 1;
+    loop {
+        let senha = entrada::ler_senha("Senha: ")?;
+        match contas.autenticar(&login, &senha) {
+            Err(ErroBiblioteca::CredenciaisInvalidas) if tentativas < TENTATIVAS_LOGIN => {
+                println!("Login ou senha inválidos. Tente novamente.");
+                tentativas += 1;
+            }
+            resultado => return resultado,
+        }
+    }
+}
+
+// `contas listar | criar LOGIN --papel P | remover LOGIN | senha [LOGIN]`
+pub fn executar(contas: &mut Contas, biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let Some(sessao) = biblioteca.sessao() else {
+        return;
+    };
+    let login = argumentos.posicionais.get(2).map(String::as_str);
+
+    let resultado = match (argumentos.posicionais.get(1).map(String::as_str), login) {
+        (None | Some("listar"), _) => sessao.exigir(Permissao::GerenciarContas).map(|()| {
+            for conta in contas.listar() {
+                println!("{} ({})", conta.login, conta.papel);
+            }
+        }),
+        (Some("criar"), Some(login)) => argumentos
+            .opcao("papel")
+            .ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos(
+                    "Informe --papel admin, bibliotecario ou assistente.".to_string(),
+                )
+            })
+            .and_then(Papel::interpretar)
+            .and_then(|papel| {
+                sessao.exigir(Permissao::GerenciarContas)?;
+                let senha = entrada::ler_nova_senha()?;
+                contas.criar(sessao, login, &senha, papel)?;
+                println!("Conta {} ({}) criada.", login, papel);
+                Ok(())
+            }),
+        (Some("remover"), Some(login)) => contas.remover(sessao, login).map(|conta| {
+            println!("Conta {} removida.", conta.login);
+        }),
+        (Some("senha"), login) => {
+            let login = login.unwrap_or(&sessao.login);
+            let permitido = if login == sessao.login {
+                Ok(())
+            } else {
+                sessao.exigir(Permissao::GerenciarContas)
+            };
+            permitido
+                .and_then(|()| entrada::ler_nova_senha())
+                .and_then(|senha| contas.alterar_senha(sessao, login, &senha))
+                .map(|()| println!("Senha de {} alterada.", login))
+        }
+        _ => Err(ErroBiblioteca::DadosInvalidos(format!("Uso: {}", USO))),
+    };
+
+    if let Err(e) = resultado {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::configuracao::Configuracao;
+use crate::documentos;
+use crate::errors::ErroBiblioteca;
+
+pub const USO: &str = "documento recibo-emprestimo|recibo-devolucao ID_EMPRESTIMO\n     \
+     documento atrasados|inventario\n     \
+     [--formato html|pdf] [--cabecalho ARQUIVO] [--modelo ARQUIVO] [--saida ARQUIVO]";
+
+// `documento TIPO [ID]`: recibos e listas para imprimir, em HTML (na tela ou em
+// `--saida`) ou PDF (sempre num arquivo)
+pub fn executar(biblioteca: &Biblioteca, configuracao: &Configuracao, argumentos: &Argumentos) {
+    let Some(tipo) = argumentos.posicionais.get(1) else {
+        eprintln!("Uso: {}", USO);
+        return;
+    };
+
+    let preparo = (|| {
+        let tipo = documentos::TipoDocumento::interpretar(tipo)?;
+        let formato = documentos::FormatoDocumento::interpretar(
+            argumentos.opcao("formato").unwrap_or("html"),
+        )?;
+        if formato == documentos::FormatoDocumento::Pdf && argumentos.opcao("saida").is_none() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "PDF precisa de --saida ARQUIVO.".to_string(),
+            ));
+        }
+        let cabecalho = match argumentos.opcao("cabecalho") {
+            Some(arquivo) => documentos::Cabecalho::ler(Path::new(arquivo))?,
+            None => configuracao.cabecalho(),
+        };
+
+        let agora = biblioteca.agora();
+        let documento = if tipo.precisa_de_emprestimo() {
+            let id = argumentos
+                .posicionais
+                .get(2)
+                .and_then(|texto| Uuid::parse_str(texto).ok())
+                .ok_or_else(|| {
+                    ErroBiblioteca::DadosInvalidos("Informe o UUID do empréstimo.".to_string())
+                })?;
+            match tipo {
+                documentos::TipoDocumento::ReciboEmprestimo => {
+                    documentos::recibo_emprestimo(biblioteca, id, agora)?
+                }
+                _ => documentos::recibo_devolucao(biblioteca, id, agora)?,
+            }
+        } else if tipo == documentos::TipoDocumento::Atrasados {
+            documentos::atrasados(biblioteca, agora.date_naive(), agora)
+        } else {
+            documentos::inventario(biblioteca, agora)
+        };
+
+        match formato {
+            documentos::FormatoDocumento::Pdf => {
+                Ok(documentos::pdf::renderizar(&documento, &cabecalho))
+            }
+            documentos::FormatoDocumento::Html => {
+                let modelo = match argumentos.opcao("modelo") {
+                    Some(arquivo) => std::fs::read_to_string(arquivo).map_err(|e| {
+                        ErroBiblioteca::ErroPersistencia(format!(
+                            "Erro ao ler modelo {}: {}",
+                            arquivo, e
+                        ))
+                    })?,
+                    None => documentos::html::MODELO_PADRAO.to_string(),
+                };
+                documentos::html::validar_modelo(&modelo)?;
+                Ok(documentos::html::renderizar(&documento, &cabecalho, &modelo).into_bytes())
+            }
+        }
+    })();
+
+    let bytes = match preparo {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    match argumentos.opcao("saida") {
+        None => print!("{}", String::from_utf8_lossy(&bytes)),
+        Some(arquivo) => match std::fs::write(arquivo, bytes) {
+            Ok(()) => println!("Documento gravado em {}", arquivo),
+            Err(e) => eprintln!("Erro ao gravar {}: {}", arquivo, e),
+        },
+    }
+}
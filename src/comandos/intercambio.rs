@@ -0,0 +1,161 @@
+use uuid::Uuid;
+
+use super::ler_data;
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::errors::ErroBiblioteca;
+
+pub const USO: &str = "intercambio [listar] [--todos]\n     \
+     intercambio parceiras\n     \
+     intercambio parceira NOME [--email EMAIL]\n     \
+     intercambio pedir ID_USUARIO --titulo T --autor A --ano N\n     \
+     intercambio enviar ID_PEDIDO PARCEIRA\n     \
+     intercambio receber ID_PEDIDO --vencimento AAAA-MM-DD\n     \
+     intercambio emprestar|devolver|cancelar ID_PEDIDO";
+
+// `intercambio ...`: livros pedidos emprestados a bibliotecas parceiras
+pub fn executar(biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let posicional = |i: usize| argumentos.posicionais.get(i).map(String::as_str);
+    let id_pedido = |texto: &str| {
+        Uuid::parse_str(texto).map_err(|_| {
+            ErroBiblioteca::DadosInvalidos(format!("UUID de pedido inválido: {}", texto))
+        })
+    };
+    let opcao = |nome: &str| {
+        argumentos
+            .opcao(nome)
+            .map(str::to_string)
+            .ok_or_else(|| ErroBiblioteca::DadosInvalidos(format!("Informe --{}.", nome)))
+    };
+
+    let alteracao: Result<String, ErroBiblioteca> = match (posicional(1), posicional(2)) {
+        (None | Some("listar"), _) => {
+            listar_solicitacoes(biblioteca, argumentos.tem_opcao("todos"));
+            return;
+        }
+        (Some("parceiras"), _) => {
+            let mut parceiras: Vec<_> = biblioteca.parceiras().values().collect();
+            if parceiras.is_empty() {
+                println!("Nenhuma biblioteca parceira cadastrada.");
+            }
+            parceiras.sort_by_key(|p| p.nome.to_lowercase());
+            for parceira in parceiras {
+                println!("{}  {}", parceira.id, parceira);
+            }
+            return;
+        }
+        (Some("parceira"), Some(nome)) => biblioteca
+            .cadastrar_parceira(
+                nome.to_string(),
+                argumentos.opcao("email").map(str::to_string),
+            )
+            .map(|_| "Biblioteca parceira cadastrada.".to_string()),
+        (Some("pedir"), Some(texto)) => (|| {
+            let id_usuario = Uuid::parse_str(texto).map_err(|_| {
+                ErroBiblioteca::DadosInvalidos(format!("UUID de usuário inválido: {}", texto))
+            })?;
+            let ano = opcao("ano")?.parse::<u16>().map_err(|_| {
+                ErroBiblioteca::DadosInvalidos("--ano precisa de um número.".to_string())
+            })?;
+            let id = biblioteca.solicitar_intercambio(
+                id_usuario,
+                opcao("titulo")?,
+                opcao("autor")?,
+                ano,
+            )?;
+            Ok(format!("Pedido registrado: {}", id))
+        })(),
+        (Some("enviar"), Some(texto)) => (|| {
+            let id = id_pedido(texto)?;
+            let nome = posicional(3).ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos("Informe a biblioteca parceira.".to_string())
+            })?;
+            let id_parceira = biblioteca.parceira(nome)?.id;
+            biblioteca.enviar_solicitacao(id, id_parceira)?;
+            Ok("Pedido enviado à parceira.".to_string())
+        })(),
+        (Some("receber"), Some(texto)) => (|| {
+            let id = id_pedido(texto)?;
+            let vencimento = ler_data(&opcao("vencimento")?)?;
+            let id_livro = biblioteca.receber_intercambio(id, vencimento)?;
+            Ok(format!(
+                "Livro recebido; registro temporário no catálogo: {}",
+                id_livro
+            ))
+        })(),
+        (Some("emprestar"), Some(texto)) => id_pedido(texto)
+            .and_then(|id| biblioteca.emprestar_intercambio(id))
+            .map(|id_emprestimo| {
+                let emprestimo = &biblioteca.emprestimos()[&id_emprestimo];
+                format!(
+                    "Livro emprestado ao leitor até {} (empréstimo {}).",
+                    emprestimo.get_data_devolucao().format("%d/%m/%Y"),
+                    id_emprestimo
+                )
+            }),
+        (Some("devolver"), Some(texto)) => id_pedido(texto)
+            .and_then(|id| biblioteca.devolver_a_parceira(id))
+            .map(|()| {
+                "Livro devolvido à parceira; o registro temporário saiu do catálogo.".to_string()
+            }),
+        (Some("cancelar"), Some(texto)) => id_pedido(texto)
+            .and_then(|id| biblioteca.cancelar_solicitacao(id))
+            .map(|()| "Pedido cancelado.".to_string()),
+        _ => Err(ErroBiblioteca::DadosInvalidos(format!("Uso: {}", USO))),
+    };
+
+    match alteracao {
+        Ok(mensagem) => match biblioteca.salvar() {
+            Ok(()) => println!("{}", mensagem),
+            Err(e) => {
+                eprintln!("Erro ao salvar: {}", e);
+                std::process::exit(2);
+            }
+        },
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+// Pedidos em aberto (ou todos), dos mais antigos para os mais novos
+fn listar_solicitacoes(biblioteca: &Biblioteca, todos: bool) {
+    let mut solicitacoes: Vec<_> = biblioteca
+        .solicitacoes()
+        .values()
+        .filter(|s| todos || s.etapa().em_aberto())
+        .collect();
+    if solicitacoes.is_empty() {
+        println!("Nenhum pedido de intercâmbio.");
+        return;
+    }
+    solicitacoes.sort_by_key(|s| (s.solicitada_em(), s.id));
+
+    let hoje = biblioteca.hoje();
+    for solicitacao in solicitacoes {
+        let leitor = biblioteca
+            .usuarios()
+            .get(&solicitacao.id_usuario)
+            .map_or_else(|| "?".to_string(), |u| u.get_nome());
+        println!(
+            "{}  \"{}\" ({}) para {} — {} desde {}",
+            solicitacao.id,
+            solicitacao.titulo,
+            solicitacao.autor,
+            leitor,
+            solicitacao.etapa(),
+            solicitacao.desde().format("%d/%m/%Y")
+        );
+        if let Some(parceira) = solicitacao
+            .get_parceira()
+            .and_then(|id| biblioteca.parceiras().get(&id))
+        {
+            print!("    Parceira: {}", parceira);
+            match solicitacao.vencimento() {
+                Some(vencimento) if solicitacao.etapa().em_aberto() => {
+                    let atraso = if vencimento < hoje { " (ATRASADO)" } else { "" };
+                    println!("; devolver até {}{}", vencimento.format("%d/%m/%Y"), atraso);
+                }
+                _ => println!(),
+            }
+        }
+    }
+}
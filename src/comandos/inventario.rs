@@ -0,0 +1,218 @@
+use std::io::{BufRead, IsTerminal};
+
+use uuid::Uuid;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::{entrada, inventario};
+
+pub const USO: &str = "inventario [--marcar-extraviados] [--desmarcar-achados] < CODIGOS";
+
+// `inventario`: confere o acervo com os códigos lidos nas estantes (stdin).
+// Num terminal, cada leitura tem resposta na hora; com o stdin redirecionado,
+// lê tudo até o fim do arquivo.
+pub fn executar(biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let terminal = std::io::stdin().is_terminal();
+    let mut conferencia = inventario::Conferencia::new(biblioteca);
+    if terminal {
+        println!("Leia os códigos (UUID ou ISBN), um por linha. Linha vazia ou \"fim\" encerra.");
+    }
+
+    for linha in std::io::stdin().lock().lines() {
+        let Ok(linha) = linha else {
+            break;
+        };
+        let codigo = linha.trim();
+        if codigo.is_empty() || codigo.eq_ignore_ascii_case("fim") {
+            if terminal {
+                break;
+            }
+            continue;
+        }
+        let leitura = conferencia.registrar(codigo);
+        if terminal {
+            match leitura {
+                inventario::Leitura::Encontrado(id) => {
+                    let livro = &biblioteca.livros()[&id];
+                    let aviso = if livro.esta_disponivel() {
+                        ""
+                    } else {
+                        " (consta como emprestado!)"
+                    };
+                    println!("  ok: {}{}", livro.get_titulo(), aviso);
+                }
+                inventario::Leitura::Repetido(id) => {
+                    println!("  já lido: {}", biblioteca.livros()[&id].get_titulo())
+                }
+                inventario::Leitura::Desconhecido => println!("  código desconhecido: {}", codigo),
+            }
+        }
+    }
+
+    let resultado = conferencia.concluir();
+    let titulo = |id: &Uuid| {
+        let livro = &biblioteca.livros()[id];
+        format!("{}  {} — {}", id, livro.get_titulo(), livro.get_autor())
+    };
+
+    println!("\nConferência: {} exemplar(es) lido(s).", resultado.lidos);
+    println!("\nAusentes ({}):", resultado.ausentes.len());
+    for id in &resultado.ausentes {
+        println!("  {}", titulo(id));
+    }
+    println!(
+        "\nNa estante, mas constam como emprestados ({}):",
+        resultado.emprestados.len()
+    );
+    for id in &resultado.emprestados {
+        let leitor = biblioteca
+            .emprestimos()
+            .values()
+            .find(|e| e.esta_ativo() && e.get_id_livro() == *id)
+            .map(|e| {
+                let nome = biblioteca
+                    .usuarios()
+                    .get(&e.get_id_usuario())
+                    .map_or_else(|| "?".to_string(), |u| u.get_nome());
+                format!(
+                    " (com {} até {})",
+                    nome,
+                    e.get_data_devolucao().format("%d/%m/%Y")
+                )
+            })
+            .unwrap_or_default();
+        println!("  {}{}", titulo(id), leitor);
+    }
+    println!(
+        "\nCódigos desconhecidos ({}):",
+        resultado.desconhecidos.len()
+    );
+    for codigo in &resultado.desconhecidos {
+        println!("  {}", codigo);
+    }
+
+    let mut alterou = false;
+    // Quem foi achado na estante volta a circular
+    if !resultado.achados.is_empty() {
+        println!(
+            "\nAchados (estavam extraviados) ({}):",
+            resultado.achados.len()
+        );
+        for id in &resultado.achados {
+            println!("  {}", titulo(id));
+        }
+        let desmarcar = confirmar(
+            argumentos,
+            terminal,
+            "desmarcar-achados",
+            &format!(
+                "\nTirar os {} achado(s) da lista de extraviados?",
+                resultado.achados.len()
+            ),
+            "tirar os achados da lista de extraviados",
+        );
+        if desmarcar {
+            match biblioteca.definir_extravio(&resultado.achados, false) {
+                Ok(()) => {
+                    println!("Voltaram a circular.");
+                    alterou = true;
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    if !resultado.ausentes.is_empty() {
+        let marcar = confirmar(
+            argumentos,
+            terminal,
+            "marcar-extraviados",
+            &format!(
+                "\nMarcar os {} ausente(s) como extraviados?",
+                resultado.ausentes.len()
+            ),
+            "marcar os ausentes como extraviados",
+        );
+        if marcar {
+            match biblioteca.definir_extravio(&resultado.ausentes, true) {
+                Ok(()) => {
+                    println!(
+                        "{} livro(s) marcado(s) como extraviado(s).",
+                        resultado.ausentes.len()
+                    );
+                    alterou = true;
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    }
+
+    if alterou && let Err(e) = biblioteca.salvar() {
+        eprintln!("Erro ao salvar: {}", e);
+        std::process::exit(2);
+    }
+}
+
+// Mudar a situação de livros só com a opção na linha de comando ou com a
+// confirmação de quem está no terminal; com o stdin redirecionado, só avisa
+fn confirmar(
+    argumentos: &Argumentos,
+    terminal: bool,
+    opcao: &str,
+    pergunta: &str,
+    acao: &str,
+) -> bool {
+    if argumentos.tem_opcao(opcao) {
+        true
+    } else if terminal {
+        entrada::ler_confirmacao(pergunta).unwrap_or(false)
+    } else {
+        println!("\nPara {}, repita a leitura com --{}.", acao, opcao);
+        false
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn argumentos(linha: &str) -> Argumentos {
+        Argumentos::interpretar(linha.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn sem_terminal_so_muda_com_a_opcao() {
+        let pergunta = "Tirar os achados da lista de extraviados?";
+        let acao = "tirar os achados da lista de extraviados";
+        assert!(!confirmar(
+            &argumentos("inventario"),
+            false,
+            "desmarcar-achados",
+            pergunta,
+            acao
+        ));
+        // A opção de marcar não vale para desmarcar
+        assert!(!confirmar(
+            &argumentos("inventario --marcar-extraviados"),
+            false,
+            "desmarcar-achados",
+            pergunta,
+            acao
+        ));
+        assert!(confirmar(
+            &argumentos("inventario --desmarcar-achados"),
+            false,
+            "desmarcar-achados",
+            pergunta,
+            acao
+        ));
+        // Com a opção, nem pergunta a quem está no terminal
+        assert!(confirmar(
+            &argumentos("inventario --desmarcar-achados"),
+            true,
+            "desmarcar-achados",
+            pergunta,
+            acao
+        ));
+    }
+}
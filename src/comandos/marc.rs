@@ -0,0 +1,103 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::errors::ErroBiblioteca;
+use crate::marc;
+
+// Formato pedido em `--formato` ou, se não houver, deduzido pela extensão do arquivo
+fn formato(
+    argumentos: &Argumentos,
+    arquivo: Option<&str>,
+) -> Result<marc::Formato, ErroBiblioteca> {
+    match argumentos.opcao("formato") {
+        Some(formato) => marc::Formato::interpretar(formato),
+        None => Ok(arquivo.map_or(marc::Formato::MarcXml, marc::Formato::do_arquivo)),
+    }
+}
+
+// `importar marc ARQUIVO`: um livro por registro, tudo ou nada
+pub fn importar(
+    biblioteca: &mut Biblioteca,
+    arquivo: &str,
+    simular: bool,
+    argumentos: &Argumentos,
+) {
+    let preparo: Result<_, ErroBiblioteca> = (|| {
+        let formato = formato(argumentos, Some(arquivo))?;
+        let bytes = std::fs::read(arquivo).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir {}: {}", arquivo, e))
+        })?;
+        let registros = marc::ler(formato, &bytes)?;
+        marc::importar(biblioteca, &registros, simular)
+    })();
+
+    let importacao = match preparo {
+        Ok(importacao) => importacao,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if !importacao.nao_mapeados.is_empty() {
+        println!("Campos sem correspondência no cadastro (ignorados):");
+        for (tag, registros) in &importacao.nao_mapeados {
+            println!("  {} em {} registro(s)", tag, registros);
+        }
+    }
+
+    for erro in &importacao.erros {
+        println!("{}", erro);
+    }
+
+    if !importacao.erros.is_empty() {
+        println!(
+            "\n{} registro(s) com erro, {} válido(s). Nada foi importado.",
+            importacao.erros.len(),
+            importacao.validos
+        );
+        std::process::exit(1);
+    }
+
+    if simular {
+        println!(
+            "Simulação: {} livro(s) seriam importados.",
+            importacao.validos
+        );
+        return;
+    }
+
+    match biblioteca.salvar() {
+        Ok(()) => println!("{} livro(s) importados.", importacao.validos),
+        Err(e) => {
+            eprintln!("Erro ao salvar: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+// `exportar marc`: o acervo inteiro (sem os arquivados), na tela ou em `--saida`
+pub fn exportar(biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let arquivo = argumentos.opcao("saida");
+    let registros = marc::exportar(biblioteca);
+
+    let resultado = formato(argumentos, arquivo)
+        .and_then(|formato| marc::escrever(formato, &registros))
+        .and_then(|bytes| match arquivo {
+            None => {
+                use std::io::Write;
+                std::io::stdout().write_all(&bytes).map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!("Erro ao escrever: {}", e))
+                })
+            }
+            Some(arquivo) => std::fs::write(arquivo, bytes)
+                .map(|()| println!("{} registro(s) gravados em {}", registros.len(), arquivo))
+                .map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar {}: {}", arquivo, e))
+                }),
+        });
+
+    if let Err(e) = resultado {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::biblioteca::Biblioteca;
+use crate::entrada;
+use crate::errors::ErroBiblioteca;
+
+// Mostra o erro de uma operação do menu e diz se o programa deve encerrar
+// (o stdin acabou, então não há mais como perguntar nada ao usuário)
+fn reportar_erro(contexto: &str, erro: ErroBiblioteca) -> bool {
+    match erro {
+        ErroBiblioteca::OperacaoCancelada => {
+            println!("Operação cancelada.");
+            false
+        }
+        ErroBiblioteca::EntradaEncerrada => true,
+        e => {
+            println!("{}: {:?}", contexto, e);
+            false
+        }
+    }
+}
+
+// Menu interativo do console, quando o programa roda sem comando
+pub fn executar(biblioteca: &mut Biblioteca) {
+    loop {
+        println!("\n===== MENU BIBLIOTECA =====");
+        println!("[1] Adicionar livro");
+        println!("[2] Adicionar usuário");
+        println!("[3] Registrar empréstimo");
+        println!("[4] Listar livros");
+        println!("[5] Listar usuários");
+        println!("[6] Listar empréstimos");
+        println!("[7] Editar ou remover cadastros");
+        let pilha = biblioteca.pilha_desfazer();
+        match pilha.proximo_desfazer() {
+            Some(comando) => println!("[8] Desfazer ({})", comando),
+            None => println!("[8] Desfazer"),
+        }
+        match pilha.proximo_refazer() {
+            Some(comando) => println!("[9] Refazer ({})", comando),
+            None => println!("[9] Refazer"),
+        }
+        println!("[10] Salvar e sair");
+        println!("[11] Sair sem salvar");
+        if biblioteca.tem_alteracoes() {
+            println!("(há alterações não salvas)");
+        }
+        println!(
+            "(digite \"{}\" em qualquer pergunta para voltar ao menu)",
+            entrada::PALAVRA_CANCELAR
+        );
+
+        let opcao = match entrada::ler_numero("Escolha uma opção: ", 1..=11) {
+            Ok(opcao) => opcao,
+            Err(ErroBiblioteca::EntradaEncerrada) => {
+                salvar_ao_encerrar(biblioteca);
+                break;
+            }
+            Err(_) => continue,
+        };
+
+        let mut encerrar = false;
+
+        match opcao {
+            1 => {
+                if let Err(e) = biblioteca.adicionar_livro() {
+                    encerrar = reportar_erro("Erro ao adicionar livro", e);
+                }
+            }
+            2 => {
+                if let Err(e) = biblioteca.adicionar_usuario() {
+                    encerrar = reportar_erro("Erro ao adicionar usuário", e);
+                }
+            }
+            3 => {
+                if let Err(e) = biblioteca.registrar_emprestimo() {
+                    encerrar = reportar_erro("Erro ao registrar empréstimo", e);
+                }
+            }
+            4 => biblioteca.listar_livros(),
+            5 => biblioteca.listar_usuarios(),
+            6 => biblioteca.listar_emprestimos(),
+            7 => {
+                if let Err(e) = menu_edicao(biblioteca) {
+                    encerrar = reportar_erro("Erro ao editar", e);
+                }
+            }
+            8 => match biblioteca.desfazer() {
+                Ok(comando) => println!("↩ Desfeito: {}", comando),
+                Err(e) => encerrar = reportar_erro("Erro ao desfazer", e),
+            },
+            9 => match biblioteca.refazer() {
+                Ok(comando) => println!("↪ Refeito: {}", comando),
+                Err(e) => encerrar = reportar_erro("Erro ao refazer", e),
+            },
+            10 => {
+                if let Err(e) = biblioteca.salvar() {
+                    println!("Erro ao salvar biblioteca: {:?}", e);
+                } else {
+                    println!("Biblioteca salva com sucesso. Saindo...");
+                }
+                break;
+            }
+            11 => {
+                if !biblioteca.tem_alteracoes() {
+                    println!("Nada a salvar. Saindo...");
+                    break;
+                }
+                match entrada::ler_confirmacao("Há alterações não salvas. Descartar mesmo assim?")
+                {
+                    Ok(true) => {
+                        biblioteca.descartar_alteracoes();
+                        println!("Alterações descartadas. Saindo...");
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(e) => encerrar = reportar_erro("Erro ao sair", e),
+                }
+            }
+            _ => println!("Opção inválida!"),
+        }
+
+        if encerrar {
+            salvar_ao_encerrar(biblioteca);
+            break;
+        }
+    }
+}
+
+fn menu_edicao(biblioteca: &mut Biblioteca) -> Result<(), ErroBiblioteca> {
+    println!("\n===== EDITAR OU REMOVER =====");
+    println!("[1] Editar livro");
+    println!("[2] Remover livro");
+    println!("[3] Editar usuário");
+    println!("[4] Remover usuário");
+    println!("[5] Editar empréstimo");
+    println!("[6] Remover empréstimo");
+
+    match entrada::ler_numero("Escolha uma opção: ", 1..=6)? {
+        1 => biblioteca.editar_livro(),
+        2 => biblioteca.excluir_livro(),
+        3 => biblioteca.editar_usuario(),
+        4 => biblioteca.excluir_usuario(),
+        5 => biblioteca.editar_emprestimo(),
+        _ => biblioteca.excluir_emprestimo(),
+    }
+}
+
+// Sem stdin não há como perguntar nada; melhor não perder o que foi feito
+fn salvar_ao_encerrar(biblioteca: &mut Biblioteca) {
+    if !biblioteca.tem_alteracoes() {
+        println!("\nEntrada encerrada. Saindo...");
+        return;
+    }
+    match biblioteca.salvar() {
+        Ok(()) => println!("\nEntrada encerrada. Alterações salvas. Saindo..."),
+        Err(e) => println!("\nEntrada encerrada. Erro ao salvar biblioteca: {:?}", e),
+    }
+}
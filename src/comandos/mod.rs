@@ -0,0 +1,46 @@
+// Comandos da linha de comando e o menu do console; cada módulo expõe
+// `executar` (ou uma função por subcomando) e o `main` só despacha.
+
+pub mod ajuda;
+pub mod auditoria;
+pub mod calendario;
+pub mod citar;
+pub mod config;
+pub mod contas;
+pub mod documento;
+pub mod intercambio;
+pub mod inventario;
+pub mod marc;
+pub mod menu;
+pub mod notificar;
+pub mod planilha;
+pub mod reconstruir;
+pub mod relatorio;
+pub mod servidor;
+pub mod unidades;
+pub mod verificar;
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::errors::ErroBiblioteca;
+
+pub fn ler_data(texto: &str) -> Result<NaiveDate, ErroBiblioteca> {
+    NaiveDate::parse_from_str(texto, "%Y-%m-%d").map_err(|_| {
+        ErroBiblioteca::DadosInvalidos(format!("Data inválida: {} (use AAAA-MM-DD).", texto))
+    })
+}
+
+// Início (00:00:00) ou fim (23:59:59) de um dia no formato AAAA-MM-DD
+fn momento_do_dia(texto: &str, fim: bool) -> Option<DateTime<Local>> {
+    let data = NaiveDate::parse_from_str(texto, "%Y-%m-%d").ok()?;
+    let hora = if fim {
+        data.and_hms_opt(23, 59, 59)?
+    } else {
+        data.and_hms_opt(0, 0, 0)?
+    };
+    if fim {
+        hora.and_local_timezone(Local).latest()
+    } else {
+        hora.and_local_timezone(Local).earliest()
+    }
+}
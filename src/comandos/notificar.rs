@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{self, Biblioteca};
+use crate::configuracao::Configuracao;
+use crate::errors::ErroBiblioteca;
+use crate::{documentos, notificacoes};
+
+pub const USO: &str = "notificar --remetente EMAIL [--dias N] [--smtp HOST[:PORTA]] [--smtp-usuario NOME]\n     \
+     [--modelos PASTA] [--cabecalho ARQUIVO] [--simular]";
+
+// Senha do servidor SMTP, para não aparecer na linha de comando do cron
+const VARIAVEL_SENHA_SMTP: &str = "BIBLIOTECA_SMTP_SENHA";
+
+// `notificar`: manda lembretes de devolução e avisos de atraso por SMTP.
+// Feito para o cron: termina com código 1 se algum aviso não pôde ser enviado.
+pub fn executar(biblioteca: &Biblioteca, configuracao: &Configuracao, argumentos: &Argumentos) {
+    if let Err(e) = enviar_avisos(biblioteca, configuracao, argumentos) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn enviar_avisos(
+    biblioteca: &Biblioteca,
+    configuracao: &Configuracao,
+    argumentos: &Argumentos,
+) -> Result<(), ErroBiblioteca> {
+    let caminho_arquivo = configuracao.caminho_dados();
+    let padrao = &configuracao.notificacoes;
+    let dias = match argumentos.opcao("dias").map(str::parse::<u32>) {
+        None => padrao.dias,
+        Some(Ok(dias)) => dias,
+        Some(Err(_)) => {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "--dias precisa de um número de dias (0 ou mais).".to_string(),
+            ));
+        }
+    };
+    let simular = argumentos.tem_opcao("simular");
+
+    // Na simulação nada sai, então o remetente pode ficar de fora
+    let remetente = match argumentos
+        .opcao("remetente")
+        .or(padrao.remetente.as_deref())
+    {
+        Some(texto) => biblioteca::usuarios::Usuario::validar_email(texto)?,
+        None if simular => Some("biblioteca@localhost".to_string()),
+        None => None,
+    }
+    .ok_or_else(|| {
+        ErroBiblioteca::DadosInvalidos(
+            "Informe --remetente EMAIL (ou notificacoes.remetente na configuração).".to_string(),
+        )
+    })?;
+    let mut smtp = padrao.smtp()?;
+    if let Some(servidor) = argumentos.opcao("smtp") {
+        smtp.definir_servidor(servidor)?;
+    }
+    if let Some(usuario) = argumentos.opcao("smtp-usuario") {
+        smtp.usuario = Some(usuario.to_string());
+    }
+    smtp.senha = std::env::var(VARIAVEL_SENHA_SMTP).ok();
+
+    let modelos = match argumentos.opcao("modelos").map(Path::new) {
+        Some(pasta) => notificacoes::Modelos::ler(pasta)?,
+        None => match &padrao.modelos {
+            Some(pasta) => notificacoes::Modelos::ler(pasta)?,
+            None => notificacoes::Modelos::padrao(),
+        },
+    };
+    let cabecalho = match argumentos.opcao("cabecalho") {
+        Some(arquivo) => documentos::Cabecalho::ler(Path::new(arquivo))?,
+        None => configuracao.cabecalho(),
+    };
+
+    // A simulação não envia nem anota nada, então pode rodar junto com o cron
+    let _trava = if simular {
+        None
+    } else {
+        Some(notificacoes::Trava::obter(caminho_arquivo)?)
+    };
+    let mut registro = notificacoes::RegistroAvisos::carregar(caminho_arquivo)?;
+
+    let hoje = biblioteca.hoje();
+    let pendencias = notificacoes::pendencias(biblioteca, &registro, hoje, dias);
+    for (nome, quantidade) in &pendencias.sem_email {
+        println!(
+            "Sem e-mail cadastrado: {} ({} empréstimo(s) não avisado(s))",
+            nome, quantidade
+        );
+    }
+    if pendencias.avisos.is_empty() {
+        println!("Nenhum aviso a enviar.");
+        return Ok(());
+    }
+
+    if simular {
+        for aviso in &pendencias.avisos {
+            let (assunto, corpo) = notificacoes::compor(aviso, &modelos, &cabecalho.nome, hoje);
+            println!(
+                "Para: {} <{}>\nAssunto: {}\n\n{}\n",
+                aviso.nome,
+                aviso.email,
+                assunto,
+                corpo.trim_end()
+            );
+        }
+        println!(
+            "{} aviso(s) seriam enviados (simulação, nada foi enviado).",
+            pendencias.avisos.len()
+        );
+        return Ok(());
+    }
+
+    let dominio = remetente.rsplit_once('@').map_or("localhost", |(_, d)| d);
+    let mut cliente = notificacoes::smtp::ClienteSmtp::conectar(&smtp, dominio)?;
+    let mut enviados = 0;
+    let mut falhas = 0;
+    for aviso in &pendencias.avisos {
+        let (assunto, corpo) = notificacoes::compor(aviso, &modelos, &cabecalho.nome, hoje);
+        let mensagem = notificacoes::mensagem(
+            &remetente,
+            &cabecalho.nome,
+            &aviso.email,
+            &assunto,
+            &corpo,
+            biblioteca.agora(),
+        );
+        match cliente.enviar(&remetente, &aviso.email, &mensagem) {
+            Ok(()) => {
+                registro.anotar(aviso, biblioteca.agora())?;
+                enviados += 1;
+                println!(
+                    "Enviado para {} <{}>: {} livro(s)",
+                    aviso.nome,
+                    aviso.email,
+                    aviso.itens.len()
+                );
+            }
+            Err(e) => {
+                falhas += 1;
+                eprintln!("Falha ao avisar {} <{}>: {}", aviso.nome, aviso.email, e);
+                cliente.reiniciar()?;
+            }
+        }
+    }
+    cliente.encerrar();
+
+    println!("{} aviso(s) enviado(s).", enviados);
+    if falhas > 0 {
+        return Err(ErroBiblioteca::ErroEnvio(format!(
+            "{} aviso(s) não foram enviados; serão tentados de novo na próxima execução.",
+            falhas
+        )));
+    }
+    Ok(())
+}
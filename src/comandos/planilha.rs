@@ -0,0 +1,121 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::errors::ErroBiblioteca;
+use crate::planilha;
+
+use super::marc;
+
+pub const USO_IMPORTAR: &str = "importar livros|usuarios ARQUIVO [--colunas campo=Coluna,...] \
+     [--delimitador C] [--simular]\n     \
+     importar marc ARQUIVO [--formato iso2709|marcxml] [--simular]";
+
+pub const USO_EXPORTAR: &str = "exportar livros|usuarios|emprestimos [--saida ARQUIVO] [--delimitador C] [--bom]\n     \
+     exportar marc [--saida ARQUIVO] [--formato iso2709|marcxml]";
+
+fn delimitador(argumentos: &Argumentos) -> Result<u8, ErroBiblioteca> {
+    argumentos
+        .opcao("delimitador")
+        .map_or(Ok(b','), planilha::interpretar_delimitador)
+}
+
+// `importar livros|usuarios ARQUIVO`: tudo ou nada; com `--simular`, só valida
+pub fn importar(biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let (Some(entidade), Some(arquivo)) =
+        (argumentos.posicionais.get(1), argumentos.posicionais.get(2))
+    else {
+        eprintln!("Uso: {}", USO_IMPORTAR);
+        std::process::exit(2);
+    };
+    let simular = argumentos.tem_opcao("simular");
+    if entidade == "marc" {
+        return marc::importar(biblioteca, arquivo, simular, argumentos);
+    }
+
+    let preparo: Result<_, ErroBiblioteca> = (|| {
+        let entidade = planilha::Entidade::interpretar(entidade)?;
+        let mapa = planilha::interpretar_mapa(argumentos.opcao("colunas").unwrap_or_default())?;
+        let delimitador = delimitador(argumentos)?;
+        let leitor = std::fs::File::open(arquivo).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir {}: {}", arquivo, e))
+        })?;
+        let importacao =
+            planilha::importar(biblioteca, entidade, leitor, &mapa, delimitador, simular)?;
+        Ok((entidade, importacao))
+    })();
+
+    let (entidade, importacao) = match preparo {
+        Ok(resultado) => resultado,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    for erro in &importacao.erros {
+        println!("{}", erro);
+    }
+
+    if !importacao.erros.is_empty() {
+        println!(
+            "\n{} linha(s) com erro, {} válida(s). Nada foi importado.",
+            importacao.erros.len(),
+            importacao.validas
+        );
+        std::process::exit(1);
+    }
+
+    if simular {
+        println!(
+            "Simulação: {} {} seriam importados.",
+            importacao.validas, entidade
+        );
+        return;
+    }
+
+    match biblioteca.salvar() {
+        Ok(()) => println!("{} {} importados.", importacao.validas, entidade),
+        Err(e) => {
+            eprintln!("Erro ao salvar: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+// `exportar livros|usuarios|emprestimos`, na tela ou em `--saida`
+pub fn exportar(biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let Some(entidade) = argumentos.posicionais.get(1) else {
+        eprintln!("Uso: {}", USO_EXPORTAR);
+        std::process::exit(2);
+    };
+    if entidade == "marc" {
+        return marc::exportar(biblioteca, argumentos);
+    }
+    let bom = argumentos.tem_opcao("bom");
+
+    let resultado = planilha::Entidade::interpretar(entidade).and_then(|entidade| {
+        let delimitador = delimitador(argumentos)?;
+        match argumentos.opcao("saida") {
+            None => planilha::exportar(
+                biblioteca,
+                entidade,
+                std::io::stdout().lock(),
+                delimitador,
+                bom,
+            )
+            .map(|_| ()),
+            Some(arquivo) => {
+                let escritor = std::fs::File::create(arquivo).map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!("Erro ao criar {}: {}", arquivo, e))
+                })?;
+                let linhas = planilha::exportar(biblioteca, entidade, escritor, delimitador, bom)?;
+                println!("{} {} gravados em {}", linhas, entidade, arquivo);
+                Ok(())
+            }
+        }
+    });
+
+    if let Err(e) = resultado {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+}
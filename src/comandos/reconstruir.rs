@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+
+use super::momento_do_dia;
+
+pub const USO: &str = "reconstruir AAAA-MM-DD";
+
+// Mostra o que estava emprestado no fim do dia pedido, refazendo o diário de eventos
+pub fn executar(caminho_arquivo: &Path, biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let Some(data) = argumentos.posicionais.get(1) else {
+        eprintln!("Informe a data no formato AAAA-MM-DD, ex.: reconstruir 2025-11-06");
+        return;
+    };
+
+    let Some(fim_do_dia) = momento_do_dia(data, true) else {
+        eprintln!("Data inválida: {}", data);
+        return;
+    };
+
+    match biblioteca.inicio_do_historico() {
+        Ok(Some(inicio)) if inicio > fim_do_dia => {
+            println!(
+                "O histórico só começa em {}; não há como saber o estado de {}.",
+                inicio.format("%Y-%m-%d %H:%M"),
+                data
+            );
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    }
+
+    match Biblioteca::reconstruir_em(caminho_arquivo, fim_do_dia) {
+        Ok(passada) => {
+            println!("Situação em {}:", data);
+            passada.listar_emprestimos_ativos();
+        }
+        Err(e) => eprintln!("Erro ao reconstruir: {}", e),
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+use crate::relatorios;
+
+pub const USO: &str = "relatorio [por-mes|titulos|autores|leitores|duracao|pontualidade|unidades|todos]\n     \
+     [--de AAAA-MM-DD] [--ate AAAA-MM-DD] [--limite N] [--formato texto|csv|json] \
+     [--saida ARQUIVO]";
+
+// `relatorio [NOME]`: estatísticas de circulação no período, na tela ou em `--saida`
+pub fn executar(biblioteca: &Biblioteca, argumentos: &Argumentos) {
+    let mut periodo = relatorios::Periodo::default();
+    for opcao in ["de", "ate"] {
+        if let Some(texto) = argumentos.opcao(opcao) {
+            let Ok(data) = NaiveDate::parse_from_str(texto, "%Y-%m-%d") else {
+                eprintln!("--{} precisa de uma data no formato AAAA-MM-DD", opcao);
+                return;
+            };
+            if opcao == "de" {
+                periodo.de = Some(data);
+            } else {
+                periodo.ate = Some(data);
+            }
+        }
+    }
+
+    let limite = match argumentos.opcao("limite").map(str::parse::<usize>) {
+        None => 10,
+        Some(Ok(limite)) if limite > 0 => limite,
+        Some(_) => {
+            eprintln!("--limite precisa de um número maior que zero");
+            return;
+        }
+    };
+
+    let preparo = relatorios::Relatorio::interpretar(
+        argumentos
+            .posicionais
+            .get(1)
+            .map_or("todos", String::as_str),
+    )
+    .and_then(|escolhidos| {
+        let formato = relatorios::FormatoRelatorio::interpretar(
+            argumentos.opcao("formato").unwrap_or("texto"),
+        )?;
+        let parametros = relatorios::Parametros {
+            periodo,
+            limite,
+            hoje: biblioteca.hoje(),
+        };
+        let tabelas: Vec<_> = escolhidos
+            .into_iter()
+            .map(|relatorio| relatorios::gerar(biblioteca, relatorio, &parametros))
+            .collect();
+        relatorios::formatar(&tabelas, &periodo, formato)
+    });
+
+    let texto = match preparo {
+        Ok(texto) => texto,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    match argumentos.opcao("saida") {
+        None => print!("{}", texto),
+        Some(arquivo) => match std::fs::write(arquivo, texto) {
+            Ok(()) => println!("Relatório gravado em {}", arquivo),
+            Err(e) => eprintln!("Erro ao gravar {}: {}", arquivo, e),
+        },
+    }
+}
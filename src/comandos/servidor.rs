@@ -0,0 +1,56 @@
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{Biblioteca, contas::Contas};
+use crate::configuracao::Configuracao;
+use crate::{oai, servidor};
+
+pub const USO: &str =
+    "serve [--porta N] [--oai-nome NOME] [--oai-email EMAIL] [--oai-dominio DOMINIO]";
+
+// Só escuta em localhost; `--porta 0` deixa o sistema escolher uma porta livre
+pub fn executar(
+    biblioteca: &mut Biblioteca,
+    contas: &mut Contas,
+    configuracao: &Configuracao,
+    argumentos: &Argumentos,
+) {
+    let porta = match argumentos.opcao("porta").map(str::parse::<u16>) {
+        None => configuracao.servidor.porta,
+        Some(Ok(porta)) => porta,
+        Some(Err(_)) => {
+            eprintln!("--porta precisa de um número entre 0 e 65535");
+            return;
+        }
+    };
+
+    let padrao = configuracao.repositorio();
+    let repositorio = oai::Repositorio {
+        nome: argumentos
+            .opcao("oai-nome")
+            .map_or(padrao.nome, str::to_string),
+        email: argumentos
+            .opcao("oai-email")
+            .map_or(padrao.email, str::to_string),
+        dominio: argumentos
+            .opcao("oai-dominio")
+            .map_or(padrao.dominio, str::to_string),
+    };
+
+    let servidor = match servidor::Servidor::iniciar(&format!("127.0.0.1:{}", porta)) {
+        Ok(s) => s.com_repositorio(repositorio),
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    println!("Servidor ouvindo em http://{}", servidor.endereco());
+    println!(
+        "Descrição da API em http://{}/openapi.json",
+        servidor.endereco()
+    );
+    println!(
+        "Catálogo OAI-PMH (oai_dc) em http://{}/oai",
+        servidor.endereco()
+    );
+    servidor.atender(biblioteca, contas);
+}
@@ -0,0 +1,158 @@
+use uuid::Uuid;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::{self, Biblioteca};
+use crate::errors::ErroBiblioteca;
+
+pub const USO: &str = "unidades [listar]\n     \
+     unidades criar SIGLA --nome NOME\n     \
+     unidades renomear SIGLA --nome NOME\n     \
+     unidades remover SIGLA\n     \
+     unidades acervo SIGLA\n     \
+     unidades transito [SIGLA]\n     \
+     unidades receber ID_LIVRO\n     \
+     unidades transferir ID_LIVRO SIGLA";
+
+// `unidades ...`: filiais que dividem o catálogo e os exemplares entre elas
+pub fn executar(biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let posicional = |i: usize| argumentos.posicionais.get(i).map(String::as_str);
+    let id_livro = |texto: &str| {
+        Uuid::parse_str(texto).map_err(|_| {
+            ErroBiblioteca::DadosInvalidos(format!("UUID de livro inválido: {}", texto))
+        })
+    };
+    let nome = || {
+        argumentos
+            .opcao("nome")
+            .map(str::to_string)
+            .ok_or_else(|| ErroBiblioteca::DadosInvalidos("Informe --nome NOME.".to_string()))
+    };
+
+    let alteracao: Result<String, ErroBiblioteca> = match (posicional(1), posicional(2)) {
+        (None | Some("listar"), _) => {
+            listar_unidades(biblioteca);
+            return;
+        }
+        (Some("acervo"), Some(sigla)) => {
+            match biblioteca.unidade(sigla).map(|u| u.id) {
+                Ok(id) => listar_exemplares(biblioteca, |l| l.get_unidade() == Some(id)),
+                Err(e) => eprintln!("{}", e),
+            }
+            return;
+        }
+        (Some("transito"), sigla) => {
+            let destino = match sigla.map(|s| biblioteca.unidade(s).map(|u| u.id)) {
+                None => None,
+                Some(Ok(id)) => Some(id),
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            listar_exemplares(biblioteca, |l| {
+                l.em_transito().is_some() && destino.is_none_or(|d| l.get_unidade() == Some(d))
+            });
+            return;
+        }
+        (Some("criar"), Some(sigla)) => nome()
+            .and_then(|nome| biblioteca.cadastrar_unidade(sigla, nome))
+            .map(|_| "Unidade cadastrada.".to_string()),
+        (Some("renomear"), Some(sigla)) => biblioteca
+            .unidade(sigla)
+            .map(|u| u.id)
+            .and_then(|id| nome().and_then(|nome| biblioteca.renomear_unidade(id, nome)))
+            .map(|()| "Unidade renomeada.".to_string()),
+        (Some("remover"), Some(sigla)) => biblioteca
+            .unidade(sigla)
+            .map(|u| u.id)
+            .and_then(|id| biblioteca.remover_unidade(id))
+            .map(|()| "Unidade removida.".to_string()),
+        (Some("receber"), Some(texto)) => id_livro(texto)
+            .and_then(|id| biblioteca.receber_livro(id))
+            .map(|()| "Exemplar recebido; já pode circular.".to_string()),
+        (Some("transferir"), Some(texto)) => (|| {
+            let id = id_livro(texto)?;
+            let sigla = posicional(3).ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos("Informe a sigla da unidade de destino.".to_string())
+            })?;
+            let destino = biblioteca.unidade(sigla)?.id;
+            biblioteca.transferir_livro(id, destino)?;
+            let livro = &biblioteca.livros()[&id];
+            Ok(if livro.em_transito().is_some() {
+                "Exemplar transferido; fica em trânsito até ser recebido.".to_string()
+            } else {
+                "Exemplar transferido.".to_string()
+            })
+        })(),
+        _ => Err(ErroBiblioteca::DadosInvalidos(format!("Uso: {}", USO))),
+    };
+
+    match alteracao {
+        Ok(mensagem) => match biblioteca.salvar() {
+            Ok(()) => println!("{}", mensagem),
+            Err(e) => {
+                eprintln!("Erro ao salvar: {}", e);
+                std::process::exit(2);
+            }
+        },
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn listar_unidades(biblioteca: &Biblioteca) {
+    let mut unidades: Vec<_> = biblioteca.unidades().values().collect();
+    if unidades.is_empty() {
+        println!("Nenhuma unidade cadastrada.");
+        return;
+    }
+    unidades.sort_by(|a, b| a.sigla.cmp(&b.sigla));
+    for unidade in unidades {
+        let exemplares = biblioteca
+            .livros()
+            .values()
+            .filter(|l| l.get_unidade() == Some(unidade.id))
+            .count();
+        let atual = if biblioteca.unidade_atual() == Some(unidade.id) {
+            " [atual]"
+        } else {
+            ""
+        };
+        println!(
+            "{:<12} {} — {} exemplar(es){}",
+            unidade.sigla, unidade.nome, exemplares, atual
+        );
+    }
+}
+
+// Exemplares com a situação de cada um: na estante, emprestado ou em trânsito
+fn listar_exemplares(biblioteca: &Biblioteca, filtro: impl Fn(&biblioteca::livros::Livro) -> bool) {
+    let sigla = |id: Option<Uuid>| {
+        id.and_then(|id| biblioteca.unidades().get(&id))
+            .map_or("?".to_string(), |u| u.sigla.clone())
+    };
+    let mut livros: Vec<_> = biblioteca
+        .livros()
+        .iter()
+        .filter(|(_, livro)| filtro(livro))
+        .collect();
+    if livros.is_empty() {
+        println!("Nenhum exemplar.");
+        return;
+    }
+    livros.sort_by(|(_, a), (_, b)| a.get_titulo().cmp(b.get_titulo()));
+    for (id, livro) in livros {
+        let situacao = if let Some(desde) = livro.em_transito() {
+            format!(
+                "em trânsito de {} para {} desde {}",
+                sigla(livro.get_local()),
+                sigla(livro.get_unidade()),
+                desde.format("%d/%m/%Y")
+            )
+        } else if livro.esta_disponivel() {
+            "na estante".to_string()
+        } else {
+            "emprestado".to_string()
+        };
+        println!("{}  {} — {}", id, livro.get_titulo(), situacao);
+    }
+}
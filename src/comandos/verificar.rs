@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use crate::argumentos::Argumentos;
+use crate::biblioteca::Biblioteca;
+
+pub const USO: &str = "verificar [ARQUIVO] [--reparar] [--simular]";
+
+// Lista os problemas de integridade e, com `--reparar`, corrige o que der.
+// `--simular` mostra o diff do reparo sem gravar nada.
+pub fn executar(caminho_arquivo: &Path, biblioteca: &mut Biblioteca, argumentos: &Argumentos) {
+    let mut outra;
+    let biblioteca = match argumentos.posicionais.get(1) {
+        Some(arquivo) if Path::new(arquivo) != caminho_arquivo => {
+            outra = match Biblioteca::carregar(arquivo) {
+                Ok(outra) => outra,
+                Err(e) => {
+                    eprintln!("Erro ao carregar {}: {}", arquivo, e);
+                    std::process::exit(2);
+                }
+            };
+            if let Some(sessao) = biblioteca.sessao() {
+                outra.entrar(sessao.clone());
+            }
+            &mut outra
+        }
+        _ => biblioteca,
+    };
+
+    let reparar = argumentos.tem_opcao("reparar") || argumentos.tem_opcao("simular");
+    if !reparar {
+        let problemas = biblioteca.verificar();
+        if problemas.is_empty() {
+            println!("Nenhum problema encontrado.");
+            return;
+        }
+        for problema in &problemas {
+            println!("{}", problema);
+        }
+        println!("\n{} problema(s) encontrado(s).", problemas.len());
+        std::process::exit(1);
+    }
+
+    let simular = argumentos.tem_opcao("simular");
+    let reparo = match biblioteca.reparar(simular) {
+        Ok(reparo) => reparo,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    for problema in &reparo.problemas {
+        println!("{}", problema);
+    }
+    if reparo.diferencas.is_empty() {
+        println!("\nNada a reparar.");
+    } else {
+        println!(
+            "\n=== Alterações{} ===",
+            if simular { " (simulação)" } else { "" }
+        );
+        for linha in &reparo.diferencas {
+            println!("{}", linha);
+        }
+    }
+
+    if !simular && !reparo.diferencas.is_empty() {
+        match biblioteca.salvar() {
+            Ok(()) => println!("\nReparo gravado."),
+            Err(e) => {
+                eprintln!("Erro ao salvar: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // Sobrou algo que só alguém pode resolver
+    if !biblioteca.verificar().is_empty() && !simular {
+        std::process::exit(1);
+    }
+}
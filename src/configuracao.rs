@@ -0,0 +1,302 @@
+// Configuração da biblioteca em TOML: onde ficam os dados, as regras de
+// empréstimo, o calendário inicial, os avisos por e-mail e o servidor.
+// O arquivo é procurado em `--config ARQUIVO`, senão em ./biblioteca.toml e
+// depois em $XDG_CONFIG_HOME/biblioteca/biblioteca.toml (~/.config se a variável
+// não existir). Sem nenhum deles, valem os padrões. Opções da linha de comando
+// continuam tendo a palavra final.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::biblioteca::{
+    autosalvamento::ModoAutosalvamento, calendario::Calendario, politicas::Politicas,
+    usuarios::Usuario,
+};
+use crate::documentos::Cabecalho;
+use crate::errors::ErroBiblioteca;
+use crate::notificacoes::smtp::ConfiguracaoSmtp;
+use crate::oai::Repositorio;
+
+const NOME_ARQUIVO: &str = "biblioteca.toml";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecaoBiblioteca {
+    pub nome: String,
+    // Endereço, telefone, horário... embaixo do nome nos documentos
+    pub linhas: Vec<String>,
+    // Sigla da unidade deste computador, quando há mais de uma (ou `--unidade`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unidade: Option<String>,
+}
+
+impl Default for SecaoBiblioteca {
+    fn default() -> Self {
+        let cabecalho = Cabecalho::default();
+        SecaoBiblioteca {
+            nome: cabecalho.nome,
+            linhas: cabecalho.linhas,
+            unidade: None,
+        }
+    }
+}
+
+// Por enquanto só existe o arquivo JSON com o diário ao lado
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Armazenamento {
+    pub backend: Backend,
+    pub arquivo: PathBuf,
+    // "sempre", "nunca" ou um intervalo em segundos, como em `--autosalvar`
+    pub autosalvar: String,
+}
+
+impl Default for Armazenamento {
+    fn default() -> Self {
+        Armazenamento {
+            backend: Backend::Json,
+            arquivo: PathBuf::from("dados_biblioteca.json"),
+            autosalvar: "60".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notificacoes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remetente: Option<String>,
+    // HOST ou HOST:PORTA; a senha fica só na variável de ambiente
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smtp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smtp_usuario: Option<String>,
+    // Antecedência do lembrete de devolução
+    pub dias: u32,
+    // Pasta com lembrete.txt e atraso.txt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modelos: Option<PathBuf>,
+}
+
+impl Default for Notificacoes {
+    fn default() -> Self {
+        Notificacoes {
+            remetente: None,
+            smtp: None,
+            smtp_usuario: None,
+            dias: 3,
+            modelos: None,
+        }
+    }
+}
+
+impl Notificacoes {
+    pub fn smtp(&self) -> Result<ConfiguracaoSmtp, ErroBiblioteca> {
+        let mut smtp = ConfiguracaoSmtp::default();
+        if let Some(servidor) = &self.smtp {
+            smtp.definir_servidor(servidor)?;
+        }
+        smtp.usuario = self.smtp_usuario.clone();
+        Ok(smtp)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Servidor {
+    pub porta: u16,
+}
+
+impl Default for Servidor {
+    fn default() -> Self {
+        Servidor { porta: 8080 }
+    }
+}
+
+// Sem nome próprio, o repositório OAI usa o nome da biblioteca
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Oai {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nome: Option<String>,
+    pub email: String,
+    pub dominio: String,
+}
+
+impl Default for Oai {
+    fn default() -> Self {
+        let padrao = Repositorio::default();
+        Oai {
+            nome: None,
+            email: padrao.email,
+            dominio: padrao.dominio,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Configuracao {
+    pub biblioteca: SecaoBiblioteca,
+    pub armazenamento: Armazenamento,
+    pub emprestimos: Politicas,
+    // Só cria o calendário ao lado dos dados, na primeira vez; depois ele é
+    // alterado pelo comando `calendario` e esta seção deixa de valer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calendario: Option<Calendario>,
+    pub notificacoes: Notificacoes,
+    pub servidor: Servidor,
+    pub oai: Oai,
+    // De onde a configuração veio (nenhum arquivo: só padrões)
+    #[serde(skip)]
+    pub origem: Option<PathBuf>,
+}
+
+impl Configuracao {
+    // `explicito` vem de `--config`; nesse caso o arquivo tem que existir
+    pub fn localizar(explicito: Option<&str>) -> Result<Option<PathBuf>, ErroBiblioteca> {
+        if let Some(caminho) = explicito {
+            let caminho = PathBuf::from(caminho);
+            if !caminho.is_file() {
+                return Err(ErroBiblioteca::DadosInvalidos(format!(
+                    "Arquivo de configuração não encontrado: {}",
+                    caminho.display()
+                )));
+            }
+            return Ok(Some(caminho));
+        }
+
+        let local = PathBuf::from(NOME_ARQUIVO);
+        if local.is_file() {
+            return Ok(Some(local));
+        }
+
+        let pasta_xdg = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|pasta| !pasta.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|casa| PathBuf::from(casa).join(".config")));
+        Ok(pasta_xdg
+            .map(|pasta| pasta.join("biblioteca").join(NOME_ARQUIVO))
+            .filter(|caminho| caminho.is_file()))
+    }
+
+    pub fn carregar(explicito: Option<&str>) -> Result<Self, ErroBiblioteca> {
+        match Self::localizar(explicito)? {
+            Some(caminho) => Self::ler(&caminho),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn ler(caminho: &Path) -> Result<Self, ErroBiblioteca> {
+        let erro = |mensagem: String| {
+            ErroBiblioteca::DadosInvalidos(format!(
+                "Configuração {}: {}",
+                caminho.display(),
+                mensagem
+            ))
+        };
+        let texto = std::fs::read_to_string(caminho).map_err(|e| erro(e.to_string()))?;
+        let mut configuracao: Configuracao =
+            toml::from_str(&texto).map_err(|e| erro(e.to_string().trim_end().to_string()))?;
+        configuracao.validar().map_err(|e| match e {
+            ErroBiblioteca::DadosInvalidos(mensagem) => erro(mensagem),
+            outro => outro,
+        })?;
+
+        // Caminhos relativos valem a partir da pasta do arquivo de configuração
+        if let Some(pasta) = caminho.parent() {
+            configuracao.armazenamento.arquivo = pasta.join(&configuracao.armazenamento.arquivo);
+            if let Some(modelos) = &configuracao.notificacoes.modelos {
+                configuracao.notificacoes.modelos = Some(pasta.join(modelos));
+            }
+        }
+        if let Some(pasta) = configuracao.armazenamento.arquivo.parent()
+            && !pasta.as_os_str().is_empty()
+            && !pasta.is_dir()
+        {
+            return Err(erro(format!(
+                "a pasta de armazenamento.arquivo não existe: {}",
+                pasta.display()
+            )));
+        }
+        configuracao.origem = Some(caminho.to_path_buf());
+        Ok(configuracao)
+    }
+
+    pub fn validar(&self) -> Result<(), ErroBiblioteca> {
+        let secao = |nome: &str, e: ErroBiblioteca| match e {
+            ErroBiblioteca::DadosInvalidos(mensagem) => {
+                ErroBiblioteca::DadosInvalidos(format!("[{}] {}", nome, mensagem))
+            }
+            outro => outro,
+        };
+
+        if self.biblioteca.nome.trim().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "biblioteca.nome não pode ficar vazio.".to_string(),
+            ));
+        }
+        if self.armazenamento.arquivo.as_os_str().is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "armazenamento.arquivo não pode ficar vazio.".to_string(),
+            ));
+        }
+        ModoAutosalvamento::interpretar(&self.armazenamento.autosalvar)
+            .map_err(|e| secao("armazenamento", e))?;
+        self.emprestimos.validar()?;
+        if let Some(calendario) = &self.calendario {
+            calendario.validar().map_err(|e| secao("calendario", e))?;
+        }
+        if let Some(remetente) = &self.notificacoes.remetente {
+            Usuario::validar_email(remetente).map_err(|e| secao("notificacoes", e))?;
+        }
+        self.notificacoes
+            .smtp()
+            .map_err(|e| secao("notificacoes", e))?;
+        if self.oai.dominio.trim().is_empty() || self.oai.dominio.contains(':') {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "oai.dominio inválido: \"{}\" (use algo como biblioteca.exemplo.org).",
+                self.oai.dominio
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn caminho_dados(&self) -> &Path {
+        &self.armazenamento.arquivo
+    }
+
+    pub fn cabecalho(&self) -> Cabecalho {
+        Cabecalho {
+            nome: self.biblioteca.nome.clone(),
+            linhas: self.biblioteca.linhas.clone(),
+        }
+    }
+
+    pub fn repositorio(&self) -> Repositorio {
+        Repositorio {
+            nome: self
+                .oai
+                .nome
+                .clone()
+                .unwrap_or_else(|| self.biblioteca.nome.clone()),
+            email: self.oai.email.clone(),
+            dominio: self.oai.dominio.clone(),
+        }
+    }
+
+    // Para `config show`: o arquivo que estaria em vigor, em TOML
+    pub fn em_toml(&self) -> Result<String, ErroBiblioteca> {
+        toml::to_string_pretty(self).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao mostrar a configuração: {}", e))
+        })
+    }
+}
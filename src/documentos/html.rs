@@ -0,0 +1,81 @@
+// Documentos em HTML, prontos para imprimir pelo navegador. O modelo padrão pode
+// ser trocado por outro arquivo com os mesmos marcadores: {{cabecalho}},
+// {{titulo}}, {{conteudo}} e {{emitido_em}} (só {{conteudo}} é obrigatório).
+
+use quick_xml::escape::escape;
+
+use crate::errors::ErroBiblioteca;
+
+use super::{Bloco, Cabecalho, Documento};
+
+pub const MODELO_PADRAO: &str = include_str!("modelo.html");
+
+pub fn validar_modelo(modelo: &str) -> Result<(), ErroBiblioteca> {
+    if modelo.contains("{{conteudo}}") {
+        Ok(())
+    } else {
+        Err(ErroBiblioteca::DadosInvalidos(
+            "O modelo HTML precisa ter o marcador {{conteudo}}.".to_string(),
+        ))
+    }
+}
+
+fn cabecalho(cabecalho: &Cabecalho) -> String {
+    let mut html = format!("<header>\n  <h1>{}</h1>\n", escape(&cabecalho.nome));
+    for linha in &cabecalho.linhas {
+        html.push_str(&format!("  <p>{}</p>\n", escape(linha)));
+    }
+    html.push_str("</header>");
+    html
+}
+
+fn bloco(bloco: &Bloco) -> String {
+    match bloco {
+        Bloco::Paragrafo(texto) => format!("<p>{}</p>\n", escape(texto)),
+        Bloco::Campos(campos) => {
+            let mut html = String::from("<dl>\n");
+            for campo in campos {
+                html.push_str(&format!(
+                    "  <dt>{}</dt><dd{}>{}</dd>\n",
+                    escape(campo.rotulo),
+                    if campo.destaque {
+                        " class=\"destaque\""
+                    } else {
+                        ""
+                    },
+                    escape(&campo.valor)
+                ));
+            }
+            html.push_str("</dl>\n");
+            html
+        }
+        Bloco::Tabela { colunas, linhas } => {
+            let mut html = String::from("<table>\n  <thead><tr>");
+            for coluna in colunas {
+                html.push_str(&format!("<th>{}</th>", escape(*coluna)));
+            }
+            html.push_str("</tr></thead>\n  <tbody>\n");
+            for linha in linhas {
+                html.push_str("    <tr>");
+                for celula in linha {
+                    html.push_str(&format!("<td>{}</td>", escape(celula)));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("  </tbody>\n</table>\n");
+            html
+        }
+    }
+}
+
+pub fn renderizar(documento: &Documento, cabecalho_biblioteca: &Cabecalho, modelo: &str) -> String {
+    let conteudo: String = documento.blocos.iter().map(bloco).collect();
+    modelo
+        .replace("{{cabecalho}}", &cabecalho(cabecalho_biblioteca))
+        .replace("{{titulo}}", &escape(&documento.titulo))
+        .replace(
+            "{{emitido_em}}",
+            &documento.emitido_em.format("%d/%m/%Y %H:%M").to_string(),
+        )
+        .replace("{{conteudo}}", conteudo.trim_end())
+}
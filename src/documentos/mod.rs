@@ -0,0 +1,603 @@
+// Documentos para imprimir: recibos de empréstimo e de devolução, a lista de
+// atrasados e o inventário do acervo. Cada documento é montado uma vez como uma
+// sequência de blocos e depois desenhado em HTML (por um modelo que a
+// biblioteca pode trocar) ou em PDF, sempre com o cabeçalho da biblioteca.
+
+pub mod html;
+pub mod pdf;
+
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDate};
+use uuid::Uuid;
+
+use crate::biblioteca::{Biblioteca, emprestimos::Emprestimo};
+use crate::errors::ErroBiblioteca;
+use crate::traits::Identificavel;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TipoDocumento {
+    ReciboEmprestimo,
+    ReciboDevolucao,
+    Atrasados,
+    Inventario,
+}
+
+impl TipoDocumento {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "recibo-emprestimo" => Ok(TipoDocumento::ReciboEmprestimo),
+            "recibo-devolucao" => Ok(TipoDocumento::ReciboDevolucao),
+            "atrasados" => Ok(TipoDocumento::Atrasados),
+            "inventario" => Ok(TipoDocumento::Inventario),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Documento desconhecido: {} (use recibo-emprestimo, recibo-devolucao, atrasados ou inventario).",
+                outro
+            ))),
+        }
+    }
+
+    // Os recibos são de um empréstimo específico
+    pub fn precisa_de_emprestimo(&self) -> bool {
+        matches!(
+            self,
+            TipoDocumento::ReciboEmprestimo | TipoDocumento::ReciboDevolucao
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatoDocumento {
+    Html,
+    Pdf,
+}
+
+impl FormatoDocumento {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "html" => Ok(FormatoDocumento::Html),
+            "pdf" => Ok(FormatoDocumento::Pdf),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Formato desconhecido: {} (use html ou pdf).",
+                outro
+            ))),
+        }
+    }
+}
+
+// Nome da biblioteca e linhas de apoio (endereço, telefone, horário...)
+#[derive(Debug, Clone)]
+pub struct Cabecalho {
+    pub nome: String,
+    pub linhas: Vec<String>,
+}
+
+impl Default for Cabecalho {
+    fn default() -> Self {
+        Cabecalho {
+            nome: "Biblioteca".to_string(),
+            linhas: Vec::new(),
+        }
+    }
+}
+
+impl Cabecalho {
+    // Arquivo de texto: a primeira linha é o nome, as outras vão embaixo dele
+    pub fn ler(caminho: &Path) -> Result<Self, ErroBiblioteca> {
+        let texto = std::fs::read_to_string(caminho).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!(
+                "Erro ao ler cabeçalho {}: {}",
+                caminho.display(),
+                e
+            ))
+        })?;
+        let mut linhas = texto
+            .lines()
+            .map(str::trim)
+            .filter(|linha| !linha.is_empty())
+            .map(str::to_string);
+        let nome = linhas.next().ok_or_else(|| {
+            ErroBiblioteca::DadosInvalidos(format!(
+                "O cabeçalho {} está vazio; a primeira linha deve ser o nome da biblioteca.",
+                caminho.display()
+            ))
+        })?;
+        Ok(Cabecalho {
+            nome,
+            linhas: linhas.collect(),
+        })
+    }
+}
+
+pub struct Campo {
+    pub rotulo: &'static str,
+    pub valor: String,
+    // Sai em negrito (ex.: a data de devolução no recibo)
+    pub destaque: bool,
+}
+
+pub enum Bloco {
+    Paragrafo(String),
+    Campos(Vec<Campo>),
+    Tabela {
+        colunas: Vec<&'static str>,
+        linhas: Vec<Vec<String>>,
+    },
+}
+
+pub struct Documento {
+    pub titulo: String,
+    pub blocos: Vec<Bloco>,
+    pub emitido_em: DateTime<Local>,
+}
+
+fn data(data: NaiveDate) -> String {
+    data.format("%d/%m/%Y").to_string()
+}
+
+fn campo(rotulo: &'static str, valor: impl Into<String>) -> Campo {
+    Campo {
+        rotulo,
+        valor: valor.into(),
+        destaque: false,
+    }
+}
+
+fn destaque(rotulo: &'static str, valor: impl Into<String>) -> Campo {
+    Campo {
+        rotulo,
+        valor: valor.into(),
+        destaque: true,
+    }
+}
+
+// Leitor, título e autor, como aparecem nos dois recibos
+fn identificacao(biblioteca: &Biblioteca, emprestimo: &Emprestimo) -> Vec<Campo> {
+    let usuario = biblioteca.usuarios().get(&emprestimo.get_id_usuario());
+    let livro = biblioteca.livros().get(&emprestimo.get_id_livro());
+    vec![
+        campo(
+            "Leitor",
+            usuario.map_or("Usuário desconhecido".to_string(), |u| u.get_nome()),
+        ),
+        campo(
+            "Título",
+            livro.map_or("Livro desconhecido".to_string(), |l| l.get_titulo().clone()),
+        ),
+        campo(
+            "Autor",
+            livro.map_or(String::new(), |l| l.get_autor().clone()),
+        ),
+    ]
+}
+
+fn buscar_emprestimo(biblioteca: &Biblioteca, id: Uuid) -> Result<&Emprestimo, ErroBiblioteca> {
+    biblioteca
+        .emprestimos()
+        .get(&id)
+        .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id))
+}
+
+pub fn recibo_emprestimo(
+    biblioteca: &Biblioteca,
+    id_emprestimo: Uuid,
+    emitido_em: DateTime<Local>,
+) -> Result<Documento, ErroBiblioteca> {
+    let emprestimo = buscar_emprestimo(biblioteca, id_emprestimo)?;
+    let mut campos = identificacao(biblioteca, emprestimo);
+    campos.push(campo(
+        "Emprestado em",
+        data(emprestimo.get_data_emprestimo()),
+    ));
+    campos.push(destaque(
+        "Devolver até",
+        data(emprestimo.get_data_devolucao()),
+    ));
+    campos.push(campo("Empréstimo nº", emprestimo.id().to_string()));
+
+    Ok(Documento {
+        titulo: "Recibo de empréstimo".to_string(),
+        blocos: vec![
+            Bloco::Campos(campos),
+            Bloco::Paragrafo(
+                "Guarde este recibo. Devoluções depois do prazo ficam registradas no seu histórico."
+                    .to_string(),
+            ),
+        ],
+        emitido_em,
+    })
+}
+
+pub fn recibo_devolucao(
+    biblioteca: &Biblioteca,
+    id_emprestimo: Uuid,
+    emitido_em: DateTime<Local>,
+) -> Result<Documento, ErroBiblioteca> {
+    let emprestimo = buscar_emprestimo(biblioteca, id_emprestimo)?;
+    if emprestimo.esta_ativo() {
+        return Err(ErroBiblioteca::EstadoInvalido(
+            "O empréstimo ainda não foi devolvido.".to_string(),
+        ));
+    }
+
+    let mut campos = identificacao(biblioteca, emprestimo);
+    campos.push(campo(
+        "Emprestado em",
+        data(emprestimo.get_data_emprestimo()),
+    ));
+    campos.push(campo("Prazo", data(emprestimo.get_data_devolucao())));
+    match emprestimo.get_data_retorno() {
+        Some(retorno) => {
+            campos.push(destaque("Devolvido em", data(retorno)));
+            let atraso = biblioteca
+                .calendario()
+                .dias_de_atraso(emprestimo.get_data_devolucao(), retorno);
+            campos.push(campo(
+                "Situação",
+                if atraso > 0 {
+                    format!("Devolvido com {} dia(s) de atraso", atraso)
+                } else {
+                    "Devolvido no prazo".to_string()
+                },
+            ));
+            if let Some(valor) = biblioteca.politicas().multa(atraso) {
+                campos.push(destaque("Multa", valor.to_string()));
+            }
+        }
+        // Devoluções registradas antes de a data de retorno ser guardada
+        None => campos.push(destaque("Devolvido em", "data não registrada")),
+    }
+    campos.push(campo("Empréstimo nº", emprestimo.id().to_string()));
+
+    Ok(Documento {
+        titulo: "Recibo de devolução".to_string(),
+        blocos: vec![Bloco::Campos(campos)],
+        emitido_em,
+    })
+}
+
+// Empréstimos ativos com o prazo vencido antes de `hoje`, por leitor; o atraso
+// conta só os dias em que a biblioteca abriu
+pub fn atrasados(
+    biblioteca: &Biblioteca,
+    hoje: NaiveDate,
+    emitido_em: DateTime<Local>,
+) -> Documento {
+    let nome = |id: Uuid| {
+        biblioteca
+            .usuarios()
+            .get(&id)
+            .map_or("Usuário desconhecido".to_string(), |u| u.get_nome())
+    };
+    let titulo = |id: Uuid| {
+        biblioteca
+            .livros()
+            .get(&id)
+            .map_or("Livro desconhecido".to_string(), |l| l.get_titulo().clone())
+    };
+
+    let atraso = |emprestimo: &Emprestimo| {
+        biblioteca
+            .calendario()
+            .dias_de_atraso(emprestimo.get_data_devolucao(), hoje)
+    };
+
+    let mut vencidos: Vec<&Emprestimo> = biblioteca
+        .emprestimos()
+        .values()
+        .filter(|e| e.esta_ativo() && atraso(e) > 0)
+        .collect();
+    vencidos.sort_by_cached_key(|e| {
+        (
+            nome(e.get_id_usuario()).to_lowercase(),
+            e.get_data_devolucao(),
+            e.id(),
+        )
+    });
+
+    let resumo = match vencidos.len() {
+        0 => format!("Nenhum empréstimo em atraso em {}.", data(hoje)),
+        1 => format!("1 empréstimo em atraso em {}.", data(hoje)),
+        n => format!("{} empréstimos em atraso em {}.", n, data(hoje)),
+    };
+    let mut blocos = vec![Bloco::Paragrafo(resumo)];
+    if !vencidos.is_empty() {
+        // A coluna de multa só aparece se a biblioteca cobra alguma
+        let politicas = biblioteca.politicas();
+        let cobra_multa = politicas.multa_por_dia.0 > 0;
+        let mut colunas = vec!["Leitor", "Título", "Prazo", "Dias de atraso"];
+        if cobra_multa {
+            colunas.push("Multa");
+        }
+        blocos.push(Bloco::Tabela {
+            colunas,
+            linhas: vencidos
+                .iter()
+                .map(|e| {
+                    let dias = atraso(e);
+                    let mut linha = vec![
+                        nome(e.get_id_usuario()),
+                        titulo(e.get_id_livro()),
+                        data(e.get_data_devolucao()),
+                        dias.to_string(),
+                    ];
+                    if cobra_multa {
+                        linha.push(
+                            politicas
+                                .multa(dias)
+                                .map_or_else(String::new, |valor| valor.to_string()),
+                        );
+                    }
+                    linha
+                })
+                .collect(),
+        });
+    }
+
+    Documento {
+        titulo: "Empréstimos em atraso".to_string(),
+        blocos,
+        emitido_em,
+    }
+}
+
+// O acervo em circulação (sem os arquivados nem os livros de parceiras), em ordem de título
+pub fn inventario(biblioteca: &Biblioteca, emitido_em: DateTime<Local>) -> Documento {
+    let mut livros: Vec<_> = biblioteca
+        .livros()
+        .values()
+        .filter(|l| !l.arquivado && l.intercambio().is_none())
+        .collect();
+    livros.sort_by_cached_key(|l| (l.get_titulo().to_lowercase(), l.id()));
+    let emprestados = livros.iter().filter(|l| !l.esta_disponivel()).count();
+
+    Documento {
+        titulo: "Inventário do acervo".to_string(),
+        blocos: vec![
+            Bloco::Paragrafo(format!(
+                "{} livro(s) no acervo, {} emprestado(s).",
+                livros.len(),
+                emprestados
+            )),
+            Bloco::Tabela {
+                colunas: vec!["Título", "Autor", "Ano", "ISBN", "Situação"],
+                linhas: livros
+                    .iter()
+                    .map(|l| {
+                        vec![
+                            l.get_titulo().clone(),
+                            l.get_autor().clone(),
+                            l.get_ano().to_string(),
+                            l.catalogacao().isbn.clone().unwrap_or_default(),
+                            if l.esta_disponivel() {
+                                "Disponível"
+                            } else {
+                                "Emprestado"
+                            }
+                            .to_string(),
+                        ]
+                    })
+                    .collect(),
+            },
+        ],
+        emitido_em,
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        contas::{Papel, Sessao},
+        politicas::{Centavos, Politicas},
+        relogio::RelogioFixo,
+    };
+
+    struct Cenario {
+        biblioteca: Biblioteca,
+        relogio: Arc<RelogioFixo>,
+        // Capitu com Dom Casmurro e Bento com Iracema, ambos em 04/03/2024
+        // (segunda), com prazo em 18/03
+        de_capitu: Uuid,
+        de_bento: Uuid,
+        _pasta: TempDir,
+    }
+
+    fn cenario() -> Cenario {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let relogio = Arc::new(
+            RelogioFixo::no_dia(NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()).expect("dia válido"),
+        );
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca.definir_relogio(relogio.clone());
+        biblioteca.definir_politicas(Politicas {
+            multa_por_dia: Centavos(50),
+            ..Politicas::default()
+        });
+
+        let capitu = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        let bento = biblioteca.cadastrar_usuario("Bento".to_string()).unwrap();
+        let dom_casmurro = biblioteca
+            .cadastrar_livro(
+                "Dom Casmurro".to_string(),
+                "Machado de Assis".to_string(),
+                1899,
+            )
+            .unwrap();
+        let iracema = biblioteca
+            .cadastrar_livro("Iracema".to_string(), "José de Alencar".to_string(), 1865)
+            .unwrap();
+        let de_capitu = biblioteca
+            .realizar_emprestimo(capitu, dom_casmurro)
+            .unwrap();
+        let de_bento = biblioteca.realizar_emprestimo(bento, iracema).unwrap();
+
+        Cenario {
+            biblioteca,
+            relogio,
+            de_capitu,
+            de_bento,
+            _pasta: pasta,
+        }
+    }
+
+    fn campos(documento: &Documento) -> Vec<(&'static str, &str)> {
+        documento
+            .blocos
+            .iter()
+            .filter_map(|bloco| match bloco {
+                Bloco::Campos(campos) => Some(campos),
+                _ => None,
+            })
+            .flatten()
+            .map(|campo| (campo.rotulo, campo.valor.as_str()))
+            .collect()
+    }
+
+    fn tabela(documento: &Documento) -> (&[&'static str], &[Vec<String>]) {
+        documento
+            .blocos
+            .iter()
+            .find_map(|bloco| match bloco {
+                Bloco::Tabela { colunas, linhas } => Some((colunas.as_slice(), linhas.as_slice())),
+                _ => None,
+            })
+            .expect("tabela no documento")
+    }
+
+    #[test]
+    fn recibo_de_emprestimo_traz_leitor_livro_e_prazo() {
+        let c = cenario();
+        let recibo =
+            recibo_emprestimo(&c.biblioteca, c.de_capitu, c.biblioteca.agora()).expect("recibo");
+
+        assert_eq!(recibo.titulo, "Recibo de empréstimo");
+        let de_capitu = c.de_capitu.to_string();
+        assert_eq!(
+            campos(&recibo),
+            [
+                ("Leitor", "Capitu"),
+                ("Título", "Dom Casmurro"),
+                ("Autor", "Machado de Assis"),
+                ("Emprestado em", "04/03/2024"),
+                ("Devolver até", "18/03/2024"),
+                ("Empréstimo nº", de_capitu.as_str()),
+            ]
+        );
+
+        // O prazo sai em destaque no HTML, junto com o cabeçalho da biblioteca
+        let cabecalho = Cabecalho {
+            nome: "Biblioteca <Central>".to_string(),
+            linhas: vec!["Rua das Flores, 10".to_string()],
+        };
+        let pagina = html::renderizar(&recibo, &cabecalho, html::MODELO_PADRAO);
+        assert!(pagina.contains("Biblioteca &lt;Central&gt;"));
+        assert!(pagina.contains("<dd class=\"destaque\">18/03/2024</dd>"));
+        assert!(pagina.contains("<dd>Dom Casmurro</dd>"));
+        assert!(pdf::renderizar(&recibo, &cabecalho).starts_with(b"%PDF"));
+
+        assert!(matches!(
+            recibo_emprestimo(&c.biblioteca, Uuid::new_v4(), c.biblioteca.agora()),
+            Err(ErroBiblioteca::EmprestimoNaoEncontrado(_))
+        ));
+    }
+
+    #[test]
+    fn recibo_de_devolucao_traz_atraso_e_multa() {
+        let mut c = cenario();
+        assert!(matches!(
+            recibo_devolucao(&c.biblioteca, c.de_capitu, c.biblioteca.agora()),
+            Err(ErroBiblioteca::EstadoInvalido(_))
+        ));
+
+        // Sexta, 22/03: quatro dias abertos depois do prazo
+        c.relogio.avancar(Duration::days(18));
+        c.biblioteca.devolver(c.de_capitu).expect("devolução");
+        let recibo =
+            recibo_devolucao(&c.biblioteca, c.de_capitu, c.biblioteca.agora()).expect("recibo");
+
+        assert_eq!(recibo.titulo, "Recibo de devolução");
+        let de_capitu = c.de_capitu.to_string();
+        assert_eq!(
+            campos(&recibo),
+            [
+                ("Leitor", "Capitu"),
+                ("Título", "Dom Casmurro"),
+                ("Autor", "Machado de Assis"),
+                ("Emprestado em", "04/03/2024"),
+                ("Prazo", "18/03/2024"),
+                ("Devolvido em", "22/03/2024"),
+                ("Situação", "Devolvido com 4 dia(s) de atraso"),
+                ("Multa", "R$ 2,00"),
+                ("Empréstimo nº", de_capitu.as_str()),
+            ]
+        );
+    }
+
+    #[test]
+    fn atrasados_lista_so_os_vencidos_em_aberto() {
+        let mut c = cenario();
+        let hoje = NaiveDate::from_ymd_opt(2024, 3, 22).unwrap();
+
+        // Antes do prazo não há ninguém
+        let documento = atrasados(
+            &c.biblioteca,
+            hoje - Duration::days(10),
+            c.biblioteca.agora(),
+        );
+        assert!(
+            documento
+                .blocos
+                .iter()
+                .all(|b| !matches!(b, Bloco::Tabela { .. }))
+        );
+
+        c.relogio.avancar(Duration::days(18));
+        c.biblioteca.devolver(c.de_capitu).expect("devolução");
+        let documento = atrasados(&c.biblioteca, hoje, c.biblioteca.agora());
+
+        assert_eq!(documento.titulo, "Empréstimos em atraso");
+        assert!(matches!(
+            &documento.blocos[0],
+            Bloco::Paragrafo(resumo) if resumo == "1 empréstimo em atraso em 22/03/2024."
+        ));
+        let (colunas, linhas) = tabela(&documento);
+        assert_eq!(
+            colunas,
+            ["Leitor", "Título", "Prazo", "Dias de atraso", "Multa"]
+        );
+        assert_eq!(linhas, [["Bento", "Iracema", "18/03/2024", "4", "R$ 2,00"]]);
+        assert!(c.biblioteca.emprestimos()[&c.de_bento].esta_ativo());
+    }
+
+    #[test]
+    fn inventario_lista_o_acervo_com_a_situacao() {
+        let mut c = cenario();
+        c.biblioteca.devolver(c.de_capitu).expect("devolução");
+        let documento = inventario(&c.biblioteca, c.biblioteca.agora());
+
+        assert_eq!(documento.titulo, "Inventário do acervo");
+        assert!(matches!(
+            &documento.blocos[0],
+            Bloco::Paragrafo(resumo) if resumo == "2 livro(s) no acervo, 1 emprestado(s)."
+        ));
+        let (colunas, linhas) = tabela(&documento);
+        assert_eq!(colunas, ["Título", "Autor", "Ano", "ISBN", "Situação"]);
+        assert_eq!(
+            linhas,
+            [
+                ["Dom Casmurro", "Machado de Assis", "1899", "", "Disponível"],
+                ["Iracema", "José de Alencar", "1865", "", "Emprestado"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+// PDF mínimo escrito à mão: páginas A4 só com texto e linhas, usando as fontes
+// Helvetica que todo leitor de PDF já tem. O texto vai em WinAnsiEncoding, que
+// cobre os acentos do português; o que não couber nela sai como "?".
+
+use super::{Bloco, Cabecalho, Documento};
+
+const LARGURA: f32 = 595.0;
+const ALTURA: f32 = 842.0;
+const MARGEM: f32 = 50.0;
+// Espaço reservado embaixo para o rodapé
+const MARGEM_INFERIOR: f32 = 60.0;
+const UTIL: f32 = LARGURA - 2.0 * MARGEM;
+
+// Largura aproximada de um caractere da Helvetica, em frações do tamanho da fonte
+fn largura_caractere(c: char, negrito: bool) -> f32 {
+    let base = match c {
+        ' ' | 'i' | 'j' | 'l' | '.' | ',' | ';' | ':' | '\'' | '!' | '|' | 'í' | 'ì' => 0.25,
+        'f' | 't' | 'r' | 'I' | '(' | ')' | '[' | ']' | '/' | '-' => 0.33,
+        'm' | 'w' | 'M' | 'W' | '@' => 0.85,
+        c if c.is_uppercase() => 0.68,
+        c if c.is_ascii_digit() => 0.556,
+        _ => 0.54,
+    };
+    if negrito { base * 1.06 } else { base }
+}
+
+fn largura_texto(texto: &str, tamanho: f32, negrito: bool) -> f32 {
+    texto
+        .chars()
+        .map(|c| largura_caractere(c, negrito) * tamanho)
+        .sum()
+}
+
+// Quebra em linhas que caibam em `largura`; palavras maiores que a linha são cortadas
+fn quebrar(texto: &str, largura: f32, tamanho: f32, negrito: bool) -> Vec<String> {
+    let mut linhas = Vec::new();
+    let mut atual = String::new();
+    for palavra in texto.split_whitespace() {
+        let candidata = if atual.is_empty() {
+            palavra.to_string()
+        } else {
+            format!("{} {}", atual, palavra)
+        };
+        if largura_texto(&candidata, tamanho, negrito) <= largura || atual.is_empty() {
+            atual = candidata;
+        } else {
+            linhas.push(std::mem::replace(&mut atual, palavra.to_string()));
+        }
+    }
+    if !atual.is_empty() || linhas.is_empty() {
+        linhas.push(atual);
+    }
+    linhas
+        .into_iter()
+        .map(|linha| encurtar(&linha, largura, tamanho, negrito))
+        .collect()
+}
+
+// Corta com reticências o que não couber (células de tabela, palavras enormes)
+fn encurtar(texto: &str, largura: f32, tamanho: f32, negrito: bool) -> String {
+    if largura_texto(texto, tamanho, negrito) <= largura {
+        return texto.to_string();
+    }
+    let mut cortado = String::new();
+    for c in texto.chars() {
+        if largura_texto(&format!("{}{}…", cortado, c), tamanho, negrito) > largura {
+            break;
+        }
+        cortado.push(c);
+    }
+    cortado.push('…');
+    cortado
+}
+
+// Texto como string literal de PDF, em WinAnsiEncoding
+fn literal(texto: &str) -> String {
+    let mut saida = String::from("(");
+    for c in texto.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                saida.push('\\');
+                saida.push(c);
+                continue;
+            }
+            ' '..='~' => {
+                saida.push(c);
+                continue;
+            }
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        };
+        saida.push_str(&format!("\\{:03o}", byte));
+    }
+    saida.push(')');
+    saida
+}
+
+// Vai descendo pela página e abre outra quando o espaço acaba
+struct Diagramador {
+    paginas: Vec<String>,
+    atual: String,
+    y: f32,
+}
+
+impl Diagramador {
+    fn new() -> Self {
+        Diagramador {
+            paginas: Vec::new(),
+            atual: String::new(),
+            y: ALTURA - MARGEM,
+        }
+    }
+
+    fn nova_pagina(&mut self) {
+        self.paginas.push(std::mem::take(&mut self.atual));
+        self.y = ALTURA - MARGEM;
+    }
+
+    // Garante `altura` livre na página atual; devolve se precisou trocar de página
+    fn reservar(&mut self, altura: f32) -> bool {
+        if self.y - altura < MARGEM_INFERIOR && !self.atual.is_empty() {
+            self.nova_pagina();
+            return true;
+        }
+        false
+    }
+
+    // Escreve na linha de base atual, sem descer
+    fn texto(&mut self, x: f32, tamanho: f32, negrito: bool, texto: &str) {
+        self.atual.push_str(&format!(
+            "BT /{} {} Tf {:.1} {:.1} Td {} Tj ET\n",
+            if negrito { "F2" } else { "F1" },
+            tamanho,
+            x,
+            self.y,
+            literal(texto)
+        ));
+    }
+
+    fn regua(&mut self, espessura: f32) {
+        self.atual.push_str(&format!(
+            "{} w {:.1} {:.1} m {:.1} {:.1} l S\n",
+            espessura,
+            MARGEM,
+            self.y,
+            LARGURA - MARGEM,
+            self.y
+        ));
+    }
+
+    fn descer(&mut self, altura: f32) {
+        self.y -= altura;
+    }
+
+    fn paragrafo(&mut self, texto: &str, tamanho: f32, negrito: bool) {
+        for linha in quebrar(texto, UTIL, tamanho, negrito) {
+            self.reservar(tamanho * 1.4);
+            self.descer(tamanho * 1.4);
+            self.texto(MARGEM, tamanho, negrito, &linha);
+        }
+    }
+
+    fn terminar(mut self) -> Vec<String> {
+        if !self.atual.is_empty() || self.paginas.is_empty() {
+            self.paginas.push(self.atual);
+        }
+        self.paginas
+    }
+}
+
+fn campos(diagramador: &mut Diagramador, campos: &[super::Campo]) {
+    const TAMANHO: f32 = 11.0;
+    let coluna = campos
+        .iter()
+        .map(|c| largura_texto(c.rotulo, TAMANHO, true))
+        .fold(0.0, f32::max)
+        + 14.0;
+    for campo in campos {
+        let linhas = quebrar(&campo.valor, UTIL - coluna, TAMANHO, campo.destaque);
+        for (i, linha) in linhas.iter().enumerate() {
+            diagramador.reservar(TAMANHO * 1.6);
+            diagramador.descer(TAMANHO * 1.6);
+            if i == 0 {
+                diagramador.texto(MARGEM, TAMANHO, true, campo.rotulo);
+            }
+            diagramador.texto(MARGEM + coluna, TAMANHO, campo.destaque, linha);
+        }
+    }
+}
+
+fn tabela(diagramador: &mut Diagramador, colunas: &[&str], linhas: &[Vec<String>]) {
+    const TAMANHO: f32 = 9.0;
+    const ESPACO: f32 = 8.0;
+    const ALTURA_LINHA: f32 = TAMANHO * 1.6;
+
+    // Cada coluna do tamanho do seu maior conteúdo; se não couber, todas encolhem juntas
+    let mut larguras: Vec<f32> = colunas
+        .iter()
+        .map(|c| largura_texto(c, TAMANHO, true))
+        .collect();
+    for linha in linhas {
+        for (largura, celula) in larguras.iter_mut().zip(linha) {
+            *largura = largura.max(largura_texto(celula, TAMANHO, false));
+        }
+    }
+    let disponivel = UTIL - ESPACO * (colunas.len().saturating_sub(1)) as f32;
+    let total: f32 = larguras.iter().sum();
+    if total > disponivel {
+        for largura in &mut larguras {
+            *largura *= disponivel / total;
+        }
+    }
+
+    let escrever = |diagramador: &mut Diagramador, celulas: &[&str], negrito: bool| {
+        let mut x = MARGEM;
+        for (celula, largura) in celulas.iter().zip(&larguras) {
+            if celula.is_empty() {
+                x += largura + ESPACO;
+                continue;
+            }
+            diagramador.texto(
+                x,
+                TAMANHO,
+                negrito,
+                &encurtar(celula, *largura, TAMANHO, negrito),
+            );
+            x += largura + ESPACO;
+        }
+    };
+    let titulos = |diagramador: &mut Diagramador| {
+        diagramador.descer(ALTURA_LINHA);
+        escrever(diagramador, colunas, true);
+        diagramador.descer(4.0);
+        diagramador.regua(0.8);
+    };
+
+    diagramador.reservar(ALTURA_LINHA * 3.0);
+    titulos(diagramador);
+    for linha in linhas {
+        // Página nova repete os títulos das colunas
+        if diagramador.reservar(ALTURA_LINHA) {
+            titulos(diagramador);
+        }
+        diagramador.descer(ALTURA_LINHA);
+        let celulas: Vec<&str> = linha.iter().map(String::as_str).collect();
+        escrever(diagramador, &celulas, false);
+    }
+}
+
+pub fn renderizar(documento: &Documento, cabecalho: &Cabecalho) -> Vec<u8> {
+    let mut diagramador = Diagramador::new();
+
+    diagramador.paragrafo(&cabecalho.nome, 16.0, true);
+    for linha in &cabecalho.linhas {
+        diagramador.paragrafo(linha, 9.0, false);
+    }
+    diagramador.descer(6.0);
+    diagramador.regua(1.0);
+    diagramador.descer(10.0);
+    diagramador.paragrafo(&documento.titulo, 14.0, true);
+    diagramador.descer(6.0);
+
+    for bloco in &documento.blocos {
+        match bloco {
+            Bloco::Paragrafo(texto) => diagramador.paragrafo(texto, 10.0, false),
+            Bloco::Campos(lista) => campos(&mut diagramador, lista),
+            Bloco::Tabela { colunas, linhas } => tabela(&mut diagramador, colunas, linhas),
+        }
+        diagramador.descer(10.0);
+    }
+
+    // Rodapé com a numeração, que só se sabe no fim
+    let emitido_em = documento.emitido_em.format("%d/%m/%Y %H:%M");
+    let paginas = diagramador.terminar();
+    let total = paginas.len();
+    let paginas: Vec<String> = paginas
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut conteudo)| {
+            conteudo.push_str(&format!(
+                "BT /F1 8 Tf {:.1} 30 Td {} Tj ET\n",
+                MARGEM,
+                literal(&format!(
+                    "{} — emitido em {} — página {} de {}",
+                    cabecalho.nome,
+                    emitido_em,
+                    i + 1,
+                    total
+                ))
+            ));
+            conteudo
+        })
+        .collect();
+
+    montar(&paginas)
+}
+
+// Objetos: 1 catálogo, 2 árvore de páginas, 3 e 4 fontes; depois página e conteúdo, aos pares
+fn montar(paginas: &[String]) -> Vec<u8> {
+    let mut objetos: Vec<String> = Vec::new();
+    let filhas: Vec<String> = (0..paginas.len())
+        .map(|i| format!("{} 0 R", 5 + 2 * i))
+        .collect();
+    objetos.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+    objetos.push(format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        filhas.join(" "),
+        paginas.len()
+    ));
+    for fonte in ["Helvetica", "Helvetica-Bold"] {
+        objetos.push(format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+            fonte
+        ));
+    }
+    for (i, conteudo) in paginas.iter().enumerate() {
+        objetos.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            LARGURA,
+            ALTURA,
+            6 + 2 * i
+        ));
+        objetos.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            conteudo.len(),
+            conteudo
+        ));
+    }
+
+    // Os literais já estão em ASCII (bytes altos vão como \ooo), então cada char é um byte
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut posicoes = Vec::new();
+    for (i, objeto) in objetos.iter().enumerate() {
+        posicoes.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, objeto));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objetos.len() + 1
+    ));
+    for posicao in posicoes {
+        pdf.push_str(&format!("{:010} 00000 n \n", posicao));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objetos.len() + 1,
+        xref
+    ));
+    pdf.into_bytes()
+}
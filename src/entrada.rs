@@ -0,0 +1,193 @@
+use std::{
+    fmt::Display,
+    io::{self, IsTerminal, Write},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::{
+    biblioteca::{contas::TAMANHO_MINIMO_SENHA, livros::faixa_ano},
+    errors::ErroBiblioteca,
+};
+
+// Palavra que, digitada em qualquer prompt, desiste da operação atual
+pub const PALAVRA_CANCELAR: &str = "cancelar";
+
+// Lê uma linha do stdin já sem espaços nas pontas.
+// EOF (ou erro de leitura) vira `EntradaEncerrada` para ninguém ficar preso num loop.
+fn ler_linha(mensagem: &str) -> Result<String, ErroBiblioteca> {
+    println!("{}", mensagem);
+    let _ = io::stdout().flush();
+
+    let mut entrada = String::new();
+    match io::stdin().read_line(&mut entrada) {
+        Ok(0) | Err(_) => Err(ErroBiblioteca::EntradaEncerrada),
+        Ok(_) => {
+            let entrada = entrada.trim();
+            if entrada.eq_ignore_ascii_case(PALAVRA_CANCELAR) {
+                Err(ErroBiblioteca::OperacaoCancelada)
+            } else {
+                Ok(entrada.to_string())
+            }
+        }
+    }
+}
+
+pub fn ler_string(mensagem: &str) -> Result<String, ErroBiblioteca> {
+    loop {
+        let entrada = ler_linha(mensagem)?;
+
+        if entrada.is_empty() {
+            println!("❌ O valor não pode ficar vazio. Tente novamente.");
+        } else {
+            return Ok(entrada);
+        }
+    }
+}
+
+// Como `ler_string`, mas aceita deixar em branco
+pub fn ler_string_opcional(mensagem: &str) -> Result<String, ErroBiblioteca> {
+    ler_linha(mensagem)
+}
+
+// Num terminal a senha é digitada sem eco; com o stdin redirecionado, é lida como uma linha comum
+pub fn ler_senha(mensagem: &str) -> Result<String, ErroBiblioteca> {
+    loop {
+        let senha = if io::stdin().is_terminal() {
+            let senha = rpassword::prompt_password(format!("{}\n", mensagem))
+                .map_err(|_| ErroBiblioteca::EntradaEncerrada)?;
+            if senha.trim().eq_ignore_ascii_case(PALAVRA_CANCELAR) {
+                return Err(ErroBiblioteca::OperacaoCancelada);
+            }
+            senha
+        } else {
+            ler_linha(mensagem)?
+        };
+
+        if senha.is_empty() {
+            println!("❌ A senha não pode ficar vazia. Tente novamente.");
+        } else {
+            return Ok(senha);
+        }
+    }
+}
+
+// Pede a senha duas vezes e só aceita quando as duas batem e têm o tamanho mínimo
+pub fn ler_nova_senha() -> Result<String, ErroBiblioteca> {
+    loop {
+        let senha = ler_senha("Nova senha: ")?;
+        if senha.chars().count() < TAMANHO_MINIMO_SENHA {
+            println!(
+                "❌ A senha deve ter pelo menos {} caracteres. Tente novamente.",
+                TAMANHO_MINIMO_SENHA
+            );
+            continue;
+        }
+        if ler_senha("Confirme a senha: ")? == senha {
+            return Ok(senha);
+        }
+        println!("❌ As senhas não conferem. Tente novamente.");
+    }
+}
+
+pub fn ler_confirmacao(mensagem: &str) -> Result<bool, ErroBiblioteca> {
+    loop {
+        let entrada = ler_linha(&format!("{} (s/n)", mensagem))?;
+
+        match entrada.to_lowercase().as_str() {
+            "s" | "sim" => return Ok(true),
+            "n" | "nao" | "não" => return Ok(false),
+            _ => println!("❌ Responda \"s\" ou \"n\"."),
+        }
+    }
+}
+
+pub fn ler_numero<T>(mensagem: &str, faixa: RangeInclusive<T>) -> Result<T, ErroBiblioteca>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    loop {
+        let entrada = ler_linha(mensagem)?;
+
+        match entrada.parse::<T>() {
+            Ok(numero) if faixa.contains(&numero) => return Ok(numero),
+            Ok(_) | Err(_) => println!(
+                "❌ Digite um número entre {} e {}. Tente novamente.",
+                faixa.start(),
+                faixa.end()
+            ),
+        }
+    }
+}
+
+pub fn ler_ano(mensagem: &str, hoje: NaiveDate) -> Result<u16, ErroBiblioteca> {
+    ler_numero(mensagem, faixa_ano(hoje))
+}
+
+// ======== Versões para edição: Enter sem digitar nada mantém o valor atual ========
+
+pub fn ler_string_ou(mensagem: &str, atual: &str) -> Result<String, ErroBiblioteca> {
+    let entrada = ler_linha(&format!("{} [{}]: ", mensagem, atual))?;
+    if entrada.is_empty() {
+        Ok(atual.to_string())
+    } else {
+        Ok(entrada)
+    }
+}
+
+pub fn ler_ano_ou(mensagem: &str, atual: u16, hoje: NaiveDate) -> Result<u16, ErroBiblioteca> {
+    let faixa = faixa_ano(hoje);
+    loop {
+        let entrada = ler_linha(&format!("{} [{}]: ", mensagem, atual))?;
+        if entrada.is_empty() {
+            return Ok(atual);
+        }
+
+        match entrada.parse::<u16>() {
+            Ok(ano) if faixa.contains(&ano) => return Ok(ano),
+            Ok(_) | Err(_) => println!(
+                "❌ Digite um número entre {} e {}. Tente novamente.",
+                faixa.start(),
+                faixa.end()
+            ),
+        }
+    }
+}
+
+pub fn ler_data_ou(mensagem: &str, atual: NaiveDate) -> Result<NaiveDate, ErroBiblioteca> {
+    loop {
+        let entrada = ler_linha(&format!("{} [{}]: ", mensagem, atual))?;
+        if entrada.is_empty() {
+            return Ok(atual);
+        }
+
+        match NaiveDate::parse_from_str(&entrada, "%Y-%m-%d") {
+            Ok(data) => return Ok(data),
+            Err(_) => println!("❌ Data inválida! Use o formato AAAA-MM-DD."),
+        }
+    }
+}
+
+// Lê a posição de um item numa lista exibida com índices de 0 a tamanho - 1
+pub fn ler_indice(mensagem: &str, tamanho: usize) -> Result<usize, ErroBiblioteca> {
+    if tamanho == 0 {
+        return Err(ErroBiblioteca::EstadoInvalido(
+            "Não há itens para escolher.".to_string(),
+        ));
+    }
+    ler_numero(mensagem, 0..=tamanho - 1)
+}
+
+pub fn ler_uuid(mensagem: &str) -> Result<Uuid, ErroBiblioteca> {
+    loop {
+        let entrada = ler_linha(mensagem)?;
+
+        match Uuid::parse_str(&entrada) {
+            Ok(uuid) => return Ok(uuid),
+            Err(_) => println!("❌ UUID inválido! Tente novamente."),
+        }
+    }
+}
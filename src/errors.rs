@@ -1,32 +1,74 @@
 use std::fmt;
 
+use uuid::Uuid;
+
 #[derive(Debug)]
 pub enum ErroBiblioteca {
-    // LivroNaoEncontrado(Uuid),
-    // UsuarioNaoEncontrado(Uuid),
-    // EmprestimoNaoEncontrado(Uuid),
+    LivroNaoEncontrado(Uuid),
+    UsuarioNaoEncontrado(Uuid),
+    EmprestimoNaoEncontrado(Uuid),
+    // Pela sigla ou pelo id, como veio de quem pediu
+    UnidadeNaoEncontrada(String),
+    // Pedido de intercâmbio com outra biblioteca
+    SolicitacaoNaoEncontrada(Uuid),
+    // Pelo nome ou pelo id
+    ParceiraNaoEncontrada(String),
+    // Conta de funcionário, pelo login
+    ContaNaoEncontrada(String),
+    DadosInvalidos(String),
     EstadoInvalido(String),
     ErroPersistencia(String),
+    // Falha ao falar com o servidor de e-mail
+    ErroEnvio(String),
+    // O papel de quem está operando (ou a falta de login) não permite a ação
+    PermissaoNegada(String),
+    CredenciaisInvalidas,
+    OperacaoCancelada,
+    EntradaEncerrada,
 }
 
 impl fmt::Display for ErroBiblioteca {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            // ErroBiblioteca::LivroNaoEncontrado(id) => {
-            //     write!(f, "Livro não encontrado: {}", id)
-            // }
-            // ErroBiblioteca::UsuarioNaoEncontrado(id) => {
-            //     write!(f, "Usuário não encontrado: {}", id)
-            // }
-            // ErroBiblioteca::EmprestimoNaoEncontrado(id) => {
-            //     write!(f, "Empréstimo não encontrado: {}", id)
-            // }
+            ErroBiblioteca::LivroNaoEncontrado(id) => {
+                write!(f, "Livro não encontrado: {}", id)
+            }
+            ErroBiblioteca::UsuarioNaoEncontrado(id) => {
+                write!(f, "Usuário não encontrado: {}", id)
+            }
+            ErroBiblioteca::EmprestimoNaoEncontrado(id) => {
+                write!(f, "Empréstimo não encontrado: {}", id)
+            }
+            ErroBiblioteca::UnidadeNaoEncontrada(unidade) => {
+                write!(f, "Unidade não encontrada: {}", unidade)
+            }
+            ErroBiblioteca::SolicitacaoNaoEncontrada(id) => {
+                write!(f, "Pedido de intercâmbio não encontrado: {}", id)
+            }
+            ErroBiblioteca::ParceiraNaoEncontrada(parceira) => {
+                write!(f, "Biblioteca parceira não encontrada: {}", parceira)
+            }
+            ErroBiblioteca::ContaNaoEncontrada(login) => {
+                write!(f, "Conta não encontrada: {}", login)
+            }
+            ErroBiblioteca::DadosInvalidos(msg) => {
+                write!(f, "Dados inválidos: {}", msg)
+            }
             ErroBiblioteca::EstadoInvalido(msg) => {
                 write!(f, "Estado inválido: {}", msg)
             }
             ErroBiblioteca::ErroPersistencia(msg) => {
                 write!(f, "Erro de persistência: {}", msg)
             }
+            ErroBiblioteca::ErroEnvio(msg) => {
+                write!(f, "Erro no envio: {}", msg)
+            }
+            ErroBiblioteca::PermissaoNegada(msg) => {
+                write!(f, "Permissão negada: {}", msg)
+            }
+            ErroBiblioteca::CredenciaisInvalidas => write!(f, "Login ou senha inválidos"),
+            ErroBiblioteca::OperacaoCancelada => write!(f, "Operação cancelada"),
+            ErroBiblioteca::EntradaEncerrada => write!(f, "Entrada encerrada"),
         }
     }
 }
@@ -0,0 +1,304 @@
+// Conferência do acervo (inventário anual): os códigos lidos nas estantes, um
+// por leitura (o UUID do livro ou o ISBN do código de barras), são comparados
+// com o catálogo. Com uma unidade definida, só os exemplares que estão nela
+// são esperados nas estantes.
+
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::biblioteca::{Biblioteca, livros::Livro};
+
+// Só dígitos e o X final: "85-359-0277-5" e "8535902775" são o mesmo ISBN
+fn normalizar_isbn(texto: &str) -> String {
+    texto
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, 'x' | 'X'))
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+pub enum Leitura {
+    Encontrado(Uuid),
+    // O código já foi lido (ou todos os exemplares com esse ISBN já foram)
+    Repetido(Uuid),
+    Desconhecido,
+}
+
+pub struct Conferencia<'a> {
+    biblioteca: &'a Biblioteca,
+    // ISBN normalizado -> exemplares, em ordem de id
+    por_isbn: HashMap<String, Vec<Uuid>>,
+    encontrados: HashSet<Uuid>,
+    desconhecidos: Vec<String>,
+}
+
+// O que a conferência apurou, cada lista em ordem de título
+pub struct Resultado {
+    pub lidos: usize,
+    // Deviam estar na estante e não foram lidos
+    pub ausentes: Vec<Uuid>,
+    // Lidos, mas o sistema diz que estão com algum leitor
+    pub emprestados: Vec<Uuid>,
+    // Lidos, mas marcados como extraviados numa conferência anterior
+    pub achados: Vec<Uuid>,
+    pub desconhecidos: Vec<String>,
+}
+
+impl<'a> Conferencia<'a> {
+    pub fn new(biblioteca: &'a Biblioteca) -> Self {
+        let mut por_isbn: HashMap<String, Vec<Uuid>> = HashMap::new();
+        for (id, livro) in biblioteca.livros() {
+            if let Some(isbn) = &livro.catalogacao().isbn
+                && !livro.arquivado
+            {
+                por_isbn.entry(normalizar_isbn(isbn)).or_default().push(*id);
+            }
+        }
+        for ids in por_isbn.values_mut() {
+            ids.sort();
+        }
+
+        Conferencia {
+            biblioteca,
+            por_isbn,
+            encontrados: HashSet::new(),
+            desconhecidos: Vec::new(),
+        }
+    }
+
+    // Um ISBN lido conta para o primeiro exemplar com ele que ainda não apareceu
+    pub fn registrar(&mut self, codigo: &str) -> Leitura {
+        let codigo = codigo.trim();
+        let candidatos = match Uuid::parse_str(codigo) {
+            Ok(id) => self
+                .biblioteca
+                .livros()
+                .get(&id)
+                .filter(|livro| !livro.arquivado)
+                .map(|_| vec![id])
+                .unwrap_or_default(),
+            Err(_) => self
+                .por_isbn
+                .get(&normalizar_isbn(codigo))
+                .cloned()
+                .unwrap_or_default(),
+        };
+
+        match candidatos.iter().find(|id| !self.encontrados.contains(id)) {
+            Some(id) => {
+                self.encontrados.insert(*id);
+                Leitura::Encontrado(*id)
+            }
+            None => match candidatos.first() {
+                Some(id) => Leitura::Repetido(*id),
+                None => {
+                    self.desconhecidos.push(codigo.to_string());
+                    Leitura::Desconhecido
+                }
+            },
+        }
+    }
+
+    pub fn concluir(self) -> Resultado {
+        let biblioteca = self.biblioteca;
+        let aqui = biblioteca.unidade_atual();
+        let ordenar = |mut ids: Vec<Uuid>| {
+            ids.sort_by_cached_key(|id| (biblioteca.livros()[id].get_titulo().to_lowercase(), *id));
+            ids
+        };
+        let selecionar = |criterio: &dyn Fn(&Uuid, &Livro) -> bool| {
+            let ids = biblioteca
+                .livros()
+                .iter()
+                .filter(|(id, livro)| !livro.arquivado && criterio(id, livro))
+                .map(|(id, _)| *id)
+                .collect();
+            ordenar(ids)
+        };
+
+        // Emprestados, em trânsito ou já extraviados não estão na estante mesmo
+        let ausentes = selecionar(&|id, livro| {
+            !self.encontrados.contains(id)
+                && livro.esta_disponivel()
+                && livro.em_transito().is_none()
+                && livro.extraviado().is_none()
+                && aqui.is_none_or(|unidade| livro.get_local() == Some(unidade))
+        });
+        let emprestados =
+            selecionar(&|id, livro| self.encontrados.contains(id) && !livro.esta_disponivel());
+        let achados =
+            selecionar(&|id, livro| self.encontrados.contains(id) && livro.extraviado().is_some());
+
+        Resultado {
+            lidos: self.encontrados.len(),
+            ausentes,
+            emprestados,
+            achados,
+            desconhecidos: self.desconhecidos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use chrono::NaiveDate;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        contas::{Papel, Sessao},
+        livros::Catalogacao,
+        relogio::RelogioFixo,
+    };
+    use crate::errors::ErroBiblioteca;
+
+    fn dia(mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, mes, dia).expect("data válida")
+    }
+
+    fn biblioteca(pasta: &TempDir) -> (Biblioteca, Arc<RelogioFixo>) {
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        let relogio = Arc::new(RelogioFixo::no_dia(dia(3, 4)).expect("dia válido"));
+        biblioteca.definir_relogio(relogio.clone());
+        (biblioteca, relogio)
+    }
+
+    fn cadastrar(biblioteca: &mut Biblioteca, titulo: &str, isbn: Option<&str>) -> Uuid {
+        let catalogacao = Catalogacao {
+            isbn: isbn.map(str::to_string),
+            ..Catalogacao::default()
+        };
+        biblioteca
+            .cadastrar_livro_catalogado(
+                titulo.to_string(),
+                "Machado de Assis".to_string(),
+                1900,
+                catalogacao,
+            )
+            .expect("cadastro")
+    }
+
+    #[test]
+    fn leituras_por_uuid_e_isbn_apontam_os_ausentes() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, _) = biblioteca(&pasta);
+        let leitor = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        let helena = cadastrar(&mut biblioteca, "Helena", None);
+        // Dois exemplares do mesmo ISBN
+        let primeiro = cadastrar(&mut biblioteca, "Dom Casmurro", Some("85-359-0277-5"));
+        let segundo = cadastrar(&mut biblioteca, "Dom Casmurro", Some("8535902775"));
+        let emprestado = cadastrar(&mut biblioteca, "Esaú e Jacó", None);
+        let fora = cadastrar(&mut biblioteca, "Iaiá Garcia", None);
+        biblioteca.realizar_emprestimo(leitor, emprestado).unwrap();
+
+        let mut conferencia = Conferencia::new(&biblioteca);
+        assert!(matches!(
+            conferencia.registrar(&helena.to_string()),
+            Leitura::Encontrado(id) if id == helena
+        ));
+        assert!(matches!(
+            conferencia.registrar(&helena.to_string()),
+            Leitura::Repetido(id) if id == helena
+        ));
+        let (a, b) = if primeiro < segundo {
+            (primeiro, segundo)
+        } else {
+            (segundo, primeiro)
+        };
+        assert!(matches!(conferencia.registrar("8535902775"), Leitura::Encontrado(id) if id == a));
+        assert!(
+            matches!(conferencia.registrar(" 85-359-0277-5 "), Leitura::Encontrado(id) if id == b)
+        );
+        assert!(matches!(
+            conferencia.registrar("8535902775"),
+            Leitura::Repetido(_)
+        ));
+        assert!(matches!(
+            conferencia.registrar("0000000000"),
+            Leitura::Desconhecido
+        ));
+        conferencia.registrar(&emprestado.to_string());
+
+        let resultado = conferencia.concluir();
+        assert_eq!(resultado.lidos, 4);
+        assert_eq!(resultado.ausentes, [fora]);
+        assert_eq!(resultado.emprestados, [emprestado]);
+        assert!(resultado.achados.is_empty());
+        assert_eq!(resultado.desconhecidos, ["0000000000"]);
+    }
+
+    #[test]
+    fn extraviado_nao_circula_ate_ser_achado() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, relogio) = biblioteca(&pasta);
+        let leitor = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        let perdido = cadastrar(&mut biblioteca, "Helena", None);
+        let outro = cadastrar(&mut biblioteca, "Iaiá Garcia", None);
+
+        let ausentes = Conferencia::new(&biblioteca).concluir().ausentes;
+        assert_eq!(ausentes.len(), 2);
+        // Tudo ou nada: um id desconhecido desfaz a marcação dos outros
+        assert!(matches!(
+            biblioteca.definir_extravio(&[perdido, Uuid::new_v4()], true),
+            Err(ErroBiblioteca::LivroNaoEncontrado(_))
+        ));
+        assert_eq!(biblioteca.livros()[&perdido].extraviado(), None);
+
+        biblioteca.definir_extravio(&[perdido], true).unwrap();
+        assert_eq!(biblioteca.livros()[&perdido].extraviado(), Some(dia(3, 4)));
+        // Marcar de novo não muda a data do extravio
+        relogio.avancar(chrono::Duration::days(2));
+        biblioteca.definir_extravio(&[perdido], true).unwrap();
+        assert_eq!(biblioteca.livros()[&perdido].extraviado(), Some(dia(3, 4)));
+
+        match biblioteca.realizar_emprestimo(leitor, perdido) {
+            Err(ErroBiblioteca::EstadoInvalido(mensagem)) => {
+                assert!(mensagem.contains("extraviado"), "{}", mensagem)
+            }
+            outro => panic!("esperava recusa, veio {:?}", outro),
+        }
+        // Já extraviado, não é cobrado de novo como ausente
+        assert_eq!(Conferencia::new(&biblioteca).concluir().ausentes, [outro]);
+
+        // Lido na estante: aparece como achado e, desmarcado, volta a circular
+        let mut conferencia = Conferencia::new(&biblioteca);
+        conferencia.registrar(&perdido.to_string());
+        assert_eq!(conferencia.concluir().achados, [perdido]);
+        biblioteca.definir_extravio(&[perdido], false).unwrap();
+        assert_eq!(biblioteca.livros()[&perdido].extraviado(), None);
+        biblioteca.realizar_emprestimo(leitor, perdido).unwrap();
+    }
+
+    #[test]
+    fn com_unidade_so_espera_o_que_esta_nela() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, _) = biblioteca(&pasta);
+        let centro = biblioteca
+            .cadastrar_unidade("CENTRO", "Central".to_string())
+            .unwrap();
+        let norte = biblioteca
+            .cadastrar_unidade("NORTE", "Zona Norte".to_string())
+            .unwrap();
+        biblioteca.definir_unidade_atual(Some(centro));
+        let no_centro = cadastrar(&mut biblioteca, "Helena", None);
+        biblioteca.definir_unidade_atual(Some(norte));
+        let no_norte = cadastrar(&mut biblioteca, "Iaiá Garcia", None);
+
+        assert_eq!(
+            Conferencia::new(&biblioteca).concluir().ausentes,
+            [no_norte]
+        );
+        biblioteca.definir_unidade_atual(Some(centro));
+        assert_eq!(
+            Conferencia::new(&biblioteca).concluir().ausentes,
+            [no_centro]
+        );
+    }
+}
@@ -1,69 +1,259 @@
 // ************* ATENÇãO *******************
 // rodar cargo run no diretorio projeto2 (:
 
-
-
+mod argumentos;
 mod biblioteca;
+mod citacoes;
+mod comandos;
+mod configuracao;
+mod documentos;
+mod entrada;
 mod errors;
+mod inventario;
+mod marc;
+mod notificacoes;
+mod oai;
+mod planilha;
+mod relatorios;
+mod servidor;
 mod traits;
+mod tui;
 
-use biblioteca::Biblioteca;
+use argumentos::Argumentos;
+use biblioteca::{
+    Biblioteca,
+    autosalvamento::ModoAutosalvamento,
+    calendario::Calendario,
+    contas::Contas,
+    relogio::{Relogio, RelogioFixo},
+};
+use chrono::{Local, NaiveTime};
+use comandos::ajuda;
+use configuracao::Configuracao;
+use errors::ErroBiblioteca;
 use std::path::Path;
+use std::sync::Arc;
+
+// Grava o que estiver pendente se o processo for interrompido
+// (Ctrl+C, kill, terminal fechado) ou entrar em pânico
+fn proteger_alteracoes(biblioteca: &Biblioteca) {
+    let salvaguarda = biblioteca.salvaguarda();
+
+    let ao_sinal = salvaguarda.clone();
+    let resultado = ctrlc::set_handler(move || {
+        match ao_sinal.salvar_pendencias() {
+            Ok(true) => eprintln!("\nInterrompido. Alterações pendentes foram salvas."),
+            Ok(false) => eprintln!("\nInterrompido."),
+            Err(e) => eprintln!("\nInterrompido. {}", e),
+        }
+        std::process::exit(130);
+    });
+    if let Err(e) = resultado {
+        eprintln!(
+            "Aviso: não foi possível tratar sinais de encerramento: {}",
+            e
+        );
+    }
+
+    let gancho_padrao = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = salvaguarda.salvar_pendencias();
+        gancho_padrao(info);
+    }));
+}
 
 fn main() {
+    let argumentos = Argumentos::do_ambiente();
+
+    // Opção antiga: quem opera é a conta do login, e a auditoria filtra por --feito-por
+    if argumentos.tem_opcao("operador") {
+        eprintln!(
+            "--operador não existe mais: as operações ficam em nome de quem fez login \
+             (--login). Para filtrar a auditoria por funcionário, use --feito-por LOGIN."
+        );
+        std::process::exit(2);
+    }
+
+    // A ajuda não depende da configuração nem dos dados
+    if argumentos.comando() == Some("ajuda") {
+        ajuda::executar(argumentos.posicionais.get(1).map(String::as_str));
+        return;
+    }
+
+    let configuracao = match Configuracao::carregar(argumentos.opcao("config")) {
+        Ok(configuracao) => configuracao,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Caminho do arquivo JSON de persistência
-    let caminho_arquivo = Path::new("dados_biblioteca.json");
+    let caminho_arquivo = configuracao.caminho_dados();
 
-    // Tenta carregar a biblioteca existente, ou cria nova se o arquivo não existir
+    // Só mostra o que está valendo; não precisa de login nem dos dados
+    if argumentos.comando() == Some("config") {
+        comandos::config::executar(&configuracao, &argumentos);
+        return;
+    }
+
+    // Sem arquivo, a biblioteca começa vazia. Se ele existe e não pôde ser lido,
+    // encerra antes de armar autosalvamento e salvaguardas: começar vazio aqui
+    // gravaria uma biblioteca vazia por cima dos dados
     let mut biblioteca = match Biblioteca::carregar(caminho_arquivo) {
         Ok(b) => b,
         Err(e) => {
-            eprintln!("Erro ao carregar biblioteca: {:?}", e);
-            println!("Criando nova biblioteca...");
-            Biblioteca::nova(caminho_arquivo)
+            eprintln!("Erro ao carregar biblioteca: {}", e);
+            eprintln!(
+                "Nada foi alterado em {}. Corrija ou restaure o arquivo e rode de novo.",
+                caminho_arquivo.display()
+            );
+            std::process::exit(1);
         }
     };
 
-    loop {
-        println!("\n===== MENU BIBLIOTECA =====");
-        println!("[1] Adicionar livro");
-        println!("[2] Adicionar usuário");
-        println!("[3] Registrar empréstimo");
-        println!("[4] Listar livros");
-        println!("[5] Listar usuários");
-        println!("[6] Listar empréstimos");
-        println!("[7] Salvar e sair");
-
-        let opcao = biblioteca::ler_i32("Escolha uma opção: ".to_string());
-
-        match opcao {
-            1 => {
-                if let Err(e) = biblioteca.adicionar_livro() {
-                    println!("Erro ao adicionar livro: {:?}", e);
-                }
+    let autosalvar = argumentos
+        .opcao("autosalvar")
+        .unwrap_or(&configuracao.armazenamento.autosalvar);
+    match ModoAutosalvamento::interpretar(autosalvar) {
+        Ok(modo) => biblioteca.definir_autosalvamento(modo),
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    }
+    proteger_alteracoes(&biblioteca);
+    biblioteca.definir_politicas(configuracao.emprestimos.clone());
+
+    match Calendario::carregar(caminho_arquivo, configuracao.calendario.as_ref()) {
+        Ok(calendario) => {
+            if calendario.diverge_de(configuracao.calendario.as_ref()) {
+                eprintln!(
+                    "Aviso: o [calendario] de {} não vale mais; o calendário em vigor está em {} \
+                     e é alterado pelo comando calendario. Apague a seção para não confundir.",
+                    configuracao
+                        .origem
+                        .as_deref()
+                        .unwrap_or(Path::new("biblioteca.toml"))
+                        .display(),
+                    Calendario::caminho_calendario(caminho_arquivo).display()
+                );
             }
-            2 => {
-                if let Err(e) = biblioteca.adicionar_usuario() {
-                    println!("Erro ao adicionar usuário: {:?}", e);
-                }
+            biblioteca.definir_calendario(calendario);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    }
+
+    // Para depuração: a biblioteca passa a achar que hoje é o dia dado, no
+    // horário em que o programa começou (o relógio fica parado durante a execução)
+    if let Some(texto) = argumentos.opcao("data-atual") {
+        let relogio = comandos::ler_data(texto).and_then(|data| {
+            RelogioFixo::no_dia(data).ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos(format!("Data inexistente neste fuso: {}", data))
+            })
+        });
+        match relogio {
+            Ok(relogio) => {
+                relogio.avancar(Local::now().time() - NaiveTime::MIN);
+                eprintln!(
+                    "Aviso: simulando a data {} (--data-atual).",
+                    relogio.hoje().format("%d/%m/%Y")
+                );
+                biblioteca.definir_relogio(Arc::new(relogio));
             }
-            3 => {
-                if let Err(e) = biblioteca.registrar_emprestimo() {
-                    println!("Erro ao registrar empréstimo: {:?}", e);
-                }
+            Err(e) => {
+                eprintln!("--data-atual: {}", e);
+                return;
+            }
+        }
+    }
+
+    // Unidade onde o programa está rodando: empréstimos e devoluções ficam
+    // registrados nela
+    if let Some(sigla) = argumentos
+        .opcao("unidade")
+        .or(configuracao.biblioteca.unidade.as_deref())
+    {
+        match biblioteca.unidade(sigla).map(|u| u.id) {
+            Ok(id) => biblioteca.definir_unidade_atual(Some(id)),
+            // Sem isso não haveria como cadastrar a unidade já configurada
+            Err(e) if argumentos.comando() == Some("unidades") => eprintln!("Aviso: {}", e),
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+    }
+
+    let mut contas = match Contas::carregar(caminho_arquivo) {
+        Ok(contas) => contas,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    // O servidor autentica cada requisição; os outros modos pedem login aqui
+    if argumentos.comando() == Some("serve") {
+        if contas.esta_vazia() {
+            eprintln!(
+                "Nenhuma conta de funcionário cadastrada. Rode o programa sem comandos para criar o administrador."
+            );
+            return;
+        }
+    } else {
+        match comandos::contas::entrar(&mut contas, &argumentos) {
+            Ok(sessao) => {
+                println!("Conectado como {} ({}).", sessao.login, sessao.papel);
+                biblioteca.entrar(sessao);
             }
-            4 => biblioteca.listar_livros(),
-            5 => biblioteca.listar_usuarios(),
-            6 => biblioteca.listar_emprestimos(),
-            7 => {
-                if let Err(e) = biblioteca.salvar() {
-                    println!("Erro ao salvar biblioteca: {:?}", e);
-                } else {
-                    println!("Biblioteca salva com sucesso. Saindo...");
+            Err(e) => {
+                if !matches!(e, ErroBiblioteca::EntradaEncerrada) {
+                    eprintln!("{}", e);
                 }
-                break;
+                std::process::exit(1);
             }
-            _ => println!("Opção inválida!"),
         }
     }
+
+    match argumentos.comando() {
+        None => comandos::menu::executar(&mut biblioteca),
+        Some("tui") => {
+            if let Err(e) = tui::executar(&mut biblioteca) {
+                eprintln!("Erro na interface de tela cheia: {}", e);
+            }
+        }
+        Some("serve") => {
+            comandos::servidor::executar(&mut biblioteca, &mut contas, &configuracao, &argumentos)
+        }
+        Some("compactar") => match biblioteca.compactar() {
+            Ok(()) => println!("Diário de eventos compactado."),
+            Err(e) => eprintln!("Erro ao compactar: {}", e),
+        },
+        Some("reconstruir") => {
+            comandos::reconstruir::executar(caminho_arquivo, &biblioteca, &argumentos)
+        }
+        Some("auditoria") => comandos::auditoria::executar(&biblioteca, &argumentos),
+        Some("verificar") => {
+            comandos::verificar::executar(caminho_arquivo, &mut biblioteca, &argumentos)
+        }
+        Some("contas") => comandos::contas::executar(&mut contas, &biblioteca, &argumentos),
+        Some("importar") => comandos::planilha::importar(&mut biblioteca, &argumentos),
+        Some("exportar") => comandos::planilha::exportar(&biblioteca, &argumentos),
+        Some("citar") => comandos::citar::executar(&biblioteca, &argumentos),
+        Some("relatorio") => comandos::relatorio::executar(&biblioteca, &argumentos),
+        Some("documento") => comandos::documento::executar(&biblioteca, &configuracao, &argumentos),
+        Some("notificar") => comandos::notificar::executar(&biblioteca, &configuracao, &argumentos),
+        Some("calendario") => {
+            comandos::calendario::executar(caminho_arquivo, &mut biblioteca, &argumentos)
+        }
+        Some("unidades") => comandos::unidades::executar(&mut biblioteca, &argumentos),
+        Some("intercambio") => comandos::intercambio::executar(&mut biblioteca, &argumentos),
+        Some("inventario") => comandos::inventario::executar(&mut biblioteca, &argumentos),
+        // Não é nenhum dos comandos acima: avisa e mostra o uso geral
+        Some(outro) => ajuda::executar(Some(outro)),
+    }
 }
@@ -0,0 +1,253 @@
+// ISO 2709: líder de 24 bytes, diretório com a posição de cada campo e os
+// campos em sequência, separados por caracteres de controle.
+
+use crate::errors::ErroBiblioteca;
+
+use super::{CampoDados, LIDER_PADRAO, Registro};
+
+const FIM_CAMPO: u8 = 0x1E;
+const FIM_REGISTRO: u8 = 0x1D;
+const DELIMITADOR: u8 = 0x1F;
+
+// Tamanhos máximos que cabem nos números de largura fixa do formato
+const MAIOR_CAMPO: usize = 9_999;
+const MAIOR_REGISTRO: usize = 99_999;
+
+fn numero(bytes: &[u8]) -> Option<usize> {
+    std::str::from_utf8(bytes).ok()?.trim().parse().ok()
+}
+
+// Registros MARC-8 (posição 9 do líder em branco) são lidos como se fossem
+// UTF-8: o texto ASCII sai certo, mas acentos podem sair trocados
+fn texto(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn ler_registro(bruto: &[u8]) -> Result<Registro, String> {
+    if bruto.len() < 24 {
+        return Err("registro menor que o líder".to_string());
+    }
+    let base = numero(&bruto[12..17]).ok_or("endereço base inválido no líder")?;
+    if base < 25 || base > bruto.len() {
+        return Err(format!("endereço base {} fora do registro", base));
+    }
+
+    let mut registro = Registro {
+        lider: texto(&bruto[..24]),
+        ..Registro::default()
+    };
+
+    // O diretório termina com um FIM_CAMPO logo antes do endereço base
+    for entrada in bruto[24..base - 1].chunks_exact(12) {
+        let tag = texto(&entrada[..3]);
+        let tamanho =
+            numero(&entrada[3..7]).ok_or_else(|| format!("tamanho inválido no campo {}", tag))?;
+        let inicio =
+            numero(&entrada[7..12]).ok_or_else(|| format!("posição inválida no campo {}", tag))?;
+
+        let dados = bruto
+            .get(base + inicio..base + inicio + tamanho)
+            .ok_or_else(|| format!("campo {} ultrapassa o fim do registro", tag))?;
+        let dados = dados.strip_suffix(&[FIM_CAMPO]).unwrap_or(dados);
+
+        if tag.starts_with("00") {
+            registro.controle.push((tag, texto(dados)));
+            continue;
+        }
+
+        let indicador = |posicao: usize| dados.get(posicao).map_or(' ', |b| *b as char);
+        let subcampos = dados
+            .get(2..)
+            .unwrap_or_default()
+            .split(|b| *b == DELIMITADOR)
+            .skip(1)
+            .filter(|subcampo| !subcampo.is_empty())
+            .map(|subcampo| (subcampo[0] as char, texto(&subcampo[1..])))
+            .collect();
+        registro.dados.push(CampoDados {
+            tag,
+            ind1: indicador(0),
+            ind2: indicador(1),
+            subcampos,
+        });
+    }
+
+    Ok(registro)
+}
+
+pub fn ler(bytes: &[u8]) -> Result<Vec<Registro>, ErroBiblioteca> {
+    bytes
+        .split(|b| *b == FIM_REGISTRO)
+        // Alguns sistemas põem uma quebra de linha entre os registros
+        .map(|bruto| bruto.trim_ascii_start())
+        .filter(|bruto| !bruto.is_empty())
+        .enumerate()
+        .map(|(indice, bruto)| {
+            ler_registro(bruto).map_err(|motivo| {
+                ErroBiblioteca::DadosInvalidos(format!("Registro {}: {}", indice + 1, motivo))
+            })
+        })
+        .collect()
+}
+
+// O diretório e os delimitadores contam bytes, não caracteres: uma tag ou um
+// código fora do ASCII deslocaria todas as posições do registro
+fn validar_campos(registro: &Registro) -> Result<(), ErroBiblioteca> {
+    let invalido = |motivo: String| Err(ErroBiblioteca::DadosInvalidos(motivo));
+    let tags = registro
+        .controle
+        .iter()
+        .map(|(tag, _)| tag)
+        .chain(registro.dados.iter().map(|campo| &campo.tag));
+    for tag in tags {
+        if tag.len() != 3 || !tag.bytes().all(|b| b.is_ascii_graphic()) {
+            return invalido(format!(
+                "Tag \"{}\" inválida: deve ter exatamente 3 caracteres ASCII.",
+                tag
+            ));
+        }
+    }
+    for campo in &registro.dados {
+        for indicador in [campo.ind1, campo.ind2] {
+            if !(indicador.is_ascii_graphic() || indicador == ' ') {
+                return invalido(format!(
+                    "Indicador {:?} do campo {} inválido: deve ser um caractere ASCII.",
+                    indicador, campo.tag
+                ));
+            }
+        }
+        if let Some((codigo, _)) = campo
+            .subcampos
+            .iter()
+            .find(|(codigo, _)| !codigo.is_ascii_graphic())
+        {
+            return invalido(format!(
+                "Código de subcampo {:?} do campo {} inválido: deve ser um caractere ASCII.",
+                codigo, campo.tag
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn escrever_registro(registro: &Registro, saida: &mut Vec<u8>) -> Result<(), ErroBiblioteca> {
+    validar_campos(registro)?;
+    let campos = registro
+        .controle
+        .iter()
+        .map(|(tag, valor)| (tag, valor.as_bytes().to_vec()))
+        .chain(registro.dados.iter().map(|campo| {
+            let mut bytes = vec![campo.ind1 as u8, campo.ind2 as u8];
+            for (codigo, valor) in &campo.subcampos {
+                bytes.push(DELIMITADOR);
+                bytes.push(*codigo as u8);
+                bytes.extend(valor.as_bytes());
+            }
+            (&campo.tag, bytes)
+        }));
+
+    let mut diretorio = Vec::new();
+    let mut corpo = Vec::new();
+    for (tag, mut bytes) in campos {
+        bytes.push(FIM_CAMPO);
+        if bytes.len() > MAIOR_CAMPO {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Campo {} grande demais para ISO 2709.",
+                tag
+            )));
+        }
+        diretorio.extend(format!("{}{:04}{:05}", tag, bytes.len(), corpo.len()).as_bytes());
+        corpo.extend(bytes);
+    }
+    diretorio.push(FIM_CAMPO);
+
+    let base = 24 + diretorio.len();
+    let total = base + corpo.len() + 1;
+    if total > MAIOR_REGISTRO {
+        return Err(ErroBiblioteca::DadosInvalidos(
+            "Registro grande demais para ISO 2709.".to_string(),
+        ));
+    }
+
+    let mut lider = if registro.lider.len() == 24 && registro.lider.is_ascii() {
+        registro.lider.clone().into_bytes()
+    } else {
+        LIDER_PADRAO.as_bytes().to_vec()
+    };
+    lider[..5].copy_from_slice(format!("{:05}", total).as_bytes());
+    // Sempre gravamos em UTF-8, com indicadores e códigos de subcampo de uma posição
+    lider[9] = b'a';
+    lider[10..12].copy_from_slice(b"22");
+    lider[12..17].copy_from_slice(format!("{:05}", base).as_bytes());
+    lider[20..24].copy_from_slice(b"4500");
+
+    saida.extend(lider);
+    saida.extend(diretorio);
+    saida.extend(corpo);
+    saida.push(FIM_REGISTRO);
+    Ok(())
+}
+
+pub fn escrever(registros: &[Registro]) -> Result<Vec<u8>, ErroBiblioteca> {
+    let mut saida = Vec::new();
+    for registro in registros {
+        escrever_registro(registro, &mut saida)?;
+    }
+    Ok(saida)
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn registro(tag: &str, ind1: char, codigo: char) -> Registro {
+        Registro {
+            lider: LIDER_PADRAO.to_string(),
+            controle: vec![("001".to_string(), "42".to_string())],
+            dados: vec![CampoDados {
+                tag: tag.to_string(),
+                ind1,
+                ind2: ' ',
+                subcampos: vec![(codigo, "Dom Casmurro".to_string())],
+            }],
+        }
+    }
+
+    fn recusado(registro: Registro) -> String {
+        match escrever(&[registro]) {
+            Err(ErroBiblioteca::DadosInvalidos(motivo)) => motivo,
+            outro => panic!("esperava DadosInvalidos, veio {:?}", outro),
+        }
+    }
+
+    #[test]
+    fn tags_precisam_de_tres_caracteres_ascii() {
+        assert!(recusado(registro("24", '1', 'a')).contains("\"24\""));
+        assert!(recusado(registro("2450", '1', 'a')).contains("\"2450\""));
+        assert!(recusado(registro("24é", '1', 'a')).contains("3 caracteres ASCII"));
+
+        let mut controle = registro("245", '1', 'a');
+        controle.controle[0].0 = "1".to_string();
+        assert!(recusado(controle).contains("\"1\""));
+    }
+
+    #[test]
+    fn indicadores_e_codigos_precisam_ser_ascii() {
+        assert!(recusado(registro("245", 'ã', 'a')).contains("Indicador"));
+        assert!(recusado(registro("245", '1', 'ç')).contains("subcampo"));
+        assert!(recusado(registro("245", '1', '\u{1f}')).contains("subcampo"));
+    }
+
+    #[test]
+    fn registro_valido_volta_igual() {
+        let original = registro("245", '1', 'a');
+        let bytes = escrever(std::slice::from_ref(&original)).expect("escrever");
+        let lidos = ler(&bytes).expect("ler");
+
+        assert_eq!(lidos.len(), 1);
+        assert_eq!(lidos[0].controle, original.controle);
+        assert_eq!(lidos[0].dados, original.dados);
+        assert_eq!(&lidos[0].lider[..5], format!("{:05}", bytes.len()));
+        assert_eq!(escrever(&lidos).expect("escrever de novo"), bytes);
+    }
+}
@@ -0,0 +1,170 @@
+// MARCXML (esquema MARC21/slim da Library of Congress). Os elementos podem
+// vir com ou sem prefixo de namespace; só o nome local importa.
+
+use quick_xml::{
+    Reader,
+    escape::escape,
+    events::{BytesStart, Event},
+};
+
+use crate::errors::ErroBiblioteca;
+
+use super::{CampoDados, Registro};
+
+const NAMESPACE: &str = "http://www.loc.gov/MARC21/slim";
+
+fn erro(posicao: u64, motivo: impl std::fmt::Display) -> ErroBiblioteca {
+    ErroBiblioteca::DadosInvalidos(format!("MARCXML inválido (byte {}): {}", posicao, motivo))
+}
+
+fn atributo(elemento: &BytesStart, nome: &str) -> Option<String> {
+    elemento
+        .try_get_attribute(nome)
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok())
+        .map(|valor| valor.into_owned())
+}
+
+fn indicador(elemento: &BytesStart, nome: &str) -> char {
+    atributo(elemento, nome)
+        .and_then(|valor| valor.chars().next())
+        .unwrap_or(' ')
+}
+
+// O que está sendo lido no momento (o texto dos elementos folha)
+enum Folha {
+    Nenhuma,
+    Lider,
+    Controle(String),
+    Subcampo(char),
+}
+
+pub fn ler(xml: &str) -> Result<Vec<Registro>, ErroBiblioteca> {
+    let mut leitor = Reader::from_str(xml);
+    let mut registros = Vec::new();
+    let mut atual: Option<Registro> = None;
+    let mut folha = Folha::Nenhuma;
+    let mut texto = String::new();
+
+    loop {
+        let posicao = leitor.buffer_position();
+        let evento = leitor.read_event().map_err(|e| erro(posicao, e))?;
+        match evento {
+            Event::Start(ref elemento) | Event::Empty(ref elemento) => {
+                let vazio = matches!(evento, Event::Empty(_));
+                texto.clear();
+                match elemento.local_name().as_ref() {
+                    b"record" if !vazio => atual = Some(Registro::default()),
+                    b"leader" => folha = Folha::Lider,
+                    b"controlfield" => {
+                        let tag = atributo(elemento, "tag")
+                            .ok_or_else(|| erro(posicao, "controlfield sem tag"))?;
+                        folha = Folha::Controle(tag);
+                    }
+                    b"datafield" => {
+                        let tag = atributo(elemento, "tag")
+                            .ok_or_else(|| erro(posicao, "datafield sem tag"))?;
+                        if let Some(registro) = atual.as_mut() {
+                            registro.dados.push(CampoDados {
+                                tag,
+                                ind1: indicador(elemento, "ind1"),
+                                ind2: indicador(elemento, "ind2"),
+                                subcampos: Vec::new(),
+                            });
+                        }
+                    }
+                    b"subfield" => {
+                        let codigo = atributo(elemento, "code")
+                            .and_then(|codigo| codigo.chars().next())
+                            .ok_or_else(|| erro(posicao, "subfield sem code"))?;
+                        folha = Folha::Subcampo(codigo);
+                    }
+                    _ => {}
+                }
+                // Elemento vazio (<subfield code="a"/>) termina aqui mesmo
+                if vazio {
+                    fechar_folha(&mut atual, &mut folha, &mut texto);
+                }
+            }
+            // Texto fora das folhas é só a indentação entre elementos
+            Event::Text(conteudo) if !matches!(folha, Folha::Nenhuma) => {
+                texto.push_str(&conteudo.unescape().map_err(|e| erro(posicao, e))?);
+            }
+            Event::CData(conteudo) if !matches!(folha, Folha::Nenhuma) => {
+                texto.push_str(&String::from_utf8_lossy(&conteudo));
+            }
+            Event::End(elemento) => match elemento.local_name().as_ref() {
+                b"record" => registros.extend(atual.take()),
+                b"leader" | b"controlfield" | b"subfield" => {
+                    fechar_folha(&mut atual, &mut folha, &mut texto)
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(registros)
+}
+
+fn fechar_folha(atual: &mut Option<Registro>, folha: &mut Folha, texto: &mut String) {
+    let valor = std::mem::take(texto);
+    let Some(registro) = atual.as_mut() else {
+        *folha = Folha::Nenhuma;
+        return;
+    };
+    match std::mem::replace(folha, Folha::Nenhuma) {
+        Folha::Nenhuma => {}
+        Folha::Lider => registro.lider = valor,
+        Folha::Controle(tag) => registro.controle.push((tag, valor)),
+        Folha::Subcampo(codigo) => {
+            if let Some(campo) = registro.dados.last_mut() {
+                campo.subcampos.push((codigo, valor.trim().to_string()));
+            }
+        }
+    }
+}
+
+pub fn escrever(registros: &[Registro]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<collection xmlns=\"{}\">\n",
+        NAMESPACE
+    );
+
+    for registro in registros {
+        xml.push_str("  <record>\n");
+        xml.push_str(&format!(
+            "    <leader>{}</leader>\n",
+            escape(&registro.lider)
+        ));
+        for (tag, valor) in &registro.controle {
+            xml.push_str(&format!(
+                "    <controlfield tag=\"{}\">{}</controlfield>\n",
+                escape(tag),
+                escape(valor)
+            ));
+        }
+        for campo in &registro.dados {
+            xml.push_str(&format!(
+                "    <datafield tag=\"{}\" ind1=\"{}\" ind2=\"{}\">\n",
+                escape(&campo.tag),
+                escape(campo.ind1.to_string()),
+                escape(campo.ind2.to_string())
+            ));
+            for (codigo, valor) in &campo.subcampos {
+                xml.push_str(&format!(
+                    "      <subfield code=\"{}\">{}</subfield>\n",
+                    escape(codigo.to_string()),
+                    escape(valor)
+                ));
+            }
+            xml.push_str("    </datafield>\n");
+        }
+        xml.push_str("  </record>\n");
+    }
+
+    xml.push_str("</collection>\n");
+    xml
+}
@@ -0,0 +1,478 @@
+// Conversão entre `Livro` e registros bibliográficos MARC 21, nos formatos
+// ISO 2709 (binário) e MARCXML. Só alguns campos têm correspondência no
+// cadastro; os demais são relatados como não mapeados na importação.
+
+mod iso2709;
+mod marcxml;
+
+use std::{collections::BTreeMap, fmt};
+
+use chrono::NaiveDate;
+
+use crate::biblioteca::{
+    Biblioteca,
+    livros::{Catalogacao, Livro},
+};
+use crate::errors::ErroBiblioteca;
+
+// Campos de dados que viram informação do livro
+const CAMPOS_MAPEADOS: &[&str] = &["020", "100", "245", "260", "264", "650", "700"];
+
+// Campos de controle esperados em qualquer registro (identificador, origem,
+// data de alteração, dados fixos); não valem um aviso de "não mapeado"
+const CAMPOS_CONTROLE: &[&str] = &["001", "003", "005", "008"];
+
+// Registro novo, em UTF-8, sem pontuação ISBD; tamanho e endereço base são
+// preenchidos na gravação em ISO 2709
+const LIDER_PADRAO: &str = "00000nam a2200000 c 4500";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Registro {
+    pub lider: String,
+    // Campos 001 a 009: só texto, sem indicadores nem subcampos
+    pub controle: Vec<(String, String)>,
+    pub dados: Vec<CampoDados>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CampoDados {
+    pub tag: String,
+    pub ind1: char,
+    pub ind2: char,
+    pub subcampos: Vec<(char, String)>,
+}
+
+impl CampoDados {
+    fn novo(tag: &str, ind1: char, ind2: char, subcampos: Vec<(char, String)>) -> Self {
+        CampoDados {
+            tag: tag.to_string(),
+            ind1,
+            ind2,
+            subcampos,
+        }
+    }
+
+    fn subcampo(&self, codigo: char) -> Option<&str> {
+        self.subcampos
+            .iter()
+            .find(|(c, _)| *c == codigo)
+            .map(|(_, valor)| valor.as_str())
+    }
+}
+
+impl Registro {
+    fn campos<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a CampoDados> {
+        self.dados.iter().filter(move |campo| campo.tag == tag)
+    }
+
+    fn controle(&self, tag: &str) -> Option<&str> {
+        self.controle
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, valor)| valor.as_str())
+    }
+
+    // Tags presentes no registro que a conversão não aproveita
+    fn nao_mapeados(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .controle
+            .iter()
+            .map(|(tag, _)| tag)
+            .filter(|tag| !CAMPOS_CONTROLE.contains(&tag.as_str()))
+            .chain(
+                self.dados
+                    .iter()
+                    .map(|campo| &campo.tag)
+                    .filter(|tag| !CAMPOS_MAPEADOS.contains(&tag.as_str())),
+            )
+            .cloned()
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Formato {
+    Iso2709,
+    MarcXml,
+}
+
+impl Formato {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "iso2709" | "mrc" => Ok(Formato::Iso2709),
+            "marcxml" | "xml" => Ok(Formato::MarcXml),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Formato MARC desconhecido: {} (use iso2709 ou marcxml).",
+                outro
+            ))),
+        }
+    }
+
+    // Pela extensão: .xml é MARCXML; o resto, ISO 2709
+    pub fn do_arquivo(caminho: &str) -> Self {
+        if caminho.to_lowercase().ends_with(".xml") {
+            Formato::MarcXml
+        } else {
+            Formato::Iso2709
+        }
+    }
+}
+
+pub fn ler(formato: Formato, bytes: &[u8]) -> Result<Vec<Registro>, ErroBiblioteca> {
+    match formato {
+        Formato::Iso2709 => iso2709::ler(bytes),
+        Formato::MarcXml => {
+            let texto = std::str::from_utf8(bytes).map_err(|e| {
+                ErroBiblioteca::DadosInvalidos(format!("MARCXML não está em UTF-8: {}", e))
+            })?;
+            marcxml::ler(texto)
+        }
+    }
+}
+
+pub fn escrever(formato: Formato, registros: &[Registro]) -> Result<Vec<u8>, ErroBiblioteca> {
+    match formato {
+        Formato::Iso2709 => iso2709::escrever(registros),
+        Formato::MarcXml => Ok(marcxml::escrever(registros).into_bytes()),
+    }
+}
+
+// Tira a pontuação ISBD que os catalogadores deixam no fim dos subcampos
+// ("Dom Casmurro /", "Assis, Machado de,", "Garnier,")
+fn limpar(texto: &str) -> String {
+    texto
+        .trim()
+        .trim_end_matches([' ', '/', ':', ';', ',', '=', '.'])
+        .trim()
+        .to_string()
+}
+
+// Primeiro número de quatro dígitos: "c1899.", "[1899]", "1899-1900"
+fn extrair_ano(texto: &str) -> Option<u16> {
+    texto
+        .as_bytes()
+        .windows(4)
+        .find(|janela| janela.iter().all(u8::is_ascii_digit))
+        .and_then(|janela| std::str::from_utf8(janela).ok()?.parse().ok())
+}
+
+fn nao_vazio(texto: String) -> Option<String> {
+    (!texto.is_empty()).then_some(texto)
+}
+
+// Livro pronto para cadastro, extraído de um registro
+pub struct LivroMarc {
+    pub titulo: String,
+    pub autor: String,
+    pub ano: u16,
+    pub catalogacao: Catalogacao,
+}
+
+pub fn para_livro(registro: &Registro) -> Result<LivroMarc, ErroBiblioteca> {
+    let sem = |o_que: &str| ErroBiblioteca::DadosInvalidos(format!("Registro sem {}.", o_que));
+
+    let titulo = registro
+        .campos("245")
+        .next()
+        .and_then(|campo| {
+            let titulo = limpar(campo.subcampo('a')?);
+            Some(match campo.subcampo('b').map(limpar) {
+                Some(subtitulo) if !subtitulo.is_empty() => format!("{}: {}", titulo, subtitulo),
+                _ => titulo,
+            })
+        })
+        .and_then(nao_vazio)
+        .ok_or_else(|| sem("título (245 $a)"))?;
+
+    // O autor principal vem do 100; sem ele, o primeiro 700 assume o lugar
+    let mut autores: Vec<String> = registro
+        .campos("100")
+        .chain(registro.campos("700"))
+        .filter_map(|campo| campo.subcampo('a'))
+        .map(limpar)
+        .filter(|autor| !autor.is_empty())
+        .collect();
+    if autores.is_empty() {
+        return Err(sem("autor (100 $a ou 700 $a)"));
+    }
+    let autor = autores.remove(0);
+
+    // 264 com segundo indicador 1 é a publicação; 260 é a forma antiga do mesmo campo
+    let publicacao = registro
+        .campos("264")
+        .find(|campo| campo.ind2 == '1')
+        .or_else(|| registro.campos("260").next())
+        .or_else(|| registro.campos("264").next());
+
+    let ano = publicacao
+        .and_then(|campo| campo.subcampo('c'))
+        .and_then(extrair_ano)
+        .or_else(|| {
+            registro
+                .controle("008")
+                .and_then(|fixos| fixos.get(7..11))
+                .and_then(extrair_ano)
+        })
+        .ok_or_else(|| sem("ano de publicação (260/264 $c ou 008)"))?;
+
+    let editora = publicacao
+        .and_then(|campo| campo.subcampo('b'))
+        .map(limpar)
+        .and_then(nao_vazio);
+
+    // "85-359-0277-5 (broch.)": só o número interessa
+    let isbn = registro
+        .campos("020")
+        .find_map(|campo| campo.subcampo('a'))
+        .and_then(|texto| texto.split_whitespace().next())
+        .map(limpar)
+        .and_then(nao_vazio);
+
+    // Subdivisões do assunto viram "Tópico -- Subdivisão"
+    let assuntos = registro
+        .campos("650")
+        .map(|campo| {
+            campo
+                .subcampos
+                .iter()
+                .filter(|(codigo, _)| matches!(codigo, 'a' | 'b' | 'v' | 'x' | 'y' | 'z'))
+                .map(|(_, valor)| limpar(valor))
+                .filter(|valor| !valor.is_empty())
+                .collect::<Vec<_>>()
+                .join(" -- ")
+        })
+        .filter(|assunto| !assunto.is_empty())
+        .collect();
+
+    Ok(LivroMarc {
+        titulo,
+        autor,
+        ano,
+        catalogacao: Catalogacao {
+            isbn,
+            coautores: autores,
+            editora,
+            assuntos,
+        },
+    })
+}
+
+pub fn do_livro(id: uuid::Uuid, livro: &Livro, hoje: NaiveDate) -> Registro {
+    let catalogacao = livro.catalogacao();
+
+    // 008: data de cadastro (AAMMDD), "s" (data única) e o ano; o resto fica em branco
+    let fixos = format!(
+        "{}s{:04}    xx {:17}und d",
+        hoje.format("%y%m%d"),
+        livro.get_ano(),
+        ""
+    );
+
+    let mut dados = Vec::new();
+    if let Some(isbn) = &catalogacao.isbn {
+        dados.push(CampoDados::novo("020", ' ', ' ', vec![('a', isbn.clone())]));
+    }
+    dados.push(CampoDados::novo(
+        "100",
+        '1',
+        ' ',
+        vec![('a', livro.get_autor().clone())],
+    ));
+    dados.push(CampoDados::novo(
+        "245",
+        '1',
+        '0',
+        vec![('a', livro.get_titulo().clone())],
+    ));
+    let mut publicacao = Vec::new();
+    if let Some(editora) = &catalogacao.editora {
+        publicacao.push(('b', editora.clone()));
+    }
+    publicacao.push(('c', livro.get_ano().to_string()));
+    dados.push(CampoDados::novo("264", ' ', '1', publicacao));
+    for assunto in &catalogacao.assuntos {
+        dados.push(CampoDados::novo(
+            "650",
+            ' ',
+            '4',
+            vec![('a', assunto.clone())],
+        ));
+    }
+    for coautor in &catalogacao.coautores {
+        dados.push(CampoDados::novo(
+            "700",
+            '1',
+            ' ',
+            vec![('a', coautor.clone())],
+        ));
+    }
+
+    Registro {
+        lider: LIDER_PADRAO.to_string(),
+        controle: vec![
+            ("001".to_string(), id.to_string()),
+            ("008".to_string(), fixos),
+        ],
+        dados,
+    }
+}
+
+#[derive(Debug)]
+pub struct ErroRegistro {
+    // Posição do registro no arquivo, a partir de 1
+    pub registro: usize,
+    pub motivo: String,
+}
+
+impl fmt::Display for ErroRegistro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Registro {}: {}", self.registro, self.motivo)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportacaoMarc {
+    // Registros aceitos (só foram gravados se não houver nenhum erro)
+    pub validos: usize,
+    pub erros: Vec<ErroRegistro>,
+    // Tag -> em quantos registros apareceu sem ter para onde ir
+    pub nao_mapeados: BTreeMap<String, usize>,
+}
+
+// Tudo ou nada, como a importação de planilhas; com `simular`, nada é gravado
+pub fn importar(
+    biblioteca: &mut Biblioteca,
+    registros: &[Registro],
+    simular: bool,
+) -> Result<ImportacaoMarc, ErroBiblioteca> {
+    let mut importacao = ImportacaoMarc::default();
+
+    let resultado = biblioteca.transacao(|biblioteca| {
+        for (indice, registro) in registros.iter().enumerate() {
+            for tag in registro.nao_mapeados() {
+                *importacao.nao_mapeados.entry(tag).or_default() += 1;
+            }
+
+            let resultado = para_livro(registro).and_then(|livro| {
+                biblioteca.cadastrar_livro_catalogado(
+                    livro.titulo,
+                    livro.autor,
+                    livro.ano,
+                    livro.catalogacao,
+                )
+            });
+
+            match resultado {
+                Ok(_) => importacao.validos += 1,
+                // Sem permissão nenhum registro passaria; não adianta continuar
+                Err(e @ ErroBiblioteca::PermissaoNegada(_)) => return Err(e),
+                Err(e) => importacao.erros.push(ErroRegistro {
+                    registro: indice + 1,
+                    motivo: e.to_string(),
+                }),
+            }
+        }
+
+        if simular || !importacao.erros.is_empty() {
+            Err(ErroBiblioteca::OperacaoCancelada)
+        } else {
+            Ok(())
+        }
+    });
+
+    match resultado {
+        Ok(()) | Err(ErroBiblioteca::OperacaoCancelada) => Ok(importacao),
+        Err(e) => Err(e),
+    }
+}
+
+// O acervo inteiro, menos os arquivados e os emprestados de parceiras, em ordem de título
+pub fn exportar(biblioteca: &Biblioteca) -> Vec<Registro> {
+    let mut livros: Vec<_> = biblioteca
+        .livros()
+        .iter()
+        .filter(|(_, livro)| !livro.arquivado && livro.intercambio().is_none())
+        .collect();
+    livros.sort_by_key(|(id, livro)| (livro.get_titulo().to_lowercase(), **id));
+    let hoje = biblioteca.hoje();
+    livros
+        .into_iter()
+        .map(|(id, livro)| do_livro(*id, livro, hoje))
+        .collect()
+}
+
+#[cfg(test)]
+mod testes {
+    use super::*;
+
+    fn registro() -> Registro {
+        let campo = |tag: &str, ind1, ind2, subcampos: &[(char, &str)]| {
+            let subcampos = subcampos
+                .iter()
+                .map(|(codigo, valor)| (*codigo, valor.to_string()))
+                .collect();
+            CampoDados::novo(tag, ind1, ind2, subcampos)
+        };
+        Registro {
+            lider: LIDER_PADRAO.to_string(),
+            controle: vec![
+                ("001".to_string(), "000042".to_string()),
+                (
+                    "008".to_string(),
+                    "240105s1881    bl            000 1 por d".to_string(),
+                ),
+            ],
+            dados: vec![
+                campo("020", ' ', ' ', &[('a', "9788535910664")]),
+                campo("100", '1', ' ', &[('a', "Assis, Machado de")]),
+                campo(
+                    "245",
+                    '1',
+                    '0',
+                    &[
+                        ('a', "Memórias póstumas de Brás Cubas"),
+                        ('c', "Machado & <Companhia>"),
+                    ],
+                ),
+                campo("650", ' ', '4', &[('a', "Romance brasileiro")]),
+                campo("650", ' ', '4', &[('a', "Ironia")]),
+            ],
+        }
+    }
+
+    #[test]
+    fn iso2709_e_marcxml_ida_e_volta() {
+        let binario = escrever(Formato::Iso2709, &[registro()]).expect("ISO 2709");
+        let do_binario = ler(Formato::Iso2709, &binario).expect("ler ISO 2709");
+        assert_eq!(do_binario.len(), 1);
+        assert_eq!(do_binario[0].controle, registro().controle);
+        assert_eq!(do_binario[0].dados, registro().dados);
+
+        let xml = escrever(Formato::MarcXml, &do_binario).expect("MARCXML");
+        let do_xml = ler(Formato::MarcXml, &xml).expect("ler MARCXML");
+        assert_eq!(do_xml, do_binario);
+
+        // E de volta ao binário, byte a byte
+        assert_eq!(
+            escrever(Formato::Iso2709, &do_xml).expect("ISO 2709"),
+            binario
+        );
+    }
+
+    #[test]
+    fn marcxml_com_tag_invalida_nao_vira_iso2709() {
+        let xml = escrever(Formato::MarcXml, &[registro()]).expect("MARCXML");
+        let xml = String::from_utf8(xml)
+            .expect("UTF-8")
+            .replace("tag=\"650\"", "tag=\"6500\"");
+        let registros = ler(Formato::MarcXml, xml.as_bytes()).expect("ler MARCXML");
+
+        assert!(matches!(
+            escrever(Formato::Iso2709, &registros),
+            Err(ErroBiblioteca::DadosInvalidos(_))
+        ));
+    }
+}
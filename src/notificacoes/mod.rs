@@ -0,0 +1,626 @@
+// Lembretes de devolução e avisos de atraso por e-mail. Cada leitor recebe uma
+// mensagem por tipo de aviso com todos os livros que se encaixam, montada a
+// partir de um modelo de texto. Cada empréstimo avisado fica anotado num
+// arquivo ao lado dos dados, para que a próxima execução (o `notificar` roda
+// pelo cron) não mande o mesmo aviso de novo. Se o prazo mudar (renovação), o
+// empréstimo volta a ser avisado.
+
+pub mod smtp;
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::biblioteca::Biblioteca;
+use crate::errors::ErroBiblioteca;
+use crate::traits::Identificavel;
+
+pub const LEMBRETE_PADRAO: &str = include_str!("lembrete.txt");
+pub const ATRASO_PADRAO: &str = include_str!("atraso.txt");
+
+// Uma trava mais velha que isso sobrou de uma execução que morreu no meio
+const TRAVA_VENCIDA: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TipoAviso {
+    Lembrete,
+    Atraso,
+}
+
+impl TipoAviso {
+    fn arquivo(&self) -> &'static str {
+        match self {
+            TipoAviso::Lembrete => "lembrete.txt",
+            TipoAviso::Atraso => "atraso.txt",
+        }
+    }
+}
+
+// Primeira linha `Assunto: ...`, uma linha em branco e o corpo. Marcadores:
+// {{nome}}, {{biblioteca}}, {{livros}}, {{quantidade}} e {{hoje}}.
+#[derive(Debug, Clone)]
+pub struct Modelo {
+    assunto: String,
+    corpo: String,
+}
+
+impl Modelo {
+    pub fn interpretar(texto: &str, origem: &str) -> Result<Self, ErroBiblioteca> {
+        let texto = texto.replace("\r\n", "\n");
+        let (primeira, resto) = texto.split_once('\n').unwrap_or((&texto, ""));
+        let assunto = primeira
+            .strip_prefix("Assunto:")
+            .map(str::trim)
+            .filter(|assunto| !assunto.is_empty())
+            .ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos(format!(
+                    "O modelo {} deve começar com uma linha \"Assunto: ...\".",
+                    origem
+                ))
+            })?;
+        if !resto.contains("{{livros}}") {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "O modelo {} precisa ter o marcador {{{{livros}}}}.",
+                origem
+            )));
+        }
+        Ok(Modelo {
+            assunto: assunto.to_string(),
+            corpo: resto.trim_start_matches('\n').to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Modelos {
+    lembrete: Modelo,
+    atraso: Modelo,
+}
+
+impl Modelos {
+    pub fn padrao() -> Self {
+        Modelos {
+            lembrete: Modelo::interpretar(LEMBRETE_PADRAO, "padrão").expect("modelo padrão"),
+            atraso: Modelo::interpretar(ATRASO_PADRAO, "padrão").expect("modelo padrão"),
+        }
+    }
+
+    // Pasta com `lembrete.txt` e/ou `atraso.txt`; o que faltar fica no padrão
+    pub fn ler(pasta: &Path) -> Result<Self, ErroBiblioteca> {
+        if !pasta.is_dir() {
+            return Err(ErroBiblioteca::DadosInvalidos(format!(
+                "{} não é uma pasta de modelos.",
+                pasta.display()
+            )));
+        }
+        let mut modelos = Self::padrao();
+        for tipo in [TipoAviso::Lembrete, TipoAviso::Atraso] {
+            let caminho = pasta.join(tipo.arquivo());
+            if !caminho.exists() {
+                continue;
+            }
+            let texto = fs::read_to_string(&caminho).map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!(
+                    "Erro ao ler modelo {}: {}",
+                    caminho.display(),
+                    e
+                ))
+            })?;
+            let modelo = Modelo::interpretar(&texto, &caminho.display().to_string())?;
+            match tipo {
+                TipoAviso::Lembrete => modelos.lembrete = modelo,
+                TipoAviso::Atraso => modelos.atraso = modelo,
+            }
+        }
+        Ok(modelos)
+    }
+
+    fn modelo(&self, tipo: TipoAviso) -> &Modelo {
+        match tipo {
+            TipoAviso::Lembrete => &self.lembrete,
+            TipoAviso::Atraso => &self.atraso,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id_emprestimo: Uuid,
+    pub titulo: String,
+    pub autor: String,
+    pub prazo: NaiveDate,
+    // Dias em que a biblioteca abriu depois do prazo
+    pub atraso: i64,
+}
+
+// Uma mensagem a mandar: um leitor, um tipo de aviso, um ou mais livros
+#[derive(Debug, Clone)]
+pub struct Aviso {
+    pub tipo: TipoAviso,
+    pub nome: String,
+    pub email: String,
+    pub itens: Vec<Item>,
+}
+
+#[derive(Debug, Default)]
+pub struct Pendencias {
+    pub avisos: Vec<Aviso>,
+    // Leitores que deveriam ser avisados mas não têm e-mail cadastrado
+    pub sem_email: Vec<(String, usize)>,
+}
+
+// Empréstimos ativos que vencem de `hoje` a `hoje + dias` (lembrete) ou que já
+// venceram, contando só dias em que a biblioteca abriu (atraso), menos os que já foram avisados com o mesmo prazo
+pub fn pendencias(
+    biblioteca: &Biblioteca,
+    registro: &RegistroAvisos,
+    hoje: NaiveDate,
+    dias: u32,
+) -> Pendencias {
+    let limite = hoje + chrono::Days::new(dias.into());
+    let mut grupos: BTreeMap<(Uuid, TipoAviso), Vec<Item>> = BTreeMap::new();
+
+    for emprestimo in biblioteca.emprestimos().values() {
+        if !emprestimo.esta_ativo() {
+            continue;
+        }
+        let prazo = emprestimo.get_data_devolucao();
+        let atraso = biblioteca.calendario().dias_de_atraso(prazo, hoje);
+        let tipo = if atraso > 0 {
+            TipoAviso::Atraso
+        } else if hoje <= prazo && prazo <= limite {
+            TipoAviso::Lembrete
+        } else {
+            continue;
+        };
+        if registro.ja_enviado(emprestimo.id(), tipo, prazo) {
+            continue;
+        }
+        let livro = biblioteca.livros().get(&emprestimo.get_id_livro());
+        grupos
+            .entry((emprestimo.get_id_usuario(), tipo))
+            .or_default()
+            .push(Item {
+                id_emprestimo: emprestimo.id(),
+                titulo: livro.map_or("Livro desconhecido".to_string(), |l| l.get_titulo().clone()),
+                autor: livro.map_or(String::new(), |l| l.get_autor().clone()),
+                prazo,
+                atraso,
+            });
+    }
+
+    let mut resultado = Pendencias::default();
+    for ((id_usuario, tipo), mut itens) in grupos {
+        itens.sort_by_key(|item| (item.prazo, item.titulo.to_lowercase()));
+        let usuario = biblioteca.usuarios().get(&id_usuario);
+        let nome = usuario.map_or("Usuário desconhecido".to_string(), |u| u.get_nome());
+        match usuario.and_then(|u| u.email.clone()) {
+            Some(email) => resultado.avisos.push(Aviso {
+                tipo,
+                nome,
+                email,
+                itens,
+            }),
+            None => resultado.sem_email.push((nome, itens.len())),
+        }
+    }
+    resultado
+        .avisos
+        .sort_by_cached_key(|aviso| (aviso.nome.to_lowercase(), aviso.tipo));
+    resultado.sem_email.sort();
+    resultado
+}
+
+fn data(data: NaiveDate) -> String {
+    data.format("%d/%m/%Y").to_string()
+}
+
+fn linha_item(item: &Item, tipo: TipoAviso, hoje: NaiveDate) -> String {
+    let livro = if item.autor.is_empty() {
+        item.titulo.clone()
+    } else {
+        format!("{}, de {}", item.titulo, item.autor)
+    };
+    let situacao = match tipo {
+        TipoAviso::Lembrete => match (item.prazo - hoje).num_days() {
+            0 => "devolver hoje".to_string(),
+            1 => format!("devolver amanhã, {}", data(item.prazo)),
+            _ => format!("devolver até {}", data(item.prazo)),
+        },
+        TipoAviso::Atraso => format!(
+            "prazo {}, {} dia(s) de atraso",
+            data(item.prazo),
+            item.atraso
+        ),
+    };
+    format!("- {} ({})", livro, situacao)
+}
+
+// Assunto e corpo já com os marcadores preenchidos
+pub fn compor(
+    aviso: &Aviso,
+    modelos: &Modelos,
+    biblioteca: &str,
+    hoje: NaiveDate,
+) -> (String, String) {
+    let modelo = modelos.modelo(aviso.tipo);
+    let livros: Vec<String> = aviso
+        .itens
+        .iter()
+        .map(|item| linha_item(item, aviso.tipo, hoje))
+        .collect();
+    let preencher = |texto: &str| {
+        texto
+            .replace("{{nome}}", &aviso.nome)
+            .replace("{{biblioteca}}", biblioteca)
+            .replace("{{quantidade}}", &aviso.itens.len().to_string())
+            .replace("{{hoje}}", &data(hoje))
+            .replace("{{livros}}", &livros.join("\n"))
+    };
+    // O assunto vai para o cabeçalho: nada de quebra de linha
+    let assunto = preencher(&modelo.assunto)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    (assunto, preencher(&modelo.corpo))
+}
+
+// Cabeçalho com acentos vira "encoded-word" (RFC 2047)
+fn cabecalho_texto(texto: &str) -> String {
+    if texto.is_ascii() {
+        texto.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(texto))
+    }
+}
+
+// Mensagem completa (RFC 5322) com o corpo em base64, que passa por qualquer
+// servidor sem depender de 8BITMIME
+pub fn mensagem(
+    remetente: &str,
+    nome_remetente: &str,
+    destinatario: &str,
+    assunto: &str,
+    corpo: &str,
+    agora: DateTime<Local>,
+) -> String {
+    let dominio = remetente.rsplit_once('@').map_or("localhost", |(_, d)| d);
+    let nome = nome_remetente.replace(['"', '\\', '\r', '\n'], "");
+    let mut texto = String::new();
+    for (campo, valor) in [
+        (
+            "From",
+            format!("{} <{}>", cabecalho_texto(&nome), remetente),
+        ),
+        ("To", format!("<{}>", destinatario)),
+        ("Subject", cabecalho_texto(assunto)),
+        ("Date", agora.to_rfc2822()),
+        ("Message-ID", format!("<{}@{}>", Uuid::new_v4(), dominio)),
+        ("MIME-Version", "1.0".to_string()),
+        ("Content-Type", "text/plain; charset=utf-8".to_string()),
+        ("Content-Transfer-Encoding", "base64".to_string()),
+        ("Auto-Submitted", "auto-generated".to_string()),
+    ] {
+        texto.push_str(&format!("{}: {}\r\n", campo, valor));
+    }
+    texto.push_str("\r\n");
+
+    let corpo = corpo.replace("\r\n", "\n").replace('\n', "\r\n");
+    let codificado = STANDARD.encode(corpo);
+    for pedaco in codificado.as_bytes().chunks(76) {
+        texto.push_str(&String::from_utf8_lossy(pedaco));
+        texto.push_str("\r\n");
+    }
+    texto
+}
+
+// Um empréstimo avisado. A chave é (empréstimo, tipo, prazo).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvisoEnviado {
+    pub id_emprestimo: Uuid,
+    pub tipo: TipoAviso,
+    pub prazo: NaiveDate,
+    pub email: String,
+    pub enviado_em: DateTime<Local>,
+}
+
+// Arquivo só de acréscimos, uma linha JSON por empréstimo avisado
+pub struct RegistroAvisos {
+    caminho: PathBuf,
+    enviados: HashSet<(Uuid, TipoAviso, NaiveDate)>,
+}
+
+impl RegistroAvisos {
+    // dados_biblioteca.json -> dados_biblioteca.avisos.jsonl
+    pub fn caminho_registro(caminho_dados: &Path) -> PathBuf {
+        caminho_dados.with_extension("avisos.jsonl")
+    }
+
+    pub fn carregar(caminho_dados: &Path) -> Result<Self, ErroBiblioteca> {
+        let caminho = Self::caminho_registro(caminho_dados);
+        let mut enviados = HashSet::new();
+        if caminho.exists() {
+            let arquivo = File::open(&caminho).map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir avisos: {}", e))
+            })?;
+            for (numero, linha) in BufReader::new(arquivo).lines().enumerate() {
+                let linha = linha.map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!("Erro ao ler avisos: {}", e))
+                })?;
+                if linha.trim().is_empty() {
+                    continue;
+                }
+                let aviso: AvisoEnviado = serde_json::from_str(&linha).map_err(|e| {
+                    ErroBiblioteca::ErroPersistencia(format!(
+                        "Linha {} de {} inválida: {}",
+                        numero + 1,
+                        caminho.display(),
+                        e
+                    ))
+                })?;
+                enviados.insert((aviso.id_emprestimo, aviso.tipo, aviso.prazo));
+            }
+        }
+        Ok(RegistroAvisos { caminho, enviados })
+    }
+
+    pub fn ja_enviado(&self, id_emprestimo: Uuid, tipo: TipoAviso, prazo: NaiveDate) -> bool {
+        self.enviados.contains(&(id_emprestimo, tipo, prazo))
+    }
+
+    // Gravado logo depois de cada mensagem aceita pelo servidor
+    pub fn anotar(&mut self, aviso: &Aviso, agora: DateTime<Local>) -> Result<(), ErroBiblioteca> {
+        let mut linhas = String::new();
+        for item in &aviso.itens {
+            let enviado = AvisoEnviado {
+                id_emprestimo: item.id_emprestimo,
+                tipo: aviso.tipo,
+                prazo: item.prazo,
+                email: aviso.email.clone(),
+                enviado_em: agora,
+            };
+            let json = serde_json::to_string(&enviado).map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao serializar aviso: {}", e))
+            })?;
+            linhas.push_str(&json);
+            linhas.push('\n');
+        }
+
+        let mut arquivo = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.caminho)
+            .map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir avisos: {}", e))
+            })?;
+        arquivo
+            .write_all(linhas.as_bytes())
+            .and_then(|()| arquivo.sync_data())
+            .map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar avisos: {}", e))
+            })?;
+
+        for item in &aviso.itens {
+            self.enviados
+                .insert((item.id_emprestimo, aviso.tipo, item.prazo));
+        }
+        Ok(())
+    }
+}
+
+// Impede duas execuções ao mesmo tempo (um cron que atrasou, alguém rodando à
+// mão); o arquivo some quando a trava é solta
+pub struct Trava {
+    caminho: PathBuf,
+}
+
+impl Trava {
+    pub fn obter(caminho_dados: &Path) -> Result<Self, ErroBiblioteca> {
+        let caminho = caminho_dados.with_extension("avisos.trava");
+        let vencida = fs::metadata(&caminho)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modificada| SystemTime::now().duration_since(modificada).ok())
+            .is_some_and(|idade| idade > TRAVA_VENCIDA);
+        if vencida {
+            let _ = fs::remove_file(&caminho);
+        }
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&caminho)
+        {
+            Ok(mut arquivo) => {
+                let _ = writeln!(arquivo, "{}", std::process::id());
+                Ok(Trava { caminho })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(ErroBiblioteca::EstadoInvalido(format!(
+                    "Outra execução do notificar está em andamento ({} existe).",
+                    caminho.display()
+                )))
+            }
+            Err(e) => Err(ErroBiblioteca::ErroPersistencia(format!(
+                "Erro ao criar {}: {}",
+                caminho.display(),
+                e
+            ))),
+        }
+    }
+}
+
+impl Drop for Trava {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.caminho);
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        contas::{Papel, Sessao},
+        relogio::RelogioFixo,
+    };
+
+    fn dia(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).expect("data válida")
+    }
+
+    fn item(titulo: &str, autor: &str, prazo: NaiveDate, atraso: i64) -> Item {
+        Item {
+            id_emprestimo: Uuid::new_v4(),
+            titulo: titulo.to_string(),
+            autor: autor.to_string(),
+            prazo,
+            atraso,
+        }
+    }
+
+    #[test]
+    fn lembrete_lista_cada_livro_com_o_prazo() {
+        let hoje = dia(2024, 3, 4);
+        let aviso = Aviso {
+            tipo: TipoAviso::Lembrete,
+            nome: "Capitu".to_string(),
+            email: "capitu@exemplo.com".to_string(),
+            itens: vec![
+                item("Dom Casmurro", "Machado de Assis", hoje, 0),
+                item("Iracema", "José de Alencar", dia(2024, 3, 5), 0),
+                item("Cartas", "", dia(2024, 3, 9), 0),
+            ],
+        };
+        let (assunto, corpo) = compor(&aviso, &Modelos::padrao(), "Biblioteca Central", hoje);
+
+        assert_eq!(assunto, "Lembrete de devolução - Biblioteca Central");
+        assert!(corpo.starts_with("Olá, Capitu!\n"));
+        assert!(corpo.contains(
+            "- Dom Casmurro, de Machado de Assis (devolver hoje)\n\
+             - Iracema, de José de Alencar (devolver amanhã, 05/03/2024)\n\
+             - Cartas (devolver até 09/03/2024)\n"
+        ));
+        assert!(corpo.trim_end().ends_with("Biblioteca Central"));
+    }
+
+    #[test]
+    fn atraso_traz_os_dias_e_o_modelo_da_biblioteca() {
+        let modelo = "Assunto: {{quantidade}} livro(s)\n\n{{nome}}, em {{hoje}}:\n{{livros}}";
+        // O assunto mal formado é recusado, e o corpo precisa listar os livros
+        assert!(Modelo::interpretar("Olá\n\n{{livros}}", "teste").is_err());
+        assert!(Modelo::interpretar("Assunto: Oi\n\nSem lista", "teste").is_err());
+        let modelos = Modelos {
+            atraso: Modelo::interpretar(modelo, "teste").expect("modelo"),
+            ..Modelos::padrao()
+        };
+        let aviso = Aviso {
+            tipo: TipoAviso::Atraso,
+            nome: "Bento".to_string(),
+            email: "bento@exemplo.com".to_string(),
+            itens: vec![item("Iracema", "José de Alencar", dia(2024, 3, 18), 4)],
+        };
+        let (assunto, corpo) = compor(&aviso, &modelos, "Central", dia(2024, 3, 22));
+
+        assert_eq!(assunto, "1 livro(s)");
+        assert_eq!(
+            corpo,
+            "Bento, em 22/03/2024:\n\
+             - Iracema, de José de Alencar (prazo 18/03/2024, 4 dia(s) de atraso)"
+        );
+    }
+
+    #[test]
+    fn mensagem_codifica_assunto_e_corpo() {
+        let agora = Local::now();
+        let texto = mensagem(
+            "avisos@biblioteca.org",
+            "Biblioteca \"Central\"",
+            "capitu@exemplo.com",
+            "Lembrete de devolução",
+            "Olá!\nLinha dois",
+            agora,
+        );
+        let (cabecalho, corpo) = texto.split_once("\r\n\r\n").expect("cabeçalho e corpo");
+
+        assert!(cabecalho.contains("From: Biblioteca Central <avisos@biblioteca.org>\r\n"));
+        assert!(cabecalho.contains("To: <capitu@exemplo.com>\r\n"));
+        assert!(cabecalho.contains(&format!(
+            "Subject: =?UTF-8?B?{}?=\r\n",
+            STANDARD.encode("Lembrete de devolução")
+        )));
+        assert!(cabecalho.contains("@biblioteca.org>\r\n"));
+        let decodificado = STANDARD
+            .decode(corpo.replace("\r\n", ""))
+            .expect("corpo em base64");
+        assert_eq!(decodificado, b"Ol\xc3\xa1!\r\nLinha dois");
+    }
+
+    #[test]
+    fn leitor_sem_email_fica_de_fora_e_e_contado() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+        let mut biblioteca = Biblioteca::nova(&caminho);
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca.definir_relogio(Arc::new(
+            RelogioFixo::no_dia(dia(2024, 3, 4)).expect("dia válido"),
+        ));
+
+        let capitu = biblioteca.cadastrar_usuario("Capitu".to_string()).unwrap();
+        biblioteca
+            .definir_email(capitu, Some("capitu@exemplo.com".to_string()))
+            .unwrap();
+        let bento = biblioteca.cadastrar_usuario("Bento".to_string()).unwrap();
+        for (leitor, titulo) in [
+            (capitu, "Dom Casmurro"),
+            (capitu, "Helena"),
+            (bento, "Iracema"),
+        ] {
+            let livro = biblioteca
+                .cadastrar_livro(titulo.to_string(), "Autor".to_string(), 1900)
+                .unwrap();
+            biblioteca.realizar_emprestimo(leitor, livro).unwrap();
+        }
+
+        // Todos vencem em 18/03: dois dias antes, é hora do lembrete
+        let mut registro = RegistroAvisos::carregar(&caminho).expect("registro");
+        let pendentes = pendencias(&biblioteca, &registro, dia(2024, 3, 16), 3);
+        assert_eq!(pendentes.sem_email, [("Bento".to_string(), 1)]);
+        assert_eq!(pendentes.avisos.len(), 1);
+        let aviso = &pendentes.avisos[0];
+        assert_eq!(aviso.tipo, TipoAviso::Lembrete);
+        assert_eq!(aviso.email, "capitu@exemplo.com");
+        let titulos: Vec<&str> = aviso.itens.iter().map(|i| i.titulo.as_str()).collect();
+        assert_eq!(titulos, ["Dom Casmurro", "Helena"]);
+
+        // Longe do prazo, ninguém; depois de avisado, não avisa de novo
+        assert!(
+            pendencias(&biblioteca, &registro, dia(2024, 3, 5), 3)
+                .avisos
+                .is_empty()
+        );
+        registro.anotar(aviso, Local::now()).expect("anotar");
+        let pendentes = pendencias(&biblioteca, &registro, dia(2024, 3, 16), 3);
+        assert!(pendentes.avisos.is_empty());
+        let relido = RegistroAvisos::carregar(&caminho).expect("registro");
+        assert!(relido.ja_enviado(
+            aviso.itens[0].id_emprestimo,
+            TipoAviso::Lembrete,
+            dia(2024, 3, 18)
+        ));
+    }
+}
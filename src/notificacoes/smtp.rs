@@ -0,0 +1,205 @@
+// Cliente SMTP mínimo (RFC 5321): EHLO, AUTH PLAIN opcional, MAIL/RCPT/DATA.
+// Não fala TLS; em produção, aponte para um relay local (postfix, msmtpd...)
+// que cuide da entrega, e nos testes para um servidor de mentira na máquina.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::errors::ErroBiblioteca;
+
+const PORTA_PADRAO: u16 = 25;
+const ESPERA: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct ConfiguracaoSmtp {
+    pub servidor: String,
+    pub porta: u16,
+    pub usuario: Option<String>,
+    pub senha: Option<String>,
+}
+
+impl Default for ConfiguracaoSmtp {
+    fn default() -> Self {
+        ConfiguracaoSmtp {
+            servidor: "localhost".to_string(),
+            porta: PORTA_PADRAO,
+            usuario: None,
+            senha: None,
+        }
+    }
+}
+
+impl ConfiguracaoSmtp {
+    // `host` ou `host:porta`
+    pub fn definir_servidor(&mut self, texto: &str) -> Result<(), ErroBiblioteca> {
+        let (servidor, porta) = match texto.rsplit_once(':') {
+            Some((servidor, porta)) => {
+                let porta = porta.parse::<u16>().map_err(|_| {
+                    ErroBiblioteca::DadosInvalidos(format!("Porta SMTP inválida: {}", porta))
+                })?;
+                (servidor, porta)
+            }
+            None => (texto, PORTA_PADRAO),
+        };
+        if servidor.is_empty() {
+            return Err(ErroBiblioteca::DadosInvalidos(
+                "Informe o servidor SMTP como HOST ou HOST:PORTA.".to_string(),
+            ));
+        }
+        self.servidor = servidor.to_string();
+        self.porta = porta;
+        Ok(())
+    }
+}
+
+fn falha(etapa: &str, erro: impl std::fmt::Display) -> ErroBiblioteca {
+    ErroBiblioteca::ErroEnvio(format!("{}: {}", etapa, erro))
+}
+
+pub struct ClienteSmtp {
+    leitor: BufReader<TcpStream>,
+    escritor: TcpStream,
+}
+
+impl ClienteSmtp {
+    // Conecta, cumprimenta e autentica (se houver usuário configurado)
+    pub fn conectar(
+        configuracao: &ConfiguracaoSmtp,
+        dominio: &str,
+    ) -> Result<Self, ErroBiblioteca> {
+        let destino = format!("{}:{}", configuracao.servidor, configuracao.porta);
+        let enderecos = destino
+            .to_socket_addrs()
+            .map_err(|e| falha(&format!("endereço {}", destino), e))?;
+
+        let mut ultimo_erro = None;
+        let mut conexao = None;
+        for endereco in enderecos {
+            match TcpStream::connect_timeout(&endereco, ESPERA) {
+                Ok(stream) => {
+                    conexao = Some(stream);
+                    break;
+                }
+                Err(e) => ultimo_erro = Some(e),
+            }
+        }
+        let stream = conexao.ok_or_else(|| {
+            falha(
+                &format!("conexão com {}", destino),
+                ultimo_erro.map_or("nenhum endereço encontrado".to_string(), |e| e.to_string()),
+            )
+        })?;
+        stream
+            .set_read_timeout(Some(ESPERA))
+            .and_then(|()| stream.set_write_timeout(Some(ESPERA)))
+            .map_err(|e| falha("conexão", e))?;
+        let escritor = stream.try_clone().map_err(|e| falha("conexão", e))?;
+
+        let mut cliente = ClienteSmtp {
+            leitor: BufReader::new(stream),
+            escritor,
+        };
+        cliente.esperar("saudação", 220)?;
+        let extensoes = cliente.comando(&format!("EHLO {}", dominio), "EHLO", 250)?;
+
+        if let Some(usuario) = &configuracao.usuario {
+            let oferece_plain = extensoes.iter().any(|linha| {
+                let linha = linha.to_ascii_uppercase();
+                linha.starts_with("AUTH") && linha.split_whitespace().any(|m| m == "PLAIN")
+            });
+            if !oferece_plain {
+                return Err(falha("autenticação", "o servidor não oferece AUTH PLAIN"));
+            }
+            let senha = configuracao.senha.as_deref().unwrap_or_default();
+            let credencial = STANDARD.encode(format!("\0{}\0{}", usuario, senha));
+            cliente.comando(&format!("AUTH PLAIN {}", credencial), "autenticação", 235)?;
+        }
+        Ok(cliente)
+    }
+
+    fn escrever(&mut self, texto: &str) -> Result<(), ErroBiblioteca> {
+        self.escritor
+            .write_all(texto.as_bytes())
+            .and_then(|()| self.escritor.flush())
+            .map_err(|e| falha("envio", e))
+    }
+
+    // Lê uma resposta (possivelmente de várias linhas, `250-...` até `250 ...`)
+    // e confere o código; devolve o texto de cada linha
+    fn esperar(&mut self, etapa: &str, esperado: u16) -> Result<Vec<String>, ErroBiblioteca> {
+        let mut linhas = Vec::new();
+        loop {
+            let mut linha = String::new();
+            let lidos = self
+                .leitor
+                .read_line(&mut linha)
+                .map_err(|e| falha(etapa, e))?;
+            if lidos == 0 {
+                return Err(falha(etapa, "o servidor encerrou a conexão"));
+            }
+            let linha = linha.trim_end();
+            let codigo = linha.get(..3).and_then(|c| c.parse::<u16>().ok());
+            let (Some(codigo), separador) = (codigo, linha.as_bytes().get(3)) else {
+                return Err(falha(etapa, format!("resposta inesperada: {}", linha)));
+            };
+            linhas.push(linha.get(4..).unwrap_or_default().to_string());
+            if separador == Some(&b'-') {
+                continue;
+            }
+            if codigo != esperado {
+                return Err(falha(etapa, format!("{} {}", codigo, linhas.join(" "))));
+            }
+            return Ok(linhas);
+        }
+    }
+
+    fn comando(
+        &mut self,
+        comando: &str,
+        etapa: &str,
+        esperado: u16,
+    ) -> Result<Vec<String>, ErroBiblioteca> {
+        self.escrever(&format!("{}\r\n", comando))?;
+        self.esperar(etapa, esperado)
+    }
+
+    // `mensagem` já vem com cabeçalhos e linhas terminadas em CRLF
+    pub fn enviar(
+        &mut self,
+        remetente: &str,
+        destinatario: &str,
+        mensagem: &str,
+    ) -> Result<(), ErroBiblioteca> {
+        self.comando(&format!("MAIL FROM:<{}>", remetente), "remetente", 250)?;
+        self.comando(&format!("RCPT TO:<{}>", destinatario), "destinatário", 250)?;
+        self.comando("DATA", "DATA", 354)?;
+
+        // Linhas que começam com ponto ganham outro, para não encerrar o DATA antes da hora
+        let mut corpo = String::with_capacity(mensagem.len() + 8);
+        for linha in mensagem.split_inclusive("\r\n") {
+            if linha.starts_with('.') {
+                corpo.push('.');
+            }
+            corpo.push_str(linha);
+        }
+        if !corpo.ends_with("\r\n") {
+            corpo.push_str("\r\n");
+        }
+        corpo.push_str(".\r\n");
+        self.escrever(&corpo)?;
+        self.esperar("mensagem", 250)?;
+        Ok(())
+    }
+
+    // Depois de uma recusa, volta ao estado inicial para a próxima mensagem
+    pub fn reiniciar(&mut self) -> Result<(), ErroBiblioteca> {
+        self.comando("RSET", "RSET", 250).map(|_| ())
+    }
+
+    pub fn encerrar(mut self) {
+        let _ = self.comando("QUIT", "QUIT", 221);
+    }
+}
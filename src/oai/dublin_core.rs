@@ -0,0 +1,49 @@
+// Dublin Core simples (oai_dc), o formato que todo repositório OAI-PMH precisa oferecer.
+// Os quinze elementos são todos opcionais e repetíveis; só usamos os que o livro tem.
+
+use quick_xml::escape::escape;
+use uuid::Uuid;
+
+use crate::biblioteca::livros::Livro;
+
+pub const PREFIXO: &str = "oai_dc";
+pub const NAMESPACE: &str = "http://www.openarchives.org/OAI/2.0/oai_dc/";
+pub const ESQUEMA: &str = "http://www.openarchives.org/OAI/2.0/oai_dc.xsd";
+const NAMESPACE_DC: &str = "http://purl.org/dc/elements/1.1/";
+
+// O elemento <oai_dc:dc>, já indentado para ficar dentro de <metadata>
+pub fn registro(id: Uuid, livro: &Livro, recuo: &str) -> String {
+    let catalogacao = livro.catalogacao();
+    let mut elementos = vec![("title", livro.get_titulo().clone())];
+    elementos.push(("creator", livro.get_autor().clone()));
+    for coautor in &catalogacao.coautores {
+        elementos.push(("creator", coautor.clone()));
+    }
+    for assunto in &catalogacao.assuntos {
+        elementos.push(("subject", assunto.clone()));
+    }
+    if let Some(editora) = &catalogacao.editora {
+        elementos.push(("publisher", editora.clone()));
+    }
+    elementos.push(("date", livro.get_ano().to_string()));
+    elementos.push(("type", "Text".to_string()));
+    if let Some(isbn) = &catalogacao.isbn {
+        elementos.push(("identifier", format!("urn:isbn:{}", isbn)));
+    }
+    elementos.push(("identifier", format!("urn:uuid:{}", id)));
+
+    let mut xml = format!(
+        "{recuo}<oai_dc:dc xmlns:oai_dc=\"{}\" xmlns:dc=\"{}\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"{} {}\">\n",
+        NAMESPACE, NAMESPACE_DC, NAMESPACE, ESQUEMA
+    );
+    for (elemento, valor) in elementos {
+        xml.push_str(&format!(
+            "{recuo}  <dc:{elemento}>{}</dc:{elemento}>\n",
+            escape(&valor)
+        ));
+    }
+    xml.push_str(&format!("{recuo}</oai_dc:dc>\n"));
+    xml
+}
@@ -0,0 +1,801 @@
+// OAI-PMH 2.0, o protocolo com que agregadores colhem catálogos. Oferecemos só
+// Dublin Core (oai_dc) e não dividimos o acervo em conjuntos (sets).
+// Livros arquivados aparecem como registros apagados; os apagados de vez somem
+// sem deixar rastro, por isso o repositório declara deletedRecord "transient".
+
+pub mod dublin_core;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use quick_xml::escape::escape;
+use uuid::Uuid;
+
+use crate::biblioteca::{Biblioteca, livros::Livro};
+
+// Registros por resposta de ListRecords/ListIdentifiers; o resto vem por resumptionToken
+const TAMANHO_PAGINA: usize = 100;
+
+const FORMATO_DATA: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+// Como o repositório se apresenta no Identify
+#[derive(Debug, Clone)]
+pub struct Repositorio {
+    pub nome: String,
+    pub email: String,
+    // Parte do meio dos identificadores: oai:DOMINIO:UUID
+    pub dominio: String,
+}
+
+impl Default for Repositorio {
+    fn default() -> Self {
+        Repositorio {
+            nome: "Biblioteca".to_string(),
+            email: "biblioteca@localhost".to_string(),
+            dominio: "biblioteca.local".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CodigoErro {
+    BadArgument,
+    BadResumptionToken,
+    BadVerb,
+    CannotDisseminateFormat,
+    IdDoesNotExist,
+    NoRecordsMatch,
+    NoSetHierarchy,
+}
+
+impl CodigoErro {
+    fn nome(&self) -> &'static str {
+        match self {
+            CodigoErro::BadArgument => "badArgument",
+            CodigoErro::BadResumptionToken => "badResumptionToken",
+            CodigoErro::BadVerb => "badVerb",
+            CodigoErro::CannotDisseminateFormat => "cannotDisseminateFormat",
+            CodigoErro::IdDoesNotExist => "idDoesNotExist",
+            CodigoErro::NoRecordsMatch => "noRecordsMatch",
+            CodigoErro::NoSetHierarchy => "noSetHierarchy",
+        }
+    }
+}
+
+struct ErroOai {
+    codigo: CodigoErro,
+    mensagem: String,
+}
+
+fn erro(codigo: CodigoErro, mensagem: impl Into<String>) -> ErroOai {
+    ErroOai {
+        codigo,
+        mensagem: mensagem.into(),
+    }
+}
+
+// Decodifica `application/x-www-form-urlencoded` (a query string ou o corpo de um POST)
+fn decodificar(texto: &str) -> Option<String> {
+    let bytes = texto.as_bytes();
+    let mut saida = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => saida.push(b' '),
+            b'%' => {
+                let hexa = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                saida.push(u8::from_str_radix(hexa, 16).ok()?);
+                i += 2;
+            }
+            b => saida.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8(saida).ok()
+}
+
+// O protocolo proíbe argumentos repetidos
+fn interpretar_consulta(consulta: &str) -> Result<HashMap<String, String>, ErroOai> {
+    let mut argumentos = HashMap::new();
+    for par in consulta.split('&').filter(|par| !par.is_empty()) {
+        let (nome, valor) = par.split_once('=').unwrap_or((par, ""));
+        let (Some(nome), Some(valor)) = (decodificar(nome), decodificar(valor)) else {
+            return Err(erro(
+                CodigoErro::BadArgument,
+                format!("Argumento mal codificado: {}", par),
+            ));
+        };
+        if argumentos.insert(nome.clone(), valor).is_some() {
+            return Err(erro(
+                CodigoErro::BadArgument,
+                format!("Argumento repetido: {}", nome),
+            ));
+        }
+    }
+    Ok(argumentos)
+}
+
+// Confere os argumentos do verbo (sem contar o próprio `verb`)
+fn conferir(
+    argumentos: &HashMap<String, String>,
+    obrigatorios: &[&str],
+    opcionais: &[&str],
+) -> Result<(), ErroOai> {
+    if let Some(nome) = argumentos.keys().find(|nome| {
+        nome.as_str() != "verb"
+            && !obrigatorios.contains(&nome.as_str())
+            && !opcionais.contains(&nome.as_str())
+    }) {
+        return Err(erro(
+            CodigoErro::BadArgument,
+            format!("Argumento não permitido neste verbo: {}", nome),
+        ));
+    }
+    if let Some(nome) = obrigatorios
+        .iter()
+        .find(|nome| !argumentos.contains_key(**nome))
+    {
+        return Err(erro(
+            CodigoErro::BadArgument,
+            format!("Argumento obrigatório ausente: {}", nome),
+        ));
+    }
+    Ok(())
+}
+
+fn conferir_formato(prefixo: &str) -> Result<(), ErroOai> {
+    if prefixo == dublin_core::PREFIXO {
+        Ok(())
+    } else {
+        Err(erro(
+            CodigoErro::CannotDisseminateFormat,
+            format!(
+                "Formato não suportado: {} (só {}).",
+                prefixo,
+                dublin_core::PREFIXO
+            ),
+        ))
+    }
+}
+
+// Datas do protocolo, em UTC: dia inteiro ou até o segundo
+enum Granularidade {
+    Dia,
+    Segundo,
+}
+
+// Em segundos desde 1970; `until` com só o dia vale até o fim desse dia
+fn interpretar_data(texto: &str, fim: bool) -> Result<(i64, Granularidade), ErroOai> {
+    if let Ok(momento) = NaiveDateTime::parse_from_str(texto, FORMATO_DATA) {
+        return Ok((momento.and_utc().timestamp(), Granularidade::Segundo));
+    }
+    if let Ok(dia) = NaiveDate::parse_from_str(texto, "%Y-%m-%d") {
+        let momento = if fim {
+            dia.and_hms_opt(23, 59, 59)
+        } else {
+            dia.and_hms_opt(0, 0, 0)
+        };
+        if let Some(momento) = momento {
+            return Ok((momento.and_utc().timestamp(), Granularidade::Dia));
+        }
+    }
+    Err(erro(
+        CodigoErro::BadArgument,
+        format!(
+            "Data inválida: {} (use AAAA-MM-DD ou AAAA-MM-DDThh:mm:ssZ).",
+            texto
+        ),
+    ))
+}
+
+fn formatar_data(segundos: i64) -> String {
+    DateTime::<Utc>::from_timestamp(segundos, 0)
+        .unwrap_or_default()
+        .format(FORMATO_DATA)
+        .to_string()
+}
+
+// Registros em ordem de datestamp (e id, para desempatar), que é a ordem da paginação
+struct Catalogo<'a> {
+    registros: Vec<(i64, Uuid, &'a Livro)>,
+    mais_antigo: i64,
+}
+
+impl<'a> Catalogo<'a> {
+    // Livros de antes do controle de datas ficam com a data mais antiga conhecida
+    fn montar(biblioteca: &'a Biblioteca) -> Self {
+        let conhecidas = biblioteca
+            .livros()
+            .values()
+            .filter_map(|livro| livro.carimbos().ultima_alteracao())
+            .chain(biblioteca.inicio_do_historico().ok().flatten())
+            .map(|momento| momento.timestamp());
+        let mais_antigo = conhecidas.min().unwrap_or_else(|| Utc::now().timestamp());
+
+        // Livros de parceiras (intercâmbio) não são do nosso acervo
+        let mut registros: Vec<_> = biblioteca
+            .livros()
+            .iter()
+            .filter(|(_, livro)| livro.intercambio().is_none())
+            .map(|(id, livro)| {
+                let datestamp = livro
+                    .carimbos()
+                    .ultima_alteracao()
+                    .map_or(mais_antigo, |momento| momento.timestamp());
+                (datestamp, *id, livro)
+            })
+            .collect();
+        registros.sort_by_key(|(datestamp, id, _)| (*datestamp, *id));
+
+        Catalogo {
+            registros,
+            mais_antigo,
+        }
+    }
+}
+
+// Continuação de uma lista: último registro entregue e o intervalo pedido.
+// Como é só o ponto de parada, continua válido se o acervo mudar no meio da colheita.
+struct Continuacao {
+    depois_de: Option<(i64, Uuid)>,
+    de: Option<i64>,
+    ate: Option<i64>,
+}
+
+impl Continuacao {
+    fn codificar(&self, ultimo: (i64, Uuid)) -> String {
+        let numero = |valor: Option<i64>| valor.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{}.{}.{}.{}",
+            ultimo.0,
+            ultimo.1,
+            numero(self.de),
+            numero(self.ate)
+        )
+    }
+
+    fn decodificar(token: &str) -> Result<Self, ErroOai> {
+        let invalido = || {
+            erro(
+                CodigoErro::BadResumptionToken,
+                format!("resumptionToken inválido: {}", token),
+            )
+        };
+        let numero = |texto: &str| -> Result<Option<i64>, ErroOai> {
+            if texto.is_empty() {
+                Ok(None)
+            } else {
+                texto.parse().map(Some).map_err(|_| invalido())
+            }
+        };
+
+        let partes: Vec<&str> = token.split('.').collect();
+        let [segundos, id, de, ate] = partes.as_slice() else {
+            return Err(invalido());
+        };
+        let segundos = segundos.parse().map_err(|_| invalido())?;
+        let id = Uuid::parse_str(id).map_err(|_| invalido())?;
+        Ok(Continuacao {
+            depois_de: Some((segundos, id)),
+            de: numero(de)?,
+            ate: numero(ate)?,
+        })
+    }
+}
+
+fn identificador(repositorio: &Repositorio, id: Uuid) -> String {
+    format!("oai:{}:{}", repositorio.dominio, id)
+}
+
+fn cabecalho(
+    repositorio: &Repositorio,
+    datestamp: i64,
+    id: Uuid,
+    livro: &Livro,
+    recuo: &str,
+) -> String {
+    let status = if livro.arquivado {
+        " status=\"deleted\""
+    } else {
+        ""
+    };
+    format!(
+        "{recuo}<header{}>\n{recuo}  <identifier>{}</identifier>\n\
+         {recuo}  <datestamp>{}</datestamp>\n{recuo}</header>\n",
+        status,
+        escape(identificador(repositorio, id)),
+        formatar_data(datestamp)
+    )
+}
+
+// Registro completo; os apagados só têm o cabeçalho
+fn registro(repositorio: &Repositorio, datestamp: i64, id: Uuid, livro: &Livro) -> String {
+    let mut xml = String::from("    <record>\n");
+    xml.push_str(&cabecalho(repositorio, datestamp, id, livro, "      "));
+    if !livro.arquivado {
+        xml.push_str("      <metadata>\n");
+        xml.push_str(&dublin_core::registro(id, livro, "        "));
+        xml.push_str("      </metadata>\n");
+    }
+    xml.push_str("    </record>\n");
+    xml
+}
+
+fn identify(
+    catalogo: &Catalogo,
+    repositorio: &Repositorio,
+    base_url: &str,
+    argumentos: &HashMap<String, String>,
+) -> Result<String, ErroOai> {
+    conferir(argumentos, &[], &[])?;
+    Ok(format!(
+        "  <Identify>\n    <repositoryName>{}</repositoryName>\n    <baseURL>{}</baseURL>\n    \
+         <protocolVersion>2.0</protocolVersion>\n    <adminEmail>{}</adminEmail>\n    \
+         <earliestDatestamp>{}</earliestDatestamp>\n    <deletedRecord>transient</deletedRecord>\n    \
+         <granularity>YYYY-MM-DDThh:mm:ssZ</granularity>\n  </Identify>\n",
+        escape(&repositorio.nome),
+        escape(base_url),
+        escape(&repositorio.email),
+        formatar_data(catalogo.mais_antigo)
+    ))
+}
+
+fn list_metadata_formats(
+    catalogo: &Catalogo,
+    repositorio: &Repositorio,
+    argumentos: &HashMap<String, String>,
+) -> Result<String, ErroOai> {
+    conferir(argumentos, &[], &["identifier"])?;
+    if let Some(identificador) = argumentos.get("identifier") {
+        localizar(catalogo, repositorio, identificador)?;
+    }
+    Ok(format!(
+        "  <ListMetadataFormats>\n    <metadataFormat>\n      <metadataPrefix>{}</metadataPrefix>\n      \
+         <schema>{}</schema>\n      <metadataNamespace>{}</metadataNamespace>\n    \
+         </metadataFormat>\n  </ListMetadataFormats>\n",
+        dublin_core::PREFIXO,
+        dublin_core::ESQUEMA,
+        dublin_core::NAMESPACE
+    ))
+}
+
+fn localizar<'a>(
+    catalogo: &Catalogo<'a>,
+    repositorio: &Repositorio,
+    identificador: &str,
+) -> Result<(i64, Uuid, &'a Livro), ErroOai> {
+    let nao_existe = || {
+        erro(
+            CodigoErro::IdDoesNotExist,
+            format!("Identificador desconhecido: {}", identificador),
+        )
+    };
+    let id = identificador
+        .strip_prefix(&format!("oai:{}:", repositorio.dominio))
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(nao_existe)?;
+    catalogo
+        .registros
+        .iter()
+        .find(|(_, id_livro, _)| *id_livro == id)
+        .copied()
+        .ok_or_else(nao_existe)
+}
+
+fn get_record(
+    catalogo: &Catalogo,
+    repositorio: &Repositorio,
+    argumentos: &HashMap<String, String>,
+) -> Result<String, ErroOai> {
+    conferir(argumentos, &["identifier", "metadataPrefix"], &[])?;
+    let (datestamp, id, livro) = localizar(catalogo, repositorio, &argumentos["identifier"])?;
+    conferir_formato(&argumentos["metadataPrefix"])?;
+    Ok(format!(
+        "  <GetRecord>\n{}  </GetRecord>\n",
+        registro(repositorio, datestamp, id, livro)
+    ))
+}
+
+// ListRecords e ListIdentifiers: mesmos argumentos e paginação, muda só o conteúdo
+fn listar(
+    catalogo: &Catalogo,
+    repositorio: &Repositorio,
+    argumentos: &HashMap<String, String>,
+    verbo: &str,
+    completo: bool,
+) -> Result<String, ErroOai> {
+    let continuacao = match argumentos.get("resumptionToken") {
+        Some(token) => {
+            conferir(argumentos, &["resumptionToken"], &[])?;
+            Continuacao::decodificar(token)?
+        }
+        None => {
+            conferir(argumentos, &["metadataPrefix"], &["from", "until", "set"])?;
+            conferir_formato(&argumentos["metadataPrefix"])?;
+            if argumentos.contains_key("set") {
+                return Err(erro(
+                    CodigoErro::NoSetHierarchy,
+                    "Este repositório não tem conjuntos.",
+                ));
+            }
+            let de = argumentos
+                .get("from")
+                .map(|texto| interpretar_data(texto, false))
+                .transpose()?;
+            let ate = argumentos
+                .get("until")
+                .map(|texto| interpretar_data(texto, true))
+                .transpose()?;
+            if let (Some((de, g_de)), Some((ate, g_ate))) = (&de, &ate) {
+                if std::mem::discriminant(g_de) != std::mem::discriminant(g_ate) {
+                    return Err(erro(
+                        CodigoErro::BadArgument,
+                        "from e until precisam ter a mesma granularidade.",
+                    ));
+                }
+                if de > ate {
+                    return Err(erro(
+                        CodigoErro::BadArgument,
+                        "from não pode ser depois de until.",
+                    ));
+                }
+            }
+            Continuacao {
+                depois_de: None,
+                de: de.map(|(segundos, _)| segundos),
+                ate: ate.map(|(segundos, _)| segundos),
+            }
+        }
+    };
+
+    let no_intervalo: Vec<_> = catalogo
+        .registros
+        .iter()
+        .filter(|(datestamp, _, _)| {
+            continuacao.de.is_none_or(|de| *datestamp >= de)
+                && continuacao.ate.is_none_or(|ate| *datestamp <= ate)
+        })
+        .collect();
+    if no_intervalo.is_empty() {
+        return Err(erro(
+            CodigoErro::NoRecordsMatch,
+            "Nenhum registro no intervalo pedido.",
+        ));
+    }
+
+    let cursor = no_intervalo
+        .iter()
+        .take_while(|(datestamp, id, _)| {
+            continuacao
+                .depois_de
+                .is_some_and(|depois_de| (*datestamp, *id) <= depois_de)
+        })
+        .count();
+    let pagina = &no_intervalo[cursor..(cursor + TAMANHO_PAGINA).min(no_intervalo.len())];
+
+    let mut xml = format!("  <{}>\n", verbo);
+    for (datestamp, id, livro) in pagina {
+        if completo {
+            xml.push_str(&registro(repositorio, *datestamp, *id, livro));
+        } else {
+            xml.push_str(&cabecalho(repositorio, *datestamp, *id, livro, "    "));
+        }
+    }
+
+    // Só há token quando a lista foi (ou está sendo) dividida; vazio na última página
+    let restantes = no_intervalo.len() - cursor - pagina.len();
+    if restantes > 0 || continuacao.depois_de.is_some() {
+        let token = match pagina.last() {
+            Some((datestamp, id, _)) if restantes > 0 => continuacao.codificar((*datestamp, *id)),
+            _ => String::new(),
+        };
+        xml.push_str(&format!(
+            "    <resumptionToken completeListSize=\"{}\" cursor=\"{}\">{}</resumptionToken>\n",
+            no_intervalo.len(),
+            cursor,
+            token
+        ));
+    }
+    xml.push_str(&format!("  </{}>\n", verbo));
+    Ok(xml)
+}
+
+// Responde a uma requisição OAI-PMH. `consulta` são os argumentos já sem o `?`
+// (query string do GET ou corpo do POST). Erros do protocolo também são
+// respostas normais, com o elemento <error>.
+pub fn responder(
+    biblioteca: &Biblioteca,
+    repositorio: &Repositorio,
+    consulta: &str,
+    base_url: &str,
+) -> String {
+    let catalogo = Catalogo::montar(biblioteca);
+    let (atributos, conteudo) = match interpretar_consulta(consulta) {
+        Err(e) => (String::new(), Err(e)),
+        Ok(argumentos) => {
+            let conteudo = match argumentos.get("verb").map(String::as_str) {
+                Some("Identify") => identify(&catalogo, repositorio, base_url, &argumentos),
+                Some("ListMetadataFormats") => {
+                    list_metadata_formats(&catalogo, repositorio, &argumentos)
+                }
+                Some("ListSets") => {
+                    conferir(&argumentos, &[], &["resumptionToken"]).and(Err(erro(
+                        CodigoErro::NoSetHierarchy,
+                        "Este repositório não tem conjuntos.",
+                    )))
+                }
+                Some("GetRecord") => get_record(&catalogo, repositorio, &argumentos),
+                Some("ListRecords") => {
+                    listar(&catalogo, repositorio, &argumentos, "ListRecords", true)
+                }
+                Some("ListIdentifiers") => listar(
+                    &catalogo,
+                    repositorio,
+                    &argumentos,
+                    "ListIdentifiers",
+                    false,
+                ),
+                Some(outro) => Err(erro(
+                    CodigoErro::BadVerb,
+                    format!("Verbo desconhecido: {}", outro),
+                )),
+                None => Err(erro(CodigoErro::BadVerb, "Falta o argumento verb.")),
+            };
+
+            // Com badVerb ou badArgument, o <request> não repete os argumentos
+            let ecoar = !matches!(
+                conteudo,
+                Err(ErroOai {
+                    codigo: CodigoErro::BadVerb | CodigoErro::BadArgument,
+                    ..
+                })
+            );
+            let mut nomes: Vec<&String> = argumentos.keys().collect();
+            nomes.sort();
+            let atributos = nomes
+                .into_iter()
+                .filter(|_| ecoar)
+                .map(|nome| format!(" {}=\"{}\"", escape(nome), escape(&argumentos[nome])))
+                .collect();
+            (atributos, conteudo)
+        }
+    };
+
+    let corpo = conteudo.unwrap_or_else(|e| {
+        format!(
+            "  <error code=\"{}\">{}</error>\n",
+            e.codigo.nome(),
+            escape(&e.mensagem)
+        )
+    });
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <OAI-PMH xmlns=\"http://www.openarchives.org/OAI/2.0/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.openarchives.org/OAI/2.0/ \
+         http://www.openarchives.org/OAI/2.0/OAI-PMH.xsd\">\n  \
+         <responseDate>{}</responseDate>\n  <request{}>{}</request>\n{}</OAI-PMH>\n",
+        formatar_data(Utc::now().timestamp()),
+        atributos,
+        escape(base_url),
+        corpo
+    )
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use chrono::{Local, TimeZone};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        contas::{Papel, Sessao},
+        relogio::RelogioFixo,
+    };
+
+    const BASE: &str = "http://localhost/oai";
+
+    fn momento(mes: u32, dia: u32) -> DateTime<Local> {
+        Utc.with_ymd_and_hms(2024, mes, dia, 12, 0, 0)
+            .single()
+            .expect("momento válido")
+            .with_timezone(&Local)
+    }
+
+    fn biblioteca(pasta: &TempDir) -> (Biblioteca, Arc<RelogioFixo>) {
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        let relogio = Arc::new(RelogioFixo::new(momento(3, 4)));
+        biblioteca.definir_relogio(relogio.clone());
+        (biblioteca, relogio)
+    }
+
+    fn cadastrar(biblioteca: &mut Biblioteca, titulo: &str) -> Uuid {
+        biblioteca
+            .cadastrar_livro(titulo.to_string(), "Machado de Assis".to_string(), 1900)
+            .expect("cadastro")
+    }
+
+    fn consultar(biblioteca: &Biblioteca, consulta: &str) -> String {
+        responder(biblioteca, &Repositorio::default(), consulta, BASE)
+    }
+
+    fn identificadores(xml: &str) -> Vec<&str> {
+        xml.split("<identifier>")
+            .skip(1)
+            .filter_map(|resto| resto.split_once("</identifier>"))
+            .map(|(identificador, _)| identificador)
+            .collect()
+    }
+
+    fn token(xml: &str) -> &str {
+        let (_, resto) = xml.split_once("<resumptionToken").expect("resumptionToken");
+        let (_, resto) = resto.split_once('>').expect("fim da tag");
+        resto
+            .split_once("</resumptionToken>")
+            .expect("fechamento")
+            .0
+    }
+
+    #[test]
+    fn list_records_respeita_from_e_until() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, relogio) = biblioteca(&pasta);
+        // Criados em 04/03, 10/03 e 20/03; o primeiro é alterado em 25/03
+        let alterado = cadastrar(&mut biblioteca, "Dom Casmurro");
+        relogio.avancar(chrono::Duration::days(6));
+        let meio = cadastrar(&mut biblioteca, "Helena");
+        relogio.avancar(chrono::Duration::days(10));
+        let fim = cadastrar(&mut biblioteca, "Iaiá Garcia");
+        relogio.avancar(chrono::Duration::days(5));
+        biblioteca
+            .atualizar_livro(
+                alterado,
+                "Dom Casmurro".to_string(),
+                "Machado de Assis".to_string(),
+                1899,
+            )
+            .expect("atualização");
+        let oai = |id| format!("oai:biblioteca.local:{}", id);
+
+        // `until` com só o dia inclui o dia inteiro
+        let xml = consultar(
+            &biblioteca,
+            "verb=ListRecords&metadataPrefix=oai_dc&from=2024-03-05&until=2024-03-20",
+        );
+        assert_eq!(identificadores(&xml), [oai(meio), oai(fim)]);
+        assert!(xml.contains("<datestamp>2024-03-10T12:00:00Z</datestamp>"));
+        assert!(xml.contains("<datestamp>2024-03-20T12:00:00Z</datestamp>"));
+        assert!(xml.contains("<dc:title>Helena</dc:title>"));
+        assert!(!xml.contains("<resumptionToken"));
+        assert!(xml.contains("from=\"2024-03-05\""));
+
+        // A alteração conta como datestamp; com segundos, o limite é exato
+        let xml = consultar(
+            &biblioteca,
+            "verb=ListRecords&metadataPrefix=oai_dc&from=2024-03-20T12:00:01Z",
+        );
+        assert_eq!(identificadores(&xml), [oai(alterado)]);
+        assert!(xml.contains("<datestamp>2024-03-25T12:00:00Z</datestamp>"));
+        let xml = consultar(
+            &biblioteca,
+            "verb=ListIdentifiers&metadataPrefix=oai_dc&until=2024-03-09",
+        );
+        assert!(xml.contains("<error code=\"noRecordsMatch\">"));
+        assert!(
+            consultar(&biblioteca, "verb=Identify")
+                .contains("<earliestDatestamp>2024-03-10T12:00:00Z</earliestDatestamp>")
+        );
+    }
+
+    #[test]
+    fn intervalo_invalido_e_bad_argument() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, _) = biblioteca(&pasta);
+        cadastrar(&mut biblioteca, "Dom Casmurro");
+
+        for consulta in [
+            "verb=ListRecords&metadataPrefix=oai_dc&from=2024-03-20&until=2024-03-05",
+            "verb=ListRecords&metadataPrefix=oai_dc&from=2024-03-05&until=2024-03-20T00:00:00Z",
+            "verb=ListRecords&metadataPrefix=oai_dc&from=ontem",
+            "verb=ListRecords&metadataPrefix=oai_dc&from=2024-03-05&from=2024-03-06",
+        ] {
+            let xml = consultar(&biblioteca, consulta);
+            assert!(xml.contains("<error code=\"badArgument\">"), "{}", consulta);
+            assert!(xml.contains(&format!("<request>{}</request>", BASE)));
+        }
+    }
+
+    #[test]
+    fn resumption_token_continua_de_onde_parou() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, relogio) = biblioteca(&pasta);
+        for numero in 0..=TAMANHO_PAGINA {
+            cadastrar(&mut biblioteca, &format!("Volume {}", numero));
+            relogio.avancar(chrono::Duration::seconds(1));
+        }
+
+        let primeira = consultar(&biblioteca, "verb=ListIdentifiers&metadataPrefix=oai_dc");
+        assert_eq!(identificadores(&primeira).len(), TAMANHO_PAGINA);
+        assert!(primeira.contains("completeListSize=\"101\" cursor=\"0\""));
+
+        let segunda = consultar(
+            &biblioteca,
+            &format!("verb=ListIdentifiers&resumptionToken={}", token(&primeira)),
+        );
+        assert_eq!(identificadores(&segunda).len(), 1);
+        assert!(segunda.contains("cursor=\"100\""));
+        assert_eq!(token(&segunda), "");
+
+        let xml = consultar(&biblioteca, "verb=ListIdentifiers&resumptionToken=abc");
+        assert!(xml.contains("<error code=\"badResumptionToken\">"));
+    }
+
+    #[test]
+    fn verbo_desconhecido_e_bad_verb() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (biblioteca, _) = biblioteca(&pasta);
+
+        for consulta in [
+            "verb=Colher&metadataPrefix=oai_dc",
+            "metadataPrefix=oai_dc",
+            "",
+        ] {
+            let xml = consultar(&biblioteca, consulta);
+            assert!(xml.contains("<error code=\"badVerb\">"), "{}", consulta);
+            // Com badVerb, o <request> não repete os argumentos
+            assert!(xml.contains(&format!("<request>{}</request>", BASE)));
+        }
+    }
+
+    #[test]
+    fn identificador_desconhecido_e_id_does_not_exist() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (mut biblioteca, _) = biblioteca(&pasta);
+        let id = cadastrar(&mut biblioteca, "Dom Casmurro");
+
+        let xml = consultar(
+            &biblioteca,
+            &format!(
+                "verb=GetRecord&metadataPrefix=oai_dc&identifier=oai:biblioteca.local:{}",
+                id
+            ),
+        );
+        assert!(xml.contains("<GetRecord>"));
+        assert!(xml.contains("<dc:title>Dom Casmurro</dc:title>"));
+
+        for identificador in [
+            format!("oai:biblioteca.local:{}", Uuid::new_v4()),
+            format!("oai:outro.dominio:{}", id),
+            "oai:biblioteca.local:nao-e-uuid".to_string(),
+        ] {
+            let xml = consultar(
+                &biblioteca,
+                &format!(
+                    "verb=GetRecord&metadataPrefix=oai_dc&identifier={}",
+                    identificador
+                ),
+            );
+            assert!(
+                xml.contains("<error code=\"idDoesNotExist\">"),
+                "{}",
+                identificador
+            );
+            // Fora badVerb e badArgument, os argumentos voltam no <request>
+            assert!(xml.contains(&format!("identifier=\"{}\"", identificador)));
+        }
+        let xml = consultar(
+            &biblioteca,
+            &format!(
+                "verb=ListMetadataFormats&identifier=oai:biblioteca.local:{}",
+                Uuid::new_v4()
+            ),
+        );
+        assert!(xml.contains("<error code=\"idDoesNotExist\">"));
+    }
+}
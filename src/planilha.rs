@@ -0,0 +1,517 @@
+// Importação e exportação de planilhas CSV, para quem mantém o acervo no Excel.
+// A importação é uma transação só: se qualquer linha for recusada, nada entra.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    io::{Read, Write},
+};
+
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::biblioteca::{Biblioteca, emprestimos::StatusEmprestimo};
+use crate::errors::ErroBiblioteca;
+use crate::traits::Identificavel;
+
+// Marca de ordem de bytes que o Excel usa para reconhecer o arquivo como UTF-8
+const BOM: &str = "\u{feff}";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entidade {
+    Livros,
+    Usuarios,
+    Emprestimos,
+}
+
+impl Entidade {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "livros" => Ok(Entidade::Livros),
+            "usuarios" => Ok(Entidade::Usuarios),
+            "emprestimos" => Ok(Entidade::Emprestimos),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Entidade desconhecida: {} (use livros, usuarios ou emprestimos).",
+                outro
+            ))),
+        }
+    }
+
+    // Colunas que a planilha precisa ter para importar
+    fn campos(&self) -> &'static [&'static str] {
+        match self {
+            Entidade::Livros => &["titulo", "autor", "ano"],
+            Entidade::Usuarios => &["nome"],
+            Entidade::Emprestimos => &[],
+        }
+    }
+
+    // Colunas aproveitadas quando existem na planilha
+    fn campos_opcionais(&self) -> &'static [&'static str] {
+        match self {
+            Entidade::Usuarios => &["email"],
+            Entidade::Livros | Entidade::Emprestimos => &[],
+        }
+    }
+}
+
+impl fmt::Display for Entidade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entidade::Livros => write!(f, "livro(s)"),
+            Entidade::Usuarios => write!(f, "usuário(s)"),
+            Entidade::Emprestimos => write!(f, "empréstimo(s)"),
+        }
+    }
+}
+
+// Um caractere só; "tab" para planilhas separadas por tabulação
+pub fn interpretar_delimitador(texto: &str) -> Result<u8, ErroBiblioteca> {
+    match texto {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if texto.len() == 1 && texto.is_ascii() => Ok(texto.as_bytes()[0]),
+        _ => Err(ErroBiblioteca::DadosInvalidos(format!(
+            "O delimitador deve ser um único caractere (ou \"tab\"), não \"{}\".",
+            texto
+        ))),
+    }
+}
+
+// `titulo=Título,ano=Publicação`: de qual coluna da planilha vem cada campo
+pub fn interpretar_mapa(texto: &str) -> Result<HashMap<String, String>, ErroBiblioteca> {
+    texto
+        .split(',')
+        .filter(|par| !par.trim().is_empty())
+        .map(|par| match par.split_once('=') {
+            Some((campo, coluna)) if !coluna.trim().is_empty() => {
+                Ok((campo.trim().to_lowercase(), coluna.trim().to_string()))
+            }
+            _ => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Mapeamento inválido: \"{}\" (use campo=Coluna).",
+                par
+            ))),
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct ErroLinha {
+    pub linha: u64,
+    pub motivo: String,
+}
+
+impl fmt::Display for ErroLinha {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Linha {}: {}", self.linha, self.motivo)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Importacao {
+    // Linhas aceitas (só foram gravadas se não houver nenhum erro)
+    pub validas: usize,
+    pub erros: Vec<ErroLinha>,
+}
+
+fn erro_csv(erro: csv::Error) -> ErroBiblioteca {
+    ErroBiblioteca::ErroPersistencia(format!("Erro no CSV: {}", erro))
+}
+
+fn erro_escrita(erro: std::io::Error) -> ErroBiblioteca {
+    ErroBiblioteca::ErroPersistencia(format!("Erro ao gravar CSV: {}", erro))
+}
+
+// Planilhas executam células que começam com estes caracteres como fórmulas
+const INICIO_DE_FORMULA: [char; 4] = ['=', '+', '-', '@'];
+
+// Um apóstrofo na frente faz o Excel e o LibreOffice tratarem a célula como texto
+fn neutralizar(celula: &str) -> Cow<'_, str> {
+    if celula.starts_with(INICIO_DE_FORMULA) {
+        Cow::Owned(format!("'{}", celula))
+    } else {
+        Cow::Borrowed(celula)
+    }
+}
+
+// Desfaz `neutralizar`, para que uma planilha exportada volte igual
+fn restaurar(celula: &str) -> &str {
+    match celula.strip_prefix('\'') {
+        Some(resto) if resto.starts_with(INICIO_DE_FORMULA) => resto,
+        _ => celula,
+    }
+}
+
+// Cabeçalhos são comparados sem diferenciar maiúsculas e sem espaços em volta
+fn normalizar(coluna: &str) -> String {
+    coluna.trim_start_matches(BOM).trim().to_lowercase()
+}
+
+fn localizar_colunas(
+    entidade: Entidade,
+    cabecalho: &StringRecord,
+    mapa: &HashMap<String, String>,
+) -> Result<HashMap<&'static str, usize>, ErroBiblioteca> {
+    let conhecidos: Vec<&str> = entidade
+        .campos()
+        .iter()
+        .chain(entidade.campos_opcionais())
+        .copied()
+        .collect();
+    if let Some(campo) = mapa
+        .keys()
+        .find(|campo| !conhecidos.contains(&campo.as_str()))
+    {
+        return Err(ErroBiblioteca::DadosInvalidos(format!(
+            "Campo desconhecido no mapeamento: {} (use {}).",
+            campo,
+            conhecidos.join(", ")
+        )));
+    }
+
+    let mut colunas = HashMap::new();
+    for campo in entidade.campos() {
+        let nome = mapa.get(*campo).map(String::as_str).unwrap_or(campo);
+        let indice = cabecalho
+            .iter()
+            .position(|coluna| normalizar(coluna) == normalizar(nome))
+            .ok_or_else(|| {
+                ErroBiblioteca::DadosInvalidos(format!(
+                    "A planilha não tem a coluna \"{}\" (campo {}).",
+                    nome, campo
+                ))
+            })?;
+        colunas.insert(*campo, indice);
+    }
+    for campo in entidade.campos_opcionais() {
+        let nome = mapa.get(*campo).map(String::as_str).unwrap_or(campo);
+        if let Some(indice) = cabecalho
+            .iter()
+            .position(|coluna| normalizar(coluna) == normalizar(nome))
+        {
+            colunas.insert(*campo, indice);
+        }
+    }
+    Ok(colunas)
+}
+
+fn importar_linha(
+    biblioteca: &mut Biblioteca,
+    entidade: Entidade,
+    colunas: &HashMap<&'static str, usize>,
+    registro: &StringRecord,
+) -> Result<(), ErroBiblioteca> {
+    let campo = |nome: &str| {
+        colunas
+            .get(nome)
+            .and_then(|indice| registro.get(*indice))
+            .map(|celula| restaurar(celula.trim()))
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    match entidade {
+        Entidade::Livros => {
+            let ano = campo("ano").parse::<u16>().map_err(|_| {
+                ErroBiblioteca::DadosInvalidos(format!("Ano inválido: \"{}\".", campo("ano")))
+            })?;
+            biblioteca.cadastrar_livro(campo("titulo"), campo("autor"), ano)?;
+        }
+        Entidade::Usuarios => {
+            let id = biblioteca.cadastrar_usuario(campo("nome"))?;
+            let email = campo("email");
+            if !email.is_empty() {
+                biblioteca.definir_email(id, Some(email))?;
+            }
+        }
+        Entidade::Emprestimos => unreachable!("empréstimos não são importados"),
+    }
+    Ok(())
+}
+
+// Valida todas as linhas antes de decidir; com `simular`, nada é gravado mesmo sem erros
+pub fn importar(
+    biblioteca: &mut Biblioteca,
+    entidade: Entidade,
+    leitor: impl Read,
+    mapa: &HashMap<String, String>,
+    delimitador: u8,
+    simular: bool,
+) -> Result<Importacao, ErroBiblioteca> {
+    if entidade == Entidade::Emprestimos {
+        return Err(ErroBiblioteca::DadosInvalidos(
+            "Só livros e usuários podem ser importados.".to_string(),
+        ));
+    }
+
+    let mut leitor = ReaderBuilder::new()
+        .delimiter(delimitador)
+        .flexible(true)
+        .from_reader(leitor);
+    let cabecalho = leitor.headers().map_err(erro_csv)?.clone();
+    let colunas = localizar_colunas(entidade, &cabecalho, mapa)?;
+
+    let mut importacao = Importacao::default();
+    let resultado = biblioteca.transacao(|biblioteca| {
+        for registro in leitor.records() {
+            let (linha, resultado) = match registro {
+                Ok(registro) => (
+                    registro.position().map_or(0, |p| p.line()),
+                    importar_linha(biblioteca, entidade, &colunas, &registro),
+                ),
+                Err(e) => (
+                    e.position().map_or(0, |p| p.line()),
+                    Err(ErroBiblioteca::DadosInvalidos(e.to_string())),
+                ),
+            };
+
+            match resultado {
+                Ok(()) => importacao.validas += 1,
+                // Sem permissão nenhuma linha passaria; não adianta continuar
+                Err(e @ ErroBiblioteca::PermissaoNegada(_)) => return Err(e),
+                Err(e) => importacao.erros.push(ErroLinha {
+                    linha,
+                    motivo: e.to_string(),
+                }),
+            }
+        }
+
+        if simular || !importacao.erros.is_empty() {
+            Err(ErroBiblioteca::OperacaoCancelada)
+        } else {
+            Ok(())
+        }
+    });
+
+    match resultado {
+        Ok(()) | Err(ErroBiblioteca::OperacaoCancelada) => Ok(importacao),
+        Err(e) => Err(e),
+    }
+}
+
+// Inclui os arquivados, com uma coluna indicando; devolve quantas linhas foram escritas
+pub fn exportar(
+    biblioteca: &Biblioteca,
+    entidade: Entidade,
+    mut escritor: impl Write,
+    delimitador: u8,
+    bom: bool,
+) -> Result<usize, ErroBiblioteca> {
+    let (cabecalho, linhas): (&[&str], Vec<Vec<String>>) = match entidade {
+        Entidade::Livros => {
+            let mut livros: Vec<_> = biblioteca.livros().values().collect();
+            livros.sort_by_key(|l| (l.get_titulo().to_lowercase(), l.id()));
+            (
+                &["id", "titulo", "autor", "ano", "status", "arquivado"],
+                livros
+                    .into_iter()
+                    .map(|l| {
+                        vec![
+                            l.id().to_string(),
+                            l.get_titulo().clone(),
+                            l.get_autor().clone(),
+                            l.get_ano().to_string(),
+                            format!("{:?}", l.status()),
+                            l.arquivado.to_string(),
+                        ]
+                    })
+                    .collect(),
+            )
+        }
+        Entidade::Usuarios => {
+            let mut usuarios: Vec<_> = biblioteca.usuarios().values().collect();
+            usuarios.sort_by_key(|u| (u.nome.to_lowercase(), u.id));
+            (
+                &["id", "nome", "email", "arquivado"],
+                usuarios
+                    .into_iter()
+                    .map(|u| {
+                        vec![
+                            u.id.to_string(),
+                            u.get_nome(),
+                            u.email.clone().unwrap_or_default(),
+                            u.arquivado.to_string(),
+                        ]
+                    })
+                    .collect(),
+            )
+        }
+        Entidade::Emprestimos => {
+            let mut emprestimos: Vec<_> = biblioteca.emprestimos().values().collect();
+            emprestimos.sort_by_key(|e| (e.get_data_emprestimo(), e.id()));
+            (
+                &[
+                    "id",
+                    "id_livro",
+                    "titulo",
+                    "id_usuario",
+                    "nome",
+                    "data_emprestimo",
+                    "data_devolucao",
+                    "data_retorno",
+                    "status",
+                ],
+                emprestimos
+                    .into_iter()
+                    .map(|e| {
+                        let titulo = biblioteca
+                            .livros()
+                            .get(&e.get_id_livro())
+                            .map(|l| l.get_titulo().clone());
+                        let nome = biblioteca
+                            .usuarios()
+                            .get(&e.get_id_usuario())
+                            .map(|u| u.get_nome());
+                        vec![
+                            e.id().to_string(),
+                            e.get_id_livro().to_string(),
+                            titulo.unwrap_or_default(),
+                            e.get_id_usuario().to_string(),
+                            nome.unwrap_or_default(),
+                            e.get_data_emprestimo().to_string(),
+                            e.get_data_devolucao().to_string(),
+                            e.get_data_retorno()
+                                .map(|data| data.to_string())
+                                .unwrap_or_default(),
+                            match e.status {
+                                StatusEmprestimo::Ativo => "Ativo",
+                                StatusEmprestimo::Devolvido => "Devolvido",
+                            }
+                            .to_string(),
+                        ]
+                    })
+                    .collect(),
+            )
+        }
+    };
+
+    if bom {
+        escritor.write_all(BOM.as_bytes()).map_err(erro_escrita)?;
+    }
+    let mut csv = WriterBuilder::new()
+        .delimiter(delimitador)
+        .from_writer(escritor);
+    csv.write_record(cabecalho).map_err(erro_csv)?;
+    for linha in &linhas {
+        csv.write_record(linha.iter().map(|celula| neutralizar(celula).into_owned()))
+            .map_err(erro_csv)?;
+    }
+    csv.flush().map_err(erro_escrita)?;
+
+    Ok(linhas.len())
+}
+
+#[cfg(test)]
+mod testes {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::contas::{Papel, Sessao};
+
+    fn biblioteca(pasta: &TempDir) -> Biblioteca {
+        let mut biblioteca =
+            Biblioteca::carregar(pasta.path().join("dados.json")).expect("carregar");
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca
+    }
+
+    fn importar_texto(
+        biblioteca: &mut Biblioteca,
+        entidade: Entidade,
+        texto: &str,
+        simular: bool,
+    ) -> Importacao {
+        importar(
+            biblioteca,
+            entidade,
+            texto.as_bytes(),
+            &HashMap::new(),
+            b',',
+            simular,
+        )
+        .expect("importação")
+    }
+
+    #[test]
+    fn exportacao_neutraliza_formulas() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = biblioteca(&pasta);
+        biblioteca
+            .cadastrar_livro(
+                "=HYPERLINK(\"http://x\")".to_string(),
+                "@Autor".to_string(),
+                1900,
+            )
+            .expect("cadastro");
+        biblioteca
+            .cadastrar_usuario("-Leitor".to_string())
+            .expect("cadastro");
+        biblioteca
+            .cadastrar_usuario("+Outro".to_string())
+            .expect("cadastro");
+
+        let mut saida = Vec::new();
+        exportar(&biblioteca, Entidade::Livros, &mut saida, b',', false).expect("exportar");
+        let texto = String::from_utf8(saida).expect("UTF-8");
+        assert!(
+            texto.contains("\"'=HYPERLINK(\"\"http://x\"\")\""),
+            "{}",
+            texto
+        );
+        assert!(texto.contains(",'@Autor,"), "{}", texto);
+
+        let mut saida = Vec::new();
+        exportar(&biblioteca, Entidade::Usuarios, &mut saida, b',', false).expect("exportar");
+        let texto = String::from_utf8(saida).expect("UTF-8");
+        assert!(texto.contains(",'-Leitor,"), "{}", texto);
+        assert!(texto.contains(",'+Outro,"), "{}", texto);
+
+        // A planilha exportada volta sem o apóstrofo
+        let outra = TempDir::new().expect("diretório temporário");
+        let mut copia = self::biblioteca(&outra);
+        let importacao = importar_texto(&mut copia, Entidade::Usuarios, &texto, false);
+        assert!(importacao.erros.is_empty(), "{:?}", importacao.erros);
+        let mut nomes: Vec<String> = copia.usuarios().values().map(|u| u.get_nome()).collect();
+        nomes.sort();
+        assert_eq!(nomes, ["+Outro", "-Leitor"]);
+    }
+
+    #[test]
+    fn importacao_aponta_a_linha_de_cada_erro_e_nao_grava_nada() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = biblioteca(&pasta);
+        let texto = "Titulo,Autor,Ano\n\
+                     Dom Casmurro,Machado de Assis,1899\n\
+                     Sem ano,Alguém,dezenove\n\
+                     Memórias Póstumas,Machado de Assis,1881\n\
+                     ,Ninguém,1900\n";
+
+        let importacao = importar_texto(&mut biblioteca, Entidade::Livros, texto, false);
+
+        assert_eq!(importacao.validas, 2);
+        let linhas: Vec<u64> = importacao.erros.iter().map(|e| e.linha).collect();
+        assert_eq!(linhas, [3, 5]);
+        assert!(importacao.erros[0].motivo.contains("dezenove"));
+        assert!(biblioteca.livros().is_empty());
+        assert!(!biblioteca.tem_alteracoes());
+    }
+
+    #[test]
+    fn simulacao_valida_sem_gravar() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let mut biblioteca = biblioteca(&pasta);
+        let texto = "nome,email\nCapitu,capitu@exemplo.com\nBentinho,\n";
+
+        let importacao = importar_texto(&mut biblioteca, Entidade::Usuarios, texto, true);
+        assert_eq!(importacao.validas, 2);
+        assert!(importacao.erros.is_empty());
+        assert!(biblioteca.usuarios().is_empty());
+        assert!(!biblioteca.tem_alteracoes());
+
+        let importacao = importar_texto(&mut biblioteca, Entidade::Usuarios, texto, false);
+        assert_eq!(importacao.validas, 2);
+        assert_eq!(biblioteca.usuarios().len(), 2);
+    }
+}
@@ -0,0 +1,807 @@
+// Relatórios de circulação para a gestão: empréstimos por mês, títulos e autores
+// mais emprestados, leitores mais ativos, duração dos empréstimos, pontualidade e
+// o movimento de cada unidade.
+// O período filtra pela data do empréstimo; sem período, vale o histórico inteiro.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{Datelike, NaiveDate};
+use csv::WriterBuilder;
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::biblioteca::{Biblioteca, calendario::Calendario, emprestimos::Emprestimo};
+use crate::errors::ErroBiblioteca;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relatorio {
+    PorMes,
+    Titulos,
+    Autores,
+    Leitores,
+    Duracao,
+    Pontualidade,
+    Unidades,
+}
+
+impl Relatorio {
+    pub const TODOS: [Relatorio; 7] = [
+        Relatorio::PorMes,
+        Relatorio::Titulos,
+        Relatorio::Autores,
+        Relatorio::Leitores,
+        Relatorio::Duracao,
+        Relatorio::Pontualidade,
+        Relatorio::Unidades,
+    ];
+
+    // "todos" devolve a lista inteira
+    pub fn interpretar(texto: &str) -> Result<Vec<Self>, ErroBiblioteca> {
+        if texto == "todos" {
+            return Ok(Self::TODOS.to_vec());
+        }
+        Self::TODOS
+            .into_iter()
+            .find(|relatorio| relatorio.nome() == texto)
+            .map(|relatorio| vec![relatorio])
+            .ok_or_else(|| {
+                let nomes: Vec<&str> = Self::TODOS.iter().map(Relatorio::nome).collect();
+                ErroBiblioteca::DadosInvalidos(format!(
+                    "Relatório desconhecido: {} (use {} ou todos).",
+                    texto,
+                    nomes.join(", ")
+                ))
+            })
+    }
+
+    pub fn nome(&self) -> &'static str {
+        match self {
+            Relatorio::PorMes => "por-mes",
+            Relatorio::Titulos => "titulos",
+            Relatorio::Autores => "autores",
+            Relatorio::Leitores => "leitores",
+            Relatorio::Duracao => "duracao",
+            Relatorio::Pontualidade => "pontualidade",
+            Relatorio::Unidades => "unidades",
+        }
+    }
+
+    fn titulo(&self) -> &'static str {
+        match self {
+            Relatorio::PorMes => "Empréstimos por mês",
+            Relatorio::Titulos => "Títulos mais emprestados",
+            Relatorio::Autores => "Autores mais emprestados",
+            Relatorio::Leitores => "Leitores mais ativos",
+            Relatorio::Duracao => "Duração dos empréstimos devolvidos",
+            Relatorio::Pontualidade => "Pontualidade das devoluções",
+            Relatorio::Unidades => "Acervo e circulação por unidade",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatoRelatorio {
+    Texto,
+    Csv,
+    Json,
+}
+
+impl FormatoRelatorio {
+    pub fn interpretar(texto: &str) -> Result<Self, ErroBiblioteca> {
+        match texto {
+            "texto" => Ok(FormatoRelatorio::Texto),
+            "csv" => Ok(FormatoRelatorio::Csv),
+            "json" => Ok(FormatoRelatorio::Json),
+            outro => Err(ErroBiblioteca::DadosInvalidos(format!(
+                "Formato desconhecido: {} (use texto, csv ou json).",
+                outro
+            ))),
+        }
+    }
+}
+
+// Limites inclusivos; o que não for informado fica em aberto
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Periodo {
+    pub de: Option<NaiveDate>,
+    pub ate: Option<NaiveDate>,
+}
+
+impl Periodo {
+    fn contem(&self, data: NaiveDate) -> bool {
+        self.de.is_none_or(|de| data >= de) && self.ate.is_none_or(|ate| data <= ate)
+    }
+}
+
+impl std::fmt::Display for Periodo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.de, self.ate) {
+            (None, None) => write!(f, "todo o histórico"),
+            (Some(de), None) => write!(f, "a partir de {}", de),
+            (None, Some(ate)) => write!(f, "até {}", ate),
+            (Some(de), Some(ate)) => write!(f, "de {} a {}", de, ate),
+        }
+    }
+}
+
+pub struct Parametros {
+    pub periodo: Periodo,
+    // Quantas linhas nos rankings (títulos, autores, leitores)
+    pub limite: usize,
+    // Referência para os atrasos ainda em aberto
+    pub hoje: NaiveDate,
+}
+
+// Células já tipadas, para o JSON sair com números de verdade
+pub struct Tabela {
+    pub relatorio: Relatorio,
+    pub colunas: &'static [&'static str],
+    pub linhas: Vec<Vec<Value>>,
+}
+
+fn dias(emprestimo: &Emprestimo) -> Option<i64> {
+    emprestimo
+        .get_data_retorno()
+        .map(|retorno| (retorno - emprestimo.get_data_emprestimo()).num_days())
+}
+
+// Uma casa decimal basta para médias e porcentagens
+fn decimal(valor: f64) -> Value {
+    json!((valor * 10.0).round() / 10.0)
+}
+
+// Mais emprestados primeiro; empates em ordem alfabética
+fn ranking<K>(contagem: HashMap<K, usize>, nome: impl Fn(&K) -> String) -> Vec<(K, usize)> {
+    let mut itens: Vec<(K, usize)> = contagem.into_iter().collect();
+    itens.sort_by_cached_key(|(chave, total)| {
+        (std::cmp::Reverse(*total), nome(chave).to_lowercase())
+    });
+    itens
+}
+
+fn por_mes(emprestimos: &[&Emprestimo], periodo: &Periodo) -> Vec<Vec<Value>> {
+    let mut meses: BTreeMap<(i32, u32), (usize, HashSet<Uuid>)> = BTreeMap::new();
+    for emprestimo in emprestimos {
+        let data = emprestimo.get_data_emprestimo();
+        let mes = meses.entry((data.year(), data.month())).or_default();
+        mes.0 += 1;
+        mes.1.insert(emprestimo.get_id_usuario());
+    }
+
+    // Meses sem nenhum empréstimo também aparecem, com zero
+    let datas = emprestimos.iter().map(|e| e.get_data_emprestimo());
+    let (Some(inicio), Some(fim)) = (
+        periodo.de.or_else(|| datas.clone().min()),
+        periodo.ate.or_else(|| datas.max()),
+    ) else {
+        return Vec::new();
+    };
+    let mut linhas = Vec::new();
+    let (mut ano, mut mes) = (inicio.year(), inicio.month());
+    while (ano, mes) <= (fim.year(), fim.month()) {
+        let (total, leitores) = meses
+            .get(&(ano, mes))
+            .map_or((0, 0), |(total, leitores)| (*total, leitores.len()));
+        linhas.push(vec![
+            json!(format!("{:04}-{:02}", ano, mes)),
+            json!(total),
+            json!(leitores),
+        ]);
+        (ano, mes) = if mes == 12 {
+            (ano + 1, 1)
+        } else {
+            (ano, mes + 1)
+        };
+    }
+    linhas
+}
+
+fn titulos(biblioteca: &Biblioteca, emprestimos: &[&Emprestimo], limite: usize) -> Vec<Vec<Value>> {
+    let mut contagem: HashMap<Uuid, usize> = HashMap::new();
+    for emprestimo in emprestimos {
+        *contagem.entry(emprestimo.get_id_livro()).or_default() += 1;
+    }
+    let livro = |id: &Uuid| biblioteca.livros().get(id);
+    ranking(contagem, |id| {
+        livro(id).map_or(id.to_string(), |l| l.get_titulo().clone())
+    })
+    .into_iter()
+    .take(limite)
+    .enumerate()
+    .map(|(posicao, (id, total))| {
+        vec![
+            json!(posicao + 1),
+            json!(livro(&id).map_or(id.to_string(), |l| l.get_titulo().clone())),
+            json!(livro(&id).map_or(String::new(), |l| l.get_autor().clone())),
+            json!(total),
+        ]
+    })
+    .collect()
+}
+
+// Coautores também contam: o empréstimo vale para cada autor do livro
+fn autores(biblioteca: &Biblioteca, emprestimos: &[&Emprestimo], limite: usize) -> Vec<Vec<Value>> {
+    let mut contagem: HashMap<String, usize> = HashMap::new();
+    let mut livros_do_autor: HashMap<String, HashSet<Uuid>> = HashMap::new();
+    for emprestimo in emprestimos {
+        let Some(livro) = biblioteca.livros().get(&emprestimo.get_id_livro()) else {
+            continue;
+        };
+        for autor in std::iter::once(livro.get_autor()).chain(&livro.catalogacao().coautores) {
+            *contagem.entry(autor.clone()).or_default() += 1;
+            livros_do_autor
+                .entry(autor.clone())
+                .or_default()
+                .insert(emprestimo.get_id_livro());
+        }
+    }
+    ranking(contagem, String::clone)
+        .into_iter()
+        .take(limite)
+        .enumerate()
+        .map(|(posicao, (autor, total))| {
+            let titulos = livros_do_autor.get(&autor).map_or(0, HashSet::len);
+            vec![
+                json!(posicao + 1),
+                json!(autor),
+                json!(total),
+                json!(titulos),
+            ]
+        })
+        .collect()
+}
+
+fn leitores(
+    biblioteca: &Biblioteca,
+    emprestimos: &[&Emprestimo],
+    limite: usize,
+) -> Vec<Vec<Value>> {
+    let mut contagem: HashMap<Uuid, usize> = HashMap::new();
+    let mut em_andamento: HashMap<Uuid, usize> = HashMap::new();
+    for emprestimo in emprestimos {
+        *contagem.entry(emprestimo.get_id_usuario()).or_default() += 1;
+        if emprestimo.esta_ativo() {
+            *em_andamento.entry(emprestimo.get_id_usuario()).or_default() += 1;
+        }
+    }
+    let nome = |id: &Uuid| {
+        biblioteca
+            .usuarios()
+            .get(id)
+            .map_or(id.to_string(), |u| u.get_nome())
+    };
+    ranking(contagem, nome)
+        .into_iter()
+        .take(limite)
+        .enumerate()
+        .map(|(posicao, (id, total))| {
+            vec![
+                json!(posicao + 1),
+                json!(nome(&id)),
+                json!(total),
+                json!(em_andamento.get(&id).copied().unwrap_or(0)),
+            ]
+        })
+        .collect()
+}
+
+fn duracao(emprestimos: &[&Emprestimo]) -> Vec<Vec<Value>> {
+    let devolvidos: Vec<&&Emprestimo> = emprestimos.iter().filter(|e| !e.esta_ativo()).collect();
+    let duracoes: Vec<i64> = devolvidos.iter().filter_map(|e| dias(e)).collect();
+    let media = (!duracoes.is_empty())
+        .then(|| decimal(duracoes.iter().sum::<i64>() as f64 / duracoes.len() as f64));
+    vec![vec![
+        json!(duracoes.len()),
+        media.unwrap_or(Value::Null),
+        json!(duracoes.iter().min()),
+        json!(duracoes.iter().max()),
+        json!(devolvidos.len() - duracoes.len()),
+    ]]
+}
+
+// Só contam como atraso os dias em que a biblioteca abriu depois do prazo
+fn pontualidade(
+    emprestimos: &[&Emprestimo],
+    calendario: &Calendario,
+    hoje: NaiveDate,
+) -> Vec<Vec<Value>> {
+    let mut no_prazo = 0;
+    let mut com_atraso = 0;
+    for emprestimo in emprestimos {
+        match emprestimo.get_data_retorno() {
+            Some(retorno)
+                if calendario.dias_de_atraso(emprestimo.get_data_devolucao(), retorno) == 0 =>
+            {
+                no_prazo += 1
+            }
+            Some(_) => com_atraso += 1,
+            None => {}
+        }
+    }
+    let atrasados_em_aberto = emprestimos
+        .iter()
+        .filter(|e| e.esta_ativo() && calendario.dias_de_atraso(e.get_data_devolucao(), hoje) > 0)
+        .count();
+    let devolvidos = no_prazo + com_atraso;
+    let taxa = (devolvidos > 0).then(|| decimal(100.0 * no_prazo as f64 / devolvidos as f64));
+    vec![vec![
+        json!(devolvidos),
+        json!(no_prazo),
+        json!(com_atraso),
+        taxa.unwrap_or(Value::Null),
+        json!(atrasados_em_aberto),
+    ]]
+}
+
+// O acervo é o de agora; empréstimos e devoluções são os do período
+fn unidades(biblioteca: &Biblioteca, emprestimos: &[&Emprestimo]) -> Vec<Vec<Value>> {
+    let mut unidades: Vec<_> = biblioteca.unidades().values().collect();
+    unidades.sort_by(|a, b| a.sigla.cmp(&b.sigla));
+    let acervo: Vec<_> = biblioteca
+        .livros()
+        .values()
+        .filter(|l| !l.arquivado)
+        .collect();
+
+    unidades
+        .into_iter()
+        .map(|unidade| {
+            let aqui = Some(unidade.id);
+            let proprios: Vec<_> = acervo.iter().filter(|l| l.get_unidade() == aqui).collect();
+            let na_estante = acervo
+                .iter()
+                .filter(|l| {
+                    l.get_local() == aqui && l.esta_disponivel() && l.em_transito().is_none()
+                })
+                .count();
+            vec![
+                json!(unidade.sigla),
+                json!(proprios.len()),
+                json!(na_estante),
+                json!(proprios.iter().filter(|l| !l.esta_disponivel()).count()),
+                json!(
+                    proprios
+                        .iter()
+                        .filter(|l| l.em_transito().is_some())
+                        .count()
+                ),
+                json!(
+                    emprestimos
+                        .iter()
+                        .filter(|e| e.get_unidade() == aqui)
+                        .count()
+                ),
+                json!(
+                    emprestimos
+                        .iter()
+                        .filter(|e| e.get_unidade_devolucao() == aqui)
+                        .count()
+                ),
+            ]
+        })
+        .collect()
+}
+
+pub fn gerar(biblioteca: &Biblioteca, relatorio: Relatorio, parametros: &Parametros) -> Tabela {
+    let mut emprestimos: Vec<&Emprestimo> = biblioteca
+        .emprestimos()
+        .values()
+        .filter(|e| parametros.periodo.contem(e.get_data_emprestimo()))
+        .collect();
+    emprestimos.sort_by_key(|e| e.get_data_emprestimo());
+
+    let (colunas, linhas): (&'static [&'static str], _) = match relatorio {
+        Relatorio::PorMes => (
+            &["mes", "emprestimos", "leitores"],
+            por_mes(&emprestimos, &parametros.periodo),
+        ),
+        Relatorio::Titulos => (
+            &["posicao", "titulo", "autor", "emprestimos"],
+            titulos(biblioteca, &emprestimos, parametros.limite),
+        ),
+        Relatorio::Autores => (
+            &["posicao", "autor", "emprestimos", "titulos"],
+            autores(biblioteca, &emprestimos, parametros.limite),
+        ),
+        Relatorio::Leitores => (
+            &["posicao", "leitor", "emprestimos", "em_andamento"],
+            leitores(biblioteca, &emprestimos, parametros.limite),
+        ),
+        Relatorio::Duracao => (
+            &[
+                "devolvidos",
+                "media_dias",
+                "menor_dias",
+                "maior_dias",
+                "sem_data_de_retorno",
+            ],
+            duracao(&emprestimos),
+        ),
+        Relatorio::Pontualidade => (
+            &[
+                "devolvidos",
+                "no_prazo",
+                "com_atraso",
+                "taxa_no_prazo",
+                "atrasados_em_aberto",
+            ],
+            pontualidade(&emprestimos, biblioteca.calendario(), parametros.hoje),
+        ),
+        Relatorio::Unidades => (
+            &[
+                "unidade",
+                "exemplares",
+                "na_estante",
+                "emprestados",
+                "em_transito",
+                "emprestimos",
+                "devolucoes_recebidas",
+            ],
+            unidades(biblioteca, &emprestimos),
+        ),
+    };
+
+    Tabela {
+        relatorio,
+        colunas,
+        linhas,
+    }
+}
+
+fn celula(valor: &Value) -> String {
+    match valor {
+        Value::Null => "-".to_string(),
+        Value::String(texto) => texto.clone(),
+        outro => outro.to_string(),
+    }
+}
+
+// Colunas alinhadas; números à direita
+fn texto(tabela: &Tabela) -> String {
+    let mut larguras: Vec<usize> = tabela.colunas.iter().map(|c| c.chars().count()).collect();
+    for linha in &tabela.linhas {
+        for (largura, valor) in larguras.iter_mut().zip(linha) {
+            *largura = (*largura).max(celula(valor).chars().count());
+        }
+    }
+
+    let formatar = |valores: Vec<(String, bool)>| -> String {
+        let partes: Vec<String> = valores
+            .into_iter()
+            .zip(&larguras)
+            .map(|((valor, numero), largura)| {
+                if numero {
+                    format!("{:>largura$}", valor)
+                } else {
+                    format!("{:<largura$}", valor)
+                }
+            })
+            .collect();
+        partes.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut saida = format!("== {} ==\n", tabela.relatorio.titulo());
+    if tabela.linhas.is_empty() {
+        saida.push_str(match tabela.relatorio {
+            Relatorio::Unidades => "Nenhuma unidade cadastrada.\n",
+            _ => "Nenhum empréstimo no período.\n",
+        });
+        return saida;
+    }
+    let numericas: Vec<bool> = (0..tabela.colunas.len())
+        .map(|i| {
+            tabela
+                .linhas
+                .iter()
+                .all(|linha| matches!(linha[i], Value::Number(_) | Value::Null))
+        })
+        .collect();
+    saida.push_str(&formatar(
+        tabela
+            .colunas
+            .iter()
+            .zip(&numericas)
+            .map(|(coluna, numero)| (coluna.to_string(), *numero))
+            .collect(),
+    ));
+    let total: usize = larguras.iter().sum::<usize>() + 2 * (larguras.len() - 1);
+    saida.push_str(&"-".repeat(total));
+    saida.push('\n');
+    for linha in &tabela.linhas {
+        saida.push_str(&formatar(
+            linha
+                .iter()
+                .zip(&numericas)
+                .map(|(valor, numero)| (celula(valor), *numero))
+                .collect(),
+        ));
+    }
+    saida
+}
+
+fn objetos(tabela: &Tabela) -> Value {
+    tabela
+        .linhas
+        .iter()
+        .map(|linha| {
+            let objeto: Map<String, Value> = tabela
+                .colunas
+                .iter()
+                .map(|coluna| coluna.to_string())
+                .zip(linha.iter().cloned())
+                .collect();
+            Value::Object(objeto)
+        })
+        .collect()
+}
+
+// CSV só comporta uma tabela; texto e JSON aceitam vários relatórios de uma vez
+pub fn formatar(
+    tabelas: &[Tabela],
+    periodo: &Periodo,
+    formato: FormatoRelatorio,
+) -> Result<String, ErroBiblioteca> {
+    match formato {
+        FormatoRelatorio::Texto => {
+            let corpo: Vec<String> = tabelas.iter().map(texto).collect();
+            Ok(format!("Período: {}\n\n{}", periodo, corpo.join("\n")))
+        }
+        FormatoRelatorio::Csv => {
+            let [tabela] = tabelas else {
+                return Err(ErroBiblioteca::DadosInvalidos(
+                    "CSV comporta um relatório só; escolha qual.".to_string(),
+                ));
+            };
+            let mut csv = WriterBuilder::new().from_writer(Vec::new());
+            let erro = |e: csv::Error| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao gerar CSV: {}", e))
+            };
+            csv.write_record(tabela.colunas).map_err(erro)?;
+            for linha in &tabela.linhas {
+                let linha: Vec<String> = linha
+                    .iter()
+                    .map(|valor| match valor {
+                        Value::Null => String::new(),
+                        outro => celula(outro),
+                    })
+                    .collect();
+                csv.write_record(&linha).map_err(erro)?;
+            }
+            let bytes = csv.into_inner().map_err(|e| {
+                ErroBiblioteca::ErroPersistencia(format!("Erro ao gerar CSV: {}", e))
+            })?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        FormatoRelatorio::Json => {
+            let relatorios: Map<String, Value> = tabelas
+                .iter()
+                .map(|tabela| (tabela.relatorio.nome().to_string(), objetos(tabela)))
+                .collect();
+            let documento = json!({
+                "periodo": { "de": periodo.de, "ate": periodo.ate },
+                "relatorios": relatorios,
+            });
+            serde_json::to_string_pretty(&documento)
+                .map(|json| json + "\n")
+                .map_err(|e| ErroBiblioteca::ErroPersistencia(format!("Erro ao gerar JSON: {}", e)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod testes {
+    use std::sync::Arc;
+
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::{
+        contas::{Papel, Sessao},
+        relogio::RelogioFixo,
+    };
+
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).expect("data válida")
+    }
+
+    fn vazia(pasta: &TempDir) -> (Biblioteca, Arc<RelogioFixo>) {
+        let relogio = Arc::new(RelogioFixo::no_dia(data(2024, 3, 4)).expect("dia válido"));
+        let mut biblioteca = Biblioteca::nova(pasta.path().join("dados.json"));
+        biblioteca.entrar(Sessao {
+            login: "admin".to_string(),
+            papel: Papel::Administrador,
+        });
+        biblioteca.definir_relogio(relogio.clone());
+        (biblioteca, relogio)
+    }
+
+    // Capitu leva Dom Casmurro em 04/03 e devolve em 11/03 (no prazo); Bento leva
+    // Iracema em 04/03 e só devolve em 10/04 (atrasado); em 10/04 Capitu leva Dom
+    // Casmurro de novo e não devolve mais
+    fn movimentada(pasta: &TempDir) -> Biblioteca {
+        let (mut biblioteca, relogio) = vazia(pasta);
+        let capitu = biblioteca
+            .cadastrar_usuario("Capitu".to_string())
+            .expect("cadastro");
+        let bento = biblioteca
+            .cadastrar_usuario("Bento".to_string())
+            .expect("cadastro");
+        let dom_casmurro = biblioteca
+            .cadastrar_livro(
+                "Dom Casmurro".to_string(),
+                "Machado de Assis".to_string(),
+                1899,
+            )
+            .expect("cadastro");
+        let iracema = biblioteca
+            .cadastrar_livro("Iracema".to_string(), "José de Alencar".to_string(), 1865)
+            .expect("cadastro");
+
+        let primeiro = biblioteca
+            .realizar_emprestimo(capitu, dom_casmurro)
+            .expect("empréstimo");
+        let segundo = biblioteca
+            .realizar_emprestimo(bento, iracema)
+            .expect("empréstimo");
+        relogio.avancar(Duration::days(7));
+        biblioteca.devolver(primeiro).expect("devolução");
+        relogio.avancar(Duration::days(30));
+        biblioteca.devolver(segundo).expect("devolução");
+        biblioteca
+            .realizar_emprestimo(capitu, dom_casmurro)
+            .expect("empréstimo");
+        biblioteca
+    }
+
+    fn linhas(biblioteca: &Biblioteca, relatorio: Relatorio, periodo: Periodo) -> Vec<Vec<Value>> {
+        let parametros = Parametros {
+            periodo,
+            limite: 10,
+            hoje: data(2024, 5, 20),
+        };
+        gerar(biblioteca, relatorio, &parametros).linhas
+    }
+
+    #[test]
+    fn contagens_no_historico_inteiro() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let biblioteca = movimentada(&pasta);
+        let todo = Periodo::default();
+
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::PorMes, todo),
+            [
+                vec![json!("2024-03"), json!(2), json!(2)],
+                vec![json!("2024-04"), json!(1), json!(1)],
+            ]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Titulos, todo),
+            [
+                vec![
+                    json!(1),
+                    json!("Dom Casmurro"),
+                    json!("Machado de Assis"),
+                    json!(2)
+                ],
+                vec![
+                    json!(2),
+                    json!("Iracema"),
+                    json!("José de Alencar"),
+                    json!(1)
+                ],
+            ]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Autores, todo),
+            [
+                vec![json!(1), json!("Machado de Assis"), json!(2), json!(1)],
+                vec![json!(2), json!("José de Alencar"), json!(1), json!(1)],
+            ]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Leitores, todo),
+            [
+                vec![json!(1), json!("Capitu"), json!(2), json!(1)],
+                vec![json!(2), json!("Bento"), json!(1), json!(0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn duracao_e_pontualidade_dos_devolvidos() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let biblioteca = movimentada(&pasta);
+        let todo = Periodo::default();
+
+        // 7 e 37 dias
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Duracao, todo),
+            [vec![json!(2), json!(22.0), json!(7), json!(37), json!(0)]]
+        );
+        // O empréstimo de 10/04 venceu antes de 20/05 e continua em aberto
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Pontualidade, todo),
+            [vec![json!(2), json!(1), json!(1), json!(50.0), json!(1)]]
+        );
+    }
+
+    #[test]
+    fn periodo_filtra_pela_data_do_emprestimo() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let biblioteca = movimentada(&pasta);
+        let abril = Periodo {
+            de: Some(data(2024, 4, 1)),
+            ate: Some(data(2024, 4, 30)),
+        };
+
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::PorMes, abril),
+            [vec![json!("2024-04"), json!(1), json!(1)]]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Titulos, abril),
+            [vec![
+                json!(1),
+                json!("Dom Casmurro"),
+                json!("Machado de Assis"),
+                json!(1)
+            ]]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Duracao, abril),
+            [vec![
+                json!(0),
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                json!(0)
+            ]]
+        );
+    }
+
+    #[test]
+    fn biblioteca_vazia_nao_quebra_nenhum_relatorio() {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let (biblioteca, _relogio) = vazia(&pasta);
+        let todo = Periodo::default();
+
+        for relatorio in [
+            Relatorio::PorMes,
+            Relatorio::Titulos,
+            Relatorio::Autores,
+            Relatorio::Leitores,
+            Relatorio::Unidades,
+        ] {
+            assert!(linhas(&biblioteca, relatorio, todo).is_empty());
+        }
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Duracao, todo),
+            [vec![
+                json!(0),
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                json!(0)
+            ]]
+        );
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::Pontualidade, todo),
+            [vec![json!(0), json!(0), json!(0), Value::Null, json!(0)]]
+        );
+
+        // Com período, os meses aparecem mesmo sem movimento
+        let periodo = Periodo {
+            de: Some(data(2024, 1, 15)),
+            ate: Some(data(2024, 2, 10)),
+        };
+        assert_eq!(
+            linhas(&biblioteca, Relatorio::PorMes, periodo),
+            [
+                vec![json!("2024-01"), json!(0), json!(0)],
+                vec![json!("2024-02"), json!(0), json!(0)],
+            ]
+        );
+    }
+}
@@ -0,0 +1,913 @@
+// Servidor HTTP local que expõe a `Biblioteca` como uma API REST/JSON.
+// Atende uma requisição por vez, então não precisa de trava sobre a biblioteca.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tiny_http::{Header, Method, Response, Server};
+use uuid::Uuid;
+
+use crate::biblioteca::{
+    Biblioteca,
+    contas::{Contas, Sessao},
+};
+use crate::errors::ErroBiblioteca;
+use crate::oai::{self, Repositorio};
+
+const OPENAPI: &str = include_str!("openapi.json");
+
+const TIPO_JSON: &str = "application/json; charset=utf-8";
+const TIPO_XML: &str = "text/xml; charset=utf-8";
+
+#[derive(Deserialize)]
+struct DadosLivro {
+    titulo: String,
+    autor: String,
+    ano: u16,
+}
+
+#[derive(Deserialize)]
+struct DadosUsuario {
+    nome: String,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+impl DadosUsuario {
+    fn cadastrar(self, biblioteca: &mut Biblioteca) -> Result<Uuid, ErroBiblioteca> {
+        let id = biblioteca.cadastrar_usuario(self.nome)?;
+        if self.email.is_some() {
+            biblioteca.definir_email(id, self.email)?;
+        }
+        Ok(id)
+    }
+}
+
+#[derive(Deserialize)]
+struct DadosEmprestimo {
+    id_usuario: Uuid,
+    id_livro: Uuid,
+}
+
+// Vários livros para o mesmo usuário, tudo ou nada
+#[derive(Deserialize)]
+struct DadosLoteEmprestimos {
+    id_usuario: Uuid,
+    ids_livros: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+struct DadosUnidade {
+    sigla: String,
+    nome: String,
+}
+
+// A unidade aceita a sigla ou o id
+#[derive(Deserialize)]
+struct DadosTransferencia {
+    unidade: String,
+}
+
+#[derive(Deserialize)]
+struct DadosParceira {
+    nome: String,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DadosSolicitacao {
+    id_usuario: Uuid,
+    titulo: String,
+    autor: String,
+    ano: u16,
+}
+
+// A parceira aceita o nome ou o id
+#[derive(Deserialize)]
+struct DadosEnvio {
+    parceira: String,
+}
+
+#[derive(Deserialize)]
+struct DadosRecebimento {
+    vencimento: NaiveDate,
+}
+
+#[derive(Deserialize)]
+struct DadosEdicaoEmprestimo {
+    id_usuario: Uuid,
+    data_devolucao: NaiveDate,
+}
+
+pub struct Resposta {
+    pub status: u16,
+    pub corpo: String,
+    pub tipo: &'static str,
+}
+
+impl Resposta {
+    fn json<T: Serialize>(status: u16, valor: &T) -> Self {
+        match serde_json::to_string_pretty(valor) {
+            Ok(corpo) => Resposta {
+                status,
+                corpo,
+                tipo: TIPO_JSON,
+            },
+            Err(e) => Resposta::erro(500, &format!("Erro ao serializar JSON: {}", e)),
+        }
+    }
+
+    fn vazia() -> Self {
+        Resposta {
+            status: 204,
+            corpo: String::new(),
+            tipo: TIPO_JSON,
+        }
+    }
+
+    fn erro(status: u16, mensagem: &str) -> Self {
+        Resposta {
+            status,
+            corpo: json!({ "erro": mensagem }).to_string(),
+            tipo: TIPO_JSON,
+        }
+    }
+
+    // O OAI-PMH responde sempre 200, inclusive com erros do protocolo
+    fn xml(corpo: String) -> Self {
+        Resposta {
+            status: 200,
+            corpo,
+            tipo: TIPO_XML,
+        }
+    }
+}
+
+impl From<ErroBiblioteca> for Resposta {
+    fn from(erro: ErroBiblioteca) -> Self {
+        let status = match erro {
+            ErroBiblioteca::LivroNaoEncontrado(_)
+            | ErroBiblioteca::UsuarioNaoEncontrado(_)
+            | ErroBiblioteca::EmprestimoNaoEncontrado(_)
+            | ErroBiblioteca::UnidadeNaoEncontrada(_)
+            | ErroBiblioteca::SolicitacaoNaoEncontrada(_)
+            | ErroBiblioteca::ParceiraNaoEncontrada(_)
+            | ErroBiblioteca::ContaNaoEncontrada(_) => 404,
+            ErroBiblioteca::DadosInvalidos(_) => 422,
+            ErroBiblioteca::EstadoInvalido(_) => 409,
+            ErroBiblioteca::CredenciaisInvalidas => 401,
+            ErroBiblioteca::PermissaoNegada(_) => 403,
+            ErroBiblioteca::ErroEnvio(_) => 502,
+            ErroBiblioteca::ErroPersistencia(_)
+            | ErroBiblioteca::OperacaoCancelada
+            | ErroBiblioteca::EntradaEncerrada => 500,
+        };
+        Resposta::erro(status, &erro.to_string())
+    }
+}
+
+pub struct Servidor {
+    http: Server,
+    repositorio: Repositorio,
+}
+
+impl Servidor {
+    // Use a porta 0 para o sistema escolher uma porta livre (útil em testes)
+    pub fn iniciar(endereco: &str) -> Result<Self, ErroBiblioteca> {
+        let http = Server::http(endereco).map_err(|e| {
+            ErroBiblioteca::ErroPersistencia(format!("Erro ao abrir {}: {}", endereco, e))
+        })?;
+        Ok(Self {
+            http,
+            repositorio: Repositorio::default(),
+        })
+    }
+
+    // Como o catálogo se apresenta aos agregadores OAI-PMH
+    pub fn com_repositorio(mut self, repositorio: Repositorio) -> Self {
+        self.repositorio = repositorio;
+        self
+    }
+
+    pub fn endereco(&self) -> String {
+        self.http.server_addr().to_string()
+    }
+
+    pub fn atender(&self, biblioteca: &mut Biblioteca, contas: &mut Contas) {
+        let mut sessoes = Sessoes::new(contas);
+
+        for mut requisicao in self.http.incoming_requests() {
+            let autorizacao = requisicao
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.as_str().to_string());
+
+            let mut corpo = String::new();
+            let (caminho, consulta) = requisicao
+                .url()
+                .split_once('?')
+                .unwrap_or((requisicao.url(), ""));
+            let resposta = if caminho == "/openapi.json" {
+                rotear(biblioteca, requisicao.method(), requisicao.url(), "")
+            } else if caminho == "/oai" {
+                // O catálogo é público: agregadores colhem sem credenciais
+                let consulta = consulta.to_string();
+                let host = requisicao
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Host"))
+                    .map_or_else(|| self.endereco(), |h| h.value.as_str().to_string());
+                let base_url = format!("http://{}/oai", host);
+                match requisicao.method() {
+                    Method::Get => Resposta::xml(oai::responder(
+                        biblioteca,
+                        &self.repositorio,
+                        &consulta,
+                        &base_url,
+                    )),
+                    Method::Post => match requisicao.as_reader().read_to_string(&mut corpo) {
+                        Ok(_) => Resposta::xml(oai::responder(
+                            biblioteca,
+                            &self.repositorio,
+                            corpo.trim(),
+                            &base_url,
+                        )),
+                        Err(_) => Resposta::erro(400, "Corpo da requisição não é UTF-8 válido"),
+                    },
+                    _ => Resposta::erro(405, "Método não permitido"),
+                }
+            } else {
+                match sessoes.autenticar(contas, autorizacao.as_deref()) {
+                    Err(e) => e.into(),
+                    Ok(sessao) => {
+                        biblioteca.entrar(sessao);
+                        match requisicao.as_reader().read_to_string(&mut corpo) {
+                            Ok(_) => {
+                                rotear(biblioteca, requisicao.method(), requisicao.url(), &corpo)
+                            }
+                            Err(_) => Resposta::erro(400, "Corpo da requisição não é UTF-8 válido"),
+                        }
+                    }
+                }
+            };
+
+            let tipo = Header::from_bytes("Content-Type", resposta.tipo)
+                .expect("cabeçalho estático válido");
+            let mut resposta_http = Response::from_string(resposta.corpo)
+                .with_status_code(resposta.status)
+                .with_header(tipo);
+            if resposta.status == 401 {
+                let desafio = Header::from_bytes(
+                    "WWW-Authenticate",
+                    "Basic realm=\"biblioteca\", charset=\"UTF-8\"",
+                )
+                .expect("cabeçalho estático válido");
+                resposta_http.add_header(desafio);
+            }
+
+            if let Err(e) = requisicao.respond(resposta_http) {
+                eprintln!("Erro ao responder requisição: {}", e);
+            }
+        }
+    }
+}
+
+// Por quanto tempo uma credencial aceita vale sem passar de novo pelo Argon2
+const VALIDADE_SESSAO: Duration = Duration::from_secs(5 * 60);
+
+// Verificar Argon2 é caro de propósito, então credenciais aceitas ficam
+// guardadas por um tempo. Se o arquivo de contas mudar (conta removida, papel ou
+// senha trocados pelo comando `contas`), as contas são relidas e tudo o que
+// estava guardado é esquecido.
+struct Sessoes {
+    aceitas: HashMap<String, (Sessao, Instant)>,
+    versao_contas: Option<SystemTime>,
+}
+
+impl Sessoes {
+    fn new(contas: &Contas) -> Self {
+        Sessoes {
+            aceitas: HashMap::new(),
+            versao_contas: contas.versao(),
+        }
+    }
+
+    fn acompanhar(&mut self, contas: &mut Contas) {
+        let versao = contas.versao();
+        if versao == self.versao_contas {
+            return;
+        }
+
+        self.aceitas.clear();
+        match contas.recarregar() {
+            Ok(()) => self.versao_contas = versao,
+            // Arquivo no meio de uma gravação: tenta de novo na próxima requisição
+            Err(e) => eprintln!("Aviso: {}", e),
+        }
+    }
+
+    // Autenticação HTTP Basic com as contas de funcionários
+    fn autenticar(
+        &mut self,
+        contas: &mut Contas,
+        autorizacao: Option<&str>,
+    ) -> Result<Sessao, ErroBiblioteca> {
+        let autorizacao = autorizacao.ok_or(ErroBiblioteca::CredenciaisInvalidas)?;
+        self.acompanhar(contas);
+        self.aceitas
+            .retain(|_, (_, aceita_em)| aceita_em.elapsed() < VALIDADE_SESSAO);
+        if let Some((sessao, _)) = self.aceitas.get(autorizacao) {
+            return Ok(sessao.clone());
+        }
+
+        let credenciais = autorizacao
+            .strip_prefix("Basic ")
+            .and_then(|b64| BASE64_STANDARD.decode(b64.trim()).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(ErroBiblioteca::CredenciaisInvalidas)?;
+        let (login, senha) = credenciais
+            .split_once(':')
+            .ok_or(ErroBiblioteca::CredenciaisInvalidas)?;
+
+        let sessao = contas.autenticar(login, senha)?;
+        self.aceitas
+            .insert(autorizacao.to_string(), (sessao.clone(), Instant::now()));
+        Ok(sessao)
+    }
+}
+
+fn ler_json<'a, T: Deserialize<'a>>(corpo: &'a str) -> Result<T, Resposta> {
+    serde_json::from_str(corpo).map_err(|e| Resposta::erro(400, &format!("JSON inválido: {}", e)))
+}
+
+fn ler_id(texto: &str) -> Result<Uuid, Resposta> {
+    Uuid::parse_str(texto).map_err(|_| Resposta::erro(400, &format!("UUID inválido: {}", texto)))
+}
+
+pub fn rotear(biblioteca: &mut Biblioteca, metodo: &Method, url: &str, corpo: &str) -> Resposta {
+    let (caminho, consulta) = url.split_once('?').unwrap_or((url, ""));
+    let partes: Vec<&str> = caminho.split('/').filter(|p| !p.is_empty()).collect();
+
+    // `?unidade=SIGLA` diz em que unidade o balcão que chamou está; sem ele,
+    // vale a unidade com que o servidor foi iniciado
+    let unidade_padrao = biblioteca.unidade_atual();
+    let unidade = consulta
+        .split('&')
+        .find_map(|par| par.strip_prefix("unidade="));
+    if let Some(sigla) = unidade {
+        match biblioteca.unidade(sigla) {
+            Ok(unidade) => {
+                let id = unidade.id;
+                biblioteca.definir_unidade_atual(Some(id));
+            }
+            Err(e) => return e.into(),
+        }
+    }
+
+    let resultado = match (metodo, partes.as_slice()) {
+        (Method::Get, ["openapi.json"]) => Ok(Resposta {
+            status: 200,
+            corpo: OPENAPI.to_string(),
+            tipo: TIPO_JSON,
+        }),
+
+        // ======== Livros ========
+        (Method::Get, ["livros"]) => Ok(Resposta::json(200, &ordenados(biblioteca.livros()))),
+        (Method::Post, ["livros"]) => ler_json::<DadosLivro>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.cadastrar_livro(dados.titulo, dados.autor, dados.ano)
+            })?;
+            Ok(Resposta::json(201, &biblioteca.livros()[&id]))
+        }),
+        (Method::Get, ["livros", id]) => ler_id(id).and_then(|id| {
+            let livro = biblioteca
+                .livros()
+                .get(&id)
+                .ok_or(ErroBiblioteca::LivroNaoEncontrado(id))?;
+            Ok(Resposta::json(200, livro))
+        }),
+        (Method::Put, ["livros", id]) => ler_id(id).and_then(|id| {
+            let dados = ler_json::<DadosLivro>(corpo)?;
+            biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.atualizar_livro(id, dados.titulo, dados.autor, dados.ano)
+            })?;
+            Ok(Resposta::json(200, &biblioteca.livros()[&id]))
+        }),
+        // Com histórico de empréstimos o livro é só arquivado e volta no corpo da resposta
+        (Method::Delete, ["livros", id]) => ler_id(id).and_then(|id| {
+            let livro = biblioteca.alterar_e_salvar(|biblioteca| biblioteca.remover_livro(id))?;
+            let resposta = if livro.arquivado {
+                Resposta::json(200, &livro)
+            } else {
+                Resposta::vazia()
+            };
+            Ok(resposta)
+        }),
+
+        // ======== Usuários ========
+        (Method::Get, ["usuarios"]) => Ok(Resposta::json(200, &ordenados(biblioteca.usuarios()))),
+        (Method::Post, ["usuarios"]) => ler_json::<DadosUsuario>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.transacao(|biblioteca| dados.cadastrar(biblioteca))
+            })?;
+            Ok(Resposta::json(201, &biblioteca.usuarios()[&id]))
+        }),
+        (Method::Post, ["usuarios", "lote"]) => {
+            ler_json::<Vec<DadosUsuario>>(corpo).and_then(|lote| {
+                let ids = biblioteca.alterar_e_salvar(|biblioteca| {
+                    biblioteca.transacao(|biblioteca| {
+                        lote.into_iter()
+                            .map(|dados| dados.cadastrar(biblioteca))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })?;
+                let usuarios: Vec<_> = ids.iter().map(|id| &biblioteca.usuarios()[id]).collect();
+                let resposta = Resposta::json(201, &usuarios);
+                Ok(resposta)
+            })
+        }
+        (Method::Get, ["usuarios", id]) => ler_id(id).and_then(|id| {
+            let usuario = biblioteca
+                .usuarios()
+                .get(&id)
+                .ok_or(ErroBiblioteca::UsuarioNaoEncontrado(id))?;
+            Ok(Resposta::json(200, usuario))
+        }),
+        (Method::Put, ["usuarios", id]) => ler_id(id).and_then(|id| {
+            let dados = ler_json::<DadosUsuario>(corpo)?;
+            // Sem `email` no corpo, o endereço é removido (o PUT substitui o usuário)
+            biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.transacao(|biblioteca| {
+                    biblioteca.atualizar_usuario(id, dados.nome)?;
+                    biblioteca.definir_email(id, dados.email)
+                })
+            })?;
+            Ok(Resposta::json(200, &biblioteca.usuarios()[&id]))
+        }),
+        (Method::Delete, ["usuarios", id]) => ler_id(id).and_then(|id| {
+            let usuario =
+                biblioteca.alterar_e_salvar(|biblioteca| biblioteca.remover_usuario(id))?;
+            let resposta = if usuario.arquivado {
+                Resposta::json(200, &usuario)
+            } else {
+                Resposta::vazia()
+            };
+            Ok(resposta)
+        }),
+        (Method::Post, ["livros", id, "recebimento"]) => ler_id(id).and_then(|id| {
+            biblioteca.alterar_e_salvar(|biblioteca| biblioteca.receber_livro(id))?;
+            Ok(Resposta::json(200, &biblioteca.livros()[&id]))
+        }),
+        (Method::Post, ["livros", id, "transferencia"]) => ler_id(id).and_then(|id| {
+            let dados = ler_json::<DadosTransferencia>(corpo)?;
+            let destino = biblioteca.unidade(&dados.unidade)?.id;
+            biblioteca.alterar_e_salvar(|biblioteca| biblioteca.transferir_livro(id, destino))?;
+            Ok(Resposta::json(200, &biblioteca.livros()[&id]))
+        }),
+
+        // ======== Unidades ========
+        (Method::Get, ["unidades"]) => Ok(Resposta::json(200, &ordenados(biblioteca.unidades()))),
+        (Method::Post, ["unidades"]) => ler_json::<DadosUnidade>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.cadastrar_unidade(&dados.sigla, dados.nome)
+            })?;
+            Ok(Resposta::json(201, &biblioteca.unidades()[&id]))
+        }),
+
+        // ======== Empréstimos ========
+        (Method::Get, ["emprestimos"]) => {
+            Ok(Resposta::json(200, &ordenados(biblioteca.emprestimos())))
+        }
+        (Method::Post, ["emprestimos"]) => ler_json::<DadosEmprestimo>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.realizar_emprestimo(dados.id_usuario, dados.id_livro)
+            })?;
+            Ok(Resposta::json(201, &biblioteca.emprestimos()[&id]))
+        }),
+        (Method::Post, ["emprestimos", "lote"]) => ler_json::<DadosLoteEmprestimos>(corpo)
+            .and_then(|dados| {
+                let ids = biblioteca.alterar_e_salvar(|biblioteca| {
+                    biblioteca.transacao(|biblioteca| {
+                        dados
+                            .ids_livros
+                            .iter()
+                            .map(|id_livro| {
+                                biblioteca.realizar_emprestimo(dados.id_usuario, *id_livro)
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                })?;
+                let emprestimos: Vec<_> =
+                    ids.iter().map(|id| &biblioteca.emprestimos()[id]).collect();
+                let resposta = Resposta::json(201, &emprestimos);
+                Ok(resposta)
+            }),
+        (Method::Get, ["emprestimos", id]) => ler_id(id).and_then(|id| {
+            let emprestimo = biblioteca
+                .emprestimos()
+                .get(&id)
+                .ok_or(ErroBiblioteca::EmprestimoNaoEncontrado(id))?;
+            Ok(Resposta::json(200, emprestimo))
+        }),
+        (Method::Put, ["emprestimos", id]) => ler_id(id).and_then(|id| {
+            let dados = ler_json::<DadosEdicaoEmprestimo>(corpo)?;
+            biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.atualizar_emprestimo(id, dados.id_usuario, dados.data_devolucao)
+            })?;
+            Ok(Resposta::json(200, &biblioteca.emprestimos()[&id]))
+        }),
+        (Method::Delete, ["emprestimos", id]) => ler_id(id).and_then(|id| {
+            biblioteca.alterar_e_salvar(|biblioteca| biblioteca.remover_emprestimo(id))?;
+            Ok(Resposta::vazia())
+        }),
+        (Method::Post, ["emprestimos", id, "devolucao"]) => ler_id(id).and_then(|id| {
+            biblioteca.alterar_e_salvar(|biblioteca| biblioteca.devolver(id))?;
+            Ok(Resposta::json(200, &biblioteca.emprestimos()[&id]))
+        }),
+        (Method::Post, ["emprestimos", id, "renovacao"]) => ler_id(id).and_then(|id| {
+            biblioteca.alterar_e_salvar(|biblioteca| biblioteca.renovar(id))?;
+            Ok(Resposta::json(200, &biblioteca.emprestimos()[&id]))
+        }),
+
+        // ======== Intercâmbio ========
+        (Method::Get, ["parceiras"]) => Ok(Resposta::json(200, &ordenados(biblioteca.parceiras()))),
+        (Method::Post, ["parceiras"]) => ler_json::<DadosParceira>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.cadastrar_parceira(dados.nome, dados.email)
+            })?;
+            Ok(Resposta::json(201, &biblioteca.parceiras()[&id]))
+        }),
+        (Method::Get, ["intercambio"]) => {
+            Ok(Resposta::json(200, &ordenados(biblioteca.solicitacoes())))
+        }
+        (Method::Post, ["intercambio"]) => ler_json::<DadosSolicitacao>(corpo).and_then(|dados| {
+            let id = biblioteca.alterar_e_salvar(|biblioteca| {
+                biblioteca.solicitar_intercambio(
+                    dados.id_usuario,
+                    dados.titulo,
+                    dados.autor,
+                    dados.ano,
+                )
+            })?;
+            Ok(Resposta::json(201, &biblioteca.solicitacoes()[&id]))
+        }),
+        (Method::Get, ["intercambio", id]) => ler_id(id).and_then(|id| {
+            let solicitacao = biblioteca
+                .solicitacoes()
+                .get(&id)
+                .ok_or(ErroBiblioteca::SolicitacaoNaoEncontrada(id))?;
+            Ok(Resposta::json(200, solicitacao))
+        }),
+        (Method::Post, ["intercambio", id, etapa]) => {
+            ler_id(id).and_then(|id| {
+                match *etapa {
+                    "envio" => {
+                        let dados = ler_json::<DadosEnvio>(corpo)?;
+                        let id_parceira = biblioteca.parceira(&dados.parceira)?.id;
+                        biblioteca.alterar_e_salvar(|biblioteca| {
+                            biblioteca.enviar_solicitacao(id, id_parceira)
+                        })?;
+                    }
+                    "recebimento" => {
+                        let dados = ler_json::<DadosRecebimento>(corpo)?;
+                        biblioteca.alterar_e_salvar(|biblioteca| {
+                            biblioteca.receber_intercambio(id, dados.vencimento)
+                        })?;
+                    }
+                    "emprestimo" => {
+                        biblioteca
+                            .alterar_e_salvar(|biblioteca| biblioteca.emprestar_intercambio(id))?;
+                    }
+                    "devolucao" => biblioteca
+                        .alterar_e_salvar(|biblioteca| biblioteca.devolver_a_parceira(id))?,
+                    "cancelamento" => biblioteca
+                        .alterar_e_salvar(|biblioteca| biblioteca.cancelar_solicitacao(id))?,
+                    _ => return Err(Resposta::erro(404, "Rota não encontrada")),
+                }
+                Ok(Resposta::json(200, &biblioteca.solicitacoes()[&id]))
+            })
+        }
+
+        (
+            _,
+            [
+                "livros" | "usuarios" | "emprestimos" | "unidades" | "parceiras" | "intercambio",
+                ..,
+            ],
+        ) => Err(Resposta::erro(405, "Método não permitido")),
+        _ => Err(Resposta::erro(404, "Rota não encontrada")),
+    };
+
+    biblioteca.definir_unidade_atual(unidade_padrao);
+    resultado.unwrap_or_else(|resposta| resposta)
+}
+
+// Lista em ordem estável (pelo id), já que o HashMap não garante ordem
+fn ordenados<T>(mapa: &HashMap<Uuid, T>) -> Vec<&T> {
+    let mut ids: Vec<&Uuid> = mapa.keys().collect();
+    ids.sort();
+    ids.into_iter().map(|id| &mapa[id]).collect()
+}
+
+#[cfg(test)]
+mod testes {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        thread,
+    };
+
+    use serde_json::Value;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::biblioteca::contas::Papel;
+
+    const ADMIN: (&str, &str) = ("admin", "segredo123");
+    const ASSISTENTE: (&str, &str) = ("balcao", "segredo456");
+
+    // Sobe o servidor numa porta livre, com uma biblioteca vazia num diretório
+    // temporário, e devolve o endereço para as requisições
+    fn iniciar() -> (String, TempDir) {
+        let pasta = TempDir::new().expect("diretório temporário");
+        let caminho = pasta.path().join("dados.json");
+
+        let mut contas = Contas::carregar(&caminho).expect("contas");
+        contas
+            .criar_primeiro_admin(ADMIN.0, ADMIN.1)
+            .expect("admin");
+        let admin = contas.autenticar(ADMIN.0, ADMIN.1).expect("login do admin");
+        contas
+            .criar(&admin, ASSISTENTE.0, ASSISTENTE.1, Papel::Assistente)
+            .expect("assistente");
+
+        let servidor = Servidor::iniciar("127.0.0.1:0").expect("porta livre");
+        let endereco = servidor.endereco();
+        let mut biblioteca = Biblioteca::nova(&caminho);
+        thread::spawn(move || servidor.atender(&mut biblioteca, &mut contas));
+
+        (endereco, pasta)
+    }
+
+    fn requisitar(
+        endereco: &str,
+        metodo: &str,
+        caminho: &str,
+        credenciais: Option<(&str, &str)>,
+        corpo: &str,
+    ) -> (u16, Value) {
+        let mut conexao = TcpStream::connect(endereco).expect("conexão com o servidor");
+        let mut cabecalhos = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            metodo,
+            caminho,
+            endereco,
+            corpo.len()
+        );
+        if let Some((login, senha)) = credenciais {
+            let token = BASE64_STANDARD.encode(format!("{}:{}", login, senha));
+            cabecalhos.push_str(&format!("Authorization: Basic {}\r\n", token));
+        }
+        write!(conexao, "{}\r\n{}", cabecalhos, corpo).expect("envio da requisição");
+
+        let mut resposta = String::new();
+        conexao
+            .read_to_string(&mut resposta)
+            .expect("leitura da resposta");
+        let (cabecalho, corpo) = resposta.split_once("\r\n\r\n").unwrap_or((&resposta, ""));
+        let status = cabecalho
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .expect("linha de status");
+        (status, serde_json::from_str(corpo).unwrap_or(Value::Null))
+    }
+
+    fn id(valor: &Value) -> String {
+        valor["id"]
+            .as_str()
+            .or_else(|| valor["id_emprestimo"].as_str())
+            .expect("id na resposta")
+            .to_string()
+    }
+
+    #[test]
+    fn crud_de_livros() {
+        let (endereco, _pasta) = iniciar();
+        let admin = Some(ADMIN);
+
+        let livro = r#"{"titulo": "Dom Casmurro", "autor": "Machado de Assis", "ano": 1899}"#;
+        let (status, criado) = requisitar(&endereco, "POST", "/livros", admin, livro);
+        assert_eq!(status, 201);
+        let caminho = format!("/livros/{}", id(&criado));
+
+        let (status, lido) = requisitar(&endereco, "GET", &caminho, admin, "");
+        assert_eq!(status, 200);
+        assert_eq!(lido["titulo"], "Dom Casmurro");
+
+        let edicao = r#"{"titulo": "Dom Casmurro", "autor": "Machado de Assis", "ano": 1900}"#;
+        let (status, editado) = requisitar(&endereco, "PUT", &caminho, admin, edicao);
+        assert_eq!(status, 200);
+        assert_eq!(editado["ano"], 1900);
+
+        let (status, lista) = requisitar(&endereco, "GET", "/livros", admin, "");
+        assert_eq!(status, 200);
+        assert_eq!(lista.as_array().map(Vec::len), Some(1));
+
+        assert_eq!(requisitar(&endereco, "DELETE", &caminho, admin, "").0, 204);
+        assert_eq!(requisitar(&endereco, "GET", &caminho, admin, "").0, 404);
+    }
+
+    #[test]
+    fn emprestimo_renovacao_e_devolucao() {
+        let (endereco, _pasta) = iniciar();
+        let admin = Some(ADMIN);
+
+        let (_, livro) = requisitar(
+            &endereco,
+            "POST",
+            "/livros",
+            admin,
+            r#"{"titulo": "Iracema", "autor": "José de Alencar", "ano": 1865}"#,
+        );
+        let (_, usuario) = requisitar(&endereco, "POST", "/usuarios", admin, r#"{"nome": "Ana"}"#);
+        let pedido = format!(
+            r#"{{"id_usuario": "{}", "id_livro": "{}"}}"#,
+            id(&usuario),
+            id(&livro)
+        );
+
+        let (status, emprestimo) = requisitar(&endereco, "POST", "/emprestimos", admin, &pedido);
+        assert_eq!(status, 201);
+        let caminho = format!("/emprestimos/{}", id(&emprestimo));
+
+        // O mesmo exemplar não sai duas vezes
+        assert_eq!(
+            requisitar(&endereco, "POST", "/emprestimos", admin, &pedido).0,
+            409
+        );
+
+        let renovacao = format!("{}/renovacao", caminho);
+        assert_eq!(requisitar(&endereco, "POST", &renovacao, admin, "").0, 200);
+
+        let devolucao = format!("{}/devolucao", caminho);
+        let (status, devolvido) = requisitar(&endereco, "POST", &devolucao, admin, "");
+        assert_eq!(status, 200);
+        assert_eq!(devolvido["status"], "Devolvido");
+        assert_eq!(requisitar(&endereco, "POST", &devolucao, admin, "").0, 409);
+        assert_eq!(requisitar(&endereco, "POST", &renovacao, admin, "").0, 409);
+
+        let (_, livro) = requisitar(
+            &endereco,
+            "GET",
+            &format!("/livros/{}", id(&livro)),
+            admin,
+            "",
+        );
+        assert_eq!(livro["status"], "Disponivel");
+    }
+
+    #[test]
+    fn codigos_de_erro() {
+        let (endereco, _pasta) = iniciar();
+
+        // 401: sem credenciais ou com a senha errada
+        assert_eq!(requisitar(&endereco, "GET", "/livros", None, "").0, 401);
+        let errada = Some((ADMIN.0, "senha-errada"));
+        assert_eq!(requisitar(&endereco, "GET", "/livros", errada, "").0, 401);
+
+        // 403: o assistente não cadastra livros
+        let livro = r#"{"titulo": "O Ateneu", "autor": "Raul Pompeia", "ano": 1888}"#;
+        let (status, _) = requisitar(&endereco, "POST", "/livros", Some(ASSISTENTE), livro);
+        assert_eq!(status, 403);
+
+        // 404: id ou rota inexistente
+        let inexistente = format!("/livros/{}", Uuid::new_v4());
+        assert_eq!(
+            requisitar(&endereco, "GET", &inexistente, Some(ADMIN), "").0,
+            404
+        );
+        assert_eq!(
+            requisitar(&endereco, "GET", "/estantes", Some(ADMIN), "").0,
+            404
+        );
+
+        // 422: dados que a biblioteca recusa
+        let sem_titulo = r#"{"titulo": " ", "autor": "Raul Pompeia", "ano": 1888}"#;
+        let (status, erro) = requisitar(&endereco, "POST", "/livros", Some(ADMIN), sem_titulo);
+        assert_eq!(status, 422);
+        assert!(erro["erro"].is_string());
+
+        // 409: remover um usuário que ainda está com um livro emprestado
+        let (_, livro) = requisitar(&endereco, "POST", "/livros", Some(ADMIN), livro);
+        let (_, usuario) = requisitar(
+            &endereco,
+            "POST",
+            "/usuarios",
+            Some(ADMIN),
+            r#"{"nome": "Bento"}"#,
+        );
+        let pedido = format!(
+            r#"{{"id_usuario": "{}", "id_livro": "{}"}}"#,
+            id(&usuario),
+            id(&livro)
+        );
+        assert_eq!(
+            requisitar(&endereco, "POST", "/emprestimos", Some(ASSISTENTE), &pedido).0,
+            201
+        );
+        let caminho = format!("/usuarios/{}", id(&usuario));
+        assert_eq!(
+            requisitar(&endereco, "DELETE", &caminho, Some(ADMIN), "").0,
+            409
+        );
+    }
+
+    #[test]
+    fn conta_removida_deixa_de_autenticar() {
+        let (endereco, pasta) = iniciar();
+        assert_eq!(
+            requisitar(&endereco, "GET", "/livros", Some(ASSISTENTE), "").0,
+            200
+        );
+
+        // Outro processo (o comando `contas remover`) altera o arquivo de contas
+        let mut contas = Contas::carregar(&pasta.path().join("dados.json")).expect("contas");
+        let admin = contas.autenticar(ADMIN.0, ADMIN.1).expect("login do admin");
+        contas.remover(&admin, ASSISTENTE.0).expect("remoção");
+
+        assert_eq!(
+            requisitar(&endereco, "GET", "/livros", Some(ASSISTENTE), "").0,
+            401
+        );
+        assert_eq!(
+            requisitar(&endereco, "GET", "/livros", Some(ADMIN), "").0,
+            200
+        );
+    }
+
+    #[test]
+    fn falha_ao_salvar_desfaz_a_alteracao() {
+        let (endereco, pasta) = iniciar();
+        let livro = r#"{"titulo": "Senhora", "autor": "José de Alencar", "ano": 1875}"#;
+
+        // Nem o diário pode ser gravado: nada foi feito e dá para tentar de novo
+        let diario = pasta.path().join("dados.diario.jsonl");
+        std::fs::create_dir(&diario).expect("bloquear o diário");
+        assert_eq!(
+            requisitar(&endereco, "POST", "/livros", Some(ADMIN), livro).0,
+            500
+        );
+        let (_, lista) = requisitar(&endereco, "GET", "/livros", Some(ADMIN), "");
+        assert_eq!(lista.as_array().map(Vec::len), Some(0));
+
+        std::fs::remove_dir(&diario).expect("liberar o diário");
+        assert_eq!(
+            requisitar(&endereco, "POST", "/livros", Some(ADMIN), livro).0,
+            201
+        );
+        let (_, lista) = requisitar(&endereco, "GET", "/livros", Some(ADMIN), "");
+        assert_eq!(lista.as_array().map(Vec::len), Some(1));
+
+        // Só o retrato falha: o diário já garante a alteração, que conta como feita
+        std::fs::create_dir(pasta.path().join("dados.json.tmp")).expect("bloquear o retrato");
+        assert_eq!(
+            requisitar(&endereco, "POST", "/livros", Some(ADMIN), livro).0,
+            201
+        );
+        let (_, lista) = requisitar(&endereco, "GET", "/livros", Some(ADMIN), "");
+        assert_eq!(lista.as_array().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn pedido_de_intercambio_fica_gravado() {
+        let (endereco, pasta) = iniciar();
+        let admin = Some(ADMIN);
+
+        let (_, usuario) = requisitar(&endereco, "POST", "/usuarios", admin, r#"{"nome": "Lia"}"#);
+        let pedido = format!(
+            r#"{{"id_usuario": "{}", "titulo": "Grande Sertão: Veredas", "autor": "Guimarães Rosa", "ano": 1956}}"#,
+            id(&usuario)
+        );
+        let (status, criado) = requisitar(&endereco, "POST", "/intercambio", admin, &pedido);
+        assert_eq!(status, 201);
+
+        // Outro processo lendo o disco encontra o pedido
+        let recarregada =
+            Biblioteca::carregar(pasta.path().join("dados.json")).expect("recarregar");
+        let gravado = recarregada
+            .solicitacoes()
+            .values()
+            .find(|s| s.id.to_string() == id(&criado))
+            .expect("pedido gravado");
+        assert_eq!(gravado.titulo, "Grande Sertão: Veredas");
+    }
+}
@@ -0,0 +1,595 @@
+// Interface de tela cheia (TUI) sobre as operações da `Biblioteca`.
+// Só usa sequências ANSI via crossterm, então funciona também por SSH.
+
+use std::io;
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+};
+use uuid::Uuid;
+
+use crate::biblioteca::{
+    Biblioteca,
+    emprestimos::{Emprestimo, StatusEmprestimo},
+    livros::StatusLivro,
+};
+use crate::traits::Identificavel;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Aba {
+    Livros,
+    Usuarios,
+    Emprestimos,
+}
+
+impl Aba {
+    const TODAS: [Aba; 3] = [Aba::Livros, Aba::Usuarios, Aba::Emprestimos];
+
+    fn titulo(self) -> &'static str {
+        match self {
+            Aba::Livros => "Livros",
+            Aba::Usuarios => "Usuários",
+            Aba::Emprestimos => "Empréstimos",
+        }
+    }
+
+    fn indice(self) -> usize {
+        Aba::TODAS.iter().position(|a| *a == self).unwrap_or(0)
+    }
+
+    fn proxima(self) -> Aba {
+        Aba::TODAS[(self.indice() + 1) % Aba::TODAS.len()]
+    }
+
+    fn anterior(self) -> Aba {
+        Aba::TODAS[(self.indice() + Aba::TODAS.len() - 1) % Aba::TODAS.len()]
+    }
+}
+
+enum Modo {
+    Navegacao,
+    Busca,
+    // Janela para escolher quem vai levar o livro selecionado
+    EscolhaUsuario {
+        id_livro: Uuid,
+        busca: String,
+        lista: ListState,
+    },
+}
+
+struct App<'a> {
+    biblioteca: &'a mut Biblioteca,
+    aba: Aba,
+    busca: String,
+    modo: Modo,
+    lista: ListState,
+    mensagem: String,
+    sair: bool,
+}
+
+fn contem(texto: &str, busca: &str) -> bool {
+    texto.to_lowercase().contains(&busca.to_lowercase())
+}
+
+fn mover(lista: &mut ListState, total: usize, passo: isize) {
+    if total == 0 {
+        lista.select(None);
+        return;
+    }
+    let atual = lista.selected().unwrap_or(0) as isize;
+    let novo = (atual + passo).clamp(0, total as isize - 1);
+    lista.select(Some(novo as usize));
+}
+
+impl<'a> App<'a> {
+    fn new(biblioteca: &'a mut Biblioteca) -> Self {
+        Self {
+            biblioteca,
+            aba: Aba::Livros,
+            busca: String::new(),
+            modo: Modo::Navegacao,
+            lista: ListState::default().with_selected(Some(0)),
+            mensagem: String::new(),
+            sair: false,
+        }
+    }
+
+    // ======== Dados exibidos (já filtrados pela busca e em ordem estável) ========
+
+    fn livros_filtrados(&self) -> Vec<Uuid> {
+        let mut livros: Vec<_> = self
+            .biblioteca
+            .livros()
+            .values()
+            .filter(|l| !l.arquivado)
+            .filter(|l| contem(l.get_titulo(), &self.busca) || contem(l.get_autor(), &self.busca))
+            .collect();
+        livros.sort_by(|a, b| a.get_titulo().cmp(b.get_titulo()));
+        livros.iter().map(|l| l.id()).collect()
+    }
+
+    fn usuarios_filtrados(&self, busca: &str) -> Vec<Uuid> {
+        let mut usuarios: Vec<_> = self
+            .biblioteca
+            .usuarios()
+            .values()
+            .filter(|u| !u.arquivado && contem(&u.nome, busca))
+            .collect();
+        usuarios.sort_by(|a, b| a.nome.cmp(&b.nome));
+        usuarios.iter().map(|u| u.id).collect()
+    }
+
+    fn emprestimos_filtrados(&self) -> Vec<Uuid> {
+        let mut emprestimos: Vec<_> = self
+            .biblioteca
+            .emprestimos()
+            .values()
+            .filter(|e| {
+                contem(&self.titulo_do_livro(e.get_id_livro()), &self.busca)
+                    || contem(&self.nome_do_usuario(e.get_id_usuario()), &self.busca)
+            })
+            .collect();
+        // Ativos primeiro, depois pela data de devolução
+        emprestimos.sort_by_key(|e| {
+            (
+                matches!(e.status, StatusEmprestimo::Devolvido),
+                e.get_data_devolucao(),
+            )
+        });
+        emprestimos.iter().map(|e| e.id()).collect()
+    }
+
+    fn ids_da_aba(&self) -> Vec<Uuid> {
+        match self.aba {
+            Aba::Livros => self.livros_filtrados(),
+            Aba::Usuarios => self.usuarios_filtrados(&self.busca),
+            Aba::Emprestimos => self.emprestimos_filtrados(),
+        }
+    }
+
+    fn selecionado(&self) -> Option<Uuid> {
+        self.lista
+            .selected()
+            .and_then(|i| self.ids_da_aba().get(i).copied())
+    }
+
+    fn titulo_do_livro(&self, id: Uuid) -> String {
+        self.biblioteca
+            .livros()
+            .get(&id)
+            .map(|l| l.get_titulo().clone())
+            .unwrap_or_else(|| "Livro não encontrado".to_string())
+    }
+
+    fn nome_do_usuario(&self, id: Uuid) -> String {
+        self.biblioteca
+            .usuarios()
+            .get(&id)
+            .map(|u| u.get_nome())
+            .unwrap_or_else(|| "Usuário não encontrado".to_string())
+    }
+
+    fn emprestimos_do_usuario(&self, id_usuario: Uuid) -> Vec<&Emprestimo> {
+        self.biblioteca
+            .emprestimos()
+            .values()
+            .filter(|e| {
+                e.get_id_usuario() == id_usuario && matches!(e.status, StatusEmprestimo::Ativo)
+            })
+            .collect()
+    }
+
+    // ======== Teclado ========
+
+    fn tratar_tecla(&mut self, tecla: KeyEvent) {
+        // Em modo raw o Ctrl+C não vira SIGINT, então salvamos aqui o que estiver pendente
+        if tecla.modifiers.contains(KeyModifiers::CONTROL) && tecla.code == KeyCode::Char('c') {
+            if let Err(e) = self.biblioteca.salvaguarda().salvar_pendencias() {
+                self.mensagem = format!("❌ {}", e);
+                return;
+            }
+            self.sair = true;
+            return;
+        }
+
+        match self.modo {
+            Modo::Navegacao => self.tecla_navegacao(tecla.code),
+            Modo::Busca => self.tecla_busca(tecla.code),
+            Modo::EscolhaUsuario { .. } => self.tecla_escolha_usuario(tecla.code),
+        }
+    }
+
+    fn tecla_navegacao(&mut self, codigo: KeyCode) {
+        let total = self.ids_da_aba().len();
+
+        match codigo {
+            KeyCode::Char('q') => match self.biblioteca.salvar() {
+                Ok(()) => self.sair = true,
+                Err(e) => self.mensagem = format!("❌ {}", e),
+            },
+            KeyCode::Char('s') => {
+                self.mensagem = match self.biblioteca.salvar() {
+                    Ok(()) => "Biblioteca salva com sucesso.".to_string(),
+                    Err(e) => format!("❌ {}", e),
+                }
+            }
+            KeyCode::Tab | KeyCode::Right => self.trocar_aba(self.aba.proxima()),
+            KeyCode::BackTab | KeyCode::Left => self.trocar_aba(self.aba.anterior()),
+            KeyCode::Char('1') => self.trocar_aba(Aba::Livros),
+            KeyCode::Char('2') => self.trocar_aba(Aba::Usuarios),
+            KeyCode::Char('3') => self.trocar_aba(Aba::Emprestimos),
+            KeyCode::Down | KeyCode::Char('j') => mover(&mut self.lista, total, 1),
+            KeyCode::Up | KeyCode::Char('k') => mover(&mut self.lista, total, -1),
+            KeyCode::PageDown => mover(&mut self.lista, total, 10),
+            KeyCode::PageUp => mover(&mut self.lista, total, -10),
+            KeyCode::Char('/') => self.modo = Modo::Busca,
+            KeyCode::Esc => {
+                self.busca.clear();
+                self.lista.select(Some(0));
+            }
+            KeyCode::Char('e') => self.iniciar_emprestimo(),
+            KeyCode::Char('d') => self.devolver_selecionado(),
+            KeyCode::Char('u') => {
+                self.mensagem = match self.biblioteca.desfazer() {
+                    Ok(comando) => format!("↩ Desfeito: {}", comando),
+                    Err(e) => format!("❌ {}", e),
+                }
+            }
+            KeyCode::Char('r') => {
+                self.mensagem = match self.biblioteca.refazer() {
+                    Ok(comando) => format!("↪ Refeito: {}", comando),
+                    Err(e) => format!("❌ {}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn tecla_busca(&mut self, codigo: KeyCode) {
+        match codigo {
+            KeyCode::Enter => self.modo = Modo::Navegacao,
+            KeyCode::Esc => {
+                self.busca.clear();
+                self.modo = Modo::Navegacao;
+            }
+            KeyCode::Backspace => {
+                self.busca.pop();
+            }
+            KeyCode::Char(c) => self.busca.push(c),
+            _ => return,
+        }
+        self.lista.select(Some(0));
+    }
+
+    fn tecla_escolha_usuario(&mut self, codigo: KeyCode) {
+        let usuarios = self.usuarios_filtrados(&self.busca_modal());
+        let Modo::EscolhaUsuario {
+            id_livro,
+            busca,
+            lista,
+        } = &mut self.modo
+        else {
+            return;
+        };
+        let id_livro = *id_livro;
+
+        match codigo {
+            KeyCode::Esc => {
+                self.modo = Modo::Navegacao;
+                self.mensagem = "Empréstimo cancelado.".to_string();
+            }
+            KeyCode::Backspace => {
+                busca.pop();
+                lista.select(Some(0));
+            }
+            KeyCode::Char(c) => {
+                busca.push(c);
+                lista.select(Some(0));
+            }
+            KeyCode::Down => mover(lista, usuarios.len(), 1),
+            KeyCode::Up => mover(lista, usuarios.len(), -1),
+            KeyCode::Enter => {
+                let Some(id_usuario) = lista.selected().and_then(|i| usuarios.get(i).copied())
+                else {
+                    self.mensagem = "Nenhum usuário selecionado.".to_string();
+                    return;
+                };
+
+                self.mensagem = match self.biblioteca.realizar_emprestimo(id_usuario, id_livro) {
+                    Ok(_) => format!(
+                        "✅ \"{}\" emprestado para {}.",
+                        self.titulo_do_livro(id_livro),
+                        self.nome_do_usuario(id_usuario)
+                    ),
+                    Err(e) => format!("❌ {}", e),
+                };
+                self.modo = Modo::Navegacao;
+            }
+            _ => {}
+        }
+    }
+
+    fn busca_modal(&self) -> String {
+        match &self.modo {
+            Modo::EscolhaUsuario { busca, .. } => busca.clone(),
+            _ => String::new(),
+        }
+    }
+
+    fn trocar_aba(&mut self, aba: Aba) {
+        self.aba = aba;
+        self.busca.clear();
+        self.lista.select(Some(0));
+    }
+
+    fn iniciar_emprestimo(&mut self) {
+        if self.aba != Aba::Livros {
+            self.mensagem = "Selecione um livro na aba Livros para emprestar.".to_string();
+            return;
+        }
+        let Some(id_livro) = self.selecionado() else {
+            return;
+        };
+        if let Some(livro) = self.biblioteca.livros().get(&id_livro)
+            && !livro.esta_disponivel()
+        {
+            self.mensagem = "❌ Livro já está emprestado!".to_string();
+            return;
+        }
+        if self.biblioteca.usuarios().is_empty() {
+            self.mensagem = "❌ Nenhum usuário cadastrado.".to_string();
+            return;
+        }
+
+        self.modo = Modo::EscolhaUsuario {
+            id_livro,
+            busca: String::new(),
+            lista: ListState::default().with_selected(Some(0)),
+        };
+    }
+
+    fn devolver_selecionado(&mut self) {
+        if self.aba != Aba::Emprestimos {
+            self.mensagem = "Selecione um empréstimo na aba Empréstimos para devolver.".to_string();
+            return;
+        }
+        let Some(id_emprestimo) = self.selecionado() else {
+            return;
+        };
+
+        self.mensagem = match self.biblioteca.devolver(id_emprestimo) {
+            Ok(()) => "✅ Devolução registrada.".to_string(),
+            Err(e) => format!("❌ {}", e),
+        };
+    }
+
+    // ======== Desenho ========
+
+    fn desenhar(&mut self, frame: &mut Frame) {
+        let [area_abas, area_busca, area_principal, area_ajuda] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(3),
+        ])
+        .areas(frame.area());
+
+        let abas = Tabs::new(Aba::TODAS.iter().map(|a| a.titulo()))
+            .select(self.aba.indice())
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .block(Block::default().borders(Borders::ALL).title(
+                if self.biblioteca.tem_alteracoes() {
+                    " Biblioteca (alterações não salvas) "
+                } else {
+                    " Biblioteca "
+                },
+            ));
+        frame.render_widget(abas, area_abas);
+
+        let estilo_busca = match self.modo {
+            Modo::Busca => Style::default().fg(Color::Yellow),
+            _ => Style::default(),
+        };
+        let busca = Paragraph::new(self.busca.as_str())
+            .style(estilo_busca)
+            .block(Block::default().borders(Borders::ALL).title(" Buscar (/) "));
+        frame.render_widget(busca, area_busca);
+
+        let [area_lista, area_detalhes] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area_principal);
+        self.desenhar_lista(frame, area_lista);
+        self.desenhar_detalhes(frame, area_detalhes);
+
+        let ajuda = if self.mensagem.is_empty() {
+            "Tab: aba  ↑↓: mover  /: buscar  e: emprestar  d: devolver  u: desfazer  r: refazer  s: salvar  q: salvar e sair"
+                .to_string()
+        } else {
+            self.mensagem.clone()
+        };
+        frame.render_widget(
+            Paragraph::new(ajuda).block(Block::default().borders(Borders::ALL)),
+            area_ajuda,
+        );
+
+        if let Modo::EscolhaUsuario { .. } = self.modo {
+            self.desenhar_escolha_usuario(frame);
+        }
+    }
+
+    fn desenhar_lista(&mut self, frame: &mut Frame, area: Rect) {
+        let ids = self.ids_da_aba();
+        let itens: Vec<ListItem> = ids
+            .iter()
+            .map(|id| match self.aba {
+                Aba::Livros => {
+                    let livro = &self.biblioteca.livros()[id];
+                    let marca = match livro.status() {
+                        StatusLivro::Disponivel => " ",
+                        StatusLivro::Emprestado => "*",
+                    };
+                    ListItem::new(format!(
+                        "{} {} — {}",
+                        marca,
+                        livro.get_titulo(),
+                        livro.get_autor()
+                    ))
+                }
+                Aba::Usuarios => ListItem::new(self.biblioteca.usuarios()[id].get_nome()),
+                Aba::Emprestimos => {
+                    let emprestimo = &self.biblioteca.emprestimos()[id];
+                    ListItem::new(format!(
+                        "{} {} → {}",
+                        emprestimo.get_data_devolucao(),
+                        self.titulo_do_livro(emprestimo.get_id_livro()),
+                        self.nome_do_usuario(emprestimo.get_id_usuario())
+                    ))
+                }
+            })
+            .collect();
+
+        if self.lista.selected().is_none_or(|i| i >= ids.len()) {
+            self.lista
+                .select(if ids.is_empty() { None } else { Some(0) });
+        }
+
+        let lista = List::new(itens)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " {} ({}) ",
+                self.aba.titulo(),
+                ids.len()
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(lista, area, &mut self.lista);
+    }
+
+    fn desenhar_detalhes(&self, frame: &mut Frame, area: Rect) {
+        let texto = match self.selecionado() {
+            None => "Nada selecionado.".to_string(),
+            Some(id) => match self.aba {
+                Aba::Livros => {
+                    let livro = &self.biblioteca.livros()[&id];
+                    format!("ID: {}\n{}", id, livro)
+                }
+                Aba::Usuarios => {
+                    let usuario = &self.biblioteca.usuarios()[&id];
+                    let mut texto = format!("ID: {}\n{}\n\nEmpréstimos ativos:", id, usuario);
+                    let ativos = self.emprestimos_do_usuario(id);
+                    if ativos.is_empty() {
+                        texto.push_str("\n  nenhum");
+                    }
+                    for emprestimo in ativos {
+                        texto.push_str(&format!(
+                            "\n  {} (até {})",
+                            self.titulo_do_livro(emprestimo.get_id_livro()),
+                            emprestimo.get_data_devolucao()
+                        ));
+                    }
+                    texto
+                }
+                Aba::Emprestimos => {
+                    let emprestimo = &self.biblioteca.emprestimos()[&id];
+                    format!(
+                        "ID: {}\nLivro: {}\nUsuário: {}\nData do Empréstimo: {}\nData de Devolução: {}\nStatus: {}",
+                        id,
+                        self.titulo_do_livro(emprestimo.get_id_livro()),
+                        self.nome_do_usuario(emprestimo.get_id_usuario()),
+                        emprestimo.get_data_emprestimo(),
+                        emprestimo.get_data_devolucao(),
+                        match emprestimo.status {
+                            StatusEmprestimo::Ativo => "Ativo",
+                            StatusEmprestimo::Devolvido => "Devolvido",
+                        }
+                    )
+                }
+            },
+        };
+
+        let detalhes = Paragraph::new(texto)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(" Detalhes "));
+        frame.render_widget(detalhes, area);
+    }
+
+    fn desenhar_escolha_usuario(&mut self, frame: &mut Frame) {
+        let busca = self.busca_modal();
+        let usuarios = self.usuarios_filtrados(&busca);
+        let itens: Vec<ListItem> = usuarios
+            .iter()
+            .map(|id| ListItem::new(self.biblioteca.usuarios()[id].get_nome()))
+            .collect();
+
+        let area = centralizar(frame.area(), 60, 60);
+        frame.render_widget(Clear, area);
+
+        let [area_busca, area_lista] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(3)]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(busca)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Emprestar para (Enter confirma, Esc cancela) "),
+            ),
+            area_busca,
+        );
+
+        if let Modo::EscolhaUsuario { lista, .. } = &mut self.modo {
+            let widget = List::new(itens)
+                .block(Block::default().borders(Borders::ALL).title(" Usuários "))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol("> ");
+            frame.render_stateful_widget(widget, area_lista, lista);
+        }
+    }
+}
+
+fn centralizar(area: Rect, largura_pct: u16, altura_pct: u16) -> Rect {
+    let [_, meio, _] = Layout::vertical([
+        Constraint::Percentage((100 - altura_pct) / 2),
+        Constraint::Percentage(altura_pct),
+        Constraint::Percentage((100 - altura_pct) / 2),
+    ])
+    .areas(area);
+    let [_, centro, _] = Layout::horizontal([
+        Constraint::Percentage((100 - largura_pct) / 2),
+        Constraint::Percentage(largura_pct),
+        Constraint::Percentage((100 - largura_pct) / 2),
+    ])
+    .areas(meio);
+    centro
+}
+
+fn rodar(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.sair {
+        terminal.draw(|frame| app.desenhar(frame))?;
+
+        if let Event::Key(tecla) = event::read()?
+            && tecla.kind == KeyEventKind::Press
+        {
+            app.mensagem.clear();
+            app.tratar_tecla(tecla);
+        }
+    }
+    Ok(())
+}
+
+pub fn executar(biblioteca: &mut Biblioteca) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let mut app = App::new(biblioteca);
+
+    let resultado = rodar(&mut terminal, &mut app);
+
+    ratatui::restore();
+    resultado
+}